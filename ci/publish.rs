@@ -22,7 +22,6 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "wasm-encoder",
     "wast",
     "wat",
-    "wasm-smith",
     "wasm-mutate",
     "wasm-shrink",
     "wit-parser",
@@ -31,6 +30,7 @@ const CRATES_TO_PUBLISH: &[&str] = &[
     "wit-encoder",
     "wasm-compose",
     "wit-smith",
+    "wasm-smith",
     "json-from-wast",
     "wasm-wave",
     "wasm-tools",
@@ -570,6 +570,7 @@ impl<'a> ComponentComposer<'a> {
                 define_components: !self.config.import_components,
                 export: Some(root_instance),
                 validate: false,
+                merge_producers: self.config.merge_producers,
             },
             &graph,
         )
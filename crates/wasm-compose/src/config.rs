@@ -105,6 +105,12 @@ pub struct Config {
     #[serde(default)]
     pub disallow_imports: bool,
 
+    /// Whether or not to merge the producers sections of all components, and
+    /// of the modules and components nested within them, into the producers
+    /// section of the composed component.
+    #[serde(default)]
+    pub merge_producers: bool,
+
     /// The explicit, transitive dependencies of the root component.
     #[serde(default, deserialize_with = "de::index_map")]
     pub dependencies: IndexMap<String, Dependency>,
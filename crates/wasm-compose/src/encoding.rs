@@ -1356,9 +1356,41 @@ impl<'a> CompositionGraphEncoder<'a> {
             self.encode_exports(&mut encoded, id)?;
         }
 
+        if self.options.merge_producers {
+            self.encode_producers(&mut encoded)?;
+        }
+
         Ok(encoded.finish())
     }
 
+    fn encode_producers(&self, encoded: &mut ComponentBuilder) -> Result<()> {
+        let mut producers = wasm_metadata::Producers::empty();
+        for entry in self.graph.components.values() {
+            if !self
+                .encoded_components
+                .contains_key(&PtrKey(&entry.component))
+            {
+                continue;
+            }
+            // Producers of nested modules and components are merged too, as
+            // they won't be visible at the top level of the composition.
+            for payload in wasmparser::Parser::new(0).parse_all(entry.component.bytes()) {
+                if let wasmparser::Payload::CustomSection(c) = payload? {
+                    if let wasmparser::KnownCustom::Producers(_) = c.as_known() {
+                        producers.merge(&wasm_metadata::Producers::from_bytes(
+                            c.data(),
+                            c.data_offset(),
+                        )?);
+                    }
+                }
+            }
+        }
+        if !producers.is_empty() {
+            encoded.raw_custom_section(&producers.raw_custom_section());
+        }
+        Ok(())
+    }
+
     fn encode_imports(&mut self, encoded: &mut ComponentBuilder) -> Result<()> {
         let imports = ImportMap::new(!self.options.define_components, self.graph)?;
 
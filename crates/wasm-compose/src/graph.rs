@@ -470,6 +470,13 @@ pub struct EncodeOptions {
 
     /// Whether or not to validate the encoded output.
     pub validate: bool,
+
+    /// Whether or not to merge the producers sections of all defined
+    /// components, including those nested within them, into a producers
+    /// section of the encoded output.
+    ///
+    /// This has no effect if `define_components` is `false`.
+    pub merge_producers: bool,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -1384,6 +1391,7 @@ mod test {
             define_components: false,
             export: None,
             validate: true,
+            merge_producers: false,
         }) {
             Ok(_) => panic!("graph should not encode"),
             Err(e) => assert_eq!(
@@ -1414,6 +1422,7 @@ mod test {
             define_components: false,
             export: None,
             validate: true,
+            merge_producers: false,
         })?;
 
         let wat = wasmprinter::print_bytes(encoded)?;
@@ -1447,6 +1456,7 @@ mod test {
             define_components: false,
             export: None,
             validate: true,
+            merge_producers: false,
         })?;
 
         let wat = wasmprinter::print_bytes(encoded)?.replace("\r\n", "\n");
@@ -1486,6 +1496,7 @@ mod test {
             define_components: true,
             export: None,
             validate: true,
+            merge_producers: false,
         })?;
 
         let wat = wasmprinter::print_bytes(encoded)?.replace("\r\n", "\n");
@@ -1553,6 +1564,7 @@ mod test {
             define_components: true,
             export: None,
             validate: true,
+            merge_producers: false,
         })?;
 
         let wat = wasmprinter::print_bytes(encoded)?.replace("\r\n", "\n");
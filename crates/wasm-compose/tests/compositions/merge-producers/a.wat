@@ -0,0 +1,16 @@
+(component
+  (type (func))
+  (func (import "b") (type 0))
+  (instance (export "a" (func 0)))
+  (export "a" (instance 0))
+  (core module
+    (@producers
+      (language "C" "")
+      (processed-by "clang" "19.1.0")
+    )
+  )
+  (@producers
+    (language "JavaScript" "")
+    (processed-by "componentize-js" "0.10.0")
+  )
+)
@@ -0,0 +1,52 @@
+(component
+  (type (;0;) (func))
+  (import "b" (func (;0;) (type 0)))
+  (component (;0;)
+    (type (;0;)
+      (instance
+        (type (;0;) (func))
+        (export (;0;) "a" (func (type 0)))
+      )
+    )
+    (import "a" (instance (;0;) (type 0)))
+    (@producers
+      (language "Rust" "1.80.0")
+      (processed-by "wit-component" "0.235.0")
+    )
+  )
+  (component (;1;)
+    (type (;0;) (func))
+    (import "b" (func (;0;) (type 0)))
+    (instance (;0;)
+      (export "a" (func 0))
+    )
+    (export (;1;) "a" (instance 0))
+    (core module (;0;)
+      (@producers
+        (language "C" "")
+        (processed-by "clang" "19.1.0")
+      )
+    )
+    (@producers
+      (language "JavaScript" "")
+      (processed-by "componentize-js" "0.10.0")
+    )
+  )
+  (instance (;0;) (instantiate 1
+      (with "b" (func 0))
+    )
+  )
+  (alias export 0 "a" (instance (;1;)))
+  (instance (;2;) (instantiate 0
+      (with "a" (instance 1))
+    )
+  )
+  (@producers
+    (language "Rust" "1.80.0")
+    (language "C" "")
+    (language "JavaScript" "")
+    (processed-by "wit-component" "0.235.0")
+    (processed-by "clang" "19.1.0")
+    (processed-by "componentize-js" "0.10.0")
+  )
+)
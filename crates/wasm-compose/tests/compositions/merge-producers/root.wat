@@ -0,0 +1,7 @@
+(component
+  (import "a" (instance (export "a" (func))))
+  (@producers
+    (language "Rust" "1.80.0")
+    (processed-by "wit-component" "0.235.0")
+  )
+)
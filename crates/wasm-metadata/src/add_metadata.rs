@@ -1,6 +1,12 @@
 use crate::{Producers, rewrite_wasm};
+#[cfg(feature = "oci")]
+use anyhow::Context;
 use anyhow::Result;
+#[cfg(feature = "oci")]
+use indexmap::IndexMap;
 use std::fmt::Debug;
+#[cfg(feature = "oci")]
+use std::str::FromStr;
 
 /// Add metadata (module name, producers) to a WebAssembly file.
 ///
@@ -59,6 +65,46 @@ impl AddMetadata {
         let add_producers = Producers::from_meta(self);
         rewrite_wasm(self, &add_producers, input)
     }
+
+    /// Set fields from [OCI image annotations], keyed by their
+    /// `org.opencontainers.image.*` names.
+    ///
+    /// Only fields which are currently [`AddMetadataField::Keep`] are updated,
+    /// so values which were set or cleared explicitly take precedence over the
+    /// annotations. Annotations which don't correspond to a metadata field are
+    /// ignored.
+    ///
+    /// [OCI image annotations]: https://specs.opencontainers.org/image-spec/annotations/
+    #[cfg(feature = "oci")]
+    pub fn add_oci_annotations(&mut self, annotations: &IndexMap<String, String>) -> Result<()> {
+        use crate::oci_annotations::*;
+
+        fn set<T: Debug + Clone + FromStr<Err = anyhow::Error>>(
+            field: &mut AddMetadataField<T>,
+            annotations: &IndexMap<String, String>,
+            key: &str,
+        ) -> Result<()> {
+            if let (AddMetadataField::Keep, Some(value)) = (&field, annotations.get(key)) {
+                let value = value
+                    .parse()
+                    .with_context(|| format!("invalid value for annotation `{key}`"))?;
+                *field = AddMetadataField::Set(value);
+            }
+            Ok(())
+        }
+
+        if let (AddMetadataField::Keep, Some(name)) = (&self.name, annotations.get(TITLE)) {
+            self.name = AddMetadataField::Set(name.clone());
+        }
+        set(&mut self.authors, annotations, AUTHORS)?;
+        set(&mut self.description, annotations, DESCRIPTION)?;
+        set(&mut self.licenses, annotations, LICENSES)?;
+        set(&mut self.source, annotations, SOURCE)?;
+        set(&mut self.homepage, annotations, HOMEPAGE)?;
+        set(&mut self.revision, annotations, REVISION)?;
+        set(&mut self.version, annotations, VERSION)?;
+        Ok(())
+    }
 }
 
 /// Defines how to modify a field of the component/module metadata
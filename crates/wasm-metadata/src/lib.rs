@@ -66,7 +66,7 @@ mod dependencies;
 #[cfg(feature = "oci")]
 pub use dependencies::Dependencies;
 #[cfg(feature = "oci")]
-mod oci_annotations;
+pub mod oci_annotations;
 #[cfg(feature = "oci")]
 pub use oci_annotations::{Authors, Description, Homepage, Licenses, Revision, Source, Version};
 #[cfg(feature = "oci")]
@@ -75,9 +75,24 @@ mod metadata;
 pub use metadata::Metadata;
 #[cfg(feature = "oci")]
 mod payload;
+#[cfg(feature = "oci")]
+mod policy;
+#[cfg(feature = "oci")]
+mod sbom;
 
 #[cfg(feature = "oci")]
 pub use payload::Payload;
+#[cfg(feature = "oci")]
+pub use policy::{BannedProducer, Field, Policy, Violation};
+#[cfg(feature = "oci")]
+pub use sbom::{Sbom, SbomFormat};
 
 #[cfg(feature = "clap")]
 pub use clap::AddMetadataOpts;
+
+#[cfg(feature = "sign")]
+mod signature;
+#[cfg(feature = "sign")]
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+#[cfg(feature = "sign")]
+pub use signature::{Signature, key_id};
@@ -1,6 +1,8 @@
+use indexmap::IndexMap;
 use serde_derive::Serialize;
 use std::ops::Range;
 
+use crate::oci_annotations;
 use crate::{
     Authors, Dependencies, Description, Homepage, Licenses, Producers, Revision, Source, Version,
 };
@@ -32,3 +34,51 @@ pub struct Metadata {
     /// Dependencies of the component
     pub dependencies: Option<Dependencies>,
 }
+
+impl Metadata {
+    /// Convert this metadata to [OCI image annotations], keyed by their
+    /// `org.opencontainers.image.*` names.
+    ///
+    /// Fields which aren't set are omitted, as is any metadata which doesn't
+    /// have a corresponding annotation such as the producers section.
+    ///
+    /// [OCI image annotations]: https://specs.opencontainers.org/image-spec/annotations/
+    pub fn oci_annotations(&self) -> IndexMap<String, String> {
+        let mut annotations = IndexMap::new();
+        let mut add = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                annotations.insert(key.to_string(), value);
+            }
+        };
+        add(oci_annotations::TITLE, self.name.clone());
+        add(
+            oci_annotations::AUTHORS,
+            self.authors.as_ref().map(|a| a.to_string()),
+        );
+        add(
+            oci_annotations::DESCRIPTION,
+            self.description.as_ref().map(|d| d.to_string()),
+        );
+        add(
+            oci_annotations::LICENSES,
+            self.licenses.as_ref().map(|l| l.to_string()),
+        );
+        add(
+            oci_annotations::SOURCE,
+            self.source.as_ref().map(|s| s.to_string()),
+        );
+        add(
+            oci_annotations::HOMEPAGE,
+            self.homepage.as_ref().map(|h| h.to_string()),
+        );
+        add(
+            oci_annotations::REVISION,
+            self.revision.as_ref().map(|r| r.to_string()),
+        );
+        add(
+            oci_annotations::VERSION,
+            self.version.as_ref().map(|v| v.to_string()),
+        );
+        annotations
+    }
+}
@@ -15,6 +15,23 @@
 //!
 //! [OCI Annotations Spec]: https://specs.opencontainers.org/image-spec/annotations/
 
+/// Annotation key for the name of the binary.
+pub const TITLE: &str = "org.opencontainers.image.title";
+/// Annotation key for [`Authors`].
+pub const AUTHORS: &str = "org.opencontainers.image.authors";
+/// Annotation key for [`Description`].
+pub const DESCRIPTION: &str = "org.opencontainers.image.description";
+/// Annotation key for [`Licenses`].
+pub const LICENSES: &str = "org.opencontainers.image.licenses";
+/// Annotation key for [`Source`].
+pub const SOURCE: &str = "org.opencontainers.image.source";
+/// Annotation key for [`Homepage`].
+pub const HOMEPAGE: &str = "org.opencontainers.image.url";
+/// Annotation key for [`Revision`].
+pub const REVISION: &str = "org.opencontainers.image.revision";
+/// Annotation key for [`Version`].
+pub const VERSION: &str = "org.opencontainers.image.version";
+
 pub use authors::Authors;
 pub use description::Description;
 pub use homepage::Homepage;
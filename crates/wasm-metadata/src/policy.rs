@@ -0,0 +1,395 @@
+use std::fmt::{self, Display};
+
+use anyhow::{Context, Result};
+use serde_derive::Deserialize;
+
+use crate::{Metadata, Payload};
+
+/// A set of requirements on the metadata of a WebAssembly binary.
+///
+/// Policies are checked against every module and component in a binary,
+/// including nested ones, with [`Policy::check`]. They are typically read from
+/// a JSON file such as:
+///
+/// ```json
+/// {
+///   "required": ["licenses", "source", "revision"],
+///   "allowed-licenses": ["Apache-2.0", "MIT"],
+///   "banned-producers": [
+///     { "field": "processed-by", "name": "rustc", "versions": "<1.80" }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Policy {
+    /// Metadata fields which must be present.
+    #[serde(default)]
+    pub required: Vec<Field>,
+
+    /// License identifiers which may be used in the `licenses` field.
+    ///
+    /// The licenses of a module or component are accepted if its SPDX
+    /// expression can be satisfied using only these licenses, so
+    /// `MIT OR GPL-3.0` is accepted if only `MIT` is allowed. Entries may
+    /// either be a license identifier such as `Apache-2.0` or a license with an
+    /// exception such as `Apache-2.0 WITH LLVM-exception`. When not specified
+    /// all licenses are allowed.
+    #[serde(default)]
+    pub allowed_licenses: Option<Vec<String>>,
+
+    /// Entries of the producers section which may not be present.
+    #[serde(default)]
+    pub banned_producers: Vec<BannedProducer>,
+
+    /// Whether the policy only applies to the outermost module or component.
+    #[serde(default)]
+    pub root_only: bool,
+}
+
+/// A metadata field which can be required by a [`Policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Field {
+    /// The module or component name.
+    Name,
+    /// The producers section.
+    Producers,
+    /// The `authors` section.
+    Authors,
+    /// The `description` section.
+    Description,
+    /// The `licenses` section.
+    Licenses,
+    /// The `source` section.
+    Source,
+    /// The `homepage` section.
+    Homepage,
+    /// The `revision` section.
+    Revision,
+    /// The `version` section.
+    Version,
+    /// The dependencies recorded by `cargo auditable`.
+    Dependencies,
+}
+
+impl Field {
+    fn is_present(&self, metadata: &Metadata) -> bool {
+        match self {
+            Field::Name => metadata.name.is_some(),
+            Field::Producers => metadata.producers.is_some(),
+            Field::Authors => metadata.authors.is_some(),
+            Field::Description => metadata.description.is_some(),
+            Field::Licenses => metadata.licenses.is_some(),
+            Field::Source => metadata.source.is_some(),
+            Field::Homepage => metadata.homepage.is_some(),
+            Field::Revision => metadata.revision.is_some(),
+            Field::Version => metadata.version.is_some(),
+            Field::Dependencies => metadata.dependencies.is_some(),
+        }
+    }
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Field::Name => "name",
+            Field::Producers => "producers",
+            Field::Authors => "authors",
+            Field::Description => "description",
+            Field::Licenses => "licenses",
+            Field::Source => "source",
+            Field::Homepage => "homepage",
+            Field::Revision => "revision",
+            Field::Version => "version",
+            Field::Dependencies => "dependencies",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A producer which may not be present in any producers section checked by a
+/// [`Policy`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+#[non_exhaustive]
+pub struct BannedProducer {
+    /// The producers field the name is found in, such as `language`,
+    /// `processed-by` or `sdk`. Matches all fields when not specified.
+    #[serde(default)]
+    pub field: Option<String>,
+
+    /// The name of the language, tool or SDK.
+    pub name: String,
+
+    /// A semver requirement, such as `<1.2.3`, for the versions which are
+    /// banned. All versions are banned when not specified.
+    ///
+    /// Only the leading semver-compatible part of a producer's version is
+    /// considered, so `1.80.0 (051478957 2024-07-21)` is treated as `1.80.0`.
+    /// Versions which can't be parsed never match a requirement.
+    #[serde(default)]
+    pub versions: Option<String>,
+}
+
+impl BannedProducer {
+    fn matches(&self, field: &str, name: &str, version: &str) -> Result<bool> {
+        if self.name != name || self.field.as_deref().is_some_and(|f| f != field) {
+            return Ok(false);
+        }
+        let req = match &self.versions {
+            Some(req) => req,
+            None => return Ok(true),
+        };
+        let req = semver::VersionReq::parse(req)
+            .with_context(|| format!("invalid version requirement `{req}` for `{name}`"))?;
+        let version = version.split_whitespace().next().unwrap_or("");
+        Ok(semver::Version::parse(version).is_ok_and(|v| req.matches(&v)))
+    }
+}
+
+/// A way in which a module or component doesn't satisfy a [`Policy`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// The path to the module or component within the binary, made up of the
+    /// names of it and all its parents.
+    pub path: Vec<String>,
+    /// The byte range of the module or component within the binary.
+    pub range: std::ops::Range<usize>,
+    /// A description of the violation.
+    pub message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [0x{:x}..0x{:x}]: {}",
+            self.path.join("/"),
+            self.range.start,
+            self.range.end,
+            self.message
+        )
+    }
+}
+
+impl Policy {
+    /// Check every module and component in `payload` against this policy,
+    /// returning all violations that were found.
+    ///
+    /// Returns an error if the policy itself is invalid, for example if it
+    /// contains a malformed version requirement.
+    pub fn check(&self, payload: &Payload) -> Result<Vec<Violation>> {
+        let mut violations = Vec::new();
+        self.check_payload(payload, &mut Vec::new(), &mut violations)?;
+        Ok(violations)
+    }
+
+    fn check_payload(
+        &self,
+        payload: &Payload,
+        path: &mut Vec<String>,
+        violations: &mut Vec<Violation>,
+    ) -> Result<()> {
+        let metadata = payload.metadata();
+        let kind = match payload {
+            Payload::Component { .. } => "component",
+            Payload::Module(_) => "module",
+        };
+        path.push(format!(
+            "{kind}({})",
+            metadata.name.as_deref().unwrap_or("<unknown>")
+        ));
+        let mut report = |message: String| {
+            violations.push(Violation {
+                path: path.clone(),
+                range: metadata.range.clone(),
+                message,
+            })
+        };
+
+        for field in &self.required {
+            if !field.is_present(metadata) {
+                report(format!("missing required field `{field}`"));
+            }
+        }
+
+        if let (Some(allowed), Some(licenses)) = (&self.allowed_licenses, &metadata.licenses) {
+            let allowed = allowed
+                .iter()
+                .map(|license| {
+                    spdx::Licensee::parse(license)
+                        .with_context(|| format!("invalid allowed license `{license}`"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let expression = spdx::Expression::parse(&licenses.to_string())?;
+            if let Err(failures) =
+                expression.evaluate_with_failures(|req| allowed.iter().any(|a| a.satisfies(req)))
+            {
+                let text: &str = expression.as_ref();
+                let failures = failures
+                    .iter()
+                    .map(|f| format!("`{}`", &text[f.span.start as usize..f.span.end as usize]))
+                    .collect::<Vec<_>>();
+                report(format!(
+                    "licenses `{licenses}` are not allowed, disallowed terms: {}",
+                    failures.join(", ")
+                ));
+            }
+        }
+
+        if let Some(producers) = &metadata.producers {
+            for (field, values) in producers.iter() {
+                for (name, version) in values.iter() {
+                    for banned in &self.banned_producers {
+                        if banned.matches(field, name, version)? {
+                            report(format!("banned producer `{field}` `{name}` [{version}]"));
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Payload::Component { children, .. } = payload {
+            if !self.root_only {
+                for child in children {
+                    self.check_payload(child, path, violations)?;
+                }
+            }
+        }
+        path.pop();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AddMetadata, AddMetadataField, Licenses, Revision};
+    use wasm_encoder::{Component, Module};
+
+    fn policy(json: &str) -> Policy {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn messages(policy: &Policy, wasm: &[u8]) -> Vec<String> {
+        let payload = Payload::from_binary(wasm).unwrap();
+        policy
+            .check(&payload)
+            .unwrap()
+            .iter()
+            .map(|v| format!("{}: {}", v.path.join("/"), v.message))
+            .collect()
+    }
+
+    #[test]
+    fn required_fields() {
+        let policy = policy(r#"{ "required": ["licenses", "revision"] }"#);
+        let mut add = AddMetadata::default();
+        add.name = AddMetadataField::Set("foo".to_owned());
+        add.revision = AddMetadataField::Set(Revision::new("abc"));
+        let module = add.to_wasm(&Module::new().finish()).unwrap();
+        assert_eq!(
+            messages(&policy, &module),
+            ["module(foo): missing required field `licenses`"]
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let policy = policy(r#"{ "required": ["licenses"] }"#);
+        let mut component = Component::new();
+        component.section(&wasm_encoder::ModuleSection(&Module::new()));
+        let mut add = AddMetadata::default();
+        add.licenses = AddMetadataField::Set(Licenses::new("MIT").unwrap());
+        let component = add.to_wasm(&component.finish()).unwrap();
+        assert_eq!(
+            messages(&policy, &component),
+            ["component(<unknown>)/module(<unknown>): missing required field `licenses`"]
+        );
+
+        let mut root_only = policy.clone();
+        root_only.root_only = true;
+        assert!(messages(&root_only, &component).is_empty());
+    }
+
+    #[test]
+    fn allowed_licenses() {
+        let policy = policy(r#"{ "allowed-licenses": ["MIT", "Apache-2.0 WITH LLVM-exception"] }"#);
+        let check = |licenses: &str| {
+            let mut add = AddMetadata::default();
+            add.licenses = AddMetadataField::Set(Licenses::new(licenses).unwrap());
+            messages(&policy, &add.to_wasm(&Module::new().finish()).unwrap())
+        };
+        assert!(check("MIT").is_empty());
+        assert!(check("MIT OR GPL-3.0-only").is_empty());
+        assert!(check("Apache-2.0 WITH LLVM-exception").is_empty());
+        assert_eq!(
+            check("MIT AND GPL-3.0-only"),
+            [
+                "module(<unknown>): licenses `MIT AND GPL-3.0-only` are not allowed, \
+                 disallowed terms: `GPL-3.0-only`"
+            ]
+        );
+        assert_eq!(check("Apache-2.0").len(), 1);
+
+        let policy = super::Policy {
+            allowed_licenses: Some(vec!["not a license".to_owned()]),
+            ..Default::default()
+        };
+        let mut add = AddMetadata::default();
+        add.licenses = AddMetadataField::Set(Licenses::new("MIT").unwrap());
+        let module = add.to_wasm(&Module::new().finish()).unwrap();
+        assert!(
+            policy
+                .check(&Payload::from_binary(&module).unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn banned_producers() {
+        let policy = policy(
+            r#"{ "banned-producers": [
+                { "field": "processed-by", "name": "rustc", "versions": "<1.80" },
+                { "name": "clang" }
+            ] }"#,
+        );
+        let check = |field: &str, name: &str, version: &str| {
+            let mut producers = crate::Producers::empty();
+            producers.add(field, name, version);
+            messages(
+                &policy,
+                &producers.add_to_wasm(&Module::new().finish()).unwrap(),
+            )
+        };
+        assert_eq!(
+            check("processed-by", "rustc", "1.79.0 (129f3b996 2024-06-10)"),
+            [
+                "module(<unknown>): banned producer `processed-by` `rustc` [1.79.0 (129f3b996 2024-06-10)]"
+            ]
+        );
+        assert!(check("processed-by", "rustc", "1.80.0").is_empty());
+        assert!(check("processed-by", "rustc", "nightly").is_empty());
+        assert!(check("language", "rustc", "1.0.0").is_empty());
+        assert_eq!(check("sdk", "clang", "17").len(), 1);
+    }
+
+    #[test]
+    fn invalid_policy() {
+        assert!(serde_json::from_str::<Policy>(r#"{ "required": ["color"] }"#).is_err());
+        assert!(serde_json::from_str::<Policy>(r#"{ "requried": [] }"#).is_err());
+
+        let policy = policy(r#"{ "banned-producers": [{ "name": "rustc", "versions": "??" }] }"#);
+        let mut producers = crate::Producers::empty();
+        producers.add("processed-by", "rustc", "1.0.0");
+        let module = producers.add_to_wasm(&Module::new().finish()).unwrap();
+        assert!(
+            policy
+                .check(&Payload::from_binary(&module).unwrap())
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,584 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Error, Result, bail};
+use auditable_serde::{DependencyKind, Package, Source};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::{Metadata, Payload};
+
+/// The document format of a Software Bill of Materials created by [`Sbom`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SbomFormat {
+    /// [SPDX 2.3](https://spdx.github.io/spdx-spec/v2.3/) in its JSON encoding.
+    #[default]
+    Spdx,
+    /// [CycloneDX 1.5](https://cyclonedx.org/docs/1.5/json/) in its JSON
+    /// encoding.
+    CycloneDx,
+}
+
+impl FromStr for SbomFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "spdx" => Ok(Self::Spdx),
+            "cyclonedx" => Ok(Self::CycloneDx),
+            _ => bail!("unknown SBOM format `{s}`, expected `spdx` or `cyclonedx`"),
+        }
+    }
+}
+
+impl Display for SbomFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spdx => write!(f, "spdx"),
+            Self::CycloneDx => write!(f, "cyclonedx"),
+        }
+    }
+}
+
+/// Create a Software Bill of Materials (SBOM) for a WebAssembly binary.
+///
+/// The SBOM describes the binary itself along with every module and component
+/// nested within it, using the metadata found in their custom sections. Rust
+/// dependencies recorded by `cargo auditable` are included as packages that
+/// the module they were found in depends on.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Sbom {
+    /// The format of the document to produce.
+    pub format: SbomFormat,
+
+    /// The creation time of the document as an RFC 3339 timestamp. Defaults to
+    /// the current time.
+    pub created: Option<String>,
+
+    /// The unique namespace of an SPDX document. Defaults to a URI derived
+    /// from the name and hash of the binary.
+    pub namespace: Option<String>,
+}
+
+impl Sbom {
+    /// Create an SBOM for a WebAssembly binary, serialized as JSON. Supports
+    /// both core WebAssembly modules and WebAssembly components.
+    pub fn to_json(&self, input: &[u8]) -> Result<String> {
+        let payload = Payload::from_binary(input)?;
+        let created = match &self.created {
+            Some(created) => created.clone(),
+            None => rfc3339(SystemTime::now()),
+        };
+        let mut builder = Builder {
+            input,
+            packages: Vec::new(),
+            relationships: Vec::new(),
+            crates: HashMap::new(),
+        };
+        let root = builder.push(&payload, None);
+        let document = match self.format {
+            SbomFormat::Spdx => {
+                let namespace = match &self.namespace {
+                    Some(namespace) => namespace.clone(),
+                    None => format!(
+                        "https://spdx.org/spdxdocs/{}-{}",
+                        builder.packages[root].name, builder.packages[root].sha256,
+                    ),
+                };
+                builder.spdx(root, &created, &namespace)
+            }
+            SbomFormat::CycloneDx => builder.cyclonedx(root, &created),
+        };
+        Ok(serde_json::to_string_pretty(&document)?)
+    }
+}
+
+/// A module, component or crate in the SBOM.
+struct Entry {
+    id: String,
+    name: String,
+    version: Option<String>,
+    kind: EntryKind,
+    sha256: String,
+    purl: Option<String>,
+    description: Option<String>,
+    authors: Option<String>,
+    licenses: Option<String>,
+    source: Option<String>,
+    homepage: Option<String>,
+    revision: Option<String>,
+    producers: Vec<(String, String)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum EntryKind {
+    Component,
+    Module,
+    Crate,
+}
+
+enum Relationship {
+    Contains,
+    DependsOn,
+    BuildDependsOn,
+}
+
+struct Builder<'a> {
+    input: &'a [u8],
+    packages: Vec<Entry>,
+    relationships: Vec<(usize, Relationship, usize)>,
+    crates: HashMap<(String, String, String), usize>,
+}
+
+impl Builder<'_> {
+    /// Add `payload` and all its children to this SBOM, returning its index.
+    fn push(&mut self, payload: &Payload, parent: Option<usize>) -> usize {
+        let Metadata {
+            name,
+            producers,
+            authors,
+            description,
+            licenses,
+            source,
+            homepage,
+            revision,
+            version,
+            range,
+            dependencies,
+        } = payload.metadata();
+        let index = self.packages.len();
+        let kind = match payload {
+            Payload::Component { .. } => EntryKind::Component,
+            Payload::Module(_) => EntryKind::Module,
+        };
+        self.packages.push(Entry {
+            id: format!("wasm-{index}"),
+            name: name.clone().unwrap_or_else(|| "unknown".to_string()),
+            version: version.as_ref().map(|v| v.to_string()),
+            kind,
+            sha256: sha256(&self.input[range.clone()]),
+            purl: None,
+            description: description.as_ref().map(|d| d.to_string()),
+            authors: authors.as_ref().map(|a| a.to_string()),
+            licenses: licenses.as_ref().map(|l| l.to_string()),
+            source: source.as_ref().map(|s| s.to_string()),
+            homepage: homepage.as_ref().map(|h| h.to_string()),
+            revision: revision.as_ref().map(|r| r.to_string()),
+            producers: producers
+                .iter()
+                .flat_map(|p| p.iter())
+                .flat_map(|(field, values)| {
+                    values.iter().map(move |(name, version)| {
+                        let value = if version.is_empty() {
+                            name.clone()
+                        } else {
+                            format!("{name} {version}")
+                        };
+                        (field.clone(), value)
+                    })
+                })
+                .collect(),
+        });
+        if let Some(parent) = parent {
+            self.relationships
+                .push((parent, Relationship::Contains, index));
+        }
+
+        if let Some(dependencies) = dependencies {
+            let packages = &dependencies.version_info().packages;
+            let indices = packages
+                .iter()
+                .map(|package| self.push_crate(package))
+                .collect::<Vec<_>>();
+            for (package, &from) in packages.iter().zip(&indices) {
+                if package.root {
+                    self.relationships
+                        .push((index, Relationship::DependsOn, from));
+                }
+                for &dep in &package.dependencies {
+                    let relationship = match packages[dep].kind {
+                        DependencyKind::Runtime => Relationship::DependsOn,
+                        DependencyKind::Build => Relationship::BuildDependsOn,
+                    };
+                    self.relationships.push((from, relationship, indices[dep]));
+                }
+            }
+        }
+
+        if let Payload::Component { children, .. } = payload {
+            for child in children {
+                self.push(child, Some(index));
+            }
+        }
+        index
+    }
+
+    /// Add a crate recorded by `cargo auditable`, returning its index.
+    ///
+    /// Crates are deduplicated across all modules in the binary. Crates which
+    /// don't come from crates.io have their source appended to their id, as
+    /// the same name and version may also be used by a crate from crates.io.
+    fn push_crate(&mut self, package: &Package) -> usize {
+        let source = String::from(package.source.clone());
+        let key = (package.name.clone(), package.version.to_string(), source);
+        if let Some(index) = self.crates.get(&key) {
+            return *index;
+        }
+        let index = self.packages.len();
+        let (id, purl) = match package.source {
+            Source::CratesIo => (
+                format!("crate-{}-{}", package.name, package.version),
+                Some(format!("pkg:cargo/{}@{}", package.name, package.version)),
+            ),
+            _ => (format!("crate-{}-{}-{}", key.0, key.1, key.2), None),
+        };
+        self.packages.push(Entry {
+            id,
+            name: package.name.clone(),
+            version: Some(package.version.to_string()),
+            kind: EntryKind::Crate,
+            sha256: String::new(),
+            purl,
+            description: None,
+            authors: None,
+            licenses: None,
+            source: None,
+            homepage: None,
+            revision: None,
+            producers: Vec::new(),
+        });
+        self.crates.insert(key, index);
+        index
+    }
+
+    fn spdx(&self, root: usize, created: &str, namespace: &str) -> Value {
+        let ids = unique_spdx_ids(self.packages.iter().map(|entry| entry.id.as_str()));
+        let spdx_id = |index: usize| &ids[index];
+        let packages = self
+            .packages
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let mut package = json!({
+                    "SPDXID": spdx_id(i),
+                    "name": entry.name,
+                    "downloadLocation": entry.source.as_deref().unwrap_or("NOASSERTION"),
+                    "filesAnalyzed": false,
+                    "licenseConcluded": "NOASSERTION",
+                    "licenseDeclared": entry.licenses.as_deref().unwrap_or("NOASSERTION"),
+                    "copyrightText": "NOASSERTION",
+                    "primaryPackagePurpose": match entry.kind {
+                        EntryKind::Component => "APPLICATION",
+                        EntryKind::Module | EntryKind::Crate => "LIBRARY",
+                    },
+                });
+                let package = package.as_object_mut().unwrap();
+                if let Some(version) = &entry.version {
+                    package.insert("versionInfo".into(), version.as_str().into());
+                }
+                if let Some(authors) = &entry.authors {
+                    package.insert("originator".into(), format!("Person: {authors}").into());
+                }
+                if let Some(description) = &entry.description {
+                    package.insert("description".into(), description.as_str().into());
+                }
+                if let Some(homepage) = &entry.homepage {
+                    package.insert("homepage".into(), homepage.as_str().into());
+                }
+                if let Some(revision) = &entry.revision {
+                    package.insert(
+                        "sourceInfo".into(),
+                        format!("built from revision {revision}").into(),
+                    );
+                }
+                if !entry.producers.is_empty() {
+                    let producers = entry
+                        .producers
+                        .iter()
+                        .map(|(field, value)| format!("{field}: {value}"))
+                        .collect::<Vec<_>>();
+                    package.insert("comment".into(), producers.join("\n").into());
+                }
+                if !entry.sha256.is_empty() {
+                    package.insert(
+                        "checksums".into(),
+                        json!([{ "algorithm": "SHA256", "checksumValue": entry.sha256 }]),
+                    );
+                }
+                if let Some(purl) = &entry.purl {
+                    package.insert(
+                        "externalRefs".into(),
+                        json!([{
+                            "referenceCategory": "PACKAGE-MANAGER",
+                            "referenceType": "purl",
+                            "referenceLocator": purl,
+                        }]),
+                    );
+                }
+                Value::Object(package.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let mut relationships = vec![json!({
+            "spdxElementId": "SPDXRef-DOCUMENT",
+            "relationshipType": "DESCRIBES",
+            "relatedSpdxElement": spdx_id(root),
+        })];
+        for (from, relationship, to) in &self.relationships {
+            let (from, ty, to) = match relationship {
+                Relationship::Contains => (*from, "CONTAINS", *to),
+                Relationship::DependsOn => (*from, "DEPENDS_ON", *to),
+                Relationship::BuildDependsOn => (*to, "BUILD_DEPENDENCY_OF", *from),
+            };
+            relationships.push(json!({
+                "spdxElementId": spdx_id(from),
+                "relationshipType": ty,
+                "relatedSpdxElement": spdx_id(to),
+            }));
+        }
+
+        json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": self.packages[root].name,
+            "documentNamespace": namespace,
+            "creationInfo": {
+                "created": created,
+                "creators": [format!("Tool: wasm-metadata-{}", env!("CARGO_PKG_VERSION"))],
+            },
+            "packages": packages,
+            "relationships": relationships,
+        })
+    }
+
+    fn cyclonedx(&self, root: usize, created: &str) -> Value {
+        // Nested modules and components are represented with nested
+        // components, while crates are listed at the top-level as they may be
+        // shared by multiple modules.
+        let mut children = vec![Vec::new(); self.packages.len()];
+        let mut depends_on = vec![Vec::new(); self.packages.len()];
+        for (from, relationship, to) in &self.relationships {
+            match relationship {
+                Relationship::Contains => children[*from].push(*to),
+                Relationship::DependsOn | Relationship::BuildDependsOn => {
+                    depends_on[*from].push(*to)
+                }
+            }
+        }
+
+        let mut components = self.cyclonedx_components(&children[root], &children);
+        components.extend(
+            self.packages
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.kind == EntryKind::Crate)
+                .map(|(i, _)| self.cyclonedx_component(i, &children)),
+        );
+        let dependencies = depends_on
+            .iter()
+            .enumerate()
+            .map(|(i, deps)| {
+                json!({
+                    "ref": self.packages[i].id,
+                    "dependsOn": deps.iter().map(|d| &self.packages[*d].id).collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut root = self.cyclonedx_component(root, &children);
+        root.as_object_mut().unwrap().remove("components");
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "metadata": {
+                "timestamp": created,
+                "tools": {
+                    "components": [{
+                        "type": "application",
+                        "name": "wasm-metadata",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    }],
+                },
+                "component": root,
+            },
+            "components": components,
+            "dependencies": dependencies,
+        })
+    }
+
+    fn cyclonedx_components(&self, indices: &[usize], children: &[Vec<usize>]) -> Vec<Value> {
+        indices
+            .iter()
+            .map(|i| self.cyclonedx_component(*i, children))
+            .collect()
+    }
+
+    fn cyclonedx_component(&self, index: usize, children: &[Vec<usize>]) -> Value {
+        let entry = &self.packages[index];
+        let mut component = json!({
+            "type": match entry.kind {
+                EntryKind::Component => "application",
+                EntryKind::Module | EntryKind::Crate => "library",
+            },
+            "bom-ref": entry.id,
+            "name": entry.name,
+        });
+        let component = component.as_object_mut().unwrap();
+        if let Some(version) = &entry.version {
+            component.insert("version".into(), version.as_str().into());
+        }
+        if let Some(authors) = &entry.authors {
+            component.insert("author".into(), authors.as_str().into());
+        }
+        if let Some(description) = &entry.description {
+            component.insert("description".into(), description.as_str().into());
+        }
+        if !entry.sha256.is_empty() {
+            component.insert(
+                "hashes".into(),
+                json!([{ "alg": "SHA-256", "content": entry.sha256 }]),
+            );
+        }
+        if let Some(licenses) = &entry.licenses {
+            component.insert("licenses".into(), json!([{ "expression": licenses }]));
+        }
+        if let Some(purl) = &entry.purl {
+            component.insert("purl".into(), purl.as_str().into());
+        }
+        let mut references = Vec::new();
+        if let Some(source) = &entry.source {
+            references.push(json!({ "type": "vcs", "url": source }));
+        }
+        if let Some(homepage) = &entry.homepage {
+            references.push(json!({ "type": "website", "url": homepage }));
+        }
+        if !references.is_empty() {
+            component.insert("externalReferences".into(), references.into());
+        }
+        let mut properties = Vec::new();
+        if let Some(revision) = &entry.revision {
+            properties.push(json!({ "name": "wasm:revision", "value": revision }));
+        }
+        for (field, value) in &entry.producers {
+            properties.push(json!({ "name": format!("wasm:producers:{field}"), "value": value }));
+        }
+        if !properties.is_empty() {
+            component.insert("properties".into(), properties.into());
+        }
+        if !children[index].is_empty() {
+            component.insert(
+                "components".into(),
+                self.cyclonedx_components(&children[index], children).into(),
+            );
+        }
+        Value::Object(component.clone())
+    }
+}
+
+/// Returns a unique SPDX identifier for each of `ids`.
+///
+/// Ids which are the same once sanitized by [`spdx_id`] are disambiguated by
+/// a numeric suffix.
+fn unique_spdx_ids<'a>(ids: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut issued = HashSet::new();
+    ids.into_iter()
+        .map(|id| {
+            let base = format!("SPDXRef-{}", spdx_id(id));
+            let mut id = base.clone();
+            let mut n = 1;
+            while !issued.insert(id.clone()) {
+                n += 1;
+                id = format!("{base}-{n}");
+            }
+            id
+        })
+        .collect()
+}
+
+/// Replace all characters which aren't valid in an SPDX identifier.
+fn spdx_id(id: &str) -> String {
+    id.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Format `time` as an RFC 3339 timestamp in UTC with second precision.
+fn rfc3339(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // Convert days since the epoch to a civil date, see
+    // <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            rfc3339(UNIX_EPOCH + Duration::from_secs(951_782_400 + 3723)),
+            "2000-02-29T01:02:03Z"
+        );
+        assert_eq!(
+            rfc3339(UNIX_EPOCH + Duration::from_secs(1_735_689_599)),
+            "2024-12-31T23:59:59Z"
+        );
+    }
+
+    #[test]
+    fn spdx_ids() {
+        assert_eq!(
+            spdx_id("crate-serde_json-1.0.0+x"),
+            "crate-serde-json-1.0.0-x"
+        );
+        assert_eq!(
+            unique_spdx_ids([
+                "crate-a_b-1.0.0",
+                "crate-a-b-1.0.0",
+                "crate-a+b-1.0.0",
+                "crate-a-b-1.0.0-2",
+            ]),
+            [
+                "SPDXRef-crate-a-b-1.0.0",
+                "SPDXRef-crate-a-b-1.0.0-2",
+                "SPDXRef-crate-a-b-1.0.0-3",
+                "SPDXRef-crate-a-b-1.0.0-2-2",
+            ]
+        );
+    }
+}
@@ -0,0 +1,368 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+use anyhow::{Result, bail, ensure};
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use wasm_encoder::{ComponentSection, CustomSection, Encode, Section};
+use wasmparser::{BinaryReader, CustomSectionReader, Parser, Payload};
+
+/// The name of the custom section a [`Signature`] is stored in.
+const SECTION_NAME: &str = "signature";
+
+/// The version of the encoding used for the `signature` custom section.
+const FORMAT_VERSION: u8 = 1;
+
+/// Prefix mixed into every digest so that signatures produced for wasm
+/// binaries can't be confused with signatures over other kinds of data.
+const DIGEST_DOMAIN: &[u8] = b"wasm-metadata-signature-v1\0";
+
+/// An Ed25519 signature over the sections of a WebAssembly binary.
+///
+/// The signature covers the preamble and every top-level section of a module
+/// or component, in order, with the exception of the `signature` custom
+/// section itself and any custom sections whose names are listed in
+/// [`Signature::excluded_sections`]. Nested modules and components are covered
+/// in their entirety as part of the section which contains them.
+///
+/// The signature is stored in a `signature` custom section alongside an
+/// identifier of the public key which can be used to verify it, which makes it
+/// possible to check the provenance of a binary without any external registry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    key_id: String,
+    excluded_sections: Vec<String>,
+    signature: ed25519_dalek::Signature,
+}
+
+impl Signature {
+    /// Sign a WebAssembly binary with `key`, returning the binary with a
+    /// `signature` custom section appended to it.
+    ///
+    /// Any existing top-level `signature` section is replaced. Custom sections
+    /// whose names are listed in `exclude` are not covered by the signature,
+    /// which allows them to be modified afterwards without invalidating it.
+    pub fn sign(input: &[u8], key: &SigningKey, exclude: &[String]) -> Result<Vec<u8>> {
+        ensure!(
+            !exclude.iter().any(|name| name == SECTION_NAME),
+            "the `{SECTION_NAME}` section is always excluded from the signature"
+        );
+        let digest = digest(input, exclude)?;
+        let signature = Signature {
+            key_id: key_id(&key.verifying_key()),
+            excluded_sections: exclude.to_vec(),
+            signature: key.sign(&digest),
+        };
+
+        let mut output = strip_signature(input)?;
+        signature.section().append_to(&mut output);
+        Ok(output)
+    }
+
+    /// Verify the `signature` section of a WebAssembly binary against `key`.
+    ///
+    /// Returns the signature that was found if it was produced by the private
+    /// half of `key` and the signed sections haven't been modified since.
+    pub fn verify(input: &[u8], key: &VerifyingKey) -> Result<Self> {
+        let signature = match Self::from_wasm(input)? {
+            Some(signature) => signature,
+            None => bail!("binary does not contain a `{SECTION_NAME}` section"),
+        };
+        let expected = key_id(key);
+        ensure!(
+            signature.key_id == expected,
+            "binary was signed by key `{}` but verifying with key `{expected}`",
+            signature.key_id,
+        );
+        let digest = digest(input, &signature.excluded_sections)?;
+        if key.verify_strict(&digest, &signature.signature).is_err() {
+            bail!("signature does not match the contents of the binary");
+        }
+        Ok(signature)
+    }
+
+    /// Read the `signature` section from a Wasm binary. Supports both core
+    /// Modules and Components. In the component case, only returns the
+    /// signature of the outer component, ignoring all interior components and
+    /// modules.
+    pub fn from_wasm(input: &[u8]) -> Result<Option<Self>> {
+        let mut depth = 0;
+        for payload in Parser::new(0).parse_all(input) {
+            match payload? {
+                Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => depth += 1,
+                Payload::End { .. } => depth -= 1,
+                Payload::CustomSection(c) if depth == 0 && c.name() == SECTION_NAME => {
+                    return Ok(Some(Self::parse_custom_section(&c)?));
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parse a `signature` custom section from a wasm binary.
+    pub(crate) fn parse_custom_section(reader: &CustomSectionReader<'_>) -> Result<Self> {
+        ensure!(
+            reader.name() == SECTION_NAME,
+            "The `signature` custom section should have a name of 'signature'"
+        );
+        let mut data = BinaryReader::new(reader.data(), reader.data_offset());
+        let version = data.read_u8()?;
+        ensure!(
+            version == FORMAT_VERSION,
+            "unsupported `signature` section version {version}"
+        );
+        let key_id = data.read_string()?.to_owned();
+        let mut excluded_sections = Vec::new();
+        for _ in 0..data.read_var_u32()? {
+            excluded_sections.push(data.read_string()?.to_owned());
+        }
+        let len = data.read_var_u32()? as usize;
+        let bytes: [u8; ed25519_dalek::SIGNATURE_LENGTH] = match data.read_bytes(len)?.try_into() {
+            Ok(bytes) => bytes,
+            Err(_) => bail!("invalid Ed25519 signature length {len}"),
+        };
+        ensure!(data.eof(), "trailing bytes in `signature` section");
+        Ok(Self {
+            key_id,
+            excluded_sections,
+            signature: ed25519_dalek::Signature::from_bytes(&bytes),
+        })
+    }
+
+    /// The identifier of the public key which can verify this signature, as
+    /// computed by [`key_id`].
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Names of the custom sections which are not covered by this signature.
+    pub fn excluded_sections(&self) -> &[String] {
+        &self.excluded_sections
+    }
+
+    /// The raw bytes of the Ed25519 signature.
+    pub fn to_bytes(&self) -> [u8; ed25519_dalek::SIGNATURE_LENGTH] {
+        self.signature.to_bytes()
+    }
+
+    fn section(&self) -> CustomSection<'static> {
+        let mut data = vec![FORMAT_VERSION];
+        self.key_id.encode(&mut data);
+        encode_names(&self.excluded_sections, &mut data);
+        self.signature.to_bytes()[..].encode(&mut data);
+        CustomSection {
+            name: SECTION_NAME.into(),
+            data: Cow::Owned(data),
+        }
+    }
+}
+
+/// Compute the identifier of a public key, which is stored alongside the
+/// signatures it can verify.
+///
+/// This is the hex encoding of the first 8 bytes of the SHA-256 hash of the
+/// key.
+pub fn key_id(key: &VerifyingKey) -> String {
+    Sha256::digest(key.as_bytes())[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Compute the SHA-256 digest of all top-level sections of `input` which are
+/// covered by a signature that excludes the custom sections in `exclude`.
+fn digest(input: &[u8], exclude: &[String]) -> Result<Vec<u8>> {
+    let mut hasher = Sha256::new();
+    hasher.update(DIGEST_DOMAIN);
+    // The list of excluded sections is itself part of what's signed to ensure
+    // it can't be extended to cover up modifications.
+    let mut names = Vec::new();
+    encode_names(exclude, &mut names);
+    hasher.update(&names);
+
+    let mut depth = 0;
+    for payload in Parser::new(0).parse_all(input) {
+        let payload = payload?;
+        match &payload {
+            Payload::Version { range, .. } if depth == 0 => {
+                hasher.update(&input[range.clone()]);
+                continue;
+            }
+            Payload::End { .. } => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+                continue;
+            }
+            Payload::CustomSection(c)
+                if depth == 0
+                    && (c.name() == SECTION_NAME || exclude.iter().any(|n| n == c.name())) =>
+            {
+                continue;
+            }
+            _ => {}
+        }
+        if depth == 0 {
+            if let Some((id, range)) = payload.as_section() {
+                let mut section = vec![id];
+                input[range].encode(&mut section);
+                hasher.update(&section);
+            }
+        }
+        if let Payload::ModuleSection { .. } | Payload::ComponentSection { .. } = payload {
+            depth += 1;
+        }
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Copy `input` while removing its top-level `signature` section, if any.
+fn strip_signature(input: &[u8]) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut depth = 0;
+    for payload in Parser::new(0).parse_all(input) {
+        let payload = payload?;
+        match &payload {
+            Payload::Version { range, .. } if depth == 0 => {
+                output.extend_from_slice(&input[range.clone()]);
+                continue;
+            }
+            Payload::End { .. } => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+                continue;
+            }
+            Payload::CustomSection(c) if depth == 0 && c.name() == SECTION_NAME => continue,
+            _ => {}
+        }
+        if depth == 0 {
+            if let Some((id, range)) = payload.as_section() {
+                wasm_encoder::RawSection {
+                    id,
+                    data: &input[range],
+                }
+                .append_to(&mut output);
+            }
+        }
+        if let Payload::ModuleSection { .. } | Payload::ComponentSection { .. } = payload {
+            depth += 1;
+        }
+    }
+    Ok(output)
+}
+
+fn encode_names(names: &[String], sink: &mut Vec<u8>) {
+    names.len().encode(sink);
+    for name in names {
+        name.encode(sink);
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ed25519 [{}]", self.key_id)
+    }
+}
+
+impl ComponentSection for Signature {
+    fn id(&self) -> u8 {
+        ComponentSection::id(&self.section())
+    }
+}
+
+impl Section for Signature {
+    fn id(&self) -> u8 {
+        Section::id(&self.section())
+    }
+}
+
+impl Encode for Signature {
+    fn encode(&self, sink: &mut Vec<u8>) {
+        self.section().encode(sink);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{AddMetadata, AddMetadataField};
+    use wasm_encoder::{Component, Module};
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn component() -> Vec<u8> {
+        let mut component = Component::new();
+        component.section(&wasm_encoder::ModuleSection(&Module::new()));
+        component.finish()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let wasm = Signature::sign(&component(), &key(1), &[]).unwrap();
+        for payload in Parser::new(0).parse_all(&wasm) {
+            payload.unwrap();
+        }
+
+        let signature = Signature::verify(&wasm, &key(1).verifying_key()).unwrap();
+        assert_eq!(signature.key_id(), key_id(&key(1).verifying_key()));
+        assert!(signature.excluded_sections().is_empty());
+        assert_eq!(Signature::from_wasm(&wasm).unwrap(), Some(signature));
+    }
+
+    #[test]
+    fn resign_replaces_signature() {
+        let wasm = Signature::sign(&component(), &key(1), &[]).unwrap();
+        let wasm = Signature::sign(&wasm, &key(2), &[]).unwrap();
+        let count = Parser::new(0)
+            .parse_all(&wasm)
+            .filter(|p| matches!(p, Ok(Payload::CustomSection(c)) if c.name() == SECTION_NAME))
+            .count();
+        assert_eq!(count, 1);
+        assert!(Signature::verify(&wasm, &key(1).verifying_key()).is_err());
+        Signature::verify(&wasm, &key(2).verifying_key()).unwrap();
+    }
+
+    #[test]
+    fn modification_invalidates() {
+        let wasm = Signature::sign(&component(), &key(1), &[]).unwrap();
+        let mut add = AddMetadata::default();
+        add.name = AddMetadataField::Set("foo".to_owned());
+        let wasm = add.to_wasm(&wasm).unwrap();
+        let err = Signature::verify(&wasm, &key(1).verifying_key()).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+    }
+
+    #[test]
+    fn excluded_sections() {
+        let exclude = vec!["producers".to_owned()];
+        let wasm = Signature::sign(&Module::new().finish(), &key(1), &exclude).unwrap();
+        let mut add = AddMetadata::default();
+        add.processed_by = vec![("baz".to_owned(), "1.0".to_owned())];
+        let wasm = add.to_wasm(&wasm).unwrap();
+        let signature = Signature::verify(&wasm, &key(1).verifying_key()).unwrap();
+        assert_eq!(signature.excluded_sections(), exclude);
+    }
+
+    #[test]
+    fn unsigned() {
+        let wasm = Module::new().finish();
+        assert_eq!(Signature::from_wasm(&wasm).unwrap(), None);
+        assert!(Signature::verify(&wasm, &key(1).verifying_key()).is_err());
+    }
+}
@@ -84,3 +84,46 @@ fn add_to_empty_module() {
         _ => panic!("metadata should be module"),
     }
 }
+
+#[test]
+fn oci_annotations_roundtrip() {
+    let mut annotations = indexmap::IndexMap::new();
+    for (key, value) in [
+        (oci_annotations::TITLE, "foo"),
+        (oci_annotations::AUTHORS, "Chashu Cat"),
+        (oci_annotations::LICENSES, "Apache-2.0 OR MIT"),
+        (oci_annotations::VERSION, "1.0.0"),
+        ("org.opencontainers.image.created", "2024-01-01T00:00:00Z"),
+    ] {
+        annotations.insert(key.to_owned(), value.to_owned());
+    }
+
+    let mut add = AddMetadata::default();
+    add.version = AddMetadataField::Set(Version::new("2.0.0"));
+    add.add_oci_annotations(&annotations).unwrap();
+    let module = add.to_wasm(&Module::new().finish()).unwrap();
+
+    // Adding the annotations again replaces the existing sections rather than
+    // duplicating them.
+    let module = add.to_wasm(&module).unwrap();
+    let authors = wasmparser::Parser::new(0)
+        .parse_all(&module)
+        .filter(|p| matches!(p, Ok(wasmparser::Payload::CustomSection(c)) if c.name() == "authors"))
+        .count();
+    assert_eq!(authors, 1);
+
+    let metadata = Payload::from_binary(&module).unwrap();
+    let roundtrip = metadata.metadata().oci_annotations();
+    annotations.shift_remove("org.opencontainers.image.created");
+    annotations.insert(oci_annotations::VERSION.to_owned(), "2.0.0".to_owned());
+    assert_eq!(roundtrip, annotations);
+}
+
+#[test]
+fn oci_annotations_invalid() {
+    let mut annotations = indexmap::IndexMap::new();
+    annotations.insert(oci_annotations::SOURCE.to_owned(), "not a url".to_owned());
+    let mut add = AddMetadata::default();
+    let err = add.add_oci_annotations(&annotations).unwrap_err();
+    assert!(err.to_string().contains(oci_annotations::SOURCE), "{err}");
+}
@@ -0,0 +1,210 @@
+use std::str::FromStr;
+
+use auditable_serde::VersionInfo;
+use serde_json::Value;
+use wasm_encoder::{Component, Module};
+use wasm_metadata::*;
+
+/// Create a component named `app` containing a module named `foo` which has a
+/// dependency tree recorded by `cargo auditable`.
+fn nested_component() -> Vec<u8> {
+    let mut add = AddMetadata::default();
+    add.name = AddMetadataField::Set("foo".to_owned());
+    add.language = vec![("Rust".to_owned(), "".to_owned())];
+    add.processed_by = vec![("rustc".to_owned(), "1.80.0".to_owned())];
+    add.licenses = AddMetadataField::Set(Licenses::new("Apache-2.0 OR MIT").unwrap());
+    add.source = AddMetadataField::Set(Source::new("https://github.com/chashu/foo").unwrap());
+    add.revision = AddMetadataField::Set(Revision::new("de978e17a80c1118f606fce919ba9b7d5a04a5ad"));
+    add.version = AddMetadataField::Set(Version::new("1.0.0"));
+
+    let json_str = r#"{"packages":[
+        {"name":"foo","version":"1.0.0","source":"local","dependencies":[1,2],"root":true},
+        {"name":"adler","version":"0.2.3","source":"crates.io"},
+        {"name":"cc","version":"1.0.0","source":"crates.io","kind":"build"}
+    ]}"#;
+    let info = VersionInfo::from_str(json_str).unwrap();
+    let mut module = Module::new();
+    module.section(&Dependencies::new(info));
+    let module = add.to_wasm(&module.finish()).unwrap();
+
+    let mut component = Component::new();
+    component.section(&wasm_encoder::RawSection {
+        id: wasm_encoder::ComponentSectionId::CoreModule.into(),
+        data: &module,
+    });
+    let mut add = AddMetadata::default();
+    add.name = AddMetadataField::Set("app".to_owned());
+    add.authors = AddMetadataField::Set(Authors::new("Chashu Cat"));
+    add.licenses = AddMetadataField::Set(Licenses::new("MIT").unwrap());
+    add.to_wasm(&component.finish()).unwrap()
+}
+
+fn sbom(format: SbomFormat, wasm: &[u8]) -> Value {
+    let mut sbom = Sbom::default();
+    sbom.format = format;
+    sbom.created = Some("2024-01-01T00:00:00Z".to_owned());
+    serde_json::from_str(&sbom.to_json(wasm).unwrap()).unwrap()
+}
+
+#[test]
+fn spdx() {
+    let doc = sbom(SbomFormat::Spdx, &nested_component());
+    assert_eq!(doc["spdxVersion"], "SPDX-2.3");
+    assert_eq!(doc["name"], "app");
+    assert_eq!(doc["creationInfo"]["created"], "2024-01-01T00:00:00Z");
+    assert!(
+        doc["documentNamespace"]
+            .as_str()
+            .unwrap()
+            .starts_with("https://spdx.org/spdxdocs/app-")
+    );
+
+    let packages = doc["packages"].as_array().unwrap();
+    let names = packages
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["app", "foo", "foo", "adler", "cc"]);
+
+    let app = &packages[0];
+    assert_eq!(app["SPDXID"], "SPDXRef-wasm-0");
+    assert_eq!(app["primaryPackagePurpose"], "APPLICATION");
+    assert_eq!(app["licenseDeclared"], "MIT");
+    assert_eq!(app["originator"], "Person: Chashu Cat");
+    assert_eq!(app["downloadLocation"], "NOASSERTION");
+    assert_eq!(app["checksums"][0]["algorithm"], "SHA256");
+
+    let foo = &packages[1];
+    assert_eq!(foo["SPDXID"], "SPDXRef-wasm-1");
+    assert_eq!(foo["versionInfo"], "1.0.0");
+    assert_eq!(foo["licenseDeclared"], "Apache-2.0 OR MIT");
+    assert_eq!(foo["downloadLocation"], "https://github.com/chashu/foo");
+    assert_eq!(
+        foo["sourceInfo"],
+        "built from revision de978e17a80c1118f606fce919ba9b7d5a04a5ad"
+    );
+    assert_eq!(foo["comment"], "language: Rust\nprocessed-by: rustc 1.80.0");
+
+    let adler = &packages[3];
+    assert_eq!(adler["SPDXID"], "SPDXRef-crate-adler-0.2.3");
+    assert_eq!(
+        adler["externalRefs"][0]["referenceLocator"],
+        "pkg:cargo/adler@0.2.3"
+    );
+
+    let relationships = doc["relationships"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            format!(
+                "{} {} {}",
+                r["spdxElementId"].as_str().unwrap(),
+                r["relationshipType"].as_str().unwrap(),
+                r["relatedSpdxElement"].as_str().unwrap()
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        relationships,
+        [
+            "SPDXRef-DOCUMENT DESCRIBES SPDXRef-wasm-0",
+            "SPDXRef-wasm-0 CONTAINS SPDXRef-wasm-1",
+            "SPDXRef-wasm-1 DEPENDS_ON SPDXRef-crate-foo-1.0.0-local",
+            "SPDXRef-crate-foo-1.0.0-local DEPENDS_ON SPDXRef-crate-adler-0.2.3",
+            "SPDXRef-crate-cc-1.0.0 BUILD_DEPENDENCY_OF SPDXRef-crate-foo-1.0.0-local",
+        ]
+    );
+}
+
+#[test]
+fn cyclonedx() {
+    let doc = sbom(SbomFormat::CycloneDx, &nested_component());
+    assert_eq!(doc["bomFormat"], "CycloneDX");
+    assert_eq!(doc["specVersion"], "1.5");
+    assert_eq!(doc["metadata"]["timestamp"], "2024-01-01T00:00:00Z");
+
+    let app = &doc["metadata"]["component"];
+    assert_eq!(app["bom-ref"], "wasm-0");
+    assert_eq!(app["type"], "application");
+    assert_eq!(app["name"], "app");
+    assert_eq!(app["author"], "Chashu Cat");
+    assert_eq!(app["licenses"][0]["expression"], "MIT");
+    assert!(app.get("components").is_none());
+
+    let components = doc["components"].as_array().unwrap();
+    let refs = components
+        .iter()
+        .map(|c| c["bom-ref"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        refs,
+        [
+            "wasm-1",
+            "crate-foo-1.0.0-local",
+            "crate-adler-0.2.3",
+            "crate-cc-1.0.0"
+        ]
+    );
+
+    let foo = &components[0];
+    assert_eq!(foo["type"], "library");
+    assert_eq!(foo["version"], "1.0.0");
+    assert_eq!(foo["hashes"][0]["alg"], "SHA-256");
+    assert_eq!(foo["externalReferences"][0]["type"], "vcs");
+    assert_eq!(foo["properties"][0]["name"], "wasm:revision");
+    assert_eq!(foo["properties"][1]["name"], "wasm:producers:language");
+    assert_eq!(foo["properties"][1]["value"], "Rust");
+    assert_eq!(components[2]["purl"], "pkg:cargo/adler@0.2.3");
+
+    let dependencies = doc["dependencies"].as_array().unwrap();
+    assert_eq!(dependencies[1]["ref"], "wasm-1");
+    assert_eq!(dependencies[1]["dependsOn"][0], "crate-foo-1.0.0-local");
+    assert_eq!(dependencies[2]["ref"], "crate-foo-1.0.0-local");
+    assert_eq!(
+        dependencies[2]["dependsOn"],
+        serde_json::json!(["crate-adler-0.2.3", "crate-cc-1.0.0"])
+    );
+}
+
+#[test]
+fn module_without_metadata() {
+    let doc = sbom(SbomFormat::Spdx, &Module::new().finish());
+    assert_eq!(doc["name"], "unknown");
+    let packages = doc["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0]["primaryPackagePurpose"], "LIBRARY");
+    assert_eq!(packages[0]["licenseDeclared"], "NOASSERTION");
+
+    let doc = sbom(SbomFormat::CycloneDx, &Module::new().finish());
+    assert_eq!(doc["metadata"]["component"]["type"], "library");
+    assert!(doc["components"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn same_crate_from_different_sources() {
+    let json_str = r#"{"packages":[
+        {"name":"app","version":"0.1.0","source":"local","dependencies":[1,2],"root":true},
+        {"name":"adler","version":"0.2.3","source":"crates.io"},
+        {"name":"adler","version":"0.2.3","source":"git"}
+    ]}"#;
+    let info = VersionInfo::from_str(json_str).unwrap();
+    let mut module = Module::new();
+    module.section(&Dependencies::new(info));
+    let doc = sbom(SbomFormat::CycloneDx, &module.finish());
+
+    let refs = doc["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["bom-ref"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        refs,
+        [
+            "crate-app-0.1.0-local",
+            "crate-adler-0.2.3",
+            "crate-adler-0.2.3-git"
+        ]
+    );
+}
@@ -5,7 +5,7 @@ use crate::{
 use std::collections::HashSet;
 use std::ops::Range;
 use wasm_encoder::{RawSection, SectionId};
-use wasmparser::{BinaryReader, Chunk, Parser, Payload};
+use wasmparser::{BinaryReader, Chunk, Operator, Parser, Payload};
 
 /// Provides module information for future usage during mutation
 /// an instance of ModuleInfo could be user to determine which mutation could be applied
@@ -21,6 +21,7 @@ pub struct ModuleInfo<'a> {
     pub imports: Option<usize>,
     pub tables: Option<usize>,
     pub memories: Option<usize>,
+    pub tags: Option<usize>,
     pub globals: Option<usize>,
     pub elements: Option<usize>,
     pub functions: Option<usize>,
@@ -36,6 +37,7 @@ pub struct ModuleInfo<'a> {
     memory_count: u32,
     table_count: u32,
     tag_count: u32,
+    rec_group_count: u32,
 
     imported_functions_count: u32,
     imported_globals_count: u32,
@@ -48,10 +50,22 @@ pub struct ModuleInfo<'a> {
 
     // function idx to type idx
     pub function_map: Vec<u32>,
+    // tag idx to type idx
+    pub tag_map: Vec<u32>,
     pub global_types: Vec<PrimitiveTypeInfo>,
     pub table_types: Vec<wasmparser::TableType>,
     pub memory_types: Vec<wasmparser::MemoryType>,
 
+    // Whether the code of the input already uses instructions from these
+    // proposals. Mutators that introduce instructions from a proposal only
+    // run on inputs that already use it, so that they don't require new
+    // features of whoever consumes the mutated module.
+    pub uses_tail_calls: bool,
+    // Only the `try_table` flavor of exception handling counts, since the
+    // legacy `try` instructions are a separate feature.
+    pub uses_exceptions: bool,
+    pub uses_gc: bool,
+
     // raw_sections
     pub raw_sections: Vec<RawSection<'a>>,
     pub input_wasm: &'a [u8],
@@ -80,6 +94,9 @@ impl<'a> ModuleInfo<'a> {
                 } => {
                     info.code = Some(info.raw_sections.len());
                     info.section(SectionId::Code.into(), range.clone(), input_wasm);
+                    // Function bodies aren't otherwise parsed here, so
+                    // leave any errors in them for the mutators to report.
+                    let _ = info.scan_proposals(&input_wasm[range.clone()], range.start);
                     parser.skip_section();
                     // update slice, bypass the section
                     wasm = &input_wasm[range.end..];
@@ -90,9 +107,21 @@ impl<'a> ModuleInfo<'a> {
                     info.types = Some(info.raw_sections.len());
                     info.section(SectionId::Type.into(), reader.range(), input_wasm);
 
-                    // Save function types
-                    for ty in reader.into_iter_err_on_gc_types() {
-                        info.types_map.push(ty?.try_into()?);
+                    // Save all types, flattening out their rec groups
+                    for group in reader {
+                        info.rec_group_count += 1;
+                        for ty in group?.into_types() {
+                            let ty = TypeInfo::try_from(ty)?;
+                            match &ty {
+                                TypeInfo::Func(ty) => {
+                                    if ty.params.iter().chain(&ty.returns).any(is_exnref) {
+                                        info.uses_exceptions = true;
+                                    }
+                                }
+                                TypeInfo::Struct(_) | TypeInfo::Array(_) => info.uses_gc = true,
+                            }
+                            info.types_map.push(ty);
+                        }
                     }
                 }
                 Payload::ImportSection(reader) => {
@@ -121,7 +150,8 @@ impl<'a> ModuleInfo<'a> {
                                 info.imported_tables_count += 1;
                                 info.table_types.push(ty);
                             }
-                            wasmparser::TypeRef::Tag(_ty) => {
+                            wasmparser::TypeRef::Tag(ty) => {
+                                info.tag_map.push(ty.func_type_idx);
                                 info.tag_count += 1;
                                 info.imported_tags_count += 1;
                             }
@@ -155,6 +185,15 @@ impl<'a> ModuleInfo<'a> {
                         info.memory_types.push(ty?);
                     }
                 }
+                Payload::TagSection(reader) => {
+                    info.tags = Some(info.raw_sections.len());
+                    info.tag_count += reader.count();
+                    info.section(SectionId::Tag.into(), reader.range(), input_wasm);
+
+                    for ty in reader {
+                        info.tag_map.push(ty?.func_type_idx);
+                    }
+                }
                 Payload::GlobalSection(reader) => {
                     info.globals = Some(info.raw_sections.len());
                     info.section(SectionId::Global.into(), reader.range(), input_wasm);
@@ -229,6 +268,36 @@ impl<'a> ModuleInfo<'a> {
         }
     }
 
+    /// Record which proposals the operators in the given code section
+    /// belong to.
+    fn scan_proposals(&mut self, section: &[u8], offset: usize) -> Result<()> {
+        let reader = BinaryReader::new(section, offset);
+        for body in wasmparser::CodeSectionReader::new(reader)? {
+            let body = body?;
+            for local in body.get_locals_reader()? {
+                let (_, ty) = local?;
+                if PrimitiveTypeInfo::try_from(ty).is_ok_and(|ty| is_exnref(&ty)) {
+                    self.uses_exceptions = true;
+                }
+            }
+            let mut operators = body.get_operators_reader()?;
+            while !operators.eof() {
+                let op = operators.read()?;
+                match operator_proposal(&op) {
+                    "tail_call" => self.uses_tail_calls = true,
+                    // A `throw` on its own may be paired with legacy `try`
+                    // blocks instead.
+                    "exceptions" if !matches!(op, Operator::Throw { .. }) => {
+                        self.uses_exceptions = true;
+                    }
+                    "gc" => self.uses_gc = true,
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn has_code(&self) -> bool {
         self.code != None
     }
@@ -414,4 +483,35 @@ impl<'a> ModuleInfo<'a> {
     pub fn num_types(&self) -> u32 {
         self.types_map.len() as u32
     }
+
+    pub fn num_rec_groups(&self) -> u32 {
+        self.rec_group_count
+    }
+}
+
+fn is_exnref(ty: &PrimitiveTypeInfo) -> bool {
+    ty.ref_type().is_some_and(|ty| {
+        matches!(
+            ty.heap_type(),
+            wasmparser::HeapType::Abstract {
+                ty: wasmparser::AbstractHeapType::Exn,
+                ..
+            }
+        )
+    })
+}
+
+/// Returns the name of the proposal that `op` originates from.
+fn operator_proposal(op: &Operator<'_>) -> &'static str {
+    macro_rules! define_operator_proposal {
+        ($( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident ($($ann:tt)*))*) => {
+            match op {
+                $(
+                    Operator::$op { .. } => stringify!($proposal),
+                )*
+                _ => "unknown",
+            }
+        };
+    }
+    wasmparser::for_each_operator!(define_operator_proposal)
 }
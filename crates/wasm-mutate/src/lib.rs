@@ -17,13 +17,13 @@ mod mutators;
 pub use error::*;
 
 use crate::mutators::{
-    Item, add_function::AddFunctionMutator, add_type::AddTypeMutator,
+    Item, add_function::AddFunctionMutator, add_type::AddTypeMutator, br_on_cast::BrOnCastMutator,
     codemotion::CodemotionMutator, custom::AddCustomSectionMutator, custom::CustomSectionMutator,
     custom::ReorderCustomSectionMutator, function_body_unreachable::FunctionBodyUnreachable,
     modify_const_exprs::ConstExpressionMutator, modify_data::ModifyDataMutator,
     peephole::PeepholeMutator, remove_export::RemoveExportMutator, remove_item::RemoveItemMutator,
     remove_section::RemoveSection, rename_export::RenameExportMutator, snip_function::SnipMutator,
-    start::RemoveStartSection,
+    start::RemoveStartSection, tail_call::TailCallMutator, try_table::TryTableMutator,
 };
 use info::ModuleInfo;
 use mutators::Mutator;
@@ -212,6 +212,9 @@ impl<'wasm> WasmMutate<'wasm> {
             &RenameExportMutator { max_name_size: 100 },
             &SnipMutator,
             &CodemotionMutator,
+            &TailCallMutator,
+            &TryTableMutator,
+            &BrOnCastMutator,
             &FunctionBodyUnreachable,
             &AddCustomSectionMutator,
             &ReorderCustomSectionMutator,
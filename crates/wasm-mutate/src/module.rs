@@ -10,18 +10,62 @@ pub enum PrimitiveTypeInfo {
     V128,
     FuncRef,
     ExternRef,
+    /// Any other reference type, such as those of the GC proposal.
+    Ref(wasmparser::RefType),
     Empty,
 }
 
+impl PrimitiveTypeInfo {
+    /// Returns the reference type this type stands for, if it is one.
+    pub fn ref_type(self) -> Option<wasmparser::RefType> {
+        match self {
+            PrimitiveTypeInfo::FuncRef => Some(wasmparser::RefType::FUNCREF),
+            PrimitiveTypeInfo::ExternRef => Some(wasmparser::RefType::EXTERNREF),
+            PrimitiveTypeInfo::Ref(ty) => Some(ty),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FuncInfo {
     pub params: Vec<PrimitiveTypeInfo>,
     pub returns: Vec<PrimitiveTypeInfo>,
 }
 
+/// The type of a struct field or array element.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StorageTypeInfo {
+    I8,
+    I16,
+    Val(PrimitiveTypeInfo),
+}
+
+impl StorageTypeInfo {
+    /// The type that values of this storage type are read as.
+    pub fn unpacked(self) -> PrimitiveTypeInfo {
+        match self {
+            StorageTypeInfo::I8 | StorageTypeInfo::I16 => PrimitiveTypeInfo::I32,
+            StorageTypeInfo::Val(ty) => ty,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructInfo {
+    pub fields: Vec<StorageTypeInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArrayInfo {
+    pub element: StorageTypeInfo,
+}
+
 #[derive(Debug, Clone)]
 pub enum TypeInfo {
     Func(FuncInfo),
+    Struct(StructInfo),
+    Array(ArrayInfo),
     // TODO: module linking support will require instance and module types.
 }
 
@@ -47,11 +91,44 @@ impl TryFrom<wasmparser::RefType> for PrimitiveTypeInfo {
         Ok(match value {
             wasmparser::RefType::FUNCREF => PrimitiveTypeInfo::FuncRef,
             wasmparser::RefType::EXTERNREF => PrimitiveTypeInfo::ExternRef,
-            other => return Err(Error::unsupported(format!("type {other:?}"))),
+            other => PrimitiveTypeInfo::Ref(other),
         })
     }
 }
 
+impl TryFrom<wasmparser::StorageType> for StorageTypeInfo {
+    type Error = Error;
+
+    fn try_from(value: wasmparser::StorageType) -> Result<Self> {
+        Ok(match value {
+            wasmparser::StorageType::I8 => StorageTypeInfo::I8,
+            wasmparser::StorageType::I16 => StorageTypeInfo::I16,
+            wasmparser::StorageType::Val(ty) => StorageTypeInfo::Val(ty.try_into()?),
+        })
+    }
+}
+
+impl TryFrom<wasmparser::SubType> for TypeInfo {
+    type Error = Error;
+
+    fn try_from(ty: wasmparser::SubType) -> Result<Self> {
+        match ty.composite_type.inner {
+            wasmparser::CompositeInnerType::Func(ft) => ft.try_into(),
+            wasmparser::CompositeInnerType::Struct(st) => Ok(TypeInfo::Struct(StructInfo {
+                fields: st
+                    .fields
+                    .iter()
+                    .map(|f| StorageTypeInfo::try_from(f.element_type))
+                    .collect::<Result<_>>()?,
+            })),
+            wasmparser::CompositeInnerType::Array(at) => Ok(TypeInfo::Array(ArrayInfo {
+                element: at.0.element_type.try_into()?,
+            })),
+            other => Err(Error::unsupported(format!("type {other:?}"))),
+        }
+    }
+}
+
 impl TryFrom<wasmparser::FuncType> for TypeInfo {
     type Error = Error;
 
@@ -24,6 +24,7 @@
 
 pub mod add_function;
 pub mod add_type;
+pub mod br_on_cast;
 pub mod codemotion;
 pub mod custom;
 pub mod function_body_unreachable;
@@ -36,16 +37,20 @@ pub mod remove_section;
 pub mod rename_export;
 pub mod snip_function;
 pub mod start;
+pub mod tail_call;
+pub mod try_table;
 
 mod translate;
 pub use self::translate::Item;
 
 use std::borrow::Cow;
+use std::ops::Range;
 
 use super::Result;
-use crate::WasmMutate;
-use wasm_encoder::Module;
-use wasmparser::Operator;
+use crate::{Error, WasmMutate};
+use rand::Rng;
+use wasm_encoder::{CodeSection, Module};
+use wasmparser::{CodeSectionReader, Operator};
 
 /// A mutation that can be applied to a Wasm module to produce a new, mutated
 /// Wasm module.
@@ -107,6 +112,73 @@ pub trait Mutator {
 /// Type helper to wrap operator and the byte offset in the code section of a Wasm module
 pub type OperatorAndByteOffset<'a> = (Operator<'a>, usize);
 
+/// The replacement for a range of operators in a function body: the indices of
+/// the operators to replace and the encoded instructions to replace them with.
+pub(crate) type OperatorsReplacement = (Range<usize>, Vec<u8>);
+
+/// Replaces a range of operators in one of the module's defined functions.
+///
+/// Functions are visited starting at a random one, and `replace` is called
+/// with each function's index and operators until it returns a replacement.
+/// Everything outside of the replaced range, including the function's locals,
+/// is copied over as-is.
+pub(crate) fn replace_operators<'a>(
+    config: &mut WasmMutate<'a>,
+    mut replace: impl FnMut(
+        &mut WasmMutate<'a>,
+        u32,
+        &[OperatorAndByteOffset<'a>],
+    ) -> Result<Option<OperatorsReplacement>>,
+) -> Result<Module> {
+    let code_section = config
+        .info()
+        .code
+        .ok_or_else(Error::no_mutations_applicable)?;
+    let reader = CodeSectionReader::new(config.info().get_binary_reader(code_section))?;
+    let bodies = reader.into_iter().collect::<wasmparser::Result<Vec<_>>>()?;
+    if bodies.is_empty() {
+        return Err(Error::no_mutations_applicable());
+    }
+
+    let start = config.rng().random_range(0..bodies.len());
+    for i in (start..bodies.len()).chain(0..start) {
+        config.consume_fuel(1)?;
+        let body = &bodies[i];
+        let operators = body
+            .get_operators_reader()?
+            .into_iter_with_offsets()
+            .collect::<wasmparser::Result<Vec<OperatorAndByteOffset>>>()?;
+        let function_index = config.info().num_imported_functions() + i as u32;
+        let Some((range, replacement)) = replace(config, function_index, &operators)? else {
+            continue;
+        };
+        log::trace!("Replacing operators {range:?} of function {function_index}");
+
+        // Operator offsets are relative to the same data as the body's range.
+        let bytes = body.as_bytes();
+        let base = body.range().start;
+        let start = operators[range.start].1 - base;
+        let end = operators
+            .get(range.end)
+            .map_or(bytes.len(), |(_, offset)| offset - base);
+        let mut new_body = bytes[..start].to_vec();
+        new_body.extend_from_slice(&replacement);
+        new_body.extend_from_slice(&bytes[end..]);
+
+        let mut codes = CodeSection::new();
+        for (j, body) in bodies.iter().enumerate() {
+            if i == j {
+                codes.raw(&new_body);
+            } else {
+                codes.raw(body.as_bytes());
+            }
+        }
+        return Ok(config.info().replace_section(code_section, &codes));
+    }
+
+    Err(Error::no_mutations_applicable())
+}
+
 #[cfg(test)]
 fn match_mutation<T>(original: &str, mutator: T, expected: &str)
 where
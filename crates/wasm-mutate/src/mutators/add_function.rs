@@ -1,9 +1,9 @@
 //! Mutator that adds new, empty functions.
 
 use super::Mutator;
-use crate::module::{PrimitiveTypeInfo, TypeInfo};
-use crate::{Result, WasmMutate};
-use rand::Rng;
+use crate::module::{PrimitiveTypeInfo, TypeInfo, map_ref_type};
+use crate::{Error, Result, WasmMutate};
+use rand::prelude::*;
 use wasm_encoder::{AbstractHeapType, HeapType, Module};
 
 /// Mutator that adds new, empty functions to a Wasm module.
@@ -15,8 +15,14 @@ impl Mutator for AddFunctionMutator {
         &self,
         config: &'a mut WasmMutate,
     ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
-        let max_ty_idx = config.info().num_types() - 1;
-        let ty_idx = config.rng().random_range(0..=max_ty_idx);
+        // Only function types can be used for the new function, so skip over
+        // any struct or array types.
+        let func_tys = (0..config.info().num_types())
+            .filter(|i| matches!(config.info().types_map[*i as usize], TypeInfo::Func(_)))
+            .collect::<Vec<_>>();
+        let ty_idx = *func_tys
+            .choose(config.rng())
+            .ok_or_else(Error::no_mutations_applicable)?;
 
         // (Re)encode the function section and add this new entry.
         let mut func_sec_enc = wasm_encoder::FunctionSection::new();
@@ -42,6 +48,7 @@ impl Mutator for AddFunctionMutator {
         }
         let func_ty = match &config.info().types_map[usize::try_from(ty_idx).unwrap()] {
             TypeInfo::Func(func_ty) => func_ty,
+            TypeInfo::Struct(_) | TypeInfo::Array(_) => unreachable!(),
         };
         let mut func = wasm_encoder::Function::new(vec![]);
         for ty in &func_ty.returns {
@@ -73,6 +80,14 @@ impl Mutator for AddFunctionMutator {
                         ty: AbstractHeapType::Extern,
                     });
                 }
+                PrimitiveTypeInfo::Ref(ty) if ty.is_nullable() => {
+                    func.instructions().ref_null(map_ref_type(*ty)?.heap_type);
+                }
+                // There's no default value for non-nullable references, so
+                // just trap instead.
+                PrimitiveTypeInfo::Ref(_) => {
+                    func.instructions().unreachable();
+                }
                 PrimitiveTypeInfo::Empty => unreachable!(),
             }
         }
@@ -106,9 +121,12 @@ impl Mutator for AddFunctionMutator {
                         added_func = true;
                     }
 
+                    // The data count and tag sections have larger ids than
+                    // the code section but come before it.
                     if !added_code
                         && sec_id >= wasm_encoder::SectionId::Code as u8
                         && sec_id != wasm_encoder::SectionId::DataCount as u8
+                        && sec_id != wasm_encoder::SectionId::Tag as u8
                     {
                         module.section(&code_sec_enc);
                         added_code = true;
@@ -0,0 +1,188 @@
+//! Mutator that simplifies `br_on_cast` and `br_on_cast_fail` instructions
+//! whose outcome is known statically.
+
+use super::{Mutator, OperatorAndByteOffset, OperatorsReplacement, replace_operators};
+use crate::{Result, WasmMutate};
+use rand::prelude::*;
+use wasm_encoder::{InstructionSink, Module};
+use wasmparser::Operator;
+
+/// Rewrites `br_on_cast` and `br_on_cast_fail` instructions that are really
+/// unconditional or null checks:
+///
+/// * A `br_on_cast` to the operand's own type always branches, so it becomes
+///   a `br`.
+/// * A `br_on_cast_fail` to the operand's own type never branches, so it is
+///   removed.
+/// * A `br_on_cast` from `(ref null ht)` to `(ref ht)` only fails on null, so
+///   it becomes a `br_on_non_null` followed by the null that was left on the
+///   stack.
+#[derive(Clone, Copy)]
+pub struct BrOnCastMutator;
+
+impl BrOnCastMutator {
+    fn replacement(
+        &self,
+        config: &mut WasmMutate,
+        operators: &[OperatorAndByteOffset],
+    ) -> Result<Option<OperatorsReplacement>> {
+        let candidates = operators
+            .iter()
+            .enumerate()
+            .filter(|(_, (op, _))| match op {
+                Operator::BrOnCast {
+                    from_ref_type,
+                    to_ref_type,
+                    ..
+                } => {
+                    from_ref_type == to_ref_type
+                        || (from_ref_type.is_nullable()
+                            && *to_ref_type == from_ref_type.as_non_null())
+                }
+                Operator::BrOnCastFail {
+                    from_ref_type,
+                    to_ref_type,
+                    ..
+                } => from_ref_type == to_ref_type,
+                _ => false,
+            })
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        let i = match candidates.choose(config.rng()) {
+            Some(i) => *i,
+            None => return Ok(None),
+        };
+        let mut bytes = Vec::new();
+        let mut insn = InstructionSink::new(&mut bytes);
+        match &operators[i].0 {
+            Operator::BrOnCast {
+                relative_depth,
+                from_ref_type,
+                to_ref_type,
+            } => {
+                if from_ref_type == to_ref_type {
+                    insn.br(*relative_depth);
+                } else {
+                    insn.br_on_non_null(*relative_depth)
+                        .ref_null(from_ref_type.heap_type().try_into()?);
+                }
+            }
+            Operator::BrOnCastFail { .. } => {}
+            _ => unreachable!(),
+        }
+        Ok(Some((i..i + 1, bytes)))
+    }
+}
+
+impl Mutator for BrOnCastMutator {
+    fn mutate<'a>(
+        &self,
+        config: &mut WasmMutate<'a>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let module = replace_operators(config, |config, _, operators| {
+            self.replacement(config, operators)
+        })?;
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.info().has_nonempty_code() && config.info().uses_gc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BrOnCastMutator;
+    use crate::WasmMutate;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn no_casts_without_gc_proposal() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (param funcref) (result funcref)
+                    local.get 0)
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!BrOnCastMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn br_on_cast_to_same_type() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (param anyref) (result anyref)
+                    local.get 0
+                    br_on_cast 0 anyref anyref)
+            )
+            "#,
+            BrOnCastMutator,
+            r#"
+            (module
+                (func (param anyref) (result anyref)
+                    local.get 0
+                    br 0)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn br_on_cast_fail_to_same_type() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (type $s (struct))
+                (func (param (ref $s)) (result (ref $s))
+                    local.get 0
+                    br_on_cast_fail 0 (ref $s) (ref $s))
+            )
+            "#,
+            BrOnCastMutator,
+            r#"
+            (module
+                (type $s (struct))
+                (func (param (ref $s)) (result (ref $s))
+                    local.get 0)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn br_on_cast_to_non_null() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (type $s (struct))
+                (func (param (ref null $s)) (result (ref null $s))
+                    block (result (ref $s))
+                        local.get 0
+                        br_on_cast 0 (ref null $s) (ref $s)
+                        return
+                    end)
+            )
+            "#,
+            BrOnCastMutator,
+            r#"
+            (module
+                (type $s (struct))
+                (func (param (ref null $s)) (result (ref null $s))
+                    block (result (ref $s))
+                        local.get 0
+                        br_on_non_null 0
+                        ref.null $s
+                        return
+                    end)
+            )
+            "#,
+        );
+    }
+}
@@ -148,7 +148,12 @@ impl<'cfg, 'wasm> Reencode for InitTranslator<'cfg, 'wasm> {
             }),
             T::FuncRef => CE::ref_null(wasm_encoder::HeapType::FUNC),
             T::ExternRef => CE::ref_null(wasm_encoder::HeapType::EXTERN),
-            T::Empty => unreachable!(),
+            T::Ref(ty) if ty.is_nullable() => CE::ref_null(self.heap_type(ty.heap_type())?),
+            // There's no constant of a non-nullable reference type that is
+            // simpler than reading the global.
+            T::Ref(_) | T::Empty => {
+                return Err(reencode::Error::UserError(Error::no_mutations_applicable()));
+            }
         };
         log::trace!("... replacing original expression with {new_op:?}");
         Ok(new_op)
@@ -278,6 +283,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reduce_global_xref_gc() {
+        match_reduction(
+            r#"(module
+                (type $s (struct))
+                (import "m" "g" (global (ref null $s)))
+                (global (ref null $s) (global.get 0)))"#,
+            super::ConstExpressionMutator::Global,
+            r#"(module
+                (type $s (struct))
+                (import "m" "g" (global (ref null $s)))
+                (global (ref null $s) (ref.null $s)))"#,
+        );
+    }
+
+    #[test]
+    fn no_reduction_of_non_nullable_global_xref() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (type $s (struct))
+                (import "m" "g" (global (ref $s)))
+                (global (ref $s) (global.get 0)))"#,
+        )
+        .unwrap();
+        let mut config = crate::WasmMutate::default();
+        config.reduce = true;
+        config.setup(&wasm).unwrap();
+        let result = crate::Mutator::mutate(&super::ConstExpressionMutator::Global, &mut config);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn reduce_global_const_f32() {
         match_reduction(
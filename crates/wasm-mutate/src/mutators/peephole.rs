@@ -98,6 +98,9 @@ impl PeepholeMutator {
 
                 Ok(all_locals)
             }
+            crate::module::TypeInfo::Struct(_) | crate::module::TypeInfo::Array(_) => {
+                unreachable!("functions always have function types")
+            }
         }
     }
 
@@ -1564,6 +1567,158 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_peep_i31() {
+        let rules: &[Rewrite<super::Lang, PeepholeMutationAnalysis>] = &[rewrite!(
+            "i31.get_u-ref.i31";
+            "(i31.get_u (ref.i31 ?x))" => "(i32.and ?x i32.const.2147483647)"
+        )];
+
+        test_peephole_mutator(
+            r#"
+            (module
+                (func (export "exported_func") (param i32) (result i32)
+                    local.get 0
+                    ref.i31
+                    i31.get_u
+                )
+            )
+            "#,
+            rules,
+            r#"
+            (module
+                (type (;0;) (func (param i32) (result i32)))
+                (func (;0;) (type 0) (param i32) (result i32)
+                    local.get 0
+                    i32.const 2147483647
+                    i32.and)
+                (export "exported_func" (func 0)))
+            "#,
+            0,
+        );
+    }
+
+    #[test]
+    fn test_peep_struct_get_packed() {
+        let rules: &[Rewrite<super::Lang, PeepholeMutationAnalysis>] = &[rewrite!(
+            "packed-get_s";
+            "(struct.get_s.0.1 ?x)" => "(i32.extend8_s (struct.get_u.0.1 ?x))"
+        )];
+
+        test_peephole_mutator(
+            r#"
+            (module
+                (type $s (struct (field i32) (field i8)))
+                (func (export "exported_func") (result i32)
+                    i32.const 1
+                    i32.const -1
+                    struct.new $s
+                    struct.get_s $s 1
+                )
+            )
+            "#,
+            rules,
+            r#"
+            (module
+                (type $s (struct (field i32) (field i8)))
+                (type (;1;) (func (result i32)))
+                (func (;0;) (type 1) (result i32)
+                    i32.const 1
+                    i32.const -1
+                    struct.new $s
+                    struct.get_u $s 1
+                    i32.extend8_s)
+                (export "exported_func" (func 0)))
+            "#,
+            0,
+        );
+    }
+
+    #[test]
+    fn ref_cast_to_own_type() {
+        test_default_peephole_mutator(
+            "(module
+                (type $s (struct))
+                (func (param (ref null $s)) (result (ref null $s))
+                    local.get 0)
+            )",
+            "(module
+                (type $s (struct))
+                (func (param (ref null $s)) (result (ref null $s))
+                    local.get 0
+                    ref.cast (ref null $s))
+            )",
+            0,
+        );
+    }
+
+    #[test]
+    fn ref_identity_ref_func() {
+        test_peephole_mutations_validate(
+            "(module
+                (type $t (func (result (ref $t))))
+                (type $s (struct))
+                (elem declare func $f)
+                (func $f (type $t)
+                    ref.func $f)
+            )",
+        );
+    }
+
+    #[test]
+    fn ref_identity_typed_select() {
+        test_peephole_mutations_validate(
+            "(module
+                (type $s (struct))
+                (func (param i32) (result (ref null $s))
+                    struct.new $s
+                    ref.null $s
+                    local.get 0
+                    select (result (ref null $s)))
+            )",
+        );
+    }
+
+    /// Check that every mutation the default rules produce for the given
+    /// module validates.
+    fn test_peephole_mutations_validate(original: &str) {
+        let original = wat::parse_str(original).unwrap();
+        for seed in 0..20 {
+            let mut config = WasmMutate::default();
+            config.fuel(100);
+            config.seed(seed);
+            config.info = Some(ModuleInfo::new(&original).unwrap());
+            config.rng = Some(SmallRng::seed_from_u64(seed));
+
+            let mutator = PeepholeMutator::new(3);
+            let rules = mutator.get_rules(&config);
+            let mutations = match mutator.mutate_with_rules(&mut config, &rules) {
+                Ok(mutations) => mutations,
+                Err(_) => continue,
+            };
+            for module in mutations.flatten() {
+                crate::validate(&module.finish());
+            }
+        }
+    }
+
+    #[test]
+    fn no_ref_cast_without_gc_proposal() {
+        let original_wasm = wat::parse_str(
+            "(module
+                (func (param funcref) (result funcref)
+                    local.get 0)
+            )",
+        )
+        .unwrap();
+        let mut config = WasmMutate::default();
+        config.info = Some(ModuleInfo::new(&original_wasm).unwrap());
+
+        let rules = PeepholeMutator::new(3).get_rules(&config);
+        assert!(rules.iter().any(|r| r.name() == "AsNonNull"));
+        assert!(!rules.iter().any(|r| r.name() == "Cast"));
+    }
+
     fn test_peephole_mutator(
         original: &str,
         rules: &[Rewrite<super::Lang, PeepholeMutationAnalysis>],
@@ -4,7 +4,8 @@
 use super::eggsy::encoder::rebuild::build_expr;
 use crate::mutators::OperatorAndByteOffset;
 use crate::mutators::peephole::{
-    Lang, MemArg, MemArgLane, MemoryCopy, MemoryInit, RefType, Shuffle, TableCopy, TableInit,
+    CastType, Lang, MemArg, MemArgLane, MemoryCopy, MemoryInit, RefType, Shuffle, StructField,
+    TableCopy, TableInit,
 };
 use crate::{ModuleInfo, WasmMutate};
 use egg::{Id, Language, RecExpr};
@@ -241,7 +242,22 @@ impl<'a> DFGBuilder {
                 | Operator::BrIf { .. }
                 | Operator::Return
                 | Operator::Unreachable
-                | Operator::BrTable { .. } => {
+                | Operator::BrTable { .. }
+                | Operator::BrOnNull { .. }
+                | Operator::BrOnNonNull { .. }
+                | Operator::BrOnCast { .. }
+                | Operator::BrOnCastFail { .. }
+                | Operator::ReturnCall { .. }
+                | Operator::ReturnCallIndirect { .. }
+                | Operator::ReturnCallRef { .. }
+                | Operator::TryTable { .. }
+                | Operator::Throw { .. }
+                | Operator::ThrowRef
+                | Operator::Try { .. }
+                | Operator::Catch { .. }
+                | Operator::CatchAll
+                | Operator::Delegate { .. }
+                | Operator::Rethrow { .. } => {
                     if !found {
                         // If the insertion point is a jump
                         // Break inmediatly
@@ -398,6 +414,9 @@ impl<'a> DFGBuilder {
 
                             self.new_color();
                         }
+                        crate::module::TypeInfo::Struct(_) | crate::module::TypeInfo::Array(_) => {
+                            unreachable!("functions always have function types")
+                        }
                     }
                 }
                 Operator::LocalGet { local_index } => {
@@ -757,15 +776,9 @@ impl<'a> DFGBuilder {
                     self.empty_node(Lang::TableSet(*table, [arg2, arg1]), idx);
                 }
 
-                Operator::RefNull {
-                    hty: wasmparser::HeapType::EXTERN,
-                } => {
-                    self.push_node(Lang::RefNull(RefType::Extern), idx);
-                }
-                Operator::RefNull {
-                    hty: wasmparser::HeapType::FUNC,
-                } => {
-                    self.push_node(Lang::RefNull(RefType::Func), idx);
+                Operator::RefNull { hty } => {
+                    let ty = RefType::try_from(*hty).ok()?;
+                    self.push_node(Lang::RefNull(ty), idx);
                 }
                 Operator::RefFunc { function_index } => {
                     self.push_node(Lang::RefFunc(*function_index), idx);
@@ -775,6 +788,122 @@ impl<'a> DFGBuilder {
                     let arg = Id::from(self.pop_operand(idx, false));
                     self.push_node(Lang::RefIsNull(arg), idx);
                 }
+                Operator::RefEq => self.binop(idx, Lang::RefEq),
+                Operator::RefAsNonNull => self.unop_cb(idx, Lang::RefAsNonNull),
+                Operator::RefTestNonNull { hty } => {
+                    let ty = cast_type(false, *hty)?;
+                    self.unop_cb(idx, |id| Lang::RefTest(ty, id));
+                }
+                Operator::RefTestNullable { hty } => {
+                    let ty = cast_type(true, *hty)?;
+                    self.unop_cb(idx, |id| Lang::RefTest(ty, id));
+                }
+                Operator::RefCastNonNull { hty } => {
+                    let ty = cast_type(false, *hty)?;
+                    self.unop_cb(idx, |id| Lang::RefCast(ty, id));
+                }
+                Operator::RefCastNullable { hty } => {
+                    let ty = cast_type(true, *hty)?;
+                    self.unop_cb(idx, |id| Lang::RefCast(ty, id));
+                }
+                Operator::RefI31 => self.unop_cb(idx, Lang::RefI31),
+                Operator::I31GetS => self.unop_cb(idx, Lang::I31GetS),
+                Operator::I31GetU => self.unop_cb(idx, Lang::I31GetU),
+                Operator::AnyConvertExtern => self.unop_cb(idx, Lang::AnyConvertExtern),
+                Operator::ExternConvertAny => self.unop_cb(idx, Lang::ExternConvertAny),
+
+                Operator::StructNew { struct_type_index } => {
+                    let fields = match info.types_map.get(*struct_type_index as usize)? {
+                        crate::module::TypeInfo::Struct(ty) => ty.fields.len(),
+                        _ => return None,
+                    };
+                    let mut operands = (0..fields)
+                        .map(|_| Id::from(self.pop_operand(idx, false)))
+                        .collect::<Vec<_>>();
+                    operands.reverse();
+                    self.push_node(Lang::StructNew(*struct_type_index, operands), idx);
+                }
+                Operator::StructNewDefault { struct_type_index } => {
+                    self.push_node(Lang::StructNewDefault(*struct_type_index), idx);
+                }
+                Operator::StructGet {
+                    struct_type_index,
+                    field_index,
+                } => self.unop_cb(idx, |id| {
+                    Lang::StructGet(struct_field(*struct_type_index, *field_index), id)
+                }),
+                Operator::StructGetS {
+                    struct_type_index,
+                    field_index,
+                } => self.unop_cb(idx, |id| {
+                    Lang::StructGetS(struct_field(*struct_type_index, *field_index), id)
+                }),
+                Operator::StructGetU {
+                    struct_type_index,
+                    field_index,
+                } => self.unop_cb(idx, |id| {
+                    Lang::StructGetU(struct_field(*struct_type_index, *field_index), id)
+                }),
+                Operator::StructSet {
+                    struct_type_index,
+                    field_index,
+                } => {
+                    let value = self.pop_operand(idx, false);
+                    let object = self.pop_operand(idx, false);
+                    self.empty_node(
+                        Lang::StructSet(
+                            struct_field(*struct_type_index, *field_index),
+                            [Id::from(object), Id::from(value)],
+                        ),
+                        idx,
+                    );
+                }
+
+                Operator::ArrayNew { array_type_index } => {
+                    self.binop_cb(idx, |ids| Lang::ArrayNew(*array_type_index, ids))
+                }
+                Operator::ArrayNewDefault { array_type_index } => {
+                    self.unop_cb(idx, |id| Lang::ArrayNewDefault(*array_type_index, id))
+                }
+                Operator::ArrayNewFixed {
+                    array_type_index,
+                    array_size,
+                } => {
+                    let mut operands = (0..*array_size)
+                        .map(|_| Id::from(self.pop_operand(idx, false)))
+                        .collect::<Vec<_>>();
+                    operands.reverse();
+                    self.push_node(Lang::ArrayNewFixed(*array_type_index, operands), idx);
+                }
+                Operator::ArrayGet { array_type_index } => {
+                    self.binop_cb(idx, |ids| Lang::ArrayGet(*array_type_index, ids))
+                }
+                Operator::ArrayGetS { array_type_index } => {
+                    self.binop_cb(idx, |ids| Lang::ArrayGetS(*array_type_index, ids))
+                }
+                Operator::ArrayGetU { array_type_index } => {
+                    self.binop_cb(idx, |ids| Lang::ArrayGetU(*array_type_index, ids))
+                }
+                Operator::ArraySet { array_type_index } => {
+                    let c = self.pop_operand(idx, false);
+                    let b = self.pop_operand(idx, false);
+                    let a = self.pop_operand(idx, false);
+                    self.empty_node(
+                        Lang::ArraySet(*array_type_index, [a, b, c].map(Id::from)),
+                        idx,
+                    );
+                }
+                Operator::ArrayLen => self.unop_cb(idx, Lang::ArrayLen),
+                Operator::ArrayFill { array_type_index } => {
+                    let d = self.pop_operand(idx, false);
+                    let c = self.pop_operand(idx, false);
+                    let b = self.pop_operand(idx, false);
+                    let a = self.pop_operand(idx, false);
+                    self.empty_node(
+                        Lang::ArrayFill(*array_type_index, [a, b, c, d].map(Id::from)),
+                        idx,
+                    );
+                }
 
                 Operator::V128Load { memarg } => self.load(idx, memarg, Lang::V128Load),
                 Operator::V128Load8x8S { memarg } => self.load(idx, memarg, Lang::V128Load8x8S),
@@ -1165,6 +1294,10 @@ impl<'a> DFGBuilder {
     }
 
     fn binop(&mut self, idx: usize, op: fn([Id; 2]) -> Lang) {
+        self.binop_cb(idx, op)
+    }
+
+    fn binop_cb(&mut self, idx: usize, op: impl Fn([Id; 2]) -> Lang) {
         let leftidx = self.pop_operand(idx, false);
         let rightidx = self.pop_operand(idx, false);
 
@@ -1259,6 +1392,22 @@ impl From<&wasmparser::MemArg> for MemArg {
     }
 }
 
+/// Returns the target type of a `ref.test` or `ref.cast`, if the heap type can
+/// be represented in [Lang].
+fn cast_type(nullable: bool, hty: wasmparser::HeapType) -> Option<CastType> {
+    Some(CastType {
+        nullable,
+        heap_type: hty.try_into().ok()?,
+    })
+}
+
+fn struct_field(type_index: u32, field_index: u32) -> StructField {
+    StructField {
+        type_index,
+        field_index,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DFGBuilder;
@@ -41,6 +41,27 @@ impl PeepholeMutationAnalysis {
         }
     }
 
+    /// Whether the type `get_returning_tpe` gives a node is exactly the type
+    /// the node has in the original code, rather than a super- or subtype.
+    ///
+    /// Rewrites that annotate instructions with the type of their operand,
+    /// such as casting a reference to its own type, are only correct for
+    /// exact types.
+    pub fn is_exact(&self, l: &Lang, eg: &EG) -> bool {
+        let exact = |id: &Id| eg[*id].data.as_ref().is_some_and(|d| d.exact);
+        match l {
+            // `ref.func` is typed as `funcref` even though its value is a
+            // non-null reference to the function's own type.
+            Lang::RefFunc(_) => false,
+            Lang::Select([consequent, alternative, _]) => exact(consequent) && exact(alternative),
+            // These take the nullability of their operand.
+            Lang::RefAsNonNull(arg) | Lang::AnyConvertExtern(arg) | Lang::ExternConvertAny(arg) => {
+                exact(arg)
+            }
+            _ => true,
+        }
+    }
+
     /// Gets returning type of node
     pub fn get_returning_tpe(&self, l: &Lang, eg: &EG) -> crate::Result<PrimitiveTypeInfo> {
         match l {
@@ -112,6 +133,9 @@ impl PeepholeMutationAnalysis {
 
                         Ok(ty.returns[0])
                     }
+                    TypeInfo::Struct(_) | TypeInfo::Array(_) => {
+                        unreachable!("functions always have function types")
+                    }
                 }
             }
             Lang::I32Popcnt(_) => Ok(PrimitiveTypeInfo::I32),
@@ -241,19 +265,16 @@ impl PeepholeMutationAnalysis {
             // This node is not directly written to Wasm
             Lang::Container(_) => Ok(PrimitiveTypeInfo::Empty),
             Lang::Select([consequent, alternative, _]) => {
-                // Get from operands
-                let consequenttpe = eg[*consequent]
-                    .data
-                    .clone()
-                    .expect("Missing operand type")
-                    .tpe;
-                let alternativetpe = eg[*alternative]
-                    .data
-                    .clone()
-                    .expect("Missing operand type")
-                    .tpe;
-                debug_assert_eq!(consequenttpe, alternativetpe);
-                Ok(consequenttpe)
+                // Only the untyped `select` is represented, whose operands
+                // have the same type. Anything else has no single type that
+                // can be derived from the operands alone.
+                let consequenttpe = operand_type(eg, *consequent)?;
+                let alternativetpe = operand_type(eg, *alternative)?;
+                if consequenttpe == alternativetpe {
+                    Ok(consequenttpe)
+                } else {
+                    Err(Error::no_mutations_applicable())
+                }
             }
             Lang::MemoryGrow(mem, _) | Lang::MemorySize(mem) => {
                 let ty = self.memory_types[*mem as usize];
@@ -296,10 +317,48 @@ impl PeepholeMutationAnalysis {
             Lang::I64UseGlobal(_) => Ok(PrimitiveTypeInfo::I64),
             Lang::F32UseGlobal(_) => Ok(PrimitiveTypeInfo::F32),
             Lang::F64UseGlobal(_) => Ok(PrimitiveTypeInfo::F64),
-            Lang::RefNull(RefType::Func) => Ok(PrimitiveTypeInfo::FuncRef),
-            Lang::RefNull(RefType::Extern) => Ok(PrimitiveTypeInfo::ExternRef),
+            Lang::RefNull(ty) => reference(ty.ref_type(true)),
             Lang::RefFunc(_) => Ok(PrimitiveTypeInfo::FuncRef),
             Lang::RefIsNull(_) => Ok(PrimitiveTypeInfo::I32),
+            Lang::RefEq(_) => Ok(PrimitiveTypeInfo::I32),
+            Lang::RefAsNonNull(arg) => reference(Some(operand_ref_type(eg, *arg)?.as_non_null())),
+            Lang::RefTest(..) => Ok(PrimitiveTypeInfo::I32),
+            Lang::RefCast(ty, _) => reference(ty.ref_type()),
+            Lang::RefI31(_) => Ok(PrimitiveTypeInfo::Ref(wasmparser::RefType::I31)),
+            Lang::I31GetS(_) => Ok(PrimitiveTypeInfo::I32),
+            Lang::I31GetU(_) => Ok(PrimitiveTypeInfo::I32),
+            // Conversions between `any` and `extern` preserve nullness.
+            Lang::AnyConvertExtern(arg) => match operand_ref_type(eg, *arg) {
+                Ok(ty) if !ty.is_nullable() => reference(Some(wasmparser::RefType::ANY)),
+                _ => reference(Some(wasmparser::RefType::ANYREF)),
+            },
+            Lang::ExternConvertAny(arg) => match operand_ref_type(eg, *arg) {
+                Ok(ty) if !ty.is_nullable() => reference(Some(wasmparser::RefType::EXTERN)),
+                _ => reference(Some(wasmparser::RefType::EXTERNREF)),
+            },
+
+            Lang::StructNew(ty, _) | Lang::StructNewDefault(ty) => {
+                reference(RefType::Concrete(*ty).ref_type(false))
+            }
+            Lang::StructGet(field, _) | Lang::StructGetS(field, _) | Lang::StructGetU(field, _) => {
+                match &self.types_map[field.type_index as usize] {
+                    TypeInfo::Struct(ty) => Ok(ty.fields[field.field_index as usize].unpacked()),
+                    _ => Err(Error::no_mutations_applicable()),
+                }
+            }
+            Lang::StructSet(..) => Ok(PrimitiveTypeInfo::Empty),
+            Lang::ArrayNew(ty, _) | Lang::ArrayNewDefault(ty, _) | Lang::ArrayNewFixed(ty, _) => {
+                reference(RefType::Concrete(*ty).ref_type(false))
+            }
+            Lang::ArrayGet(ty, _) | Lang::ArrayGetS(ty, _) | Lang::ArrayGetU(ty, _) => {
+                match &self.types_map[*ty as usize] {
+                    TypeInfo::Array(ty) => Ok(ty.element.unpacked()),
+                    _ => Err(Error::no_mutations_applicable()),
+                }
+            }
+            Lang::ArraySet(..) => Ok(PrimitiveTypeInfo::Empty),
+            Lang::ArrayLen(_) => Ok(PrimitiveTypeInfo::I32),
+            Lang::ArrayFill(..) => Ok(PrimitiveTypeInfo::Empty),
 
             Lang::V128Load(..) => Ok(PrimitiveTypeInfo::V128),
             Lang::V128Load8x8S(..) => Ok(PrimitiveTypeInfo::V128),
@@ -570,16 +629,42 @@ impl PeepholeMutationAnalysis {
     }
 }
 
+/// Maps a reference type to its `PrimitiveTypeInfo`, failing if the type
+/// couldn't be constructed.
+fn reference(ty: Option<wasmparser::RefType>) -> crate::Result<PrimitiveTypeInfo> {
+    ty.ok_or_else(Error::no_mutations_applicable)?.try_into()
+}
+
+/// Gets the type of an operand, failing if it is unknown.
+fn operand_type(eg: &EG, id: Id) -> crate::Result<PrimitiveTypeInfo> {
+    eg[id]
+        .data
+        .as_ref()
+        .map(|d| d.tpe)
+        .ok_or_else(Error::no_mutations_applicable)
+}
+
+/// Gets the reference type of an operand, failing if it isn't a reference.
+fn operand_ref_type(eg: &EG, id: Id) -> crate::Result<wasmparser::RefType> {
+    eg[id]
+        .data
+        .as_ref()
+        .and_then(|d| d.tpe.ref_type())
+        .ok_or_else(Error::no_mutations_applicable)
+}
+
 #[derive(Debug, Clone)]
 pub struct ClassData {
     /// Type 't' of the operator
     /// 't'.op
     pub tpe: PrimitiveTypeInfo,
+    /// Whether `tpe` is exact, see [`PeepholeMutationAnalysis::is_exact`].
+    pub exact: bool,
 }
 
 impl PartialEq for ClassData {
     fn eq(&self, other: &Self) -> bool {
-        self.tpe == other.tpe
+        self.tpe == other.tpe && self.exact == other.exact
     }
 
     fn ne(&self, other: &Self) -> bool {
@@ -600,11 +685,18 @@ impl Analysis<Lang> for PeepholeMutationAnalysis {
                 .analysis
                 .get_returning_tpe(l, egraph)
                 .unwrap_or(PrimitiveTypeInfo::Empty),
+            exact: egraph.analysis.is_exact(l, egraph),
         })
     }
 
     fn merge(&self, to: &mut Self::Data, from: Self::Data) -> bool {
-        egg::merge_if_different(to, to.clone().or(from))
+        // Equivalent nodes have the same value, so an exact type found for
+        // any of them is exact for the whole class.
+        let merged = match (to.clone(), from) {
+            (Some(to), Some(from)) if !to.exact && from.exact => Some(from),
+            (to, from) => to.or(from),
+        };
+        egg::merge_if_different(to, merged)
     }
 
     fn modify(_: &mut EGraph<Lang, Self>, _: Id) {}
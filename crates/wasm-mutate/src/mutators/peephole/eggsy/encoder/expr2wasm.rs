@@ -385,6 +385,39 @@ pub fn expr2wasm(
                     Lang::RefNull(valtype) => insn.ref_null((*valtype).into()),
                     Lang::RefFunc(idx) => insn.ref_func(*idx),
                     Lang::RefIsNull(_) => insn.ref_is_null(),
+                    Lang::RefEq(_) => insn.ref_eq(),
+                    Lang::RefAsNonNull(_) => insn.ref_as_non_null(),
+                    Lang::RefTest(ty, _) if ty.nullable => {
+                        insn.ref_test_nullable(ty.heap_type.into())
+                    }
+                    Lang::RefTest(ty, _) => insn.ref_test_non_null(ty.heap_type.into()),
+                    Lang::RefCast(ty, _) if ty.nullable => {
+                        insn.ref_cast_nullable(ty.heap_type.into())
+                    }
+                    Lang::RefCast(ty, _) => insn.ref_cast_non_null(ty.heap_type.into()),
+                    Lang::RefI31(_) => insn.ref_i31(),
+                    Lang::I31GetS(_) => insn.i31_get_s(),
+                    Lang::I31GetU(_) => insn.i31_get_u(),
+                    Lang::AnyConvertExtern(_) => insn.any_convert_extern(),
+                    Lang::ExternConvertAny(_) => insn.extern_convert_any(),
+
+                    Lang::StructNew(ty, _) => insn.struct_new(*ty),
+                    Lang::StructNewDefault(ty) => insn.struct_new_default(*ty),
+                    Lang::StructGet(f, _) => insn.struct_get(f.type_index, f.field_index),
+                    Lang::StructGetS(f, _) => insn.struct_get_s(f.type_index, f.field_index),
+                    Lang::StructGetU(f, _) => insn.struct_get_u(f.type_index, f.field_index),
+                    Lang::StructSet(f, _) => insn.struct_set(f.type_index, f.field_index),
+                    Lang::ArrayNew(ty, _) => insn.array_new(*ty),
+                    Lang::ArrayNewDefault(ty, _) => insn.array_new_default(*ty),
+                    Lang::ArrayNewFixed(ty, elems) => {
+                        insn.array_new_fixed(*ty, u32::try_from(elems.len()).unwrap())
+                    }
+                    Lang::ArrayGet(ty, _) => insn.array_get(*ty),
+                    Lang::ArrayGetS(ty, _) => insn.array_get_s(*ty),
+                    Lang::ArrayGetU(ty, _) => insn.array_get_u(*ty),
+                    Lang::ArraySet(ty, _) => insn.array_set(*ty),
+                    Lang::ArrayLen(_) => insn.array_len(),
+                    Lang::ArrayFill(ty, _) => insn.array_fill(*ty),
 
                     Lang::V128Not(_) => insn.v128_not(),
                     Lang::V128And(_) => insn.v128_and(),
@@ -3,7 +3,7 @@
 use egg::Id;
 use std::fmt::{self, Display};
 use std::str::FromStr;
-use wasm_encoder::{AbstractHeapType, HeapType};
+use wasm_encoder::HeapType;
 
 /// This is a macro used to define the `Lang` enum.
 ///
@@ -675,6 +675,32 @@ lang! {
         TableGrow(u32, [Id; 2]) = "table.grow",
         TableSize(u32) = "table.size",
         RefIsNull(Id) = "ref.is_null",
+        RefEq([Id; 2]) = "ref.eq",
+        RefAsNonNull(Id) = "ref.as_non_null",
+        RefTest(CastType, Id) = "ref.test",
+        RefCast(CastType, Id) = "ref.cast",
+        RefI31(Id) = "ref.i31",
+        I31GetS(Id) = "i31.get_s",
+        I31GetU(Id) = "i31.get_u",
+        AnyConvertExtern(Id) = "any.convert_extern",
+        ExternConvertAny(Id) = "extern.convert_any",
+
+        StructNew(u32, Vec<Id>) = "struct.new",
+        StructNewDefault(u32) = "struct.new_default",
+        StructGet(StructField, Id) = "struct.get",
+        StructGetS(StructField, Id) = "struct.get_s",
+        StructGetU(StructField, Id) = "struct.get_u",
+        StructSet(StructField, [Id; 2]) = "struct.set",
+        ArrayNew(u32, [Id; 2]) = "array.new",
+        ArrayNewDefault(u32, Id) = "array.new_default",
+        // The size of `array.new_fixed` is the number of children
+        ArrayNewFixed(u32, Vec<Id>) = "array.new_fixed",
+        ArrayGet(u32, [Id; 2]) = "array.get",
+        ArrayGetS(u32, [Id; 2]) = "array.get_s",
+        ArrayGetU(u32, [Id; 2]) = "array.get_u",
+        ArraySet(u32, [Id; 3]) = "array.set",
+        ArrayLen(Id) = "array.len",
+        ArrayFill(u32, [Id; 4]) = "array.fill",
 
         V128Load(MemArg, Id) = "v128.load",
         V128Load8x8S(MemArg, Id) = "v128.load8x8_s",
@@ -1088,24 +1114,80 @@ impl fmt::Display for F64 {
     }
 }
 
+/// A heap type, as used in the immediates of `ref.null`, `ref.test` and
+/// `ref.cast`.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
 pub enum RefType {
     Func,
     Extern,
+    Any,
+    Eq,
+    I31,
+    Struct,
+    Array,
+    None,
+    NoFunc,
+    NoExtern,
+    Concrete(u32),
+}
+
+impl RefType {
+    fn heap_type(self) -> wasmparser::HeapType {
+        use wasmparser::AbstractHeapType as A;
+        let ty = match self {
+            RefType::Func => A::Func,
+            RefType::Extern => A::Extern,
+            RefType::Any => A::Any,
+            RefType::Eq => A::Eq,
+            RefType::I31 => A::I31,
+            RefType::Struct => A::Struct,
+            RefType::Array => A::Array,
+            RefType::None => A::None,
+            RefType::NoFunc => A::NoFunc,
+            RefType::NoExtern => A::NoExtern,
+            RefType::Concrete(i) => {
+                return wasmparser::HeapType::Concrete(wasmparser::UnpackedIndex::Module(i));
+            }
+        };
+        wasmparser::HeapType::Abstract { shared: false, ty }
+    }
+
+    /// Returns the reference type to this heap type.
+    pub fn ref_type(self, nullable: bool) -> Option<wasmparser::RefType> {
+        wasmparser::RefType::new(nullable, self.heap_type())
+    }
+}
+
+impl TryFrom<wasmparser::HeapType> for RefType {
+    type Error = ();
+
+    fn try_from(ty: wasmparser::HeapType) -> Result<Self, ()> {
+        use wasmparser::AbstractHeapType as A;
+        Ok(match ty {
+            wasmparser::HeapType::Concrete(i) => RefType::Concrete(i.as_module_index().ok_or(())?),
+            wasmparser::HeapType::Abstract { shared: true, .. } => return Err(()),
+            wasmparser::HeapType::Abstract { shared: false, ty } => match ty {
+                A::Func => RefType::Func,
+                A::Extern => RefType::Extern,
+                A::Any => RefType::Any,
+                A::Eq => RefType::Eq,
+                A::I31 => RefType::I31,
+                A::Struct => RefType::Struct,
+                A::Array => RefType::Array,
+                A::None => RefType::None,
+                A::NoFunc => RefType::NoFunc,
+                A::NoExtern => RefType::NoExtern,
+                A::Exn | A::NoExn | A::Cont | A::NoCont => return Err(()),
+            },
+        })
+    }
 }
 
 impl From<RefType> for HeapType {
     fn from(rt: RefType) -> Self {
-        match rt {
-            RefType::Func => HeapType::Abstract {
-                shared: false,
-                ty: AbstractHeapType::Func,
-            },
-            RefType::Extern => HeapType::Abstract {
-                shared: false,
-                ty: AbstractHeapType::Extern,
-            },
-        }
+        rt.heap_type()
+            .try_into()
+            .expect("module-level indices are always encodable")
     }
 }
 
@@ -1115,7 +1197,18 @@ impl FromStr for RefType {
         match s {
             "func" => Ok(RefType::Func),
             "extern" => Ok(RefType::Extern),
-            s => Err(format!("{s} is not a valid reference type").into()),
+            "any" => Ok(RefType::Any),
+            "eq" => Ok(RefType::Eq),
+            "i31" => Ok(RefType::I31),
+            "struct" => Ok(RefType::Struct),
+            "array" => Ok(RefType::Array),
+            "none" => Ok(RefType::None),
+            "nofunc" => Ok(RefType::NoFunc),
+            "noextern" => Ok(RefType::NoExtern),
+            s => match s.parse() {
+                Ok(i) => Ok(RefType::Concrete(i)),
+                Err(_) => Err(format!("{s} is not a valid reference type").into()),
+            },
         }
     }
 }
@@ -1125,6 +1218,63 @@ impl fmt::Display for RefType {
         f.write_str(match self {
             RefType::Func => "func",
             RefType::Extern => "extern",
+            RefType::Any => "any",
+            RefType::Eq => "eq",
+            RefType::I31 => "i31",
+            RefType::Struct => "struct",
+            RefType::Array => "array",
+            RefType::None => "none",
+            RefType::NoFunc => "nofunc",
+            RefType::NoExtern => "noextern",
+            RefType::Concrete(i) => return write!(f, "{i}"),
+        })
+    }
+}
+
+/// The target type of `ref.test` and `ref.cast`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct CastType {
+    pub nullable: bool,
+    pub heap_type: RefType,
+}
+
+impl CastType {
+    /// Returns the reference type this cast is to.
+    pub fn ref_type(self) -> Option<wasmparser::RefType> {
+        self.heap_type.ref_type(self.nullable)
+    }
+}
+
+impl TryFrom<wasmparser::RefType> for CastType {
+    type Error = ();
+
+    fn try_from(ty: wasmparser::RefType) -> Result<Self, ()> {
+        Ok(CastType {
+            nullable: ty.is_nullable(),
+            heap_type: ty.heap_type().try_into()?,
+        })
+    }
+}
+
+impl fmt::Display for CastType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.nullable {
+            write!(f, "null.")?;
+        }
+        write!(f, "{}", self.heap_type)
+    }
+}
+
+impl FromStr for CastType {
+    type Err = Box<dyn std::error::Error + Send + Sync>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (nullable, heap_type) = match s.strip_prefix("null.") {
+            Some(heap_type) => (true, heap_type),
+            None => (false, s),
+        };
+        Ok(CastType {
+            nullable,
+            heap_type: heap_type.parse()?,
         })
     }
 }
@@ -1405,6 +1555,30 @@ impl FromStr for MemoryInit {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+pub struct StructField {
+    pub type_index: u32,
+    pub field_index: u32,
+}
+
+impl fmt::Display for StructField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.type_index, self.field_index)
+    }
+}
+
+impl FromStr for StructField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<StructField, String> {
+        let (type_index, field_index) = parse_pair(s)?;
+        Ok(StructField {
+            type_index,
+            field_index,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Lang, MemArg, RefType};
@@ -3,13 +3,19 @@
 //! New rewriting rules should be declared inside the
 //! [`get_rules`](/src/wasm_mutate/mutators/peephole/rules.rs.html#17) function.
 
-use egg::{Id, Rewrite, Subst, rewrite};
+use egg::{Id, Rewrite, Subst};
 
-use crate::{WasmMutate, module::PrimitiveTypeInfo};
+use crate::{
+    WasmMutate,
+    module::{PrimitiveTypeInfo, StorageTypeInfo, TypeInfo},
+};
 
 use super::{
     EG, PeepholeMutator,
-    eggsy::{analysis::PeepholeMutationAnalysis, lang::Lang},
+    eggsy::{
+        analysis::PeepholeMutationAnalysis,
+        lang::{CastType, Lang},
+    },
 };
 
 impl PeepholeMutator {
@@ -384,6 +390,96 @@ impl PeepholeMutator {
             );
         }
 
+        // Identities for GC references.
+        rewrite!("ref.eq-commutes"; "(ref.eq ?x ?y)" <=> "(ref.eq ?y ?x)");
+        if config.reduce {
+            rewrite!(
+                "ref.as_non_null-twice";
+                "(ref.as_non_null (ref.as_non_null ?x))" => "(ref.as_non_null ?x)"
+            );
+        } else {
+            rewrite!(
+                "ref.as_non_null-twice";
+                "(ref.as_non_null (ref.as_non_null ?x))" <=> "(ref.as_non_null ?x)"
+            );
+            rewrite!(
+                "ref.i31-mask";
+                "(ref.i31 ?x)" <=> "(ref.i31 (i32.and ?x i32.const.2147483647))"
+            );
+            rewrite!(
+                "i31.get_u-ref.i31";
+                "(i31.get_u (ref.i31 ?x))" => "(i32.and ?x i32.const.2147483647)"
+            );
+            rewrite!(
+                "i31.get_s-ref.i31";
+                "(i31.get_s (ref.i31 ?x))" => "(i32.shr_s (i32.shl ?x i32.const.1) i32.const.1)"
+            );
+
+            // Wrap a reference in a cast to its own type, or in a
+            // `ref.as_non_null` if it is already non-nullable. Casts are only
+            // introduced into modules that already use GC, whereas
+            // non-nullable references only exist in modules that already use
+            // typed function references.
+            let appliers: &[RefIdentity] = if config.info().uses_gc {
+                &[RefIdentity::Cast, RefIdentity::AsNonNull]
+            } else {
+                &[RefIdentity::AsNonNull]
+            };
+            for applier in appliers.iter().copied() {
+                let name = format!("{applier:?}");
+                let long_name = format!("?x => {applier:?}");
+                let lhs = "?x".parse::<egg::Pattern<_>>().unwrap();
+                rules.push(Rewrite::new(name, long_name, lhs, applier).unwrap());
+            }
+        }
+
+        // Reading a packed field is the same as reading it unsigned and then
+        // extending or masking it.
+        for (i, ty) in config.info().types_map.iter().enumerate() {
+            let accessors = match ty {
+                TypeInfo::Struct(ty) => ty
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(f, field)| {
+                        let get_s = format!("(struct.get_s.{i}.{f} ?x)");
+                        let get_u = format!("(struct.get_u.{i}.{f} ?x)");
+                        (*field, get_s, get_u)
+                    })
+                    .collect(),
+                TypeInfo::Array(ty) => {
+                    let get_s = format!("(array.get_s.{i} ?x ?y)");
+                    let get_u = format!("(array.get_u.{i} ?x ?y)");
+                    vec![(ty.element, get_s, get_u)]
+                }
+                TypeInfo::Func(_) => vec![],
+            };
+            for (j, (storage, get_s, get_u)) in accessors.into_iter().enumerate() {
+                let (extend, mask) = match storage {
+                    StorageTypeInfo::I8 => ("i32.extend8_s", 0xff),
+                    StorageTypeInfo::I16 => ("i32.extend16_s", 0xffff),
+                    StorageTypeInfo::Val(_) => continue,
+                };
+                let extended = format!("({extend} {get_u})");
+                let masked = format!("(i32.and {get_u} i32.const.{mask})");
+                let extend_name = format!("packed-get_s-{i}-{j}");
+                let mask_name = format!("packed-get_u-{i}-{j}");
+                if config.reduce {
+                    self.add_rewrite(&mut rules, &extend_name, &extended, &get_s, &[]);
+                    self.add_rewrite(&mut rules, &mask_name, &masked, &get_u, &[]);
+                } else {
+                    self.add_bidirectional_rewrite(
+                        &mut rules,
+                        &extend_name,
+                        &get_s,
+                        &extended,
+                        &[],
+                    );
+                    self.add_bidirectional_rewrite(&mut rules, &mask_name, &get_u, &masked, &[]);
+                }
+            }
+        }
+
         // If we aren't preserving semantics, then go wild with mutations. Only
         // thing we need to preserve is that we are emitting valid, well-typed
         // Wasm.
@@ -677,6 +773,39 @@ impl PeepholeMutator {
     }
 }
 
+/// Applier that wraps a reference in an instruction that returns it
+/// unchanged, based on the reference's type.
+#[derive(Clone, Copy, Debug)]
+enum RefIdentity {
+    /// `x => ref.cast T x` where `T` is the exact type of `x`.
+    Cast,
+    /// `x => ref.as_non_null x` where `x` is non-nullable.
+    AsNonNull,
+}
+
+impl egg::Applier<Lang, PeepholeMutationAnalysis> for RefIdentity {
+    fn apply_one(&self, egraph: &mut EG, eclass: Id, _subst: &Subst) -> Vec<Id> {
+        let ty = match egraph[eclass]
+            .data
+            .as_ref()
+            .filter(|d| d.exact)
+            .and_then(|d| d.tpe.ref_type())
+        {
+            Some(ty) => ty,
+            None => return vec![],
+        };
+        let node = match self {
+            RefIdentity::Cast => match CastType::try_from(ty) {
+                Ok(cast) => Lang::RefCast(cast, eclass),
+                Err(()) => return vec![],
+            },
+            RefIdentity::AsNonNull if !ty.is_nullable() => Lang::RefAsNonNull(eclass),
+            RefIdentity::AsNonNull => return vec![],
+        };
+        vec![egraph.add(node)]
+    }
+}
+
 #[derive(Clone)]
 enum Condition {
     Type(egg::Var, PrimitiveTypeInfo),
@@ -69,7 +69,9 @@ impl Item {
             Item::Memory => info.num_memories() > 0,
             Item::Global => info.num_globals() > 0,
             Item::Tag => info.num_tags() > 0,
-            Item::Type => info.num_types() > 0,
+            // Types are removed one rec group at a time, which only lines up
+            // with type indices when every rec group holds a single type.
+            Item::Type => info.num_types() > 0 && info.num_types() == info.num_rec_groups(),
 
             // Note that data/elements can lead to traps and side-effectful
             // initialization of imported tables/memories, so these are only
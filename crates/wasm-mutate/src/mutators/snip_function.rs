@@ -1,7 +1,7 @@
 //! Mutator that replaces the body of a function with an empty body
 
 use super::Mutator;
-use crate::module::{PrimitiveTypeInfo, TypeInfo};
+use crate::module::{PrimitiveTypeInfo, TypeInfo, map_ref_type};
 use crate::{Result, WasmMutate};
 use rand::Rng;
 use wasm_encoder::{CodeSection, Function, HeapType, Module};
@@ -66,12 +66,19 @@ impl Mutator for SnipMutator {
                             PrimitiveTypeInfo::ExternRef => {
                                 f.instructions().ref_null(HeapType::EXTERN);
                             }
+                            PrimitiveTypeInfo::Ref(ty) if ty.is_nullable() => {
+                                f.instructions().ref_null(map_ref_type(*ty)?.heap_type);
+                            }
+                            PrimitiveTypeInfo::Ref(_) => {
+                                f.instructions().unreachable();
+                            }
                             PrimitiveTypeInfo::Empty => {
                                 unreachable!()
                             }
                         }
                     }
                 }
+                TypeInfo::Struct(_) | TypeInfo::Array(_) => unreachable!(),
             }
 
             f.instructions().end();
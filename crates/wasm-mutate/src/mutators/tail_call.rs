@@ -0,0 +1,249 @@
+//! Mutator that converts calls in tail position into tail calls.
+
+use super::{Mutator, OperatorAndByteOffset, OperatorsReplacement, replace_operators};
+use crate::module::{PrimitiveTypeInfo, TypeInfo};
+use crate::{Result, WasmMutate};
+use rand::prelude::*;
+use wasm_encoder::{InstructionSink, Module};
+use wasmparser::Operator;
+
+/// Replaces a `call`, `call_indirect` or `call_ref` whose results are
+/// immediately returned with the corresponding `return_call*` instruction.
+///
+/// When semantics don't need to be preserved, tail calls are also turned back
+/// into a regular call followed by a `return`.
+#[derive(Clone, Copy)]
+pub struct TailCallMutator;
+
+impl TailCallMutator {
+    fn replacement(
+        &self,
+        config: &mut WasmMutate,
+        function_index: u32,
+        operators: &[OperatorAndByteOffset],
+    ) -> Result<Option<OperatorsReplacement>> {
+        let info = config.info();
+        let results = returns(info.get_functype_idx(function_index));
+
+        // Whether each enclosing block handles exceptions. A tail call leaves
+        // the handler behind, so calls within such blocks must stay as-is.
+        let mut handlers = Vec::new();
+        let mut candidates = Vec::new();
+        for (i, (op, _)) in operators.iter().enumerate() {
+            let callee = match op {
+                Operator::Block { .. } | Operator::Loop { .. } | Operator::If { .. } => {
+                    handlers.push(false);
+                    continue;
+                }
+                Operator::TryTable { .. } | Operator::Try { .. } => {
+                    handlers.push(true);
+                    continue;
+                }
+                Operator::End | Operator::Delegate { .. } => {
+                    handlers.pop();
+                    continue;
+                }
+                Operator::ReturnCall { .. }
+                | Operator::ReturnCallIndirect { .. }
+                | Operator::ReturnCallRef { .. } => {
+                    if !config.preserve_semantics && !config.reduce {
+                        candidates.push(i..i + 1);
+                    }
+                    continue;
+                }
+                Operator::Call { function_index } => info.get_functype_idx(*function_index),
+                Operator::CallIndirect { type_index, .. } | Operator::CallRef { type_index } => {
+                    &info.types_map[*type_index as usize]
+                }
+                _ => continue,
+            };
+            if handlers.contains(&true) || returns(callee) != results {
+                continue;
+            }
+            match operators.get(i + 1) {
+                Some((Operator::Return, _)) => candidates.push(i..i + 2),
+                // The `end` of the function itself.
+                Some((Operator::End, _)) if i + 2 == operators.len() => candidates.push(i..i + 1),
+                _ => {}
+            }
+        }
+
+        let range = match candidates.choose(config.rng()) {
+            Some(range) => range.clone(),
+            None => return Ok(None),
+        };
+        let mut bytes = Vec::new();
+        let mut insn = InstructionSink::new(&mut bytes);
+        match &operators[range.start].0 {
+            Operator::Call { function_index } => insn.return_call(*function_index),
+            Operator::CallIndirect {
+                type_index,
+                table_index,
+            } => insn.return_call_indirect(*table_index, *type_index),
+            Operator::CallRef { type_index } => insn.return_call_ref(*type_index),
+            Operator::ReturnCall { function_index } => insn.call(*function_index).return_(),
+            Operator::ReturnCallIndirect {
+                type_index,
+                table_index,
+            } => insn.call_indirect(*table_index, *type_index).return_(),
+            Operator::ReturnCallRef { type_index } => insn.call_ref(*type_index).return_(),
+            _ => unreachable!(),
+        };
+        Ok(Some((range, bytes)))
+    }
+}
+
+fn returns(ty: &TypeInfo) -> &[PrimitiveTypeInfo] {
+    match ty {
+        TypeInfo::Func(ty) => &ty.returns,
+        TypeInfo::Struct(_) | TypeInfo::Array(_) => &[],
+    }
+}
+
+impl Mutator for TailCallMutator {
+    fn mutate<'a>(
+        &self,
+        config: &mut WasmMutate<'a>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let module = replace_operators(config, |config, function_index, operators| {
+            self.replacement(config, function_index, operators)
+        })?;
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.info().has_nonempty_code() && config.info().uses_tail_calls
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TailCallMutator;
+    use crate::WasmMutate;
+
+    #[test]
+    fn call_then_return() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func $f (param i32) (result i32)
+                    local.get 0
+                    call $f
+                    return)
+                (func $g
+                    return_call $g)
+            )
+            "#,
+            TailCallMutator,
+            r#"
+            (module
+                (func $f (param i32) (result i32)
+                    local.get 0
+                    return_call $f)
+                (func $g
+                    return_call $g)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn call_indirect_at_end() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (type $t (func (result i64)))
+                (table 1 funcref)
+                (func (type $t)
+                    i32.const 0
+                    call_indirect (type $t))
+                (func $g
+                    return_call $g)
+            )
+            "#,
+            TailCallMutator,
+            r#"
+            (module
+                (type $t (func (result i64)))
+                (table 1 funcref)
+                (func (type $t)
+                    i32.const 0
+                    return_call_indirect (type $t))
+                (func $g
+                    return_call $g)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn return_call_to_call() {
+        let mut config = WasmMutate::default();
+        config.preserve_semantics(false);
+        config.match_mutation(
+            r#"
+            (module
+                (func $f (result i32)
+                    return_call $f)
+            )
+            "#,
+            TailCallMutator,
+            r#"
+            (module
+                (func $f (result i32)
+                    call $f
+                    return)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn no_tail_calls_without_tail_call_proposal() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func $f (param i32) (result i32)
+                    local.get 0
+                    call $f
+                    return)
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!crate::mutators::Mutator::can_mutate(
+            &TailCallMutator,
+            &config
+        ));
+    }
+
+    #[test]
+    fn no_tail_call_within_try_table() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (tag $e)
+                (func $f
+                    try_table (catch_all 0)
+                        call $f
+                        return
+                    end)
+                (func $g (result i32)
+                    i32.const 0
+                    call $f
+                    return)
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        for seed in 0..10 {
+            config.seed(seed);
+            let result = crate::mutators::Mutator::mutate(&TailCallMutator, &mut config);
+            assert!(result.is_err());
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Mutator that rewrites exception handling constructs into equivalent ones.
+
+use super::{Mutator, OperatorAndByteOffset, OperatorsReplacement, replace_operators};
+use crate::module::TypeInfo;
+use crate::{Result, WasmMutate};
+use rand::prelude::*;
+use wasm_encoder::{BlockType, InstructionSink, Module, ValType};
+use wasmparser::{Catch, Operator, TryTable};
+
+/// Rewrites `block`, `try_table` and `throw` instructions in ways that don't
+/// change which exceptions are thrown or where they are caught.
+///
+/// This includes:
+///
+/// * Turning a `block` into a `try_table` without catch clauses, and back.
+/// * Duplicating a catch clause of a `try_table`, which will never be taken
+///   since the original clause comes first.
+/// * Removing a catch clause that is shadowed by an earlier one.
+/// * Throwing an exception from within a `try_table` that catches it and
+///   rethrows it with `throw_ref`.
+#[derive(Clone, Copy)]
+pub struct TryTableMutator;
+
+enum Change<'a> {
+    BlockToTryTable(wasmparser::BlockType),
+    TryTableToBlock(wasmparser::BlockType),
+    DuplicateCatch(&'a TryTable),
+    RemoveCatch(&'a TryTable, usize),
+    RethrowThrow(u32),
+}
+
+impl TryTableMutator {
+    fn replacement(
+        &self,
+        config: &mut WasmMutate,
+        operators: &[OperatorAndByteOffset],
+    ) -> Result<Option<OperatorsReplacement>> {
+        let mut candidates = Vec::new();
+        for (i, (op, _)) in operators.iter().enumerate() {
+            match op {
+                Operator::Block { blockty } if !config.reduce => {
+                    candidates.push((i, Change::BlockToTryTable(*blockty)));
+                }
+                Operator::TryTable { try_table } => {
+                    if try_table.catches.is_empty() {
+                        candidates.push((i, Change::TryTableToBlock(try_table.ty)));
+                    } else if !config.reduce {
+                        candidates.push((i, Change::DuplicateCatch(try_table)));
+                    }
+                    for (j, catch) in try_table.catches.iter().enumerate() {
+                        if is_shadowed(&try_table.catches[..j], catch) {
+                            candidates.push((i, Change::RemoveCatch(try_table, j)));
+                        }
+                    }
+                }
+                Operator::Throw { tag_index } if !config.reduce => {
+                    // The thrown values would have to be passed into the new
+                    // blocks, which requires a new function type, so only
+                    // rethrow exceptions without any.
+                    let info = config.info();
+                    let ty = info.tag_map[*tag_index as usize];
+                    if let TypeInfo::Func(ty) = &info.types_map[ty as usize] {
+                        if ty.params.is_empty() {
+                            candidates.push((i, Change::RethrowThrow(*tag_index)));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let (i, change) = match candidates.choose(config.rng()) {
+            Some(candidate) => candidate,
+            None => return Ok(None),
+        };
+        let mut bytes = Vec::new();
+        let mut insn = InstructionSink::new(&mut bytes);
+        match change {
+            Change::BlockToTryTable(ty) => {
+                insn.try_table((*ty).try_into()?, []);
+            }
+            Change::TryTableToBlock(ty) => {
+                insn.block((*ty).try_into()?);
+            }
+            Change::DuplicateCatch(try_table) => {
+                let mut catches = try_table.catches.clone();
+                let catch = *catches.choose(config.rng()).unwrap();
+                catches.push(catch);
+                try_table_with_catches(&mut insn, try_table.ty, catches)?;
+            }
+            Change::RemoveCatch(try_table, j) => {
+                let mut catches = try_table.catches.clone();
+                catches.remove(*j);
+                try_table_with_catches(&mut insn, try_table.ty, catches)?;
+            }
+            Change::RethrowThrow(tag) => {
+                insn.block(BlockType::Result(ValType::EXNREF))
+                    .try_table(BlockType::Empty, [wasm_encoder::Catch::AllRef { label: 0 }])
+                    .throw(*tag)
+                    .end()
+                    .unreachable()
+                    .end()
+                    .throw_ref();
+            }
+        }
+        Ok(Some((*i..*i + 1, bytes)))
+    }
+}
+
+fn try_table_with_catches(
+    insn: &mut InstructionSink,
+    ty: wasmparser::BlockType,
+    catches: Vec<Catch>,
+) -> Result<()> {
+    let catches = catches
+        .into_iter()
+        .map(wasm_encoder::Catch::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    insn.try_table(ty.try_into()?, catches);
+    Ok(())
+}
+
+/// Whether a catch clause can never be taken because an earlier clause
+/// catches every exception it would.
+fn is_shadowed(earlier: &[Catch], catch: &Catch) -> bool {
+    earlier.iter().any(|prev| match (prev, catch) {
+        (Catch::All { .. } | Catch::AllRef { .. }, _) => true,
+        (
+            Catch::One { tag: a, .. } | Catch::OneRef { tag: a, .. },
+            Catch::One { tag: b, .. } | Catch::OneRef { tag: b, .. },
+        ) => a == b,
+        _ => false,
+    })
+}
+
+impl Mutator for TryTableMutator {
+    fn mutate<'a>(
+        &self,
+        config: &mut WasmMutate<'a>,
+    ) -> Result<Box<dyn Iterator<Item = Result<Module>> + 'a>> {
+        let module = replace_operators(config, |config, _, operators| {
+            self.replacement(config, operators)
+        })?;
+        Ok(Box::new(std::iter::once(Ok(module))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        config.info().has_nonempty_code() && config.info().uses_exceptions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TryTableMutator;
+    use crate::WasmMutate;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn no_exceptions_without_exceptions_proposal() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (func (result i32)
+                    block (result i32)
+                        i32.const 1
+                    end)
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!TryTableMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn no_exceptions_with_only_legacy_exceptions() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (tag $e)
+                (func
+                    try
+                        throw $e
+                    catch_all
+                    end)
+            )
+            "#,
+        )
+        .unwrap();
+        let mut config = WasmMutate::default();
+        config.setup(&wasm).unwrap();
+        assert!(!TryTableMutator.can_mutate(&config));
+    }
+
+    #[test]
+    fn block_to_try_table() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func (result i32)
+                    block (result i32)
+                        i32.const 1
+                    end)
+                (func (param exnref))
+            )
+            "#,
+            TryTableMutator,
+            r#"
+            (module
+                (func (result i32)
+                    try_table (result i32)
+                        i32.const 1
+                    end)
+                (func (param exnref))
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn try_table_to_block() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (func
+                    try_table
+                        br 0
+                    end)
+            )
+            "#,
+            TryTableMutator,
+            r#"
+            (module
+                (func
+                    block
+                        br 0
+                    end)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn duplicate_catch() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (tag $e)
+                (func
+                    block
+                        try_table (catch $e 0)
+                        end
+                    end)
+            )
+            "#,
+            TryTableMutator,
+            r#"
+            (module
+                (tag $e)
+                (func
+                    block
+                        try_table (catch $e 0) (catch $e 0)
+                        end
+                    end)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn remove_shadowed_catch() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (tag $e)
+                (func
+                    block (result exnref)
+                        try_table (catch_all_ref 0) (catch_ref $e 0)
+                            unreachable
+                        end
+                        unreachable
+                    end
+                    drop)
+            )
+            "#,
+            TryTableMutator,
+            r#"
+            (module
+                (tag $e)
+                (func
+                    block (result exnref)
+                        try_table (catch_all_ref 0)
+                            unreachable
+                        end
+                        unreachable
+                    end
+                    drop)
+            )
+            "#,
+        );
+    }
+
+    #[test]
+    fn rethrow_throw() {
+        crate::mutators::match_mutation(
+            r#"
+            (module
+                (tag $e)
+                (func
+                    throw $e)
+                (func (param exnref))
+            )
+            "#,
+            TryTableMutator,
+            r#"
+            (module
+                (tag $e)
+                (func
+                    block (result exnref)
+                        try_table (catch_all_ref 0)
+                            throw $e
+                        end
+                        unreachable
+                    end
+                    throw_ref)
+                (func (param exnref))
+            )
+            "#,
+        );
+    }
+}
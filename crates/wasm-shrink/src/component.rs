@@ -0,0 +1,458 @@
+//! Size-reducing mutations for components.
+//!
+//! `wasm-mutate` only knows how to reduce core modules, so components are
+//! shrunk here instead. A component is parsed into a tree of its sections,
+//! where nested core modules and components are kept as their own nodes, a
+//! single reduction is applied somewhere in that tree, and then the tree is
+//! encoded back into a component.
+//!
+//! None of these reductions attempt to preserve validity; the caller is
+//! expected to filter out candidates that fail validation.
+
+use anyhow::{Result, bail};
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+use std::ops::Range;
+use wasm_encoder::reencode::{ReencodeComponent, RoundtripReencoder};
+use wasm_encoder::{ComponentSectionId, Encode};
+use wasm_mutate::WasmMutate;
+use wasmparser::{BinaryReader, FromReader, Parser, Payload, SectionLimited};
+
+/// Every component section id, to map the raw ids of parsed sections back to
+/// a [`ComponentSectionId`].
+const SECTION_IDS: &[ComponentSectionId] = &[
+    ComponentSectionId::CoreCustom,
+    ComponentSectionId::CoreModule,
+    ComponentSectionId::CoreInstance,
+    ComponentSectionId::CoreType,
+    ComponentSectionId::Component,
+    ComponentSectionId::Instance,
+    ComponentSectionId::Alias,
+    ComponentSectionId::Type,
+    ComponentSectionId::CanonicalFunction,
+    ComponentSectionId::Start,
+    ComponentSectionId::Import,
+    ComponentSectionId::Export,
+];
+
+/// An empty component, used in place of the empty module when shrinking
+/// components.
+#[rustfmt::skip]
+pub(crate) static EMPTY_COMPONENT: &[u8] = &[
+    // Magic.
+    0x00, b'a', b's', b'm',
+    // Version and layer.
+    0x0d, 0x00, 0x01, 0x00,
+];
+
+/// Apply a single, randomly chosen size-reducing mutation to the given
+/// component, returning the new component.
+pub(crate) fn shrink(wasm: &[u8], seed: u64) -> Result<Vec<u8>> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut component = Component::parse(wasm)?;
+    component.shrink(&mut rng)?;
+    Ok(component.encode())
+}
+
+#[derive(Clone, Copy)]
+enum Strategy {
+    RemoveSection,
+    RemoveItem,
+    SimplifyInstantiation,
+    StubModule,
+    ShrinkModule,
+}
+
+const STRATEGIES: &[Strategy] = &[
+    Strategy::RemoveSection,
+    Strategy::RemoveItem,
+    Strategy::SimplifyInstantiation,
+    Strategy::StubModule,
+    Strategy::ShrinkModule,
+];
+
+struct Component {
+    sections: Vec<Section>,
+}
+
+enum Section {
+    Module(Vec<u8>),
+    Component(Component),
+    Other {
+        id: ComponentSectionId,
+        data: Vec<u8>,
+    },
+}
+
+impl Component {
+    fn parse(wasm: &[u8]) -> Result<Component> {
+        let mut sections = Vec::new();
+        let mut depth = 0;
+        for payload in Parser::new(0).parse_all(wasm) {
+            let payload = payload?;
+            match &payload {
+                Payload::Version { .. } => {
+                    depth += 1;
+                    continue;
+                }
+                Payload::End(_) => {
+                    depth -= 1;
+                    continue;
+                }
+                _ => {}
+            }
+            // Only sections directly within this component are recorded here;
+            // the contents of nested modules and components are handled when
+            // their own bytes are parsed.
+            if depth != 1 {
+                continue;
+            }
+            let Some((id, range)) = payload.as_section() else {
+                continue;
+            };
+            let Some(id) = SECTION_IDS.iter().copied().find(|s| *s as u8 == id) else {
+                bail!("unknown component section id {id}");
+            };
+            let data = &wasm[range];
+            sections.push(match id {
+                ComponentSectionId::CoreModule => Section::Module(data.to_vec()),
+                ComponentSectionId::Component => Section::Component(Component::parse(data)?),
+                _ => Section::Other {
+                    id,
+                    data: data.to_vec(),
+                },
+            });
+        }
+        Ok(Component { sections })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut component = wasm_encoder::Component::new();
+        for section in &self.sections {
+            match section {
+                Section::Module(module) => {
+                    component.section(&wasm_encoder::RawSection {
+                        id: ComponentSectionId::CoreModule as u8,
+                        data: module,
+                    });
+                }
+                Section::Component(nested) => {
+                    component.section(&wasm_encoder::RawSection {
+                        id: ComponentSectionId::Component as u8,
+                        data: &nested.encode(),
+                    });
+                }
+                Section::Other { id, data } => {
+                    component.section(&wasm_encoder::RawSection {
+                        id: *id as u8,
+                        data,
+                    });
+                }
+            }
+        }
+        component.finish()
+    }
+
+    fn nested_components(&mut self) -> impl Iterator<Item = &mut Component> {
+        self.sections.iter_mut().filter_map(|s| match s {
+            Section::Component(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Indices of sections in this component that match `f`.
+    fn sections_where(&self, f: impl Fn(&Section) -> bool) -> Vec<usize> {
+        (0..self.sections.len())
+            .filter(|i| f(&self.sections[*i]))
+            .collect()
+    }
+
+    fn shrink(&mut self, rng: &mut SmallRng) -> Result<()> {
+        // Pick either this component or one of its nested components to
+        // mutate.
+        let nested = self.nested_components().count();
+        let choice = rng.random_range(0..=nested);
+        if choice < nested {
+            return self.nested_components().nth(choice).unwrap().shrink(rng);
+        }
+
+        // Attempt all strategies, but start at an arbitrary index.
+        let start = rng.random_range(0..STRATEGIES.len());
+        for strategy in STRATEGIES.iter().cycle().skip(start).take(STRATEGIES.len()) {
+            if self.apply(*strategy, rng)? {
+                return Ok(());
+            }
+        }
+        bail!("no component shrinking strategy applies")
+    }
+
+    /// Attempt to apply the given strategy, returning whether this component
+    /// was mutated.
+    fn apply(&mut self, strategy: Strategy, rng: &mut SmallRng) -> Result<bool> {
+        let candidates = match strategy {
+            Strategy::RemoveSection => self.sections_where(|_| true),
+            Strategy::RemoveItem => self.sections_where(|s| {
+                matches!(
+                    s,
+                    Section::Other {
+                        id: ComponentSectionId::CoreInstance
+                            | ComponentSectionId::CoreType
+                            | ComponentSectionId::Instance
+                            | ComponentSectionId::Alias
+                            | ComponentSectionId::Type
+                            | ComponentSectionId::CanonicalFunction
+                            | ComponentSectionId::Import
+                            | ComponentSectionId::Export,
+                        ..
+                    }
+                )
+            }),
+            Strategy::SimplifyInstantiation => self.sections_where(|s| {
+                matches!(
+                    s,
+                    Section::Other {
+                        id: ComponentSectionId::CoreInstance | ComponentSectionId::Instance,
+                        ..
+                    }
+                )
+            }),
+            Strategy::StubModule | Strategy::ShrinkModule => {
+                self.sections_where(|s| matches!(s, Section::Module(_)))
+            }
+        };
+        if candidates.is_empty() {
+            return Ok(false);
+        }
+        let index = candidates[rng.random_range(0..candidates.len())];
+
+        match strategy {
+            Strategy::RemoveSection => {
+                log::trace!("removing component section {index}");
+                self.sections.remove(index);
+                Ok(true)
+            }
+            Strategy::StubModule | Strategy::ShrinkModule => {
+                let Section::Module(module) = &mut self.sections[index] else {
+                    unreachable!()
+                };
+                let new_module = match strategy {
+                    Strategy::StubModule => stub_module(module)?,
+                    _ => shrink_module(module, rng)?,
+                };
+                match new_module {
+                    Some(m) if m.len() < module.len() => {
+                        *module = m;
+                        Ok(true)
+                    }
+                    _ => Ok(false),
+                }
+            }
+            Strategy::RemoveItem | Strategy::SimplifyInstantiation => {
+                let Section::Other { id, data } = &mut self.sections[index] else {
+                    unreachable!()
+                };
+                let new_data = match strategy {
+                    Strategy::RemoveItem => remove_item(*id, data, rng)?,
+                    _ => simplify_instantiation(*id, data, rng)?,
+                };
+                match new_data {
+                    // Don't leave empty sections behind, they are just
+                    // wasted bytes.
+                    Some(new_data) if new_data == [0] => {
+                        self.sections.remove(index);
+                        Ok(true)
+                    }
+                    Some(new_data) => {
+                        *data = new_data;
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                }
+            }
+        }
+    }
+}
+
+/// Byte ranges, relative to the start of `data`, of each item in a
+/// count-prefixed section.
+fn item_ranges<'a, T: FromReader<'a>>(data: &'a [u8]) -> Result<Vec<Range<usize>>> {
+    let reader = SectionLimited::<T>::new(BinaryReader::new(data, 0))?;
+    let mut starts = Vec::new();
+    for item in reader.into_iter_with_offsets() {
+        let (offset, _) = item?;
+        starts.push(offset);
+    }
+    let ends = starts.iter().skip(1).copied().chain([data.len()]);
+    Ok(starts
+        .iter()
+        .copied()
+        .zip(ends)
+        .map(|(s, e)| s..e)
+        .collect())
+}
+
+/// Remove a randomly chosen item from a count-prefixed section.
+fn remove_item(id: ComponentSectionId, data: &[u8], rng: &mut SmallRng) -> Result<Option<Vec<u8>>> {
+    let ranges = match id {
+        ComponentSectionId::CoreInstance => item_ranges::<wasmparser::Instance>(data)?,
+        ComponentSectionId::CoreType => item_ranges::<wasmparser::CoreType>(data)?,
+        ComponentSectionId::Instance => item_ranges::<wasmparser::ComponentInstance>(data)?,
+        ComponentSectionId::Alias => item_ranges::<wasmparser::ComponentAlias>(data)?,
+        ComponentSectionId::Type => item_ranges::<wasmparser::ComponentType>(data)?,
+        ComponentSectionId::CanonicalFunction => {
+            item_ranges::<wasmparser::CanonicalFunction>(data)?
+        }
+        ComponentSectionId::Import => item_ranges::<wasmparser::ComponentImport>(data)?,
+        ComponentSectionId::Export => item_ranges::<wasmparser::ComponentExport>(data)?,
+        _ => return Ok(None),
+    };
+    if ranges.is_empty() {
+        return Ok(None);
+    }
+    let removed = rng.random_range(0..ranges.len());
+    log::trace!("removing item {removed} from component section {id:?}");
+
+    let mut new_data = Vec::with_capacity(data.len());
+    (ranges.len() as u32 - 1).encode(&mut new_data);
+    for (i, range) in ranges.into_iter().enumerate() {
+        if i != removed {
+            new_data.extend_from_slice(&data[range]);
+        }
+    }
+    Ok(Some(new_data))
+}
+
+/// Drop a randomly chosen argument from an instantiation (or export from an
+/// instance created from exports) in a core or component instance section.
+fn simplify_instantiation(
+    id: ComponentSectionId,
+    data: &[u8],
+    rng: &mut SmallRng,
+) -> Result<Option<Vec<u8>>> {
+    let mut reencoder = RoundtripReencoder;
+    let reader = BinaryReader::new(data, 0);
+    let mut encoded = Vec::new();
+    match id {
+        ComponentSectionId::CoreInstance => {
+            let mut instances = SectionLimited::<wasmparser::Instance>::new(reader)?
+                .into_iter()
+                .collect::<wasmparser::Result<Vec<_>>>()?;
+            let candidates = (0..instances.len())
+                .filter(|i| match &instances[*i] {
+                    wasmparser::Instance::Instantiate { args, .. } => !args.is_empty(),
+                    wasmparser::Instance::FromExports(exports) => !exports.is_empty(),
+                })
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                return Ok(None);
+            }
+            let instance = &mut instances[candidates[rng.random_range(0..candidates.len())]];
+            match instance {
+                wasmparser::Instance::Instantiate { args, .. } => {
+                    *args = remove_random(args, rng);
+                }
+                wasmparser::Instance::FromExports(exports) => {
+                    *exports = remove_random(exports, rng);
+                }
+            }
+            let mut section = wasm_encoder::InstanceSection::new();
+            for instance in instances {
+                reencoder.parse_instance(&mut section, instance)?;
+            }
+            section.encode(&mut encoded);
+        }
+        ComponentSectionId::Instance => {
+            let mut instances = SectionLimited::<wasmparser::ComponentInstance>::new(reader)?
+                .into_iter()
+                .collect::<wasmparser::Result<Vec<_>>>()?;
+            let candidates = (0..instances.len())
+                .filter(|i| match &instances[*i] {
+                    wasmparser::ComponentInstance::Instantiate { args, .. } => !args.is_empty(),
+                    wasmparser::ComponentInstance::FromExports(exports) => !exports.is_empty(),
+                })
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                return Ok(None);
+            }
+            let instance = &mut instances[candidates[rng.random_range(0..candidates.len())]];
+            match instance {
+                wasmparser::ComponentInstance::Instantiate { args, .. } => {
+                    *args = remove_random(args, rng);
+                }
+                wasmparser::ComponentInstance::FromExports(exports) => {
+                    *exports = remove_random(exports, rng);
+                }
+            }
+            let mut section = wasm_encoder::ComponentInstanceSection::new();
+            for instance in instances {
+                reencoder.parse_component_instance(&mut section, instance)?;
+            }
+            section.encode(&mut encoded);
+        }
+        _ => return Ok(None),
+    }
+    Ok(Some(encoded))
+}
+
+fn remove_random<T: Clone>(items: &[T], rng: &mut SmallRng) -> Box<[T]> {
+    let removed = rng.random_range(0..items.len());
+    items
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != removed)
+        .map(|(_, item)| item.clone())
+        .collect()
+}
+
+/// Replace a core module with a stub that has the same imports, exports and
+/// entity definitions, but whose functions all trap and which has no start
+/// function, element or data segments, or custom sections.
+fn stub_module(module: &[u8]) -> Result<Option<Vec<u8>>> {
+    let mut stub = wasm_encoder::Module::new();
+    for payload in Parser::new(0).parse_all(module) {
+        let payload = payload?;
+        match &payload {
+            Payload::CodeSectionStart { count, .. } => {
+                let mut code = wasm_encoder::CodeSection::new();
+                for _ in 0..*count {
+                    let mut func = wasm_encoder::Function::new([]);
+                    func.instructions().unreachable().end();
+                    code.function(&func);
+                }
+                stub.section(&code);
+            }
+            Payload::TypeSection(_)
+            | Payload::ImportSection(_)
+            | Payload::FunctionSection(_)
+            | Payload::TableSection(_)
+            | Payload::MemorySection(_)
+            | Payload::TagSection(_)
+            | Payload::GlobalSection(_)
+            | Payload::ExportSection(_) => {
+                let (id, range) = payload.as_section().unwrap();
+                stub.section(&wasm_encoder::RawSection {
+                    id,
+                    data: &module[range],
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(Some(stub.finish()))
+}
+
+/// Reduce a core module with `wasm-mutate`.
+fn shrink_module(module: &[u8], rng: &mut SmallRng) -> Result<Option<Vec<u8>>> {
+    let mut mutate = WasmMutate::default();
+    mutate.reduce(true).seed(rng.random());
+    let mutated = match mutate.run(module) {
+        Ok(mut mutations) => mutations.next().transpose(),
+        Err(e) => Err(e),
+    };
+    match mutated {
+        Ok(m) => Ok(m),
+        Err(e) => {
+            log::trace!("failed to shrink nested module: {e:?}");
+            Ok(None)
+        }
+    }
+}
@@ -5,10 +5,15 @@
 
 use anyhow::{Context, Result};
 use rand::{Rng, SeedableRng, rngs::SmallRng};
+use rayon::prelude::*;
 use std::collections::HashSet;
 use wasm_mutate::WasmMutate;
 use wasmparser::WasmFeatures;
 
+mod component;
+#[cfg(feature = "wast")]
+mod script;
+
 #[rustfmt::skip]
 static EMPTY_WASM: &'static [u8] = &[
     // Magic.
@@ -145,6 +150,45 @@ impl WasmShrink {
     {
         ShrinkRun::new(self, input).run(predicate)
     }
+
+    /// Run this configured Wasm shrinking task, testing candidates with the
+    /// predicate in parallel.
+    ///
+    /// Each round of shrinking generates `jobs` candidates and tests them all
+    /// with the predicate on a thread pool. The smallest interesting candidate
+    /// of the round is then committed, so the result is deterministic for a
+    /// given seed and number of jobs as long as the predicate is deterministic
+    /// too.
+    ///
+    /// With a single job this is the same as [`WasmShrink::run`].
+    pub fn run_parallel<P, I>(self, input: Vec<u8>, jobs: usize, predicate: P) -> Result<ShrinkInfo>
+    where
+        P: Fn(&[u8]) -> Result<I> + Sync,
+        I: IsInteresting + Send,
+    {
+        if jobs <= 1 {
+            return self.run(input, predicate);
+        }
+        ShrinkRun::new(self, input).run_parallel(jobs, predicate)
+    }
+
+    /// Run this configured shrinking task on a `*.wast` script.
+    ///
+    /// This removes directives from the script that aren't needed to keep it
+    /// interesting and shrinks each module that it defines in the text format.
+    /// The `predicate` function is called on the text of each candidate
+    /// script, and the `on_new_smallest` callback is given the text of each
+    /// new smallest script.
+    ///
+    /// The returned `ShrinkInfo`'s output is the text of the shrunken script.
+    #[cfg(feature = "wast")]
+    pub fn run_wast<P, I>(self, input: &str, predicate: P) -> Result<ShrinkInfo>
+    where
+        P: FnMut(&str) -> Result<I>,
+        I: IsInteresting,
+    {
+        script::ShrinkWastRun::new(self, input)?.run(predicate)
+    }
 }
 
 struct ShrinkRun {
@@ -165,12 +209,16 @@ struct ShrinkRun {
     // The count of how many times we've attempted to shrink our current test
     // case smaller than `best`.
     attempt: u32,
+
+    // Whether the input is a component rather than a core module.
+    is_component: bool,
 }
 
 impl ShrinkRun {
     pub fn new(shrink: WasmShrink, input: Vec<u8>) -> ShrinkRun {
         let rng = SmallRng::seed_from_u64(shrink.seed);
         let input_size = input.len() as u64;
+        let is_component = wasmparser::Parser::is_component(&input);
         let best = input;
         ShrinkRun {
             shrink,
@@ -179,12 +227,23 @@ impl ShrinkRun {
             best,
             already_tested: HashSet::new(),
             attempt: 0,
+            is_component,
+        }
+    }
+
+    /// The empty Wasm of the same kind as the input.
+    fn empty_wasm(&self) -> &'static [u8] {
+        if self.is_component {
+            component::EMPTY_COMPONENT
+        } else {
+            EMPTY_WASM
         }
     }
 
     fn on_new_best(&mut self, new_best: Vec<u8>) -> Result<()> {
         debug_assert!(
-            new_best.len() < self.best.len() || (new_best == EMPTY_WASM && self.best == EMPTY_WASM)
+            new_best.len() < self.best.len()
+                || (new_best == self.empty_wasm() && self.best == self.empty_wasm())
         );
         log::info!("New smallest Wasm found: {} bytes", new_best.len());
         if let Some(f) = self.shrink.on_new_smallest.as_mut() {
@@ -215,12 +274,6 @@ impl ShrinkRun {
         Ok(())
     }
 
-    fn validate_wasm(&self, wasm: &[u8]) -> Result<()> {
-        let mut validator = wasmparser::Validator::new_with_features(WasmFeatures::all());
-        validator.validate_all(wasm)?;
-        Ok(())
-    }
-
     fn finish(self) -> ShrinkInfo {
         ShrinkInfo {
             input_size: self.input_size,
@@ -229,27 +282,16 @@ impl ShrinkRun {
         }
     }
 
-    pub fn run<P, I>(mut self, mut predicate: P) -> Result<ShrinkInfo>
+    /// Check that the input Wasm and the predicate are sensible, returning
+    /// whether shrinking is already finished because the empty Wasm is
+    /// interesting and allowed.
+    fn check_prerequisites<P, I>(&mut self, predicate: &mut P) -> Result<bool>
     where
         P: FnMut(&[u8]) -> Result<I>,
         I: IsInteresting,
     {
-        // The Wasm that we are currently mutating.
-        //
-        // This can differ from `best` in that, with a very small probability,
-        // we will sometimes accept mutations that don't shrink Wasm size. This
-        // behavior is borrowed from MCMC[0] and helps us avoid getting stuck in
-        // local minima. For example, we might replace a `ref.func $f` with a
-        // `ref.null`, which doesn't actually shrink code size itself, but which
-        // might make `$f` dead code such that we can remove `$f` altogether in
-        // a follow up mutation.
-        //
-        // [0]: https://en.wikipedia.org/wiki/Markov_chain_Monte_Carlo
-        let mut current = self.best.clone();
-
         // Check prerequisites for the input Wasm.
-        self.validate_wasm(&current)
-            .context("The input is not valid Wasm.")?;
+        validate_wasm(&self.best).context("The input is not valid Wasm.")?;
 
         // First double check that the input Wasm passes the predicate.
         //
@@ -257,7 +299,7 @@ impl ShrinkRun {
         // doesn't consider the input Wasm interesting. Better to surface this
         // user error as quick as possible than to make them wait until we've
         // exhausted all the ways we could shrink it further.
-        let result = predicate(&current)?;
+        let result = predicate(&self.best)?;
         anyhow::ensure!(
             result.is_interesting(),
             "The predicate does not consider the input Wasm interesting: {}",
@@ -270,11 +312,12 @@ impl ShrinkRun {
         // considers the empty module interesting, and we might as well check
         // for it eagerly, rather than make the user wait forever until we
         // finally to reduce the whole Wasm module to nothing.
-        let result = predicate(EMPTY_WASM)?;
+        let empty_wasm = self.empty_wasm();
+        let result = predicate(empty_wasm)?;
         if result.is_interesting() {
             if self.shrink.allow_empty {
-                self.on_new_best(EMPTY_WASM.to_vec())?;
-                return Ok(self.finish());
+                self.on_new_best(empty_wasm.to_vec())?;
+                return Ok(true);
             } else {
                 anyhow::bail!(
                     "The predicate considers the empty Wasm module \
@@ -287,6 +330,31 @@ impl ShrinkRun {
             }
         }
 
+        Ok(false)
+    }
+
+    pub fn run<P, I>(mut self, mut predicate: P) -> Result<ShrinkInfo>
+    where
+        P: FnMut(&[u8]) -> Result<I>,
+        I: IsInteresting,
+    {
+        // The Wasm that we are currently mutating.
+        //
+        // This can differ from `best` in that, with a very small probability,
+        // we will sometimes accept mutations that don't shrink Wasm size. This
+        // behavior is borrowed from MCMC[0] and helps us avoid getting stuck in
+        // local minima. For example, we might replace a `ref.func $f` with a
+        // `ref.null`, which doesn't actually shrink code size itself, but which
+        // might make `$f` dead code such that we can remove `$f` altogether in
+        // a follow up mutation.
+        //
+        // [0]: https://en.wikipedia.org/wiki/Markov_chain_Monte_Carlo
+        let mut current = self.best.clone();
+
+        if self.check_prerequisites(&mut predicate)? {
+            return Ok(self.finish());
+        }
+
         // Now we perform the main search. Keep trying to find smaller and
         // interesting variants of the current smallest interesting Wasm file
         // until we run out of attempts and get stuck.
@@ -298,13 +366,19 @@ impl ShrinkRun {
             mutate.reduce(true).seed(seed);
             log::trace!("Attempt #{}: seed: {}", self.attempt, seed);
 
-            let mutations = match mutate.run(&current) {
-                Ok(m) => m,
-                Err(e) => {
-                    // This mutation failed, but another randomly chosen
-                    // mutation might succeed, so keep attempting.
-                    log::trace!("Attempt #{}: mutation failed ({:?})", self.attempt, e);
-                    continue;
+            // `wasm-mutate` only handles core modules, so components are
+            // reduced with our own component-level mutations instead.
+            let mutations: Box<dyn Iterator<Item = Result<Vec<u8>>>> = if self.is_component {
+                Box::new(std::iter::once(component::shrink(&current, seed)))
+            } else {
+                match mutate.run(&current) {
+                    Ok(m) => Box::new(m.map(|m| m.map_err(Into::into))),
+                    Err(e) => {
+                        // This mutation failed, but another randomly chosen
+                        // mutation might succeed, so keep attempting.
+                        log::trace!("Attempt #{}: mutation failed ({:?})", self.attempt, e);
+                        continue;
+                    }
                 }
             };
 
@@ -346,6 +420,15 @@ impl ShrinkRun {
                     continue;
                 }
 
+                // Component mutations don't preserve validity, so filter out
+                // invalid candidates before bothering the predicate with them.
+                if self.is_component {
+                    if let Err(e) = validate_wasm(&mutated_wasm) {
+                        log::trace!("Attempt #{}: candidate is invalid ({:?})", self.attempt, e);
+                        continue;
+                    }
+                }
+
                 log::trace!(
                     "Attempt #{}: testing candidate ({} bytes)",
                     self.attempt,
@@ -372,6 +455,108 @@ impl ShrinkRun {
 
         Ok(self.finish())
     }
+
+    pub fn run_parallel<P, I>(mut self, jobs: usize, predicate: P) -> Result<ShrinkInfo>
+    where
+        P: Fn(&[u8]) -> Result<I> + Sync,
+        I: IsInteresting + Send,
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()
+            .context("Failed to create a thread pool.")?;
+
+        // See `run` for details on how `current` and `best` differ.
+        let mut current = self.best.clone();
+
+        if self.check_prerequisites(&mut |wasm| predicate(wasm))? {
+            return Ok(self.finish());
+        }
+
+        while self.attempt < self.shrink.attempts {
+            // Draw all of this round's seeds up front, on this thread, so that
+            // the candidates don't depend on how the thread pool schedules
+            // their generation.
+            let n = std::cmp::min(jobs as u32, self.shrink.attempts - self.attempt);
+            let seeds = (0..n).map(|_| self.rng.random()).collect::<Vec<u64>>();
+            self.attempt += n;
+            log::trace!("Attempt #{}: seeds: {:?}", self.attempt, seeds);
+
+            let is_component = self.is_component;
+            let candidates = pool.install(|| {
+                seeds
+                    .par_iter()
+                    .map(|seed| generate_candidate(&current, *seed, is_component))
+                    .collect::<Vec<_>>()
+            });
+            let candidates = candidates
+                .into_iter()
+                .flatten()
+                .filter(|wasm| self.already_tested.insert(blake3::hash(wasm)))
+                .collect::<Vec<_>>();
+            log::trace!(
+                "Attempt #{}: testing {} candidates",
+                self.attempt,
+                candidates.len()
+            );
+
+            let results = pool.install(|| {
+                candidates
+                    .par_iter()
+                    .map(|wasm| predicate(wasm))
+                    .collect::<Vec<_>>()
+            });
+
+            // Commit the smallest interesting candidate, preferring earlier
+            // candidates on ties, so that the outcome of a round doesn't
+            // depend on the order in which predicates finished.
+            let mut smallest: Option<Vec<u8>> = None;
+            for (wasm, result) in candidates.into_iter().zip(results) {
+                if !result?.is_interesting() {
+                    continue;
+                }
+                match &smallest {
+                    Some(s) if s.len() <= wasm.len() => {}
+                    _ => smallest = Some(wasm),
+                }
+            }
+            if let Some(wasm) = smallest {
+                log::trace!(
+                    "Attempt #{}: smallest interesting candidate is {} bytes",
+                    self.attempt,
+                    wasm.len()
+                );
+                if self.should_accept(&current, &wasm) {
+                    log::trace!("Attempt #{}: accepting candidate", self.attempt);
+                    self.on_new_interesting(&mut current, wasm)?;
+                }
+            }
+        }
+
+        Ok(self.finish())
+    }
+}
+
+/// Generate a single size-reducing candidate of the given Wasm from `seed`.
+///
+/// This is used by parallel shrinking, where only the first mutation for each
+/// seed is used.
+fn generate_candidate(wasm: &[u8], seed: u64, is_component: bool) -> Option<Vec<u8>> {
+    if is_component {
+        let candidate = component::shrink(wasm, seed).ok()?;
+        validate_wasm(&candidate).ok()?;
+        return Some(candidate);
+    }
+    let mut mutate = WasmMutate::default();
+    mutate.reduce(true).seed(seed);
+    let mut mutations = mutate.run(wasm).ok()?;
+    mutations.find_map(|m| m.ok())
+}
+
+fn validate_wasm(wasm: &[u8]) -> Result<()> {
+    let mut validator = wasmparser::Validator::new_with_features(WasmFeatures::all());
+    validator.validate_all(wasm)?;
+    Ok(())
 }
 
 /// A type that describes whether a Wasm is interesting or not.
@@ -0,0 +1,317 @@
+//! Shrinking `*.wast` scripts.
+//!
+//! A script is reduced in two ways: first by removing whole directives (such
+//! as `assert_*` directives or modules that aren't relevant), and second by
+//! shrinking the modules that remain with the usual binary shrinking and then
+//! printing them back into the script.
+
+use crate::{IsInteresting, ShrinkInfo, WasmShrink};
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use wast::lexer::{Lexer, TokenKind};
+use wast::parser::{self, ParseBuffer};
+use wast::{QuoteWat, Wast, WastDirective};
+
+pub(crate) struct ShrinkWastRun {
+    shrink: WasmShrink,
+
+    // The size of the original input script.
+    input_size: u64,
+
+    // The top-level directives of the smallest script that passes the
+    // predicate.
+    directives: Vec<String>,
+
+    // The smallest script that passes the predicate.
+    best: String,
+
+    // Candidate scripts that we've already tested, and whether they were
+    // interesting.
+    //
+    // Unlike with binary shrinking, the same candidate is sometimes
+    // deliberately tested again, for example when the printed form of a module
+    // is identical to its original source text, so the results are kept.
+    already_tested: HashMap<blake3::Hash, bool>,
+}
+
+impl ShrinkWastRun {
+    pub fn new(shrink: WasmShrink, input: &str) -> Result<ShrinkWastRun> {
+        let buf = ParseBuffer::new(input)?;
+        let wast = parser::parse::<Wast>(&buf).context("The input is not a valid wast script.")?;
+        Ok(ShrinkWastRun {
+            shrink,
+            input_size: input.len() as u64,
+            directives: split_directives(input, &wast)?,
+            best: input.to_string(),
+            already_tested: HashMap::new(),
+        })
+    }
+
+    pub fn run<P, I>(mut self, mut predicate: P) -> Result<ShrinkInfo>
+    where
+        P: FnMut(&str) -> Result<I>,
+        I: IsInteresting,
+    {
+        // Same as with binary shrinking, eagerly surface predicates that don't
+        // consider the input interesting, or that consider the empty script
+        // interesting.
+        let result = predicate(&self.best)?;
+        anyhow::ensure!(
+            result.is_interesting(),
+            "The predicate does not consider the input script interesting: {}",
+            result
+        );
+
+        let result = predicate("")?;
+        if result.is_interesting() {
+            if self.shrink.allow_empty {
+                self.on_new_best(String::new())?;
+                return Ok(self.finish());
+            } else {
+                bail!(
+                    "The predicate considers the empty script interesting, \
+                     which is usually not desired and is a symptom of a bug \
+                     in the predicate:\n\
+                     \n\
+                     {}",
+                    result
+                );
+            }
+        }
+
+        // Splitting the script into directives drops any comments and
+        // whitespace between them, so double check that this normalized
+        // script is still interesting before building on it.
+        let directives = self.directives.clone();
+        anyhow::ensure!(
+            self.test(&directives, &mut predicate)?,
+            "The predicate does not consider the input script interesting once \
+             comments are removed."
+        );
+
+        // Remove directives first since that's the cheapest way to make
+        // progress, then shrink the modules that are left, and finally try
+        // removing directives again now that modules have fewer exports for
+        // the remaining directives to refer to.
+        self.remove_directives(&mut predicate)?;
+        self.shrink_modules(&mut predicate)?;
+        self.remove_directives(&mut predicate)?;
+
+        Ok(self.finish())
+    }
+
+    fn on_new_best(&mut self, new_best: String) -> Result<()> {
+        debug_assert!(new_best.len() < self.best.len() || new_best.is_empty());
+        log::info!("New smallest script found: {} bytes", new_best.len());
+        if let Some(f) = self.shrink.on_new_smallest.as_mut() {
+            f(new_best.as_bytes())?;
+        }
+        self.best = new_best;
+        Ok(())
+    }
+
+    /// Test whether the script made up of the given directives is
+    /// interesting, recording it as the new best if it is also the smallest
+    /// so far.
+    fn test<P, I>(&mut self, directives: &[String], predicate: &mut P) -> Result<bool>
+    where
+        P: FnMut(&str) -> Result<I>,
+        I: IsInteresting,
+    {
+        let script = join(directives);
+        let hash = blake3::hash(script.as_bytes());
+        if let Some(interesting) = self.already_tested.get(&hash) {
+            log::trace!("already tested this candidate");
+            return Ok(*interesting);
+        }
+        log::trace!("testing candidate ({} bytes)", script.len());
+        let interesting = predicate(&script)?.is_interesting();
+        self.already_tested.insert(hash, interesting);
+        if interesting && script.len() < self.best.len() {
+            self.on_new_best(script)?;
+        }
+        Ok(interesting)
+    }
+
+    /// Remove as many directives as possible, starting with large contiguous
+    /// chunks of directives and working down to individual directives.
+    fn remove_directives<P, I>(&mut self, predicate: &mut P) -> Result<()>
+    where
+        P: FnMut(&str) -> Result<I>,
+        I: IsInteresting,
+    {
+        let mut chunk = std::cmp::max(self.directives.len() / 2, 1);
+        loop {
+            let mut removed_any = false;
+            let mut i = 0;
+            while i < self.directives.len() {
+                let end = std::cmp::min(i + chunk, self.directives.len());
+                let mut candidate = self.directives.clone();
+                candidate.drain(i..end);
+                // The empty script was already found to be uninteresting.
+                if !candidate.is_empty() && self.test(&candidate, predicate)? {
+                    log::debug!("removed directives {i}..{end}");
+                    self.directives = candidate;
+                    removed_any = true;
+                } else {
+                    i += chunk;
+                }
+            }
+            if chunk > 1 {
+                chunk /= 2;
+            } else if !removed_any {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Shrink each module defined by a top-level `module` or `component`
+    /// directive.
+    fn shrink_modules<P, I>(&mut self, predicate: &mut P) -> Result<()>
+    where
+        P: FnMut(&str) -> Result<I>,
+        I: IsInteresting,
+    {
+        for i in 0..self.directives.len() {
+            let Some((wasm, definition)) = encode_module(&self.directives[i]) else {
+                continue;
+            };
+            log::debug!("shrinking module in directive {i}");
+
+            let shrink = WasmShrink {
+                attempts: self.shrink.attempts,
+                allow_empty: true,
+                seed: self.shrink.seed,
+                on_new_smallest: None,
+            };
+            let mut candidate = self.directives.clone();
+            let result = shrink.run(wasm, |wasm| {
+                candidate[i] = print_module(wasm, definition)?;
+                self.test(&candidate, predicate)
+            });
+
+            // Failing to shrink one module, for example because its printed
+            // form isn't interesting, shouldn't stop us from shrinking the
+            // others.
+            let info = match result {
+                Ok(info) => info,
+                Err(e) => {
+                    log::debug!("failed to shrink module in directive {i}: {e:?}");
+                    continue;
+                }
+            };
+            let module = print_module(&info.output, definition)?;
+            if module.len() < self.directives[i].len() {
+                self.directives[i] = module;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> ShrinkInfo {
+        ShrinkInfo {
+            input_size: self.input_size,
+            output_size: self.best.len() as u64,
+            output: self.best.into_bytes(),
+        }
+    }
+}
+
+fn join(directives: &[String]) -> String {
+    let mut script = String::new();
+    for directive in directives {
+        script.push_str(directive);
+        script.push('\n');
+    }
+    script
+}
+
+/// Split a script into the source text of each of its top-level directives,
+/// as parsed into `wast`, dropping comments and whitespace between them.
+fn split_directives(input: &str, wast: &Wast<'_>) -> Result<Vec<String>> {
+    // Offsets of each opening paren, and of the end of each token which isn't
+    // whitespace or a comment.
+    let mut parens = Vec::new();
+    let mut ends = Vec::new();
+    for token in Lexer::new(input).iter(0) {
+        let token = token?;
+        match token.kind {
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment => {}
+            kind => {
+                if let TokenKind::LParen = kind {
+                    parens.push(token.offset);
+                }
+                ends.push(token.offset + token.len as usize);
+            }
+        }
+    }
+
+    // The span of a directive points just past its opening paren, and the
+    // directive extends up to the last token before the next directive.
+    let starts = wast
+        .directives
+        .iter()
+        .map(|directive| {
+            let offset = directive.span().offset();
+            match parens.partition_point(|p| *p < offset) {
+                0 => 0,
+                i => parens[i - 1],
+            }
+        })
+        .collect::<Vec<_>>();
+    let limits = starts.iter().skip(1).copied().chain([input.len()]);
+    Ok(starts
+        .iter()
+        .zip(limits)
+        .map(|(start, limit)| {
+            let end = match ends.partition_point(|e| *e <= limit) {
+                0 => limit,
+                i => ends[i - 1],
+            };
+            input[*start..end].to_string()
+        })
+        .collect())
+}
+
+/// If the given directive defines a module or component in the text format,
+/// encode it into its binary form, also returning whether it is a
+/// `definition` rather than an instantiated module.
+fn encode_module(directive: &str) -> Option<(Vec<u8>, bool)> {
+    // Scripts whose top-level s-expressions are module fields are parsed as a
+    // single inline module, which we don't want to confuse with a module
+    // directive here.
+    let keyword = directive.strip_prefix('(')?.trim_start();
+    if !keyword.starts_with("module") && !keyword.starts_with("component") {
+        return None;
+    }
+    let buf = ParseBuffer::new(directive).ok()?;
+    let mut wast = parser::parse::<Wast>(&buf).ok()?;
+    if wast.directives.len() != 1 {
+        return None;
+    }
+    let (mut wat, definition) = match wast.directives.pop()? {
+        WastDirective::Module(QuoteWat::Wat(wat)) => (wat, false),
+        WastDirective::ModuleDefinition(QuoteWat::Wat(wat)) => (wat, true),
+        _ => return None,
+    };
+    Some((wat.encode().ok()?, definition))
+}
+
+/// Print a module or component as a directive in a script.
+///
+/// Any `$id` given to the module in the original script is retained through
+/// the name section.
+fn print_module(wasm: &[u8], definition: bool) -> Result<String> {
+    let mut text = wasmprinter::print_bytes(wasm)?;
+    if definition {
+        let keyword = if text.starts_with("(module") {
+            "(module"
+        } else if text.starts_with("(component") {
+            "(component"
+        } else {
+            bail!("unexpected printed module: {text}");
+        };
+        text.insert_str(keyword.len(), " definition");
+    }
+    Ok(text.trim_end().to_string())
+}
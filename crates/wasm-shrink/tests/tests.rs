@@ -56,3 +56,159 @@ fn smoke_test() -> Result<()> {
     wasmparser::validate(&info.output)?;
     Ok(())
 }
+
+fn component() -> Vec<u8> {
+    let _ = env_logger::try_init();
+    wat::parse_str(
+        r#"
+            (component
+                (import "unused" (func $unused))
+                (core module $m
+                    (memory (export "memory") 1)
+                    (func (export "f") (param i32 i32) (result i32)
+                        local.get 0
+                        local.get 1
+                        i32.add
+                    )
+                    (func (export "g") (result i32)
+                        i32.const 42
+                    )
+                )
+                (core module $other
+                    (func (export "h"))
+                )
+                (core instance $i (instantiate $m))
+                (core instance $j (instantiate $other))
+                (component $inner
+                    (core module $nested
+                        (func (export "nested") (result i32)
+                            i32.const 1
+                            i32.const 2
+                            i32.sub
+                        )
+                    )
+                    (core instance (instantiate $nested))
+                )
+                (instance (instantiate $inner))
+                (func (export "add") (param "a" s32) (param "b" s32) (result s32)
+                    (canon lift (core func $i "f"))
+                )
+                (func (export "answer") (result s32)
+                    (canon lift (core func $i "g"))
+                )
+            )
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn shrink_component_to_empty_is_error() -> Result<()> {
+    let result = WasmShrink::default().run(component(), |_| Ok(true));
+    assert!(result.is_err());
+    let err_msg = result.err().unwrap().to_string();
+    assert!(err_msg.contains("empty Wasm module"));
+    Ok(())
+}
+
+#[test]
+fn shrink_component() -> Result<()> {
+    let info = WasmShrink::default()
+        .attempts(200)
+        .run(component(), |wasm| {
+            let wat = wasmprinter::print_bytes(&wasm)?;
+            Ok(wat.contains("i32.add") && wat.contains("canon lift"))
+        })?;
+
+    assert!(info.input_size > info.output_size);
+
+    let wat = wasmprinter::print_bytes(&info.output)?;
+    assert!(wat.contains("i32.add"));
+    assert!(wat.contains("canon lift"));
+    assert!(!wat.contains("\"unused\""));
+    assert!(!wat.contains("i32.sub"));
+
+    wasmparser::validate(&info.output)?;
+    Ok(())
+}
+
+const WAST: &str = r#"
+(module $a
+  (func (export "add") (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    i32.add)
+  (func (export "sub") (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    i32.sub))
+
+(assert_return (invoke "add" (i32.const 1) (i32.const 2)) (i32.const 3))
+(assert_return (invoke "sub" (i32.const 3) (i32.const 2)) (i32.const 1))
+
+;; A second module (which doesn't matter.
+(module $b
+  (memory 1)
+  (func (export "load") (result i32)
+    i32.const 0
+    i32.load))
+
+(assert_return (invoke $b "load") (i32.const 0))
+(assert_invalid
+  (module (func (result i32)))
+  "type mismatch")
+"#;
+
+#[test]
+fn shrink_wast_to_empty_is_error() -> Result<()> {
+    let result = WasmShrink::default().run_wast(WAST, |_| Ok(true));
+    assert!(result.is_err());
+    let err_msg = result.err().unwrap().to_string();
+    assert!(err_msg.contains("empty script"));
+    Ok(())
+}
+
+#[test]
+fn shrink_wast() -> Result<()> {
+    let _ = env_logger::try_init();
+    let info = WasmShrink::default().attempts(100).run_wast(WAST, |wast| {
+        Ok(wast.contains("assert_return (invoke \"sub\"") && wast.contains("i32.sub"))
+    })?;
+
+    assert!(info.input_size > info.output_size);
+
+    let wast = String::from_utf8(info.output)?;
+    assert!(wast.contains("assert_return (invoke \"sub\""));
+    assert!(wast.contains("i32.sub"));
+    assert!(!wast.contains("i32.add"));
+    assert!(!wast.contains("$b"));
+    assert!(!wast.contains("assert_invalid"));
+    assert!(!wast.contains(";;"));
+    Ok(())
+}
+
+#[test]
+fn parallel_smoke_test() -> Result<()> {
+    let shrink = |seed| {
+        WasmShrink::default()
+            .attempts(100)
+            .seed(seed)
+            .run_parallel(wasm(), 4, |wasm| {
+                let wat = wasmprinter::print_bytes(&wasm)?;
+                Ok(wat.contains("local.get"))
+            })
+    };
+
+    let info = shrink(7)?;
+    assert!(info.input_size > info.output_size);
+
+    let wat = wasmprinter::print_bytes(&info.output)?;
+    assert!(wat.contains("local.get"));
+    wasmparser::validate(&info.output)?;
+
+    // The same seed results in the same output, regardless of scheduling.
+    for _ in 0..3 {
+        assert_eq!(shrink(7)?.output, info.output);
+    }
+    Ok(())
+}
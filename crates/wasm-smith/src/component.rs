@@ -4,6 +4,7 @@
 // FIXME(#1000): component support in `wasm-smith` is a work in progress.
 #![allow(unused_variables, dead_code)]
 
+use crate::core::RequiredExport;
 use crate::{Config, arbitrary_loop};
 use arbitrary::{Arbitrary, Result, Unstructured};
 use std::collections::BTreeMap;
@@ -12,7 +13,8 @@ use std::{
     rc::Rc,
 };
 use wasm_encoder::{
-    ComponentTypeRef, ComponentValType, HeapType, PrimitiveValType, RefType, TypeBounds, ValType,
+    ComponentTypeRef, ComponentValType, HeapType, MemoryType, PrimitiveValType, RefType,
+    TypeBounds, ValType,
 };
 
 mod encode;
@@ -133,10 +135,6 @@ struct ComponentContext {
     // Which entries in `funcs` are component functions?
     component_funcs: Vec<u32>,
 
-    // Which entries in `component_funcs` are component functions that only use scalar
-    // types?
-    scalar_component_funcs: Vec<u32>,
-
     // Which entries in `funcs` are core Wasm functions?
     //
     // Note that a component can't import core functions, so these entries will
@@ -178,6 +176,15 @@ struct ComponentContext {
     // This component's instance index space.
     instances: Vec<ComponentOrCoreInstanceType>,
 
+    // Which entries in `instances` are core Wasm instances?
+    core_instances: Vec<u32>,
+
+    // The number of entries in this component's core memory index space.
+    //
+    // These are all aliased from core instances and are suitable for use with
+    // the canonical ABI.
+    num_core_memories: u32,
+
     // This component's value index space.
     values: Vec<ComponentValType>,
 }
@@ -191,11 +198,12 @@ impl ComponentContext {
             import_urls: HashSet::default(),
             funcs: vec![],
             component_funcs: vec![],
-            scalar_component_funcs: vec![],
             core_funcs: vec![],
             components: vec![],
             modules: vec![],
             instances: vec![],
+            core_instances: vec![],
+            num_core_memories: 0,
             values: vec![],
         }
     }
@@ -249,13 +257,33 @@ struct TypesScope {
 
     // The indices of all the entries in `types` that are instance types.
     instance_types: Vec<u32>,
+
+    // The indices of all the entries in `types` that are resource types.
+    resource_types: Vec<u32>,
+
+    // Information about each entry in `types`, see `TypeInfo`.
+    type_info: Vec<TypeInfo>,
 }
 
 impl TypesScope {
     fn push(&mut self, ty: Rc<Type>) -> u32 {
+        let info = self.type_info_of(&ty);
+        self.push_with_info(ty, info)
+    }
+
+    /// Push a new entry for the existing type at `index` in this scope.
+    fn push_copy(&mut self, index: u32) -> u32 {
+        let ty = Rc::clone(self.get(index));
+        let info = self.type_info[index as usize].clone();
+        self.push_with_info(ty, info)
+    }
+
+    fn push_with_info(&mut self, ty: Rc<Type>, info: TypeInfo) -> u32 {
         let ty_idx = u32::try_from(self.types.len()).unwrap();
+        self.type_info.push(info);
 
         let kind_list = match &*ty {
+            Type::Resource(_) => &mut self.resource_types,
             Type::Defined(_) => &mut self.defined_types,
             Type::Func(func_ty) => {
                 self.func_type_to_indices
@@ -305,6 +333,95 @@ impl TypesScope {
         // All component types and core module types may be referenced
         !self.types.is_empty() || !self.module_types.is_empty()
     }
+
+    /// The indices of all the types in this scope which may be aliased by an
+    /// inner scope, which excludes anything referring to resources.
+    fn outer_aliasable_types(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..u32::try_from(self.types.len()).unwrap())
+            .filter(|i| !self.type_info[*i as usize].refers_to_resources)
+    }
+
+    /// The indices of all the types in this scope which don't refer to any
+    /// other value types, and so may be imported or exported without also
+    /// importing or exporting the types they refer to.
+    fn self_contained_types(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..u32::try_from(self.types.len()).unwrap()).filter(|i| self.get(*i).is_self_contained())
+    }
+
+    /// Like `self_contained_types` but only for function types.
+    fn self_contained_func_types(&self) -> impl Iterator<Item = u32> + '_ {
+        self.func_types
+            .iter()
+            .copied()
+            .filter(|i| self.get(*i).is_self_contained())
+    }
+
+    fn val_type_info(&self, ty: &ComponentValType) -> TypeInfo {
+        match ty {
+            ComponentValType::Primitive(ty) => TypeInfo::primitive(*ty),
+            ComponentValType::Type(i) => self.type_info[*i as usize].clone(),
+        }
+    }
+
+    fn type_info_of(&self, ty: &Type) -> TypeInfo {
+        let info = |ty: &ComponentValType| self.val_type_info(ty);
+        match ty {
+            Type::Defined(ty) => match ty {
+                DefinedType::Primitive(ty) => TypeInfo::primitive(*ty),
+                DefinedType::Record(ty) => {
+                    TypeInfo::product(&ty.fields.iter().map(|(_, ty)| info(ty)).collect::<Vec<_>>())
+                }
+                DefinedType::Tuple(ty) => {
+                    TypeInfo::product(&ty.fields.iter().map(info).collect::<Vec<_>>())
+                }
+                DefinedType::Variant(ty) => TypeInfo::sum(
+                    &ty.cases
+                        .iter()
+                        .filter_map(|(_, ty, _)| ty.as_ref().map(info))
+                        .collect::<Vec<_>>(),
+                ),
+                DefinedType::Option(ty) => TypeInfo::sum(&[info(&ty.inner_ty)]),
+                DefinedType::Result(ty) => TypeInfo::sum(
+                    &ty.ok_ty
+                        .iter()
+                        .chain(&ty.err_ty)
+                        .map(info)
+                        .collect::<Vec<_>>(),
+                ),
+                DefinedType::List(ty) => TypeInfo {
+                    contains_ptr: true,
+                    flat: Some(vec![ValType::I32, ValType::I32]),
+                    ..info(&ty.elem_ty)
+                },
+                DefinedType::Flags(ty) => TypeInfo {
+                    flat: Some(vec![ValType::I32; ty.fields.len().div_ceil(32)]),
+                    ..TypeInfo::default()
+                },
+                DefinedType::Enum(_) => TypeInfo {
+                    flat: Some(vec![ValType::I32]),
+                    ..TypeInfo::default()
+                },
+                DefinedType::Own(_) => TypeInfo::handle(false),
+                DefinedType::Borrow(_) => TypeInfo::handle(true),
+            },
+            Type::Func(ty) => TypeInfo {
+                refers_to_resources: ty
+                    .params
+                    .iter()
+                    .map(|(_, ty)| ty)
+                    .chain(&ty.result)
+                    .any(|ty| info(ty).refers_to_resources),
+                ..TypeInfo::default()
+            },
+            Type::Resource(_) => TypeInfo {
+                refers_to_resources: true,
+                ..TypeInfo::default()
+            },
+            // Component and instance types have their own scopes which never
+            // contain resources.
+            Type::Component(_) | Type::Instance(_) => TypeInfo::default(),
+        }
+    }
 }
 
 impl<'a> Arbitrary<'a> for Component {
@@ -390,6 +507,12 @@ impl ComponentBuilder {
 
                 if self.total_modules < self.config.max_modules {
                     choices.push(Self::arbitrary_core_module_section);
+
+                    if self.total_instances < self.config.max_instances
+                        && self.component().funcs.len() < self.config.max_funcs
+                    {
+                        choices.push(Self::arbitrary_canonical_abi_module);
+                    }
                 }
 
                 if self.components.len() < self.config.max_nesting_depth
@@ -572,8 +695,14 @@ impl ComponentBuilder {
         let max = self.config.max_types - self.current_type_scope().types.len();
 
         arbitrary_loop(u, min, max, |u| {
-            let mut type_fuel = self.config.max_type_size;
-            let ty = self.arbitrary_type(u, &mut type_fuel)?;
+            // Resources may only be defined directly within a component, so
+            // they're generated here rather than in `arbitrary_type`.
+            let ty = if u.ratio(1, 8)? {
+                Rc::new(Type::Resource(self.arbitrary_resource_type(u)?))
+            } else {
+                let mut type_fuel = self.config.max_type_size;
+                self.arbitrary_type(u, &mut type_fuel)?
+            };
             self.push_type(ty);
             Ok(true)
         })?;
@@ -581,6 +710,18 @@ impl ComponentBuilder {
         Ok(Step::StillBuilding)
     }
 
+    fn arbitrary_resource_type(&self, u: &mut Unstructured) -> Result<ResourceType> {
+        let dtors = self
+            .core_funcs_of_type(&[ValType::I32], &[])
+            .collect::<Vec<_>>();
+        let dtor = if !dtors.is_empty() && u.arbitrary()? {
+            Some(*u.choose(&dtors)?)
+        } else {
+            None
+        };
+        Ok(ResourceType { dtor })
+    }
+
     fn arbitrary_type_ref<'a>(
         &self,
         u: &mut Unstructured<'a>,
@@ -601,14 +742,19 @@ impl ComponentBuilder {
         }
 
         // Types cannot be imported currently
+        //
+        // Only types which don't refer to other types are exported, since we
+        // don't export the types that they would refer to.
         if !for_import
-            && !scope.types.is_empty()
+            && scope.self_contained_types().next().is_some()
             && (for_type_def || scope.types.len() < self.config.max_types)
         {
             choices.push(|me, u| {
-                Ok(ComponentTypeRef::Type(TypeBounds::Eq(u.int_in_range(
-                    0..=u32::try_from(me.current_type_scope().types.len() - 1).unwrap(),
-                )?)))
+                let types = me
+                    .current_type_scope()
+                    .self_contained_types()
+                    .collect::<Vec<_>>();
+                Ok(ComponentTypeRef::Type(TypeBounds::Eq(*u.choose(&types)?)))
             });
         }
 
@@ -618,13 +764,15 @@ impl ComponentBuilder {
         //     choices.push(|me, u| Ok(ComponentTypeRef::Value(me.arbitrary_component_val_type(u)?)));
         // }
 
-        if !scope.func_types.is_empty()
+        if scope.self_contained_func_types().next().is_some()
             && (for_type_def || !for_import || self.component().num_funcs() < self.config.max_funcs)
         {
             choices.push(|me, u| {
-                Ok(ComponentTypeRef::Func(
-                    *u.choose(&me.current_type_scope().func_types)?,
-                ))
+                let func_types = me
+                    .current_type_scope()
+                    .self_contained_func_types()
+                    .collect::<Vec<_>>();
+                Ok(ComponentTypeRef::Func(*u.choose(&func_types)?))
             });
         }
 
@@ -1091,8 +1239,7 @@ impl ComponentBuilder {
             choices.push(|me, exports, export_urls, u, _type_fuel| {
                 let ty = me.arbitrary_type_ref(u, false, true)?.unwrap();
                 if let ComponentTypeRef::Type(TypeBounds::Eq(idx)) = ty {
-                    let ty = me.current_type_scope().get(idx).clone();
-                    me.current_type_scope_mut().push(ty);
+                    me.current_type_scope_mut().push_copy(idx);
                 }
                 Ok(InstanceTypeDecl::Export {
                     name: crate::unique_kebab_string(100, exports, u)?,
@@ -1107,18 +1254,22 @@ impl ComponentBuilder {
         }
 
         // Outer type alias.
-        if self
-            .types
-            .iter()
-            .any(|scope| !scope.types.is_empty() || !scope.core_types.is_empty())
-        {
+        if self.types.iter().any(|scope| {
+            scope.outer_aliasable_types().next().is_some() || !scope.core_types.is_empty()
+        }) {
             choices.push(|me, _exports, _export_urls, u, _type_fuel| {
                 let alias = me.arbitrary_outer_type_alias(u)?;
                 match &alias {
                     Alias::Outer {
                         kind: OuterAliasKind::Type(ty),
-                        ..
-                    } => me.current_type_scope_mut().push(ty.clone()),
+                        count,
+                        i,
+                    } => {
+                        // The aliased type refers to other types by their index
+                        // in the outer scope, so take its info from there.
+                        let info = me.outer_types_scope(*count).type_info[*i as usize].clone();
+                        me.current_type_scope_mut().push_with_info(ty.clone(), info)
+                    }
                     Alias::Outer {
                         kind: OuterAliasKind::CoreType(ty),
                         ..
@@ -1191,25 +1342,32 @@ impl ComponentBuilder {
             .iter()
             .rev()
             .enumerate()
-            .filter(|(_, scope)| !scope.types.is_empty() || !scope.core_types.is_empty())
+            .map(|(count, scope)| {
+                (
+                    count,
+                    scope,
+                    scope.outer_aliasable_types().collect::<Vec<_>>(),
+                )
+            })
+            .filter(|(_, scope, types)| !types.is_empty() || !scope.core_types.is_empty())
             .collect();
         assert!(
             !non_empty_types_scopes.is_empty(),
             "precondition: there are non-empty types scopes"
         );
 
-        let (count, scope) = u.choose(&non_empty_types_scopes)?;
+        let (count, scope, types) = u.choose(&non_empty_types_scopes)?;
         let count = u32::try_from(*count).unwrap();
-        assert!(!scope.types.is_empty() || !scope.core_types.is_empty());
+        assert!(!types.is_empty() || !scope.core_types.is_empty());
 
-        let max_type_in_scope = scope.types.len() + scope.core_types.len() - 1;
+        let max_type_in_scope = types.len() + scope.core_types.len() - 1;
         let i = u.int_in_range(0..=max_type_in_scope)?;
 
-        let (i, kind) = if i < scope.types.len() {
-            let i = u32::try_from(i).unwrap();
+        let (i, kind) = if i < types.len() {
+            let i = types[i];
             (i, OuterAliasKind::Type(Rc::clone(scope.get(i))))
-        } else if i - scope.types.len() < scope.core_types.len() {
-            let i = u32::try_from(i - scope.types.len()).unwrap();
+        } else if i - types.len() < scope.core_types.len() {
+            let i = u32::try_from(i - types.len()).unwrap();
             (i, OuterAliasKind::CoreType(Rc::clone(scope.get_core(i))))
         } else {
             unreachable!()
@@ -1224,19 +1382,12 @@ impl ComponentBuilder {
         type_fuel: &mut u32,
     ) -> Result<Rc<FuncType>> {
         let mut params = Vec::new();
-        let mut results = Vec::new();
         let mut names = HashSet::new();
 
-        // Note: parameters are currently limited to a maximum of 16
-        // because any additional parameters will require indirect access
-        // via a pointer argument; when this occurs, validation of any
-        // lowered function will fail because it will be missing a
-        // memory option (not yet implemented).
-        //
-        // When options are correctly specified on canonical functions,
-        // we should increase this maximum to test indirect parameter
-        // passing.
-        arbitrary_loop(u, 0, 16, |u| {
+        // Note: parameters which flatten to more than `MAX_FLAT_PARAMS` core
+        // values are passed indirectly through linear memory, so lifting and
+        // lowering such functions requires the `memory` option.
+        arbitrary_loop(u, 0, 20, |u| {
             *type_fuel = type_fuel.saturating_sub(1);
             if *type_fuel == 0 {
                 return Ok(false);
@@ -1250,41 +1401,22 @@ impl ComponentBuilder {
             Ok(true)
         })?;
 
-        names.clear();
-
-        // Likewise, the limit for results is 1 before the memory option is
-        // required. When the memory option is implemented, this restriction
-        // should be relaxed.
-        arbitrary_loop(u, 0, 1, |u| {
+        let result = if *type_fuel > 0 && u.arbitrary()? {
             *type_fuel = type_fuel.saturating_sub(1);
-            if *type_fuel == 0 {
-                return Ok(false);
-            }
-
-            // If the result list is empty (i.e. first push), then arbitrarily give
-            // the result a name. Otherwise, all of the subsequent items must be named.
-            let name = if results.is_empty() {
-                // Most of the time we should have a single, unnamed result.
-                u.ratio::<u8>(10, 100)?
-                    .then(|| crate::unique_kebab_string(100, &mut names, u))
-                    .transpose()?
-            } else {
-                Some(crate::unique_kebab_string(100, &mut names, u)?)
-            };
-
             let ty = self.arbitrary_component_val_type(u)?;
-
-            results.push((name, ty));
-
-            // There can be only one unnamed result.
-            if results.len() == 1 && results[0].0.is_none() {
-                return Ok(false);
+            // Function results may not contain borrowed handles.
+            if self.current_type_scope().val_type_info(&ty).contains_borrow {
+                Some(ComponentValType::Primitive(
+                    self.arbitrary_primitive_val_type(u)?,
+                ))
+            } else {
+                Some(ty)
             }
+        } else {
+            None
+        };
 
-            Ok(true)
-        })?;
-
-        Ok(Rc::new(FuncType { params, results }))
+        Ok(Rc::new(FuncType { params, result }))
     }
 
     fn arbitrary_component_val_type(&self, u: &mut Unstructured) -> Result<ComponentValType> {
@@ -1332,9 +1464,11 @@ impl ComponentBuilder {
     ) -> Result<RecordType> {
         let mut fields = vec![];
         let mut field_names = HashSet::new();
-        arbitrary_loop(u, 0, 100, |u| {
+        arbitrary_loop(u, 1, 100, |u| {
             *type_fuel = type_fuel.saturating_sub(1);
-            if *type_fuel == 0 {
+            // Records, variants, tuples, flags, and enums must all have at least
+            // one entry.
+            if *type_fuel == 0 && !fields.is_empty() {
                 return Ok(false);
             }
 
@@ -1356,7 +1490,7 @@ impl ComponentBuilder {
         let mut case_names = HashSet::new();
         arbitrary_loop(u, 1, 100, |u| {
             *type_fuel = type_fuel.saturating_sub(1);
-            if *type_fuel == 0 {
+            if *type_fuel == 0 && !cases.is_empty() {
                 return Ok(false);
             }
 
@@ -1389,9 +1523,9 @@ impl ComponentBuilder {
 
     fn arbitrary_tuple_type(&self, u: &mut Unstructured, type_fuel: &mut u32) -> Result<TupleType> {
         let mut fields = vec![];
-        arbitrary_loop(u, 0, 100, |u| {
+        arbitrary_loop(u, 1, 100, |u| {
             *type_fuel = type_fuel.saturating_sub(1);
-            if *type_fuel == 0 {
+            if *type_fuel == 0 && !fields.is_empty() {
                 return Ok(false);
             }
 
@@ -1404,9 +1538,9 @@ impl ComponentBuilder {
     fn arbitrary_flags_type(&self, u: &mut Unstructured, type_fuel: &mut u32) -> Result<FlagsType> {
         let mut fields = vec![];
         let mut field_names = HashSet::new();
-        arbitrary_loop(u, 0, 100, |u| {
+        arbitrary_loop(u, 1, 32, |u| {
             *type_fuel = type_fuel.saturating_sub(1);
-            if *type_fuel == 0 {
+            if *type_fuel == 0 && !fields.is_empty() {
                 return Ok(false);
             }
 
@@ -1421,7 +1555,7 @@ impl ComponentBuilder {
         let mut variant_names = HashSet::new();
         arbitrary_loop(u, 1, 100, |u| {
             *type_fuel = type_fuel.saturating_sub(1);
-            if *type_fuel == 0 {
+            if *type_fuel == 0 && !variants.is_empty() {
                 return Ok(false);
             }
 
@@ -1455,7 +1589,12 @@ impl ComponentBuilder {
         u: &mut Unstructured,
         type_fuel: &mut u32,
     ) -> Result<DefinedType> {
-        match u.int_in_range(0..=8)? {
+        let max_choice = if self.current_type_scope().resource_types.is_empty() {
+            8
+        } else {
+            10
+        };
+        match u.int_in_range(0..=max_choice)? {
             0 => Ok(DefinedType::Primitive(
                 self.arbitrary_primitive_val_type(u)?,
             )),
@@ -1471,6 +1610,12 @@ impl ComponentBuilder {
             6 => Ok(DefinedType::Enum(self.arbitrary_enum_type(u, type_fuel)?)),
             7 => Ok(DefinedType::Option(self.arbitrary_option_type(u)?)),
             8 => Ok(DefinedType::Result(self.arbitrary_result_type(u)?)),
+            9 => Ok(DefinedType::Own(
+                *u.choose(&self.current_type_scope().resource_types)?,
+            )),
+            10 => Ok(DefinedType::Borrow(
+                *u.choose(&self.current_type_scope().resource_types)?,
+            )),
             _ => unreachable!(),
         }
     }
@@ -1499,11 +1644,6 @@ impl ComponentBuilder {
                     _ => unreachable!(),
                 };
 
-                if func_ty.is_scalar() {
-                    let func_index = u32::try_from(self.component().component_funcs.len()).unwrap();
-                    self.component_mut().scalar_component_funcs.push(func_index);
-                }
-
                 let func_index = u32::try_from(self.component().funcs.len()).unwrap();
                 self.component_mut()
                     .funcs
@@ -1516,8 +1656,7 @@ impl ComponentBuilder {
                 self.component_mut().values.push(ty);
             }
             ComponentTypeRef::Type(TypeBounds::Eq(ty_index)) => {
-                let ty = self.current_type_scope().get(ty_index).clone();
-                self.current_type_scope_mut().push(ty);
+                self.current_type_scope_mut().push_copy(ty_index);
             }
             ComponentTypeRef::Type(TypeBounds::SubResource) => {
                 unimplemented!()
@@ -1565,10 +1704,6 @@ impl ComponentBuilder {
         let ty = match &func {
             Func::CanonLift { func_ty, .. } => {
                 let ty = Rc::clone(self.current_type_scope().get_func(*func_ty));
-                if ty.is_scalar() {
-                    let func_index = u32::try_from(self.component().component_funcs.len()).unwrap();
-                    self.component_mut().scalar_component_funcs.push(func_index);
-                }
                 self.component_mut().component_funcs.push(func_index);
                 ComponentOrCoreFuncType::Component(ty)
             }
@@ -1577,10 +1712,24 @@ impl ComponentBuilder {
                 ..
             } => {
                 let comp_func_ty = self.component_function_type(*comp_func_index);
-                let core_func_ty = canonical_abi_for(comp_func_ty);
+                let core_func_ty = self.canonical_abi_for(comp_func_ty, Abi::Lower);
                 self.component_mut().core_funcs.push(func_index);
                 ComponentOrCoreFuncType::Core(core_func_ty)
             }
+            Func::ResourceNew(_) | Func::ResourceRep(_) => {
+                self.component_mut().core_funcs.push(func_index);
+                ComponentOrCoreFuncType::Core(Rc::new(crate::core::FuncType {
+                    params: vec![ValType::I32],
+                    results: vec![ValType::I32],
+                }))
+            }
+            Func::ResourceDrop(_) => {
+                self.component_mut().core_funcs.push(func_index);
+                ComponentOrCoreFuncType::Core(Rc::new(crate::core::FuncType {
+                    params: vec![ValType::I32],
+                    results: vec![],
+                }))
+            }
         };
 
         self.component_mut().funcs.push(ty);
@@ -1644,49 +1793,43 @@ impl ComponentBuilder {
         let max = self.config.max_funcs - self.component().funcs.len();
 
         let mut choices: Vec<fn(&mut Unstructured, &mut ComponentBuilder) -> Result<Option<Func>>> =
-            Vec::with_capacity(2);
+            Vec::with_capacity(5);
 
         crate::arbitrary_loop(u, min, max, |u| {
             choices.clear();
 
-            // NB: We only lift/lower scalar component functions.
-            //
-            // If we generated lifting and lowering of compound value types,
-            // the probability of generating a corresponding Wasm module that
-            // generates valid instances of the compound value types would
-            // be vanishingly tiny (e.g. for `list<string>` we would have to
-            // generate a core Wasm module that correctly produces a pointer and
-            // length for a memory region that itself is a series of pointers
-            // and lengths of valid strings, as well as `canonical_abi_realloc`
-            // and `canonical_abi_free` functions that do the right thing).
-            //
-            // This is a pretty serious limitation of `wasm-smith`'s component
-            // types support, but it is one we are intentionally
-            // accepting. `wasm-smith` will focus on generating arbitrary
-            // component sections, structures, and import/export topologies; not
-            // component functions and core Wasm implementations of component
-            // functions. In the future, we intend to build a new, distinct test
-            // case generator specifically for exercising component functions
-            // and the canonical ABI. This new generator won't emit arbitrary
-            // component sections, structures, or import/export topologies, and
-            // will instead leave that to `wasm-smith`.
-
-            if !self.component().scalar_component_funcs.is_empty() {
+            // Lowering a component function whose type refers to strings, lists,
+            // or more values than can be passed directly requires a memory,
+            // and possibly a `realloc` function, from some core instance. Such
+            // lowerings are skipped when this component doesn't have them yet;
+            // see `arbitrary_canonical_abi_module` for where they come from.
+            if !self.component().component_funcs.is_empty() {
                 choices.push(|u, c| {
-                    let func_index = *u.choose(&c.component().scalar_component_funcs)?;
-                    Ok(Some(Func::CanonLower {
-                        // Scalar component functions don't use any canonical options.
-                        options: vec![],
-                        func_index,
-                    }))
+                    let func_index = u.int_in_range(
+                        0..=u32::try_from(c.component().component_funcs.len() - 1).unwrap(),
+                    )?;
+                    let func_ty = Rc::clone(c.component_function_type(func_index));
+                    Ok(c.arbitrary_canonical_options(u, &func_ty, Abi::Lower)?
+                        .map(|options| Func::CanonLower {
+                            options,
+                            func_index,
+                        }))
                 });
             }
 
-            if !self.component().core_funcs.is_empty() {
+            // Lift an existing core function with a component function type made
+            // up of scalars which flatten to that core function's type.
+            if self
+                .component()
+                .core_funcs
+                .iter()
+                .any(|f| can_lift_with_scalars(self.component().funcs[*f as usize].as_core()))
+            {
                 choices.push(|u, c| {
-                    let core_func_index = u.int_in_range(
-                        0..=u32::try_from(c.component().core_funcs.len() - 1).unwrap(),
-                    )?;
+                    let candidates = (0..u32::try_from(c.component().core_funcs.len()).unwrap())
+                        .filter(|i| can_lift_with_scalars(c.core_function_type(*i)))
+                        .collect::<Vec<_>>();
+                    let core_func_index = *u.choose(&candidates)?;
                     let core_func_ty = c.core_function_type(core_func_index);
                     let comp_func_ty = inverse_scalar_canonical_abi_for(u, core_func_ty)?;
 
@@ -1720,6 +1863,18 @@ impl ComponentBuilder {
                 });
             }
 
+            if !self.current_type_scope().resource_types.is_empty() {
+                choices.push(|u, c| {
+                    let resource = *u.choose(&c.current_type_scope().resource_types)?;
+                    Ok(Some(match u.int_in_range(0..=2)? {
+                        0 => Func::ResourceNew(resource),
+                        1 => Func::ResourceDrop(resource),
+                        2 => Func::ResourceRep(resource),
+                        _ => unreachable!(),
+                    }))
+                });
+            }
+
             if choices.is_empty() {
                 return Ok(false);
             }
@@ -1735,14 +1890,281 @@ impl ComponentBuilder {
         Ok(Step::StillBuilding)
     }
 
+    /// Generate the canonical options used to lift or lower a function of
+    /// type `func_ty`, or `None` if this component doesn't have the memory or
+    /// `realloc` function that the canonical ABI requires for it.
+    fn arbitrary_canonical_options(
+        &self,
+        u: &mut Unstructured,
+        func_ty: &FuncType,
+        abi: Abi,
+    ) -> Result<Option<Vec<CanonOpt>>> {
+        let (needs_memory, needs_realloc) = self.canonical_abi_requirements(func_ty, abi);
+        let num_memories = self.component().num_core_memories;
+        let reallocs = self
+            .core_funcs_of_type(&[ValType::I32; 4], &[ValType::I32])
+            .collect::<Vec<_>>();
+
+        let mut options = vec![];
+        match u.int_in_range(0..=3)? {
+            0 => {}
+            1 => options.push(CanonOpt::StringUtf8),
+            2 => options.push(CanonOpt::StringUtf16),
+            3 => options.push(CanonOpt::StringLatin1Utf16),
+            _ => unreachable!(),
+        }
+
+        if needs_memory || needs_realloc || (num_memories > 0 && u.arbitrary()?) {
+            if num_memories == 0 {
+                return Ok(None);
+            }
+            options.push(CanonOpt::Memory(u.int_in_range(0..=num_memories - 1)?));
+
+            if needs_realloc || (!reallocs.is_empty() && u.arbitrary()?) {
+                if reallocs.is_empty() {
+                    return Ok(None);
+                }
+                options.push(CanonOpt::Realloc(*u.choose(&reallocs)?));
+            }
+        }
+
+        Ok(Some(options))
+    }
+
+    /// Whether lifting or lowering a function of type `func_ty` requires the
+    /// `memory` and `realloc` canonical options, respectively.
+    fn canonical_abi_requirements(&self, func_ty: &FuncType, abi: Abi) -> (bool, bool) {
+        let scope = self.current_type_scope();
+        let params = func_ty
+            .params
+            .iter()
+            .map(|(_, ty)| scope.val_type_info(ty))
+            .collect::<Vec<_>>();
+        let params_contain_ptr = params.iter().any(|p| p.contains_ptr);
+        let params_spill = TypeInfo::product(&params).flat.is_none();
+
+        let result = func_ty.result.map(|ty| scope.val_type_info(&ty));
+        let result_contains_ptr = result.as_ref().is_some_and(|r| r.contains_ptr);
+        let result_spills = result.as_ref().is_some_and(|r| {
+            r.flat
+                .as_ref()
+                .map_or(true, |flat| flat.len() > MAX_FLAT_RESULTS)
+        });
+
+        match abi {
+            // Lowered functions read their arguments out of, and have their
+            // results written into, the caller's memory.
+            Abi::Lower => (
+                params_contain_ptr || params_spill || result_spills,
+                result_contains_ptr,
+            ),
+            // Lifted functions need to allocate space for their arguments in
+            // the callee's memory, and return results through it.
+            Abi::Lift => {
+                let needs_realloc = params_contain_ptr || params_spill;
+                (needs_realloc || result_spills, needs_realloc)
+            }
+        }
+    }
+
+    /// The core function type that a function of type `func_ty` is lifted
+    /// from or lowered into.
+    fn canonical_abi_for(&self, func_ty: &FuncType, abi: Abi) -> Rc<crate::core::FuncType> {
+        let scope = self.current_type_scope();
+        let params = func_ty
+            .params
+            .iter()
+            .map(|(_, ty)| scope.val_type_info(ty))
+            .collect::<Vec<_>>();
+        let mut params = TypeInfo::product(&params)
+            .flat
+            .unwrap_or_else(|| vec![ValType::I32]);
+
+        let mut results = vec![];
+        if let Some(ty) = &func_ty.result {
+            match scope.val_type_info(ty).flat {
+                Some(flat) if flat.len() <= MAX_FLAT_RESULTS => results = flat,
+                // Results which don't fit are returned through a pointer to
+                // linear memory, which lowered functions receive as an extra
+                // parameter.
+                _ => match abi {
+                    Abi::Lower => params.push(ValType::I32),
+                    Abi::Lift => results.push(ValType::I32),
+                },
+            }
+        }
+
+        Rc::new(crate::core::FuncType { params, results })
+    }
+
+    /// The indices of all core functions of the given type.
+    fn core_funcs_of_type<'a>(
+        &'a self,
+        params: &'a [ValType],
+        results: &'a [ValType],
+    ) -> impl Iterator<Item = u32> + 'a {
+        (0..u32::try_from(self.component().core_funcs.len()).unwrap()).filter(move |i| {
+            let ty = self.core_function_type(*i);
+            ty.params == params && ty.results == results
+        })
+    }
+
     fn arbitrary_core_module_section(&mut self, u: &mut Unstructured) -> Result<Step> {
         let module = crate::core::Module::new_internal(
             self.config.clone(),
             u,
             crate::core::DuplicateImportsBehavior::Disallowed,
         )?;
+        self.push_core_module(module);
+        Ok(Step::StillBuilding)
+    }
+
+    fn push_core_module(&mut self, module: crate::Module) -> u32 {
         self.push_section(Section::CoreModule(module));
+        let section_index = self.component().component.sections.len() - 1;
+        let module_index = u32::try_from(self.component().modules.len()).unwrap();
+        self.component_mut().modules.push((section_index, 0));
         self.total_modules += 1;
+        module_index
+    }
+
+    /// Generate a core module which implements component functions with the
+    /// canonical ABI, instantiate it, and lift its functions.
+    ///
+    /// The module exports a memory and a `realloc` function in addition to
+    /// the functions being lifted, and those are aliased into this component
+    /// so that they can also be used when lowering functions.
+    fn arbitrary_canonical_abi_module(&mut self, u: &mut Unstructured) -> Result<Step> {
+        // Each lift needs an alias of the core function, an alias of its
+        // optional post-return function, and the lifted function itself. One
+        // more function is aliased for `realloc`.
+        let max_lifts = self
+            .config
+            .max_funcs
+            .saturating_sub(self.component().funcs.len() + 1)
+            / 3;
+
+        let mut lifts = vec![];
+        arbitrary_loop(u, 0, max_lifts, |u| {
+            let func_types = &self.current_type_scope().func_types;
+            let func_ty = if !func_types.is_empty() && u.arbitrary()? {
+                *u.choose(func_types)?
+            } else if self.current_type_scope().types.len() < self.config.max_types {
+                let mut type_fuel = self.config.max_type_size;
+                let ty = self.arbitrary_func_type(u, &mut type_fuel)?;
+                self.push_type(Rc::new(Type::Func(ty)))
+            } else {
+                return Ok(false);
+            };
+            lifts.push((func_ty, u.arbitrary::<bool>()?));
+            Ok(true)
+        })?;
+
+        let realloc_ty = Rc::new(crate::core::FuncType {
+            params: vec![ValType::I32; 4],
+            results: vec![ValType::I32],
+        });
+        let mut exports = vec![
+            (
+                "memory".to_string(),
+                RequiredExport::Memory(MemoryType {
+                    minimum: u.int_in_range(0..=16)?,
+                    maximum: None,
+                    memory64: false,
+                    shared: false,
+                    page_size_log2: None,
+                }),
+            ),
+            ("cabi_realloc".to_string(), RequiredExport::Func(realloc_ty)),
+        ];
+        for (i, (func_ty, post_return)) in lifts.iter().enumerate() {
+            let func_ty = self.current_type_scope().get_func(*func_ty);
+            let core_func_ty = self.canonical_abi_for(func_ty, Abi::Lift);
+            if *post_return {
+                let post_return_ty = Rc::new(crate::core::FuncType {
+                    params: core_func_ty.results.clone(),
+                    results: vec![],
+                });
+                exports.push((
+                    format!("cabi_post_f{i}"),
+                    RequiredExport::Func(post_return_ty),
+                ));
+            }
+            exports.push((format!("f{i}"), RequiredExport::Func(core_func_ty)));
+        }
+
+        // The module is instantiated without any arguments, and it must not
+        // define any memories other than the exported one so that it's valid
+        // without the multi-memory proposal.
+        let mut config = self.config.clone();
+        config.min_imports = 0;
+        config.max_imports = 0;
+        config.available_imports = None;
+        config.exports = None;
+        config.module_shape = None;
+        config.min_memories = 0;
+        config.max_memories = 0;
+        let module = crate::core::Module::new_with_required_exports(config, u, exports)?;
+        let export_types = module.export_types();
+        let module_index = self.push_core_module(module);
+
+        self.push_section(Section::CoreInstance(CoreInstanceSection {
+            instances: vec![CoreInstance::Instantiate { module_index }],
+        }));
+        let instance = u32::try_from(self.component().core_instances.len()).unwrap();
+        let instance_index = u32::try_from(self.component().instances.len()).unwrap();
+        self.component_mut().core_instances.push(instance_index);
+        self.component_mut()
+            .instances
+            .push(ComponentOrCoreInstanceType::Core(export_types.clone()));
+        self.total_instances += 1;
+
+        let mut aliases = vec![Alias::CoreInstanceExport {
+            instance,
+            name: "memory".to_string(),
+            kind: CoreInstanceExportAliasKind::Memory,
+        }];
+        self.component_mut().num_core_memories += 1;
+        let mut alias_func = |me: &mut Self, name: String| {
+            let ty = match &export_types[&name] {
+                crate::core::EntityType::Func(_, ty) => Rc::clone(ty),
+                _ => unreachable!(),
+            };
+            aliases.push(Alias::CoreInstanceExport {
+                instance,
+                name,
+                kind: CoreInstanceExportAliasKind::Func,
+            });
+            let func_index = u32::try_from(me.component().funcs.len()).unwrap();
+            let core_func_index = u32::try_from(me.component().core_funcs.len()).unwrap();
+            me.component_mut()
+                .funcs
+                .push(ComponentOrCoreFuncType::Core(ty));
+            me.component_mut().core_funcs.push(func_index);
+            core_func_index
+        };
+        alias_func(self, "cabi_realloc".to_string());
+        let mut lifted = vec![];
+        for (i, (func_ty, post_return)) in lifts.into_iter().enumerate() {
+            let post_return = post_return.then(|| alias_func(self, format!("cabi_post_f{i}")));
+            let core_func_index = alias_func(self, format!("f{i}"));
+            lifted.push((func_ty, core_func_index, post_return));
+        }
+        self.push_section(Section::Alias(AliasSection { aliases }));
+
+        for (func_ty, core_func_index, post_return) in lifted {
+            let ty = Rc::clone(self.current_type_scope().get_func(func_ty));
+            let mut options = self
+                .arbitrary_canonical_options(u, &ty, Abi::Lift)?
+                .expect("a memory and `realloc` function were just aliased");
+            options.extend(post_return.map(CanonOpt::PostReturn));
+            self.push_func(Func::CanonLift {
+                func_ty,
+                options,
+                core_func_index,
+            });
+        }
+
         Ok(Step::StillBuilding)
     }
 
@@ -1770,39 +2192,36 @@ impl ComponentBuilder {
     }
 }
 
-fn canonical_abi_for(func_ty: &FuncType) -> Rc<crate::core::FuncType> {
-    let to_core_ty = |ty| match ty {
-        ComponentValType::Primitive(prim_ty) => match prim_ty {
-            PrimitiveValType::Char
-            | PrimitiveValType::Bool
-            | PrimitiveValType::S8
-            | PrimitiveValType::U8
-            | PrimitiveValType::S16
-            | PrimitiveValType::U16
-            | PrimitiveValType::S32
-            | PrimitiveValType::U32 => ValType::I32,
-            PrimitiveValType::S64 | PrimitiveValType::U64 => ValType::I64,
-            PrimitiveValType::F32 => ValType::F32,
-            PrimitiveValType::F64 => ValType::F64,
-            PrimitiveValType::String | PrimitiveValType::ErrorContext => {
-                unimplemented!("non-scalar types are not supported yet")
-            }
-        },
-        ComponentValType::Type(_) => unimplemented!("non-scalar types are not supported yet"),
-    };
+/// The maximum number of core values that parameters are flattened into
+/// before they're instead passed through linear memory.
+const MAX_FLAT_PARAMS: usize = 16;
 
-    Rc::new(crate::core::FuncType {
-        params: func_ty
+/// The maximum number of core values that results are flattened into before
+/// they're instead returned through linear memory.
+const MAX_FLAT_RESULTS: usize = 1;
+
+/// Whether a function is being lifted or lowered with the canonical ABI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Abi {
+    Lift,
+    Lower,
+}
+
+/// Can a function of type `core_func_ty` be lifted with a component function
+/// type made up only of scalars, without any canonical options?
+fn can_lift_with_scalars(core_func_ty: &crate::core::FuncType) -> bool {
+    core_func_ty.params.len() <= MAX_FLAT_PARAMS
+        && core_func_ty.results.len() <= MAX_FLAT_RESULTS
+        && core_func_ty
             .params
             .iter()
-            .map(|(_, ty)| to_core_ty(*ty))
-            .collect(),
-        results: func_ty
-            .results
-            .iter()
-            .map(|(_, ty)| to_core_ty(*ty))
-            .collect(),
-    })
+            .chain(&core_func_ty.results)
+            .all(|ty| {
+                matches!(
+                    ty,
+                    ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64
+                )
+            })
 }
 
 fn inverse_scalar_canonical_abi_for(
@@ -1845,22 +2264,13 @@ fn inverse_scalar_canonical_abi_for(
         ));
     }
 
-    names.clear();
-
-    let results = match core_func_ty.results.len() {
-        0 => Vec::new(),
-        1 => vec![(
-            if u.arbitrary()? {
-                Some(crate::unique_kebab_string(100, &mut names, u)?)
-            } else {
-                None
-            },
-            from_core_ty(u, core_func_ty.results[0])?,
-        )],
-        _ => unimplemented!("non-scalar types are not supported yet"),
+    let result = match core_func_ty.results.as_slice() {
+        [] => None,
+        [ty] => Some(from_core_ty(u, *ty)?),
+        _ => unreachable!("checked by `can_lift_with_scalars`"),
     };
 
-    Ok(FuncType { params, results })
+    Ok(FuncType { params, result })
 }
 
 #[derive(Debug)]
@@ -1930,6 +2340,38 @@ enum Type {
     Func(Rc<FuncType>),
     Component(Rc<ComponentType>),
     Instance(Rc<InstanceType>),
+    Resource(ResourceType),
+}
+
+impl Type {
+    fn is_self_contained(&self) -> bool {
+        let primitive = |ty: &ComponentValType| matches!(ty, ComponentValType::Primitive(_));
+        match self {
+            Type::Defined(ty) => match ty {
+                DefinedType::Primitive(_) | DefinedType::Flags(_) | DefinedType::Enum(_) => true,
+                DefinedType::Record(ty) => ty.fields.iter().all(|(_, ty)| primitive(ty)),
+                DefinedType::Variant(ty) => {
+                    ty.cases.iter().all(|(_, ty, _)| ty.iter().all(primitive))
+                }
+                DefinedType::List(ty) => primitive(&ty.elem_ty),
+                DefinedType::Tuple(ty) => ty.fields.iter().all(primitive),
+                DefinedType::Option(ty) => primitive(&ty.inner_ty),
+                DefinedType::Result(ty) => ty.ok_ty.iter().chain(&ty.err_ty).all(primitive),
+                DefinedType::Own(_) | DefinedType::Borrow(_) => false,
+            },
+            Type::Func(ty) => ty.is_primitive(),
+            Type::Component(_) | Type::Instance(_) => true,
+            Type::Resource(_) => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ResourceType {
+    // The core function index of this resource's destructor, if any.
+    //
+    // Resources are always represented with an `i32`.
+    dtor: Option<u32>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -2031,30 +2473,46 @@ enum InstanceTypeDecl {
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct FuncType {
     params: Vec<(String, ComponentValType)>,
-    results: Vec<(Option<String>, ComponentValType)>,
+    result: Option<ComponentValType>,
 }
 
 impl FuncType {
-    fn unnamed_result_ty(&self) -> Option<ComponentValType> {
-        if self.results.len() == 1 {
-            let (name, ty) = &self.results[0];
-            if name.is_none() {
-                return Some(*ty);
-            }
-        }
-        None
+    /// Does this function type only refer to primitive value types?
+    ///
+    /// Such function types may be imported and exported without also
+    /// importing or exporting the defined types they refer to.
+    fn is_primitive(&self) -> bool {
+        self.params
+            .iter()
+            .map(|(_, ty)| ty)
+            .chain(&self.result)
+            .all(|ty| matches!(ty, ComponentValType::Primitive(_)))
     }
+}
 
-    fn is_scalar(&self) -> bool {
-        self.params.iter().all(|(_, ty)| is_scalar(ty))
-            && self.results.len() == 1
-            && is_scalar(&self.results[0].1)
-    }
+/// Information about a value type which is relevant to the canonical ABI and
+/// to resources.
+#[derive(Clone, Debug, Default)]
+struct TypeInfo {
+    // Whether this type is, or transitively refers to, a resource type.
+    refers_to_resources: bool,
+
+    // Whether this type transitively contains a `borrow` handle, which is not
+    // allowed in function results.
+    contains_borrow: bool,
+
+    // Whether this type transitively contains a string or a list, both of
+    // which are passed through linear memory.
+    contains_ptr: bool,
+
+    // The core types this type is flattened into by the canonical ABI, or
+    // `None` if it flattens into more than `MAX_FLAT_PARAMS` core types.
+    flat: Option<Vec<ValType>>,
 }
 
-fn is_scalar(ty: &ComponentValType) -> bool {
-    match ty {
-        ComponentValType::Primitive(prim) => match prim {
+impl TypeInfo {
+    fn primitive(ty: PrimitiveValType) -> Self {
+        let flat = match ty {
             PrimitiveValType::Bool
             | PrimitiveValType::S8
             | PrimitiveValType::U8
@@ -2062,14 +2520,88 @@ fn is_scalar(ty: &ComponentValType) -> bool {
             | PrimitiveValType::U16
             | PrimitiveValType::S32
             | PrimitiveValType::U32
-            | PrimitiveValType::S64
-            | PrimitiveValType::U64
-            | PrimitiveValType::F32
-            | PrimitiveValType::F64
-            | PrimitiveValType::Char => true,
-            PrimitiveValType::String | PrimitiveValType::ErrorContext => false,
-        },
-        ComponentValType::Type(_) => false,
+            | PrimitiveValType::Char
+            | PrimitiveValType::ErrorContext => vec![ValType::I32],
+            PrimitiveValType::S64 | PrimitiveValType::U64 => vec![ValType::I64],
+            PrimitiveValType::F32 => vec![ValType::F32],
+            PrimitiveValType::F64 => vec![ValType::F64],
+            PrimitiveValType::String => vec![ValType::I32, ValType::I32],
+        };
+        TypeInfo {
+            contains_ptr: ty == PrimitiveValType::String,
+            flat: Some(flat),
+            ..TypeInfo::default()
+        }
+    }
+
+    fn handle(borrow: bool) -> Self {
+        TypeInfo {
+            refers_to_resources: true,
+            contains_borrow: borrow,
+            contains_ptr: false,
+            flat: Some(vec![ValType::I32]),
+        }
+    }
+
+    /// The information for a record or tuple of the given fields.
+    fn product<'a>(fields: impl IntoIterator<Item = &'a TypeInfo>) -> Self {
+        let mut info = TypeInfo {
+            flat: Some(vec![]),
+            ..TypeInfo::default()
+        };
+        for field in fields {
+            info.merge_flags(field);
+            info.flat = match (info.flat, &field.flat) {
+                (Some(mut flat), Some(field)) if flat.len() + field.len() <= MAX_FLAT_PARAMS => {
+                    flat.extend(field);
+                    Some(flat)
+                }
+                _ => None,
+            };
+        }
+        info
+    }
+
+    /// The information for a variant, option, or result with the given case
+    /// payloads.
+    fn sum<'a>(cases: impl IntoIterator<Item = &'a TypeInfo>) -> Self {
+        let mut info = TypeInfo {
+            // The discriminant.
+            flat: Some(vec![ValType::I32]),
+            ..TypeInfo::default()
+        };
+        for case in cases {
+            info.merge_flags(case);
+            info.flat = match (info.flat, &case.flat) {
+                (Some(mut flat), Some(case)) if case.len() < MAX_FLAT_PARAMS => {
+                    for (i, ty) in case.iter().enumerate() {
+                        match flat.get_mut(i + 1) {
+                            Some(prev) => *prev = join_flat_types(*prev, *ty),
+                            None => flat.push(*ty),
+                        }
+                    }
+                    Some(flat)
+                }
+                _ => None,
+            };
+        }
+        info
+    }
+
+    fn merge_flags(&mut self, other: &TypeInfo) {
+        self.refers_to_resources |= other.refers_to_resources;
+        self.contains_borrow |= other.contains_borrow;
+        self.contains_ptr |= other.contains_ptr;
+    }
+}
+
+/// The core type used by the canonical ABI for a position in a flattened
+/// variant whose cases flatten to `a` and `b` respectively.
+fn join_flat_types(a: ValType, b: ValType) -> ValType {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (ValType::I32, ValType::F32) | (ValType::F32, ValType::I32) => ValType::I32,
+        _ => ValType::I64,
     }
 }
 
@@ -2084,6 +2616,8 @@ enum DefinedType {
     Enum(EnumType),
     Option(OptionType),
     Result(ResultType),
+    Own(u32),
+    Borrow(u32),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -2155,6 +2689,9 @@ enum Func {
         options: Vec<CanonOpt>,
         func_index: u32,
     },
+    ResourceNew(u32),
+    ResourceDrop(u32),
+    ResourceRep(u32),
 }
 
 #[derive(Debug)]
@@ -2177,10 +2714,20 @@ struct ExportSection {}
 struct StartSection {}
 
 #[derive(Debug)]
-struct AliasSection {}
+struct AliasSection {
+    aliases: Vec<Alias>,
+}
 
 #[derive(Debug)]
-struct CoreInstanceSection {}
+struct CoreInstanceSection {
+    instances: Vec<CoreInstance>,
+}
+
+#[derive(Debug)]
+enum CoreInstance {
+    // Instantiate a core module which doesn't have any imports.
+    Instantiate { module_index: u32 },
+}
 
 #[derive(Debug)]
 struct CoreTypeSection {
@@ -31,7 +31,7 @@ impl Section {
                     data: &bytes,
                 });
             }
-            Self::CoreInstance(_) => todo!(),
+            Self::CoreInstance(sec) => sec.encode(component),
             Self::CoreType(sec) => sec.encode(component),
             Self::Component(comp) => {
                 let bytes = comp.to_bytes();
@@ -41,7 +41,7 @@ impl Section {
                 });
             }
             Self::Instance(_) => todo!(),
-            Self::Alias(_) => todo!(),
+            Self::Alias(sec) => sec.encode(component),
             Self::Type(sec) => sec.encode(component),
             Self::Canonical(sec) => sec.encode(component),
             Self::Start(_) => todo!(),
@@ -100,12 +100,48 @@ impl CanonicalSection {
                     let options = translate_canon_opt(options);
                     sec.lower(*func_index, options);
                 }
+                Func::ResourceNew(ty) => {
+                    sec.resource_new(*ty);
+                }
+                Func::ResourceDrop(ty) => {
+                    sec.resource_drop(*ty);
+                }
+                Func::ResourceRep(ty) => {
+                    sec.resource_rep(*ty);
+                }
+            }
+        }
+        component.section(&sec);
+    }
+}
+
+impl CoreInstanceSection {
+    fn encode(&self, component: &mut wasm_encoder::Component) {
+        let mut sec = wasm_encoder::InstanceSection::new();
+        for instance in &self.instances {
+            match instance {
+                CoreInstance::Instantiate { module_index } => {
+                    sec.instantiate(
+                        *module_index,
+                        std::iter::empty::<(&str, wasm_encoder::ModuleArg)>(),
+                    );
+                }
             }
         }
         component.section(&sec);
     }
 }
 
+impl AliasSection {
+    fn encode(&self, component: &mut wasm_encoder::Component) {
+        let mut sec = wasm_encoder::ComponentAliasSection::new();
+        for alias in &self.aliases {
+            sec.alias(translate_alias(alias));
+        }
+        component.section(&sec);
+    }
+}
+
 impl CoreTypeSection {
     fn encode(&self, component: &mut wasm_encoder::Component) {
         let mut sec = wasm_encoder::CoreTypeSection::new();
@@ -172,7 +208,7 @@ impl Type {
                 let mut f = enc.function();
 
                 f.params(func_ty.params.iter().map(|(name, ty)| (name.as_str(), *ty)));
-                f.result(func_ty.unnamed_result_ty());
+                f.result(func_ty.result);
             }
             Self::Component(comp_ty) => {
                 let mut enc_comp_ty = wasm_encoder::ComponentType::new();
@@ -217,6 +253,9 @@ impl Type {
                 }
                 enc.instance(&enc_inst_ty);
             }
+            Self::Resource(ty) => {
+                enc.resource(ValType::I32, ty.dtor);
+            }
         }
     }
 }
@@ -253,6 +292,12 @@ impl DefinedType {
             Self::Result(ty) => {
                 enc.result(ty.ok_ty, ty.err_ty);
             }
+            Self::Own(ty) => {
+                enc.own(*ty);
+            }
+            Self::Borrow(ty) => {
+                enc.borrow(*ty);
+            }
         }
     }
 }
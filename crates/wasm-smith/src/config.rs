@@ -3,6 +3,7 @@
 use crate::InstructionKinds;
 use anyhow::bail;
 use arbitrary::{Arbitrary, Result, Unstructured};
+use std::collections::BTreeMap;
 
 macro_rules! define_config {
     (
@@ -370,6 +371,21 @@ define_config! {
         /// [`InstructionKind::NumericInt`]: crate::InstructionKind::NumericInt
         pub allowed_instructions: InstructionKinds = InstructionKinds::all(),
 
+        /// Relative weights for how often individual instructions are
+        /// generated.
+        ///
+        /// By default every valid instruction is roughly as likely to be
+        /// generated as any other. Each weight here is a percentage of an
+        /// instruction's default likelihood, so `200` makes an instruction
+        /// twice as likely, `50` half as likely, and `0` prevents it from
+        /// being generated at all. Instructions without a weight keep their
+        /// default likelihood.
+        ///
+        /// See [`InstructionWeights`] for how instructions are named. Weights
+        /// are typically derived from a corpus of existing modules with
+        /// [`Profile`](crate::Profile).
+        pub instruction_weights: InstructionWeights = InstructionWeights::default(),
+
         /// Determines whether we generate floating point instructions and types.
         ///
         /// Defaults to `true`.
@@ -411,6 +427,53 @@ define_config! {
         /// Defaults to `false`.
         pub disallow_traps: bool = false,
 
+        /// Generate modules suitable for differential execution, where the
+        /// same module is run in two engines and their results are compared.
+        ///
+        /// When enabled, generated modules always terminate and their
+        /// observable behavior doesn't depend on which spec-compliant engine
+        /// runs them. Specifically:
+        ///
+        /// * [`Self::disallow_traps`] and [`Self::canonicalize_nans`] are
+        ///   enabled.
+        ///
+        /// * Every function checks for fuel, as with
+        ///   [`Module::ensure_termination`](crate::Module::ensure_termination),
+        ///   starting from [`Self::differential_fuel`]. Running out of fuel
+        ///   traps deterministically, and it also bounds the depth of the
+        ///   call stack so stack overflow isn't a concern in practice.
+        ///
+        /// * Relaxed SIMD, whose results are implementation-defined, and
+        ///   threads, whose `wait` instructions depend on timing, are disabled
+        ///   along with [`Self::allow_invalid_funcs`].
+        ///
+        /// * `memory.grow`, `table.grow`, `array.new`, and `array.new_default`
+        ///   are never generated because whether they succeed depends on the
+        ///   engine's resource limits. For the same reason memories are capped
+        ///   at 256KiB and tables at 10,000 elements.
+        ///
+        /// * A function named
+        ///   [`DIFFERENTIAL_HASH_EXPORT`](crate::DIFFERENTIAL_HASH_EXPORT) of
+        ///   type `[] -> [i64]` is exported. It hashes the value of every
+        ///   global and the contents of every memory, so a harness can call
+        ///   exports in the same order in both engines and then compare the
+        ///   hash. References are only hashed by whether they're null.
+        ///
+        /// All other features, such as SIMD, exceptions, GC, tail calls,
+        /// memory64 and multi-memory, remain available as configured.
+        ///
+        /// Defaults to `false`.
+        pub differential_mode: bool = false,
+
+        /// The amount of fuel modules generated with
+        /// [`Self::differential_mode`] start with.
+        ///
+        /// This is roughly the number of loop iterations and function calls
+        /// that can be executed before a trap is raised.
+        ///
+        /// Defaults to `1000`.
+        pub differential_fuel: u32 = 1000,
+
         /// Determines whether the exception-handling proposal is enabled for
         /// generating instructions.
         ///
@@ -725,6 +788,19 @@ define_config! {
         /// Defaults to `false`.
         pub wide_arithmetic_enabled: bool = false,
 
+        /// Determines whether the [stack-switching proposal] is enabled.
+        ///
+        /// This enables generating continuation types along with the
+        /// `cont.new`, `cont.bind`, `suspend`, `resume`, `resume_throw` and
+        /// `switch` instructions. The proposal builds on GC and exception
+        /// handling, so this has no effect unless [`Config::gc_enabled`] and
+        /// [`Config::exceptions_enabled`] are also enabled.
+        ///
+        /// [stack-switching proposal]: https://github.com/WebAssembly/stack-switching
+        ///
+        /// Defaults to `false`.
+        pub stack_switching_enabled: bool = false,
+
         /// Determines whether the [extended-const proposal] is enabled.
         ///
         /// [extended-const proposal]: https://github.com/WebAssembly/extended-const
@@ -792,6 +868,68 @@ impl std::str::FromStr for MemoryOffsetChoices {
     }
 }
 
+/// Relative weights for individual instructions, used by
+/// [`Config::instruction_weights`].
+///
+/// Instructions are named the same way as the methods of
+/// [`wasmparser::VisitOperator`] without the `visit_` prefix, for example
+/// `i32_add`, `local_get`, or `br_table`. Underscores and periods are ignored
+/// when matching names, so names in the text format such as `i32.add` work
+/// too. Names of instructions that `wasm-smith` doesn't generate are ignored.
+///
+/// The textual form, used on the command line, is a comma-separated list of
+/// `name=weight` pairs such as `i32_add=200,call_indirect=0`.
+///
+/// [`wasmparser::VisitOperator`]: https://docs.rs/wasmparser/latest/wasmparser/trait.VisitOperator.html
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Deserialize, serde_derive::Serialize),
+    serde(transparent)
+)]
+pub struct InstructionWeights(BTreeMap<String, u32>);
+
+impl InstructionWeights {
+    /// Set the weight, as a percentage of its default likelihood, of the
+    /// instruction named `name`.
+    pub fn set(&mut self, name: &str, weight: u32) {
+        self.0.insert(name.to_string(), weight);
+    }
+
+    /// Returns whether no weights have been set.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over all instruction names and their weights.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> + '_ {
+        self.0.iter().map(|(name, weight)| (name.as_str(), *weight))
+    }
+
+    /// The form of `name` used to match instructions.
+    pub(crate) fn normalize(name: &str) -> String {
+        name.trim_start_matches("r#")
+            .chars()
+            .filter(|c| *c != '_' && *c != '.')
+            .collect()
+    }
+}
+
+impl std::str::FromStr for InstructionWeights {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = InstructionWeights::default();
+        for part in s.split(',').filter(|s| !s.is_empty()) {
+            let (name, weight) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected `name=weight`, found `{part}`"))?;
+            let weight = weight.parse::<u32>().map_err(|e| e.to_string())?;
+            weights.set(name.trim(), weight);
+        }
+        Ok(weights)
+    }
+}
+
 impl<'a> Arbitrary<'a> for Config {
     fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
         const MAX_MAXIMUM: usize = 1000;
@@ -836,6 +974,7 @@ impl<'a> Arbitrary<'a> for Config {
                 }
                 InstructionKinds::new(&allowed)
             },
+            instruction_weights: InstructionWeights::default(),
             table_max_size_required: u.arbitrary()?,
             max_table_elements: u.int_in_range(0..=1_000_000)?,
             disallow_traps: u.arbitrary()?,
@@ -871,11 +1010,14 @@ impl<'a> Arbitrary<'a> for Config {
             export_everything: false,
             generate_custom_sections: false,
             allow_invalid_funcs: false,
+            differential_mode: false,
+            differential_fuel: 1000,
 
             // Proposals that are not stage4+ are disabled by default.
             custom_page_sizes_enabled: false,
             wide_arithmetic_enabled: false,
             shared_everything_threads_enabled: false,
+            stack_switching_enabled: false,
         };
         config.sanitize();
         Ok(config)
@@ -894,8 +1036,20 @@ impl Config {
     ///
     /// This method will not enable anything that isn't already enabled or
     /// increase any limit of an item, but it may turn features off or shrink
-    /// limits from what they're previously specified as.
+    /// limits from what they're previously specified as. The one exception is
+    /// `differential_mode`, which implies other options.
     pub(crate) fn sanitize(&mut self) {
+        if self.differential_mode {
+            self.disallow_traps = true;
+            self.canonicalize_nans = true;
+            self.allow_invalid_funcs = false;
+            self.relaxed_simd_enabled = false;
+            self.threads_enabled = false;
+            self.max_memory32_bytes = self.max_memory32_bytes.min(1 << 18);
+            self.max_memory64_bytes = self.max_memory64_bytes.min(1 << 18);
+            self.max_table_elements = self.max_table_elements.min(10_000);
+        }
+
         // If reference types are disabled then automatically flag tables as
         // capped at 1 and disable gc as well.
         if !self.reference_types_enabled {
@@ -910,6 +1064,12 @@ impl Config {
             self.shared_everything_threads_enabled = false;
         }
 
+        // Continuations are GC-managed references and are suspended to tags,
+        // so stack-switching requires both GC and exceptions.
+        if !self.gc_enabled || !self.exceptions_enabled {
+            self.stack_switching_enabled = false;
+        }
+
         // If simd is disabled then disable all relaxed simd instructions as
         // well.
         if !self.simd_enabled {
@@ -972,6 +1132,7 @@ impl Config {
         );
         features.set(WasmFeatures::EXTENDED_CONST, self.extended_const_enabled);
         features.set(WasmFeatures::WIDE_ARITHMETIC, self.wide_arithmetic_enabled);
+        features.set(WasmFeatures::STACK_SWITCHING, self.stack_switching_enabled);
 
         features
     }
@@ -1,12 +1,17 @@
 //! Generating arbitrary core Wasm modules.
 
 mod code_builder;
+mod differential;
 pub(crate) mod encode;
 mod terminate;
 
+pub use differential::DIFFERENTIAL_HASH_EXPORT;
+
 use crate::{Config, arbitrary_loop, limited_string, unique_string};
 use arbitrary::{Arbitrary, Result, Unstructured};
 use code_builder::CodeBuilderAllocations;
+#[cfg(feature = "wasmparser")]
+pub(crate) use code_builder::DEFAULT_WEIGHTS;
 use flagset::{FlagSet, flags};
 use std::collections::{HashMap, HashSet};
 use std::fmt;
@@ -48,6 +53,11 @@ pub struct Module {
     duplicate_imports_behavior: DuplicateImportsBehavior,
     valtypes: Vec<ValType>,
 
+    /// The weight of each instruction the code builder can choose from,
+    /// resolved from `config.instruction_weights`, or `None` to use the
+    /// default weights.
+    instruction_weights: Option<Vec<u32>>,
+
     /// All types locally defined in this module (available in the type index
     /// space).
     types: Vec<SubType>,
@@ -88,6 +98,9 @@ pub struct Module {
     /// Indices within `types that are struct types.
     struct_types: Vec<u32>,
 
+    /// Indices within `types` that are continuation types.
+    cont_types: Vec<u32>,
+
     /// Number of imported items into this module.
     num_imports: usize,
 
@@ -144,6 +157,11 @@ pub struct Module {
     /// Names currently exported from this module.
     export_names: HashSet<String>,
 
+    /// Exports which must be defined by this module regardless of its
+    /// configuration, used when implementing component functions.
+    #[cfg(feature = "component-model")]
+    component_required_exports: Vec<(String, RequiredExport)>,
+
     /// Reusable buffer in `self.arbitrary_const_expr` to amortize the cost of
     /// allocation.
     const_expr_choices: Vec<Box<dyn Fn(&mut Unstructured, ValType) -> Result<ConstExpr>>>,
@@ -212,12 +230,53 @@ impl Module {
         Ok(module)
     }
 
+    /// Creates a new `Module` which, in addition to its arbitrary contents,
+    /// defines and exports each of `exports`.
+    ///
+    /// This is used by components to generate core modules which implement
+    /// lifted functions with the canonical ABI.
+    #[cfg(feature = "component-model")]
+    pub(crate) fn new_with_required_exports(
+        config: Config,
+        u: &mut Unstructured<'_>,
+        exports: Vec<(String, RequiredExport)>,
+    ) -> Result<Self> {
+        let mut module = Module::empty(config, DuplicateImportsBehavior::Disallowed);
+        module.component_required_exports = exports;
+        module.build(u)?;
+        Ok(module)
+    }
+
+    /// Returns the type of each of this module's exports, keyed by name.
+    #[cfg(feature = "component-model")]
+    pub(crate) fn export_types(&self) -> std::collections::BTreeMap<String, EntityType> {
+        self.exports
+            .iter()
+            .map(|(name, kind, index)| {
+                let index = *index as usize;
+                let ty = match kind {
+                    ExportKind::Func => {
+                        let (type_index, func_type) = &self.funcs[index];
+                        EntityType::Func(*type_index, Rc::clone(func_type))
+                    }
+                    ExportKind::Table => EntityType::Table(self.tables[index]),
+                    ExportKind::Memory => EntityType::Memory(self.memories[index]),
+                    ExportKind::Global => EntityType::Global(self.globals[index]),
+                    ExportKind::Tag => EntityType::Tag(self.tags[index].clone()),
+                };
+                (name.clone(), ty)
+            })
+            .collect()
+    }
+
     fn empty(mut config: Config, duplicate_imports_behavior: DuplicateImportsBehavior) -> Self {
         config.sanitize();
+        let instruction_weights = code_builder::resolve_instruction_weights(&config);
         Module {
             config,
             duplicate_imports_behavior,
             valtypes: Vec::new(),
+            instruction_weights,
             types: Vec::new(),
             rec_groups: Vec::new(),
             can_subtype: Vec::new(),
@@ -228,6 +287,7 @@ impl Module {
             array_types: Vec::new(),
             func_types: Vec::new(),
             struct_types: Vec::new(),
+            cont_types: Vec::new(),
             num_imports: 0,
             num_defined_tags: 0,
             num_defined_funcs: 0,
@@ -246,6 +306,8 @@ impl Module {
             data: Vec::new(),
             type_size: 0,
             export_names: HashSet::new(),
+            #[cfg(feature = "component-model")]
+            component_required_exports: Vec::new(),
             const_expr_choices: Vec::new(),
             max_type_limit: MaxTypeLimit::ModuleTypes,
             interesting_values32: Vec::new(),
@@ -286,7 +348,6 @@ pub(crate) struct CompositeType {
 }
 
 impl CompositeType {
-    #[cfg(any(feature = "component-model", feature = "wasmparser"))]
     pub(crate) fn new_func(func: Rc<FuncType>, shared: bool) -> Self {
         Self {
             inner: CompositeInnerType::Func(func),
@@ -324,6 +385,9 @@ impl From<&CompositeType> for wasm_encoder::CompositeType {
                 wasm_encoder::FuncType::new(f.params.iter().cloned(), f.results.iter().cloned()),
             ),
             CompositeInnerType::Struct(s) => wasm_encoder::CompositeInnerType::Struct(s.clone()),
+            CompositeInnerType::Cont(f) => {
+                wasm_encoder::CompositeInnerType::Cont(wasm_encoder::ContType(*f))
+            }
         };
         wasm_encoder::CompositeType {
             shared: ty.shared,
@@ -337,6 +401,8 @@ pub(crate) enum CompositeInnerType {
     Array(ArrayType),
     Func(Rc<FuncType>),
     Struct(StructType),
+    /// A continuation type, holding the index of its function type.
+    Cont(u32),
 }
 
 /// A function signature.
@@ -374,6 +440,17 @@ pub(crate) enum EntityType {
     Func(u32, Rc<FuncType>),
 }
 
+/// An export which a module generated with
+/// [`Module::new_with_required_exports`] must define.
+#[cfg(feature = "component-model")]
+#[derive(Debug)]
+pub(crate) enum RequiredExport {
+    /// A function of the given type.
+    Func(Rc<FuncType>),
+    /// A memory of the given type.
+    Memory(MemoryType),
+}
+
 /// Type of a tag.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct TagType {
@@ -440,6 +517,10 @@ pub(crate) enum Offset {
 impl Module {
     fn build(&mut self, u: &mut Unstructured) -> Result<()> {
         self.valtypes = configured_valtypes(&self.config);
+        if self.config.differential_mode {
+            self.export_names
+                .insert(DIFFERENTIAL_HASH_EXPORT.to_string());
+        }
 
         let mut generate_arbitrary_imports = true;
         let mut generate_arbitrary_exports = true;
@@ -470,6 +551,11 @@ impl Module {
         if self.required_exports(u)? {
             generate_arbitrary_exports = false;
         }
+        #[cfg(feature = "component-model")]
+        if !self.component_required_exports.is_empty() {
+            self.add_component_required_exports()?;
+            generate_arbitrary_exports = false;
+        }
         if generate_arbitrary_exports {
             self.arbitrary_exports(u)?;
         }
@@ -478,6 +564,9 @@ impl Module {
         self.arbitrary_elems(u)?;
         self.arbitrary_data(u)?;
         self.arbitrary_code(u)?;
+        if self.config.differential_mode {
+            self.finish_differential();
+        }
         Ok(())
     }
 
@@ -528,6 +617,7 @@ impl Module {
                         (NoFunc, Func) => true,
                         (None, I31 | Array | Struct) => true,
                         (NoExn, Exn) => true,
+                        (NoCont, Cont) => true,
                         _ => false,
                     }
             }
@@ -542,6 +632,7 @@ impl Module {
                     Struct => matches!(a_ty.inner, CT::Struct(_)),
                     Array => matches!(a_ty.inner, CT::Array(_)),
                     Func => matches!(a_ty.inner, CT::Func(_)),
+                    Cont => matches!(a_ty.inner, CT::Cont(_)),
                     _ => false,
                 }
             }
@@ -554,6 +645,7 @@ impl Module {
                 match ty {
                     None => matches!(b_ty.inner, CT::Array(_) | CT::Struct(_)),
                     NoFunc => matches!(b_ty.inner, CT::Func(_)),
+                    NoCont => matches!(b_ty.inner, CT::Cont(_)),
                     _ => false,
                 }
             }
@@ -601,6 +693,7 @@ impl Module {
             CompositeInnerType::Array(_) => &mut self.array_types,
             CompositeInnerType::Func(_) => &mut self.func_types,
             CompositeInnerType::Struct(_) => &mut self.struct_types,
+            CompositeInnerType::Cont(_) => &mut self.cont_types,
         };
         list.push(index);
 
@@ -736,6 +829,9 @@ impl Module {
                     m.arbitrary_matching_struct_type(u, s)
                 })?;
             }
+            // Continuation types may only subtype other continuation types
+            // whose function types match, so keep the same function type.
+            CompositeInnerType::Cont(_) => {}
         }
         Ok(SubType {
             is_final: u.arbitrary()?,
@@ -863,7 +959,11 @@ impl Module {
                     Extern => {
                         add_abstract(&mut choices, &[NoExtern]);
                     }
-                    Exn | NoExn | None | NoExtern | NoFunc | Cont | NoCont => {}
+                    Cont => {
+                        add_abstract(&mut choices, &[NoCont]);
+                        add_concrete(&mut choices, &self.cont_types);
+                    }
+                    Exn | NoExn | None | NoExtern | NoFunc | NoCont => {}
                 }
             }
             HT::Concrete(idx) => {
@@ -883,6 +983,10 @@ impl Module {
                         shared,
                         ty: AbstractHeapType::NoFunc,
                     }),
+                    Some((shared, CT::Cont(_))) => choices.push(HT::Abstract {
+                        shared,
+                        ty: AbstractHeapType::NoCont,
+                    }),
                     None => {
                         // The referenced type might be part of this same rec
                         // group we are currently generating, but not generated
@@ -997,6 +1101,7 @@ impl Module {
                     }
                     NoCont => {
                         add_abstract(&mut choices, &[Cont]);
+                        add_concrete(&mut choices, &self.cont_types);
                     }
                     Exn | Any | Func | Extern | Cont => {}
                 }
@@ -1018,6 +1123,9 @@ impl Module {
                         CT::Struct(_) => {
                             choices.extend([ht(Any), ht(Eq), ht(Struct)]);
                         }
+                        CT::Cont(_) => {
+                            choices.push(ht(Cont));
+                        }
                     }
                 } else {
                     // Same as in `arbitrary_matching_heap_type`: this was a
@@ -1051,7 +1159,20 @@ impl Module {
             });
         }
 
-        match u.int_in_range(0..=2)? {
+        // Continuation types must refer to an already-defined function type.
+        // Shared continuations aren't generated at this time.
+        let cont_func_types = if self.config.stack_switching_enabled && !shared {
+            self.func_types
+                .iter()
+                .copied()
+                .filter(|i| !self.is_shared_type(*i))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let max_choice = if cont_func_types.is_empty() { 2 } else { 3 };
+
+        match u.int_in_range(0..=max_choice)? {
             0 => Ok(CompositeType {
                 shared,
                 inner: CT::Array(ArrayType(
@@ -1066,6 +1187,10 @@ impl Module {
                 shared,
                 inner: CT::Struct(self.propagate_shared(shared, |m| m.arbitrary_struct_type(u))?),
             }),
+            3 => Ok(CompositeType {
+                shared,
+                inner: CT::Cont(*u.choose(&cont_func_types)?),
+            }),
             _ => unreachable!(),
         }
     }
@@ -1143,6 +1268,9 @@ impl Module {
                     .copied(),
             );
         }
+        if self.config.stack_switching_enabled {
+            choices.extend([Cont, NoCont]);
+        }
 
         Ok(HeapType::Abstract {
             shared: self.arbitrary_shared(u)?,
@@ -1158,6 +1286,23 @@ impl Module {
             params.push(self.arbitrary_valtype(u)?);
             Ok(true)
         })?;
+        // Functions whose last parameter is a continuation can be the target
+        // of `switch`, so generate those from time to time. `switch` is only
+        // valid when all of the other parameters are on the stack too, so
+        // often make the continuation the only parameter.
+        if self.config.stack_switching_enabled
+            && !self.must_share
+            && !self.cont_types.is_empty()
+            && u.ratio(1, 4)?
+        {
+            if u.arbitrary()? {
+                params.clear();
+            }
+            params.push(ValType::Ref(RefType {
+                nullable: true,
+                heap_type: HeapType::Concrete(*u.choose(&self.cont_types)?),
+            }));
+        }
         let max_results = if self.config.multi_value_enabled {
             max_params
         } else {
@@ -1791,6 +1936,21 @@ impl Module {
         }
     }
 
+    fn cont_types(&self) -> impl Iterator<Item = (u32, &FuncType)> + '_ {
+        self.cont_types
+            .iter()
+            .copied()
+            .map(move |type_i| (type_i, &**self.cont_func_type(type_i)))
+    }
+
+    /// Returns the function type wrapped by the continuation type at `idx`.
+    fn cont_func_type(&self, idx: u32) -> &Rc<FuncType> {
+        match &self.ty(idx).composite_type.inner {
+            CompositeInnerType::Cont(f) => self.func_type(*f),
+            _ => panic!("types[{idx}] is not a cont type"),
+        }
+    }
+
     fn tags(&self) -> impl Iterator<Item = (u32, &TagType)> + '_ {
         self.tags
             .iter()
@@ -1810,10 +1970,11 @@ impl Module {
     }
 
     fn tag_func_types(&self) -> impl Iterator<Item = u32> + '_ {
-        self.func_types
-            .iter()
-            .copied()
-            .filter(move |i| self.func_type(*i).results.is_empty())
+        // Tags with results can only be used with `suspend`, which requires
+        // stack switching.
+        self.func_types.iter().copied().filter(move |i| {
+            self.config.stack_switching_enabled || self.func_type(*i).results.is_empty()
+        })
     }
 
     fn arbitrary_valtype(&self, u: &mut Unstructured) -> Result<ValType> {
@@ -2272,12 +2433,7 @@ impl Module {
                 // For functions, add the type and a function with that type.
                 wasmparser::types::EntityType::Func(id) => {
                     let (func_type, subtype) = check_and_get_func_type(id);
-                    self.rec_groups.push(self.types.len()..self.types.len() + 1);
-                    let type_index = self.add_type(subtype);
-                    let func_index = self.funcs.len() as u32;
-                    self.funcs.push((type_index, func_type));
-                    self.num_defined_funcs += 1;
-                    func_index
+                    self.add_func_of_type(subtype, func_type)
                 }
                 // For globals, add a new global.
                 wasmparser::types::EntityType::Global(global_type) => {
@@ -2313,6 +2469,40 @@ impl Module {
         Ok(())
     }
 
+    #[cfg(feature = "component-model")]
+    fn add_component_required_exports(&mut self) -> Result<()> {
+        for (name, export) in mem::take(&mut self.component_required_exports) {
+            let (kind, index) = match export {
+                RequiredExport::Func(func_type) => {
+                    let subtype = SubType {
+                        is_final: true,
+                        supertype: None,
+                        depth: 1,
+                        composite_type: CompositeType::new_func(Rc::clone(&func_type), false),
+                    };
+                    (ExportKind::Func, self.add_func_of_type(subtype, func_type))
+                }
+                RequiredExport::Memory(ty) => {
+                    (ExportKind::Memory, self.add_arbitrary_memory_of_type(ty)?)
+                }
+            };
+            self.exports.push((name.clone(), kind, index));
+            self.export_names.insert(name);
+        }
+        Ok(())
+    }
+
+    /// Add a new function, and a new type for it in its own rec group, and
+    /// return its function index.
+    fn add_func_of_type(&mut self, subtype: SubType, func_type: Rc<FuncType>) -> u32 {
+        self.rec_groups.push(self.types.len()..self.types.len() + 1);
+        let type_index = self.add_type(subtype);
+        let func_index = self.funcs.len() as u32;
+        self.funcs.push((type_index, func_type));
+        self.num_defined_funcs += 1;
+        func_index
+    }
+
     fn arbitrary_exports(&mut self, u: &mut Unstructured) -> Result<()> {
         if self.config.max_type_size < self.type_size && !self.config.export_everything {
             return Ok(());
@@ -3474,8 +3664,8 @@ impl TryFrom<wasmparser::CompositeType> for CompositeType {
             wasmparser::CompositeInnerType::Struct(struct_type) => {
                 CompositeInnerType::Struct(struct_type.try_into().map_err(|_| ())?)
             }
-            wasmparser::CompositeInnerType::Cont(_) => {
-                panic!("continuation type is not supported by wasm-smith currently.")
+            wasmparser::CompositeInnerType::Cont(cont_type) => {
+                CompositeInnerType::Cont(cont_type.0.as_module_index().ok_or(())?)
             }
         };
 
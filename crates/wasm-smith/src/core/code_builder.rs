@@ -2,13 +2,13 @@ use super::{
     CompositeInnerType, Elements, FuncType, Instruction, InstructionKind::*, InstructionKinds,
     Module, ValType,
 };
-use crate::{MemoryOffsetChoices, unique_string};
+use crate::{InstructionWeights, MemoryOffsetChoices, unique_string};
 use arbitrary::{Result, Unstructured};
 use std::collections::{BTreeMap, BTreeSet};
 use std::rc::Rc;
 use wasm_encoder::{
     AbstractHeapType, ArrayType, BlockType, Catch, ConstExpr, ExportKind, FieldType, GlobalType,
-    HeapType, MemArg, RefType, StorageType, StructType,
+    Handle, HeapType, MemArg, RefType, StorageType, StructType,
 };
 mod no_traps;
 
@@ -18,11 +18,18 @@ macro_rules! instructions {
             ($predicate:expr, $generator_fn:ident, $instruction_kind:ident $(, $cost:tt)?),
         )*
     ) => {
-        static NUM_OPTIONS: usize = instructions!(
+        const NUM_OPTIONS: usize = instructions!(
             @count;
             $( $generator_fn )*
         );
 
+        // The name of each instruction along with its default weight.
+        pub(crate) static DEFAULT_WEIGHTS: [(&str, u32); NUM_OPTIONS] = [
+            $(
+                (stringify!($generator_fn), 1000 $(- $cost)?),
+            )*
+        ];
+
         fn choose_instruction(
             u: &mut Unstructured<'_>,
             module: &Module,
@@ -32,7 +39,9 @@ macro_rules! instructions {
             fn(&mut Unstructured<'_>, &Module, &mut CodeBuilder, &mut Vec<Instruction>) -> Result<()>
         > {
             builder.allocs.options.clear();
+            let weights = module.instruction_weights.as_deref();
             let mut cost = 0;
+            let mut index = 0;
             // Unroll the loop that checks whether each instruction is valid in
             // the current context and, if it is valid, pushes it onto our
             // options. Unrolling this loops lets us avoid dynamic calls through
@@ -43,10 +52,18 @@ macro_rules! instructions {
                 let predicate: Option<fn(&Module, &mut CodeBuilder) -> bool> = $predicate;
                 if predicate.map_or(true, |f| f(module, builder))
                     && allowed_instructions.contains($instruction_kind) {
-                    builder.allocs.options.push(($generator_fn, cost));
-                    cost += 1000 $(- $cost)?;
+                    let weight = match weights {
+                        Some(weights) => weights[index],
+                        None => 1000 $(- $cost)?,
+                    };
+                    if weight > 0 {
+                        builder.allocs.options.push(($generator_fn, cost));
+                        cost += weight;
+                    }
                 }
+                index += 1;
             )*
+            debug_assert_eq!(index, NUM_OPTIONS);
 
             // If there aren't actually any candidate instructions due to
             // various filters in place then return `None` to indicate the
@@ -73,6 +90,31 @@ macro_rules! instructions {
     };
 }
 
+/// Scale the default weight of each instruction by its percentage in
+/// `config.instruction_weights`.
+pub(crate) fn resolve_instruction_weights(config: &crate::Config) -> Option<Vec<u32>> {
+    if config.instruction_weights.is_empty() {
+        return None;
+    }
+    let percents = config
+        .instruction_weights
+        .iter()
+        .map(|(name, percent)| (InstructionWeights::normalize(name), percent))
+        .collect::<BTreeMap<_, _>>();
+    let weights = DEFAULT_WEIGHTS
+        .iter()
+        .map(|(name, default)| {
+            let percent = percents
+                .get(&InstructionWeights::normalize(name))
+                .copied()
+                .unwrap_or(100);
+            // Cap weights so that the sum of all weights fits in a `u32`.
+            default * percent.min(10_000) / 100
+        })
+        .collect();
+    Some(weights)
+}
+
 // The static set of options of instruction to generate that could be valid at
 // some given time. One entry per Wasm instruction.
 //
@@ -89,7 +131,8 @@ macro_rules! instructions {
 //
 // 4. An optional number used to weight how often this instruction is chosen.
 //    Higher numbers are less likely to be chosen, and number specified must be
-//    less than 1000.
+//    less than 1000. This default weight is scaled by
+//    `Config::instruction_weights`, if any.
 instructions! {
     // Control instructions.
     (Some(unreachable_valid), unreachable, Control, 990),
@@ -116,6 +159,12 @@ instructions! {
     (Some(br_on_non_null_valid), br_on_non_null, Control),
     (Some(br_on_cast_valid), br_on_cast, Control),
     (Some(br_on_cast_fail_valid), br_on_cast_fail, Control),
+    (Some(cont_new_valid), cont_new, Control),
+    (Some(cont_bind_valid), cont_bind, Control),
+    (Some(suspend_valid), suspend, Control, 850),
+    (Some(resume_valid), resume, Control),
+    (Some(resume_throw_valid), resume_throw, Control),
+    (Some(switch_valid), switch, Control),
     // Parametric instructions.
     (Some(drop_valid), drop, Parametric, 990),
     (Some(select_valid), select, Parametric),
@@ -736,6 +785,11 @@ impl CodeBuilderAllocations {
 
         let mut tags = BTreeMap::new();
         for (idx, tag_type) in module.tags() {
+            // Tags with results can only be used for stack switching, not
+            // exceptions.
+            if !tag_type.func_type.results.is_empty() {
+                continue;
+            }
             tags.entry(tag_type.func_type.params.to_vec())
                 .or_insert(Vec::new())
                 .push(idx);
@@ -1199,7 +1253,9 @@ impl CodeBuilder<'_> {
             ValType::Ref(r) => match r.heap_type {
                 HeapType::Concrete(idx) => match &module.ty(idx).composite_type.inner {
                     CompositeInnerType::Func(_) => Some(r),
-                    CompositeInnerType::Struct(_) | CompositeInnerType::Array(_) => None,
+                    CompositeInnerType::Struct(_)
+                    | CompositeInnerType::Array(_)
+                    | CompositeInnerType::Cont(_) => None,
                 },
                 _ => None,
             },
@@ -1237,6 +1293,15 @@ impl CodeBuilder<'_> {
                 }
             }
         }
+        // Blocks producing a continuation are the targets of `resume` handlers,
+        // so make sure they show up with some regularity.
+        if module.config.stack_switching_enabled && !module.cont_types.is_empty() {
+            options.push(Box::new(|u| {
+                Ok(BlockType::Result(nullable_concrete_ref(
+                    *u.choose(&module.cont_types)?,
+                )))
+            }));
+        }
         let f = u.choose(&options)?;
         f(u)
     }
@@ -2181,7 +2246,9 @@ fn return_call_ref_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     };
     let func_ty = match &module.ty(idx).composite_type.inner {
         CompositeInnerType::Func(f) => f,
-        CompositeInnerType::Array(_) | CompositeInnerType::Struct(_) => return false,
+        CompositeInnerType::Array(_)
+        | CompositeInnerType::Struct(_)
+        | CompositeInnerType::Cont(_) => return false,
     };
 
     let ty = builder.allocs.operands.pop().unwrap();
@@ -2627,6 +2694,372 @@ fn br_on_cast_fail(
     Ok(())
 }
 
+#[inline]
+fn stack_switching_valid(module: &Module) -> bool {
+    module.config.stack_switching_enabled
+        // TODO: stack-switching instructions trap on null or already-resumed
+        // continuations and on unhandled suspensions, so avoid them when
+        // traps are disallowed.
+        && !module.config.disallow_traps
+}
+
+fn nullable_concrete_ref(idx: u32) -> ValType {
+    ValType::Ref(RefType {
+        nullable: true,
+        heap_type: HeapType::Concrete(idx),
+    })
+}
+
+/// Are the given types, followed by a `(ref null $cont_ty)`, on top of the
+/// stack?
+fn types_and_cont_on_stack(
+    module: &Module,
+    builder: &CodeBuilder,
+    types: &[ValType],
+    cont_ty: u32,
+) -> bool {
+    builder.type_on_stack(module, nullable_concrete_ref(cont_ty))
+        && types
+            .iter()
+            .rev()
+            .enumerate()
+            .all(|(idx, ty)| builder.type_on_stack_at(module, idx + 1, *ty))
+}
+
+fn val_types_are_sub_types(module: &Module, a: &[ValType], b: &[ValType]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| module.val_type_is_sub_type(*a, *b))
+}
+
+/// Returns the continuation types which can be created with `cont.new` from
+/// the function reference on top of the stack.
+fn cont_new_candidates<'a>(
+    module: &'a Module,
+    builder: &'a CodeBuilder,
+) -> impl Iterator<Item = u32> + 'a {
+    module.cont_types.iter().copied().filter(move |&ct| {
+        let func_ty = match module.ty(ct).composite_type.inner {
+            CompositeInnerType::Cont(f) => f,
+            _ => unreachable!(),
+        };
+        builder.type_on_stack(module, nullable_concrete_ref(func_ty))
+    })
+}
+
+#[inline]
+fn cont_new_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    stack_switching_valid(module) && cont_new_candidates(module, builder).next().is_some()
+}
+
+fn cont_new(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let candidates = cont_new_candidates(module, builder).collect::<Vec<_>>();
+    let ct = *u.choose(&candidates)?;
+    builder.pop_operand();
+    builder.push_operand(Some(ValType::Ref(RefType {
+        nullable: false,
+        heap_type: HeapType::Concrete(ct),
+    })));
+    instructions.push(Instruction::ContNew(ct));
+    Ok(())
+}
+
+/// Returns the arguments that `cont.bind` binds when converting a
+/// continuation of type `from` to one of type `to`, if that's valid.
+fn cont_bind_args<'a>(
+    module: &'a Module,
+    from: &'a FuncType,
+    to: &FuncType,
+) -> Option<&'a [ValType]> {
+    let argcnt = from.params.len().checked_sub(to.params.len())?;
+    let (args, rest) = from.params.split_at(argcnt);
+    if val_types_are_sub_types(module, &to.params, rest)
+        && val_types_are_sub_types(module, &from.results, &to.results)
+    {
+        Some(args)
+    } else {
+        None
+    }
+}
+
+fn cont_bind_candidates<'a>(
+    module: &'a Module,
+    builder: &'a CodeBuilder,
+) -> impl Iterator<Item = (u32, u32, usize)> + 'a {
+    module.cont_types().flat_map(move |(from, from_ty)| {
+        module.cont_types().filter_map(move |(to, to_ty)| {
+            let args = cont_bind_args(module, from_ty, to_ty)?;
+            if types_and_cont_on_stack(module, builder, args, from) {
+                Some((from, to, args.len()))
+            } else {
+                None
+            }
+        })
+    })
+}
+
+#[inline]
+fn cont_bind_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    stack_switching_valid(module) && cont_bind_candidates(module, builder).next().is_some()
+}
+
+fn cont_bind(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let candidates = cont_bind_candidates(module, builder).collect::<Vec<_>>();
+    let (argument_index, result_index, num_args) = *u.choose(&candidates)?;
+    for _ in 0..num_args + 1 {
+        builder.pop_operand();
+    }
+    builder.push_operand(Some(ValType::Ref(RefType {
+        nullable: false,
+        heap_type: HeapType::Concrete(result_index),
+    })));
+    instructions.push(Instruction::ContBind {
+        argument_index,
+        result_index,
+    });
+    Ok(())
+}
+
+#[inline]
+fn suspend_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    stack_switching_valid(module)
+        && module
+            .tags()
+            .any(|(_, t)| builder.types_on_stack(module, &t.func_type.params))
+}
+
+fn suspend(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let candidates = module
+        .tags()
+        .filter(|(_, t)| builder.types_on_stack(module, &t.func_type.params))
+        .collect::<Vec<_>>();
+    let (tag_idx, tag_type) = *u.choose(&candidates)?;
+    builder.pop_operands(module, &tag_type.func_type.params);
+    builder.push_operands(&tag_type.func_type.results);
+    instructions.push(Instruction::Suspend(tag_idx));
+    Ok(())
+}
+
+/// Generates a handler table for resuming a continuation of type `cont_ty`.
+fn arbitrary_resume_table(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &CodeBuilder,
+    cont_ty: &FuncType,
+) -> Result<Vec<Handle>> {
+    let mut options = Vec::new();
+
+    // A suspension to `tag` can be handled by branching to a label whose types
+    // are the tag's parameters followed by a continuation which takes the
+    // tag's results and produces the same results as `cont_ty`.
+    for (i, ctrl) in builder.allocs.controls.iter().rev().enumerate() {
+        let label = u32::try_from(i).unwrap();
+        let (rest, new_cont) = match ctrl.label_types().split_last() {
+            Some((
+                ValType::Ref(RefType {
+                    heap_type: HeapType::Concrete(idx),
+                    ..
+                }),
+                rest,
+            )) if matches!(
+                module.ty(*idx).composite_type.inner,
+                CompositeInnerType::Cont(_)
+            ) =>
+            {
+                (rest, module.cont_func_type(*idx))
+            }
+            _ => continue,
+        };
+        for (tag, tag_ty) in module.tags() {
+            let tag_ty = &tag_ty.func_type;
+            if val_types_are_sub_types(module, &tag_ty.params, rest)
+                && val_types_are_sub_types(module, &new_cont.params, &tag_ty.results)
+                && val_types_are_sub_types(module, &cont_ty.results, &new_cont.results)
+            {
+                options.push(Handle::OnLabel { tag, label });
+            }
+        }
+    }
+
+    // Any tag without parameters can be used as a `switch` target.
+    for (tag, tag_ty) in module.tags() {
+        if tag_ty.func_type.params.is_empty() {
+            options.push(Handle::OnSwitch { tag });
+        }
+    }
+
+    let mut table = Vec::new();
+    if !options.is_empty() {
+        for _ in 0..u.int_in_range(0..=10)? {
+            table.push(u.choose(&options)?.clone());
+        }
+    }
+    Ok(table)
+}
+
+#[inline]
+fn resume_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    stack_switching_valid(module)
+        && module
+            .cont_types()
+            .any(|(ct, ty)| types_and_cont_on_stack(module, builder, &ty.params, ct))
+}
+
+fn resume(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let candidates = module
+        .cont_types()
+        .filter(|(ct, ty)| types_and_cont_on_stack(module, builder, &ty.params, *ct))
+        .collect::<Vec<_>>();
+    let (cont_type_index, ty) = *u.choose(&candidates)?;
+    let resume_table = arbitrary_resume_table(u, module, builder, ty)?;
+    builder.pop_operand();
+    builder.pop_operands(module, &ty.params);
+    builder.push_operands(&ty.results);
+    instructions.push(Instruction::Resume {
+        cont_type_index,
+        resume_table: resume_table.into(),
+    });
+    Ok(())
+}
+
+fn resume_throw_candidates<'a, 'b>(
+    module: &'a Module,
+    builder: &'b CodeBuilder,
+) -> impl Iterator<Item = (u32, &'a FuncType, u32, &'a FuncType)> + 'b
+where
+    'a: 'b,
+{
+    module.cont_types().flat_map(move |(ct, ty)| {
+        module.tags().filter_map(move |(tag, tag_ty)| {
+            let tag_ty = &*tag_ty.func_type;
+            if tag_ty.results.is_empty()
+                && types_and_cont_on_stack(module, builder, &tag_ty.params, ct)
+            {
+                Some((ct, ty, tag, tag_ty))
+            } else {
+                None
+            }
+        })
+    })
+}
+
+#[inline]
+fn resume_throw_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    stack_switching_valid(module) && resume_throw_candidates(module, builder).next().is_some()
+}
+
+fn resume_throw(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let candidates = resume_throw_candidates(module, builder).collect::<Vec<_>>();
+    let (cont_type_index, ty, tag_index, tag_ty) = *u.choose(&candidates)?;
+    let resume_table = arbitrary_resume_table(u, module, builder, ty)?;
+    builder.pop_operand();
+    builder.pop_operands(module, &tag_ty.params);
+    builder.push_operands(&ty.results);
+    instructions.push(Instruction::ResumeThrow {
+        cont_type_index,
+        tag_index,
+        resume_table: resume_table.into(),
+    });
+    Ok(())
+}
+
+/// Returns `(cont, tag)` pairs usable with `switch`, along with the arguments
+/// passed to the target continuation and the values received when switched
+/// back to.
+fn switch_candidates<'a, 'b>(
+    module: &'a Module,
+    builder: &'b CodeBuilder,
+) -> impl Iterator<Item = (u32, u32, &'a [ValType], &'a [ValType])> + 'b
+where
+    'a: 'b,
+{
+    module.cont_types().flat_map(move |(ct, ty)| {
+        // The last parameter of the target continuation is the continuation
+        // that the current one is suspended into.
+        let other = match ty.params.split_last() {
+            Some((
+                ValType::Ref(RefType {
+                    heap_type: HeapType::Concrete(idx),
+                    ..
+                }),
+                args,
+            )) if matches!(
+                module.ty(*idx).composite_type.inner,
+                CompositeInnerType::Cont(_)
+            ) =>
+            {
+                Some((args, module.cont_func_type(*idx)))
+            }
+            _ => None,
+        };
+        other
+            .filter(|(args, _)| types_and_cont_on_stack(module, builder, args, ct))
+            .into_iter()
+            .flat_map(move |(args, other)| {
+                module.tags().filter_map(move |(tag, tag_ty)| {
+                    let tag_ty = &tag_ty.func_type;
+                    if tag_ty.params.is_empty()
+                        && val_types_are_sub_types(module, &ty.results, &tag_ty.results)
+                        && val_types_are_sub_types(module, &tag_ty.results, &other.results)
+                    {
+                        Some((ct, tag, args, &other.params[..]))
+                    } else {
+                        None
+                    }
+                })
+            })
+    })
+}
+
+#[inline]
+fn switch_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    stack_switching_valid(module) && switch_candidates(module, builder).next().is_some()
+}
+
+fn switch(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let candidates = switch_candidates(module, builder).collect::<Vec<_>>();
+    let (cont_type_index, tag_index, args, results) = *u.choose(&candidates)?;
+    builder.pop_operand();
+    builder.pop_operands(module, args);
+    builder.push_operands(results);
+    instructions.push(Instruction::Switch {
+        cont_type_index,
+        tag_index,
+    });
+    Ok(())
+}
+
 #[inline]
 fn drop_valid(_module: &Module, builder: &mut CodeBuilder) -> bool {
     !builder.operands().is_empty()
@@ -3319,8 +3752,9 @@ fn memory_size(
 
 #[inline]
 fn memory_grow_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
-    (builder.allocs.memory32.len() > 0 && builder.type_on_stack(module, ValType::I32))
-        || (builder.allocs.memory64.len() > 0 && builder.type_on_stack(module, ValType::I64))
+    !module.config.differential_mode
+        && ((builder.allocs.memory32.len() > 0 && builder.type_on_stack(module, ValType::I32))
+            || (builder.allocs.memory64.len() > 0 && builder.type_on_stack(module, ValType::I64)))
 }
 
 fn memory_grow(
@@ -5709,7 +6143,9 @@ fn table_size(
 
 #[inline]
 fn table_grow_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
-    module.config.reference_types_enabled && table_grow_candidates(module, builder).next().is_some()
+    !module.config.differential_mode
+        && module.config.reference_types_enabled
+        && table_grow_candidates(module, builder).next().is_some()
 }
 
 fn table_grow_candidates<'a>(
@@ -6038,6 +6474,7 @@ fn struct_set(
 #[inline]
 fn array_new_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.gc_enabled
+        && !module.config.differential_mode
         && builder.type_on_stack(module, ValType::I32)
         && module
             .array_types
@@ -6138,6 +6575,7 @@ fn array_new_fixed(
 #[inline]
 fn array_new_default_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.gc_enabled
+        && !module.config.differential_mode
         && builder.type_on_stack(module, ValType::I32)
         && module
             .array_types
@@ -0,0 +1,139 @@
+use super::*;
+use wasm_encoder::MemArg;
+
+/// The name of the function exported by modules generated with
+/// [`Config::differential_mode`] which hashes the module's state.
+pub const DIFFERENTIAL_HASH_EXPORT: &str = "wasm-smith-state-hash";
+
+// Parameters of the 64-bit FNV-1a hash.
+const FNV_OFFSET_BASIS: i64 = 0xcbf2_9ce4_8422_2325_u64 as i64;
+const FNV_PRIME: i64 = 0x0000_0100_0000_01b3;
+
+impl Module {
+    /// Finish a module generated with [`Config::differential_mode`].
+    ///
+    /// This adds fuel to every function so that execution always terminates,
+    /// and then adds and exports the function which hashes all state.
+    pub(super) fn finish_differential(&mut self) {
+        self.ensure_termination(self.config.differential_fuel)
+            .expect("invalid functions are disabled in differential mode");
+
+        let func_type = Rc::new(FuncType {
+            params: vec![],
+            results: vec![ValType::I64],
+        });
+        let subtype = SubType {
+            is_final: true,
+            supertype: None,
+            depth: 1,
+            composite_type: CompositeType::new_func(Rc::clone(&func_type), false),
+        };
+        let func_index = self.add_func_of_type(subtype, func_type);
+        self.should_encode_types = true;
+        self.code.push(Code {
+            // The hash, the current address, and the end address.
+            locals: vec![ValType::I64, ValType::I64, ValType::I64],
+            instructions: Instructions::Generated(self.state_hash_body()),
+        });
+        self.exports.push((
+            DIFFERENTIAL_HASH_EXPORT.to_string(),
+            ExportKind::Func,
+            func_index,
+        ));
+    }
+
+    /// Generate a function body which folds the value of every global and
+    /// every byte of every memory into a hash.
+    ///
+    /// Note that the hash function itself intentionally doesn't consume fuel,
+    /// it's bounded by the size of memory.
+    fn state_hash_body(&self) -> Vec<Instruction> {
+        const HASH: u32 = 0;
+        const ADDR: u32 = 1;
+        const END: u32 = 2;
+
+        // hash = (hash ^ x) * prime, where `x` is on the stack.
+        let mix = |insts: &mut Vec<Instruction>| {
+            insts.push(Instruction::LocalGet(HASH));
+            insts.push(Instruction::I64Xor);
+            insts.push(Instruction::I64Const(FNV_PRIME));
+            insts.push(Instruction::I64Mul);
+            insts.push(Instruction::LocalSet(HASH));
+        };
+
+        let mut insts = vec![
+            Instruction::I64Const(FNV_OFFSET_BASIS),
+            Instruction::LocalSet(HASH),
+        ];
+
+        for (i, global) in self.globals.iter().enumerate() {
+            let i = i as u32;
+            insts.push(Instruction::GlobalGet(i));
+            match global.val_type {
+                ValType::I32 => insts.push(Instruction::I64ExtendI32U),
+                ValType::I64 => {}
+                ValType::F32 => {
+                    insts.push(Instruction::I32ReinterpretF32);
+                    insts.push(Instruction::I64ExtendI32U);
+                }
+                ValType::F64 => insts.push(Instruction::I64ReinterpretF64),
+                ValType::V128 => {
+                    insts.push(Instruction::I64x2ExtractLane(0));
+                    mix(&mut insts);
+                    insts.push(Instruction::GlobalGet(i));
+                    insts.push(Instruction::I64x2ExtractLane(1));
+                }
+                // The identity of references isn't observable across engines,
+                // only whether they're null.
+                ValType::Ref(_) => {
+                    insts.push(Instruction::RefIsNull);
+                    insts.push(Instruction::I64ExtendI32U);
+                }
+            }
+            mix(&mut insts);
+        }
+
+        for (i, memory) in self.memories.iter().enumerate() {
+            let i = i as u32;
+
+            // end = memory.size * page_size
+            insts.push(Instruction::MemorySize(i));
+            if !memory.memory64 {
+                insts.push(Instruction::I64ExtendI32U);
+            }
+            insts.push(Instruction::I64Const(crate::page_size(memory).into()));
+            insts.push(Instruction::I64Mul);
+            insts.push(Instruction::LocalSet(END));
+
+            // for addr in 0..end { mix(load8_u(addr)) }
+            insts.push(Instruction::I64Const(0));
+            insts.push(Instruction::LocalSet(ADDR));
+            insts.push(Instruction::Block(BlockType::Empty));
+            insts.push(Instruction::Loop(BlockType::Empty));
+            insts.push(Instruction::LocalGet(ADDR));
+            insts.push(Instruction::LocalGet(END));
+            insts.push(Instruction::I64GeU);
+            insts.push(Instruction::BrIf(1));
+            insts.push(Instruction::LocalGet(ADDR));
+            if !memory.memory64 {
+                insts.push(Instruction::I32WrapI64);
+            }
+            insts.push(Instruction::I64Load8U(MemArg {
+                offset: 0,
+                align: 0,
+                memory_index: i,
+            }));
+            mix(&mut insts);
+            insts.push(Instruction::LocalGet(ADDR));
+            insts.push(Instruction::I64Const(1));
+            insts.push(Instruction::I64Add);
+            insts.push(Instruction::LocalSet(ADDR));
+            insts.push(Instruction::Br(0));
+            insts.push(Instruction::End);
+            insts.push(Instruction::End);
+        }
+
+        insts.push(Instruction::LocalGet(HASH));
+        insts
+    }
+}
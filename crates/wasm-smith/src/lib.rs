@@ -59,14 +59,22 @@
 mod component;
 mod config;
 mod core;
+#[cfg(feature = "wasmparser")]
+mod profile;
+#[cfg(feature = "wit")]
+mod wit;
 
-pub use crate::core::{InstructionKind, InstructionKinds, Module};
+pub use crate::core::{DIFFERENTIAL_HASH_EXPORT, InstructionKind, InstructionKinds, Module};
 use arbitrary::{Result, Unstructured};
 #[cfg(feature = "component-model")]
 pub use component::Component;
-pub use config::{Config, MemoryOffsetChoices};
+pub use config::{Config, InstructionWeights, MemoryOffsetChoices};
+#[cfg(feature = "wasmparser")]
+pub use profile::Profile;
 use std::{collections::HashSet, fmt::Write, str};
 use wasm_encoder::MemoryType;
+#[cfg(feature = "wit")]
+pub use wit::WitComponent;
 
 #[doc(hidden)]
 pub use config::InternalOptionalConfig;
@@ -0,0 +1,517 @@
+//! Derivation of a [`Config`] from a corpus of existing modules.
+
+use crate::core::DEFAULT_WEIGHTS;
+use crate::{Config, InstructionWeights};
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, BTreeSet};
+use wasm_encoder::{EntityType, ImportSection, Module as EncodedModule, TypeSection};
+use wasmparser::{
+    CompositeInnerType, FuncType, GlobalType, MemoryType, Operator, Parser, Payload, TableType,
+    TypeRef, ValType, Validator, WasmFeatures,
+};
+
+/// The maximum number of imports made available to modules generated with a
+/// profile's configuration.
+const MAX_AVAILABLE_IMPORTS: usize = 1000;
+
+/// Statistics about a corpus of modules, used to derive a [`Config`] which
+/// generates modules that statistically resemble the corpus.
+///
+/// Modules are added to a profile with [`Profile::add_module`], and once the
+/// whole corpus has been added [`Profile::config`] produces the configuration.
+/// The configuration:
+///
+/// * Only enables the proposals which are used by the corpus.
+/// * Limits the number of each kind of entity, such as functions, globals, or
+///   data segments, to the largest number found in a single module.
+/// * Limits the size of functions to the 90th percentile of function sizes
+///   in the corpus, and their nesting depth to the deepest found.
+/// * Weighs instructions by how frequently they appear in the corpus through
+///   [`Config::instruction_weights`].
+/// * Makes the most common imports of the corpus available to generated
+///   modules through [`Config::available_imports`].
+///
+/// # Example
+///
+/// ```
+/// use wasm_smith::Profile;
+///
+/// let wasm = wat::parse_str(r#"
+///     (module
+///         (import "env" "log" (func (param i32)))
+///         (func (param i32) (result i32)
+///             local.get 0
+///             i32.const 1
+///             i32.add)
+///     )
+/// "#)?;
+///
+/// let mut profile = Profile::new();
+/// profile.add_module(&wasm)?;
+/// let config = profile.config();
+/// assert!(!config.simd_enabled);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Profile {
+    /// Proposals, and other optional features, used by any module.
+    features: BTreeSet<&'static str>,
+    /// How many times each operator appears, keyed by its name in
+    /// `wasmparser::VisitOperator` without the `visit_` prefix.
+    operators: BTreeMap<&'static str, u64>,
+    /// The number of operators in each function body.
+    func_sizes: Vec<usize>,
+    /// The deepest nesting of control instructions in any function body.
+    max_nesting_depth: usize,
+    /// The maximum number of each kind of entity in any module.
+    max_entities: Entities,
+    /// Every import along with the number of modules which import it.
+    imports: BTreeMap<(String, String), (ImportShape, usize)>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Entities {
+    types: usize,
+    imports: usize,
+    funcs: usize,
+    tables: usize,
+    memories: usize,
+    globals: usize,
+    tags: usize,
+    exports: usize,
+    elem_segments: usize,
+    elements: usize,
+    data_segments: usize,
+}
+
+impl Entities {
+    fn max(&mut self, other: &Entities) {
+        self.types = self.types.max(other.types);
+        self.imports = self.imports.max(other.imports);
+        self.funcs = self.funcs.max(other.funcs);
+        self.tables = self.tables.max(other.tables);
+        self.memories = self.memories.max(other.memories);
+        self.globals = self.globals.max(other.globals);
+        self.tags = self.tags.max(other.tags);
+        self.exports = self.exports.max(other.exports);
+        self.elem_segments = self.elem_segments.max(other.elem_segments);
+        self.elements = self.elements.max(other.elements);
+        self.data_segments = self.data_segments.max(other.data_segments);
+    }
+}
+
+#[derive(Debug)]
+enum ImportShape {
+    Func(FuncType),
+    Table(TableType),
+    Memory(MemoryType),
+    Global(GlobalType),
+}
+
+impl Profile {
+    /// Create a new, empty profile.
+    pub fn new() -> Profile {
+        Profile::default()
+    }
+
+    /// Add the core wasm module `wasm` to this profile.
+    ///
+    /// Returns an error if `wasm` isn't a valid core wasm module.
+    pub fn add_module(&mut self, wasm: &[u8]) -> Result<()> {
+        if wasmparser::Parser::is_component(wasm) {
+            bail!("components are not supported, only core modules");
+        }
+        Validator::new_with_features(WasmFeatures::all()).validate_all(wasm)?;
+
+        let mut entities = Entities::default();
+        // Function types by type index, or `None` for other kinds of types.
+        let mut func_types = Vec::new();
+        let mut imports = BTreeMap::new();
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::TypeSection(reader) => {
+                    for rec_group in reader {
+                        let rec_group = rec_group?;
+                        if rec_group.is_explicit_rec_group() {
+                            self.features.insert("gc");
+                        }
+                        for ty in rec_group.types() {
+                            entities.types += 1;
+                            if !ty.is_final || ty.supertype_idx.is_some() {
+                                self.features.insert("gc");
+                            }
+                            if ty.composite_type.shared {
+                                self.features.insert("shared_everything_threads");
+                            }
+                            match &ty.composite_type.inner {
+                                CompositeInnerType::Func(f) => {
+                                    for ty in f.params().iter().chain(f.results()) {
+                                        self.add_val_type(*ty);
+                                    }
+                                    if f.results().len() > 1 {
+                                        self.features.insert("multi_value");
+                                    }
+                                    func_types.push(Some(f.clone()));
+                                }
+                                CompositeInnerType::Cont(_) => {
+                                    self.features.insert("stack_switching");
+                                    func_types.push(None);
+                                }
+                                _ => {
+                                    self.features.insert("gc");
+                                    func_types.push(None);
+                                }
+                            }
+                        }
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import?;
+                        entities.imports += 1;
+                        let shape = match import.ty {
+                            TypeRef::Func(idx) => {
+                                entities.funcs += 1;
+                                func_types[idx as usize].clone().map(ImportShape::Func)
+                            }
+                            TypeRef::Table(ty) => {
+                                entities.tables += 1;
+                                self.add_table_type(&ty);
+                                Some(ImportShape::Table(ty))
+                            }
+                            TypeRef::Memory(ty) => {
+                                entities.memories += 1;
+                                self.add_memory_type(&ty);
+                                Some(ImportShape::Memory(ty))
+                            }
+                            TypeRef::Global(ty) => {
+                                entities.globals += 1;
+                                self.add_val_type(ty.content_type);
+                                Some(ImportShape::Global(ty))
+                            }
+                            // Tags refer to types by index which can't be
+                            // reused across modules.
+                            TypeRef::Tag(_) => {
+                                entities.tags += 1;
+                                self.features.insert("exceptions");
+                                None
+                            }
+                        };
+                        // Imports which refer to concrete types can't be
+                        // moved to another module either.
+                        if let Some(shape) = shape.filter(|s| !s.has_concrete_type()) {
+                            imports.insert(
+                                (import.module.to_string(), import.name.to_string()),
+                                shape,
+                            );
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => entities.funcs += reader.count() as usize,
+                Payload::TableSection(reader) => {
+                    for table in reader {
+                        entities.tables += 1;
+                        self.add_table_type(&table?.ty);
+                    }
+                }
+                Payload::MemorySection(reader) => {
+                    for memory in reader {
+                        entities.memories += 1;
+                        self.add_memory_type(&memory?);
+                    }
+                }
+                Payload::TagSection(reader) => {
+                    entities.tags += reader.count() as usize;
+                    self.features.insert("exceptions");
+                }
+                Payload::GlobalSection(reader) => {
+                    for global in reader {
+                        let global = global?;
+                        entities.globals += 1;
+                        self.add_val_type(global.ty.content_type);
+                        self.add_const_expr(&global.init_expr)?;
+                    }
+                }
+                Payload::ExportSection(reader) => entities.exports += reader.count() as usize,
+                Payload::ElementSection(reader) => {
+                    for elem in reader {
+                        let elem = elem?;
+                        entities.elem_segments += 1;
+                        entities.elements += match &elem.items {
+                            wasmparser::ElementItems::Functions(r) => r.count(),
+                            wasmparser::ElementItems::Expressions(_, r) => r.count(),
+                        } as usize;
+                        if let wasmparser::ElementKind::Active { offset_expr, .. } = &elem.kind {
+                            self.add_const_expr(offset_expr)?;
+                        }
+                    }
+                }
+                Payload::DataSection(reader) => {
+                    for data in reader {
+                        let data = data?;
+                        entities.data_segments += 1;
+                        match &data.kind {
+                            wasmparser::DataKind::Active { offset_expr, .. } => {
+                                self.add_const_expr(offset_expr)?;
+                            }
+                            wasmparser::DataKind::Passive => {
+                                self.features.insert("bulk_memory");
+                            }
+                        }
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    for local in body.get_locals_reader()? {
+                        let (_, ty) = local?;
+                        self.add_val_type(ty);
+                    }
+                    let mut size = 0;
+                    let mut depth = 0;
+                    let mut reader = body.get_operators_reader()?;
+                    while !reader.eof() {
+                        let op = reader.read()?;
+                        size += 1;
+                        match op {
+                            Operator::Block { .. }
+                            | Operator::Loop { .. }
+                            | Operator::If { .. }
+                            | Operator::Try { .. }
+                            | Operator::TryTable { .. } => {
+                                depth += 1;
+                                self.max_nesting_depth = self.max_nesting_depth.max(depth);
+                            }
+                            Operator::End => depth = depth.saturating_sub(1),
+                            _ => {}
+                        }
+                        self.add_operator(&op);
+                    }
+                    self.func_sizes.push(size);
+                }
+                _ => {}
+            }
+        }
+
+        if entities.memories > 1 {
+            self.features.insert("multi_memory");
+        }
+        if entities.tables > 1 {
+            self.features.insert("reference_types");
+        }
+        self.max_entities.max(&entities);
+        for (key, shape) in imports {
+            self.imports.entry(key).or_insert((shape, 0)).1 += 1;
+        }
+        Ok(())
+    }
+
+    fn add_val_type(&mut self, ty: ValType) {
+        match ty {
+            ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64 => {}
+            ValType::V128 => {
+                self.features.insert("simd");
+            }
+            ValType::Ref(r) => {
+                self.features.insert("reference_types");
+                if !r.is_func_ref() && !r.is_extern_ref() {
+                    self.features.insert("gc");
+                }
+            }
+        }
+    }
+
+    fn add_table_type(&mut self, ty: &TableType) {
+        self.add_val_type(ValType::Ref(ty.element_type));
+        if ty.table64 {
+            self.features.insert("memory64");
+        }
+        if ty.shared {
+            self.features.insert("shared_everything_threads");
+        }
+    }
+
+    fn add_memory_type(&mut self, ty: &MemoryType) {
+        if ty.memory64 {
+            self.features.insert("memory64");
+        }
+        if ty.shared {
+            self.features.insert("threads");
+        }
+        if ty.page_size_log2.is_some() {
+            self.features.insert("custom_page_sizes");
+        }
+    }
+
+    fn add_const_expr(&mut self, expr: &wasmparser::ConstExpr<'_>) -> Result<()> {
+        let mut reader = expr.get_operators_reader();
+        let mut count = 0;
+        while !reader.eof() {
+            let op = reader.read()?;
+            count += 1;
+            self.features.insert(operator_info(&op).0);
+        }
+        // Anything more than a single instruction followed by `end` is an
+        // extended constant expression.
+        if count > 2 {
+            self.features.insert("extended_const");
+        }
+        Ok(())
+    }
+
+    fn add_operator(&mut self, op: &Operator<'_>) {
+        let (proposal, name) = operator_info(op);
+        self.features.insert(proposal);
+        *self.operators.entry(name).or_insert(0) += 1;
+        if let Operator::Block { blockty } | Operator::Loop { blockty } | Operator::If { blockty } =
+            op
+        {
+            if matches!(blockty, wasmparser::BlockType::FuncType(_)) {
+                self.features.insert("multi_value");
+            }
+        }
+    }
+
+    /// Derive a configuration which generates modules that resemble the
+    /// modules added to this profile.
+    pub fn config(&self) -> Config {
+        let mut config = Config::default();
+        let has = |feature: &str| self.features.contains(feature);
+
+        config.sign_extension_ops_enabled = has("sign_extension");
+        config.saturating_float_to_int_enabled = has("saturating_float_to_int");
+        config.bulk_memory_enabled = has("bulk_memory");
+        config.multi_value_enabled = has("multi_value");
+        config.extended_const_enabled = has("extended_const");
+        config.tail_call_enabled = has("tail_call");
+        config.exceptions_enabled = has("exceptions") || has("legacy_exceptions");
+        config.threads_enabled = has("threads");
+        config.wide_arithmetic_enabled = has("wide_arithmetic");
+        config.memory64_enabled = has("memory64");
+        config.custom_page_sizes_enabled = has("custom_page_sizes");
+        config.stack_switching_enabled = has("stack_switching");
+        config.shared_everything_threads_enabled = has("shared_everything_threads");
+        config.gc_enabled = has("gc") || has("function_references") || has("stack_switching");
+        config.reference_types_enabled = config.gc_enabled || has("reference_types");
+        config.relaxed_simd_enabled = has("relaxed_simd");
+        config.simd_enabled = config.relaxed_simd_enabled || has("simd");
+
+        let max = &self.max_entities;
+        config.max_types = max.types;
+        config.max_imports = max.imports;
+        config.max_funcs = max.funcs;
+        config.max_tables = max.tables;
+        config.max_memories = max.memories;
+        config.max_globals = max.globals;
+        config.max_tags = max.tags;
+        config.max_exports = max.exports;
+        config.max_element_segments = max.elem_segments;
+        config.max_elements = max.elements;
+        config.max_data_segments = max.data_segments;
+        config.max_nesting_depth = self.max_nesting_depth;
+
+        let mut sizes = self.func_sizes.clone();
+        sizes.sort_unstable();
+        if let Some(size) = sizes.get(sizes.len() * 9 / 10) {
+            config.max_instructions = (*size).max(1);
+        }
+
+        config.instruction_weights = self.instruction_weights();
+        config.available_imports = self.available_imports();
+        config
+    }
+
+    /// Weigh each instruction that `wasm-smith` can generate by how often it
+    /// appears in the corpus relative to how often it's generated by default.
+    fn instruction_weights(&self) -> InstructionWeights {
+        let mut weights = InstructionWeights::default();
+        let total = self.operators.values().sum::<u64>();
+        if total == 0 {
+            return weights;
+        }
+        let counts = self
+            .operators
+            .iter()
+            .map(|(name, count)| (InstructionWeights::normalize(name), *count))
+            .collect::<BTreeMap<_, _>>();
+
+        // An instruction which makes up `1 / DEFAULT_WEIGHTS.len()` of the
+        // corpus keeps its default weight.
+        let scale = DEFAULT_WEIGHTS.len() as u64 * 100;
+        for (name, _) in DEFAULT_WEIGHTS.iter() {
+            let count = counts
+                .get(&InstructionWeights::normalize(name))
+                .copied()
+                .unwrap_or(0);
+            let weight = if count == 0 {
+                0
+            } else {
+                (count * scale / total).clamp(1, 10_000) as u32
+            };
+            weights.set(name, weight);
+        }
+        weights
+    }
+
+    /// Encode the most common imports of the corpus into a module suitable
+    /// for [`Config::available_imports`].
+    fn available_imports(&self) -> Option<Vec<u8>> {
+        if self.imports.is_empty() {
+            return None;
+        }
+        let mut imports = self.imports.iter().collect::<Vec<_>>();
+        imports.sort_by_key(|(_, (_, count))| std::cmp::Reverse(*count));
+        imports.truncate(MAX_AVAILABLE_IMPORTS);
+
+        let mut types = TypeSection::new();
+        let mut section = ImportSection::new();
+        for ((module, name), (shape, _)) in imports {
+            let ty = match shape {
+                ImportShape::Func(ty) => {
+                    types.ty().func_type(&ty.clone().try_into().unwrap());
+                    EntityType::Function(types.len() - 1)
+                }
+                ImportShape::Table(ty) => EntityType::Table((*ty).try_into().unwrap()),
+                ImportShape::Memory(ty) => EntityType::Memory((*ty).into()),
+                ImportShape::Global(ty) => EntityType::Global((*ty).try_into().unwrap()),
+            };
+            section.import(module, name, ty);
+        }
+
+        let mut module = EncodedModule::new();
+        module.section(&types);
+        module.section(&section);
+        Some(module.finish())
+    }
+}
+
+impl ImportShape {
+    fn has_concrete_type(&self) -> bool {
+        match self {
+            ImportShape::Func(ty) => ty
+                .params()
+                .iter()
+                .chain(ty.results())
+                .any(|ty| matches!(ty, ValType::Ref(r) if r.is_concrete_type_ref())),
+            ImportShape::Table(ty) => ty.element_type.is_concrete_type_ref(),
+            ImportShape::Memory(_) => false,
+            ImportShape::Global(ty) => {
+                matches!(ty.content_type, ValType::Ref(r) if r.is_concrete_type_ref())
+            }
+        }
+    }
+}
+
+/// Returns the proposal which `op` originates from and its name.
+fn operator_info(op: &Operator<'_>) -> (&'static str, &'static str) {
+    macro_rules! define_operator_info {
+        ($( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident ($($ann:tt)*))*) => {
+            match op {
+                $(
+                    Operator::$op { .. } => (stringify!($proposal), &stringify!($visit)["visit_".len()..]),
+                )*
+                _ => ("unknown", "unknown"),
+            }
+        };
+    }
+    wasmparser::for_each_operator!(define_operator_info)
+}
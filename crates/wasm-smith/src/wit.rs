@@ -0,0 +1,654 @@
+//! Generation of components which target a WIT world.
+
+mod abi;
+
+use self::abi::{Body, Context, val_type};
+use crate::{Config, Module};
+use arbitrary::{Result, Unstructured};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use wasm_encoder::reencode::{self, Reencode, RoundtripReencoder};
+use wasm_encoder::{
+    CodeSection, ConstExpr, DataCountSection, DataSection, ElementSection, Elements, EntityType,
+    ExportKind, ExportSection, Function, FunctionSection, GlobalSection, GlobalType, ImportSection,
+    MemorySection, MemoryType, TableSection, TagSection, TypeSection, ValType,
+};
+use wit_component::{ComponentEncoder, StringEncoding};
+use wit_parser::abi::AbiVariant;
+use wit_parser::{
+    Function as WitFunction, FunctionKind, LiftLowerAbi, ManglingAndAbi, Resolve,
+    ResourceIntrinsic, SizeAlign, TypeDefKind, TypeId, WasmExport, WasmExportKind, WasmImport,
+    WorldId, WorldItem, WorldKey,
+};
+
+/// The first address handed out by the generated `cabi_realloc`.
+const HEAP_START: i32 = 8;
+
+/// A pseudo-random component whose imports and exports are those of a WIT
+/// world.
+///
+/// The component's imports and exports are exactly those of the world it was
+/// generated for. Its implementation is a single core module, which is turned
+/// into a component with `wit-component`, and which implements the canonical
+/// ABI for the world:
+///
+/// * Each export lifts its arguments, reading strings and lists out of linear
+///   memory and dropping borrowed handles, and then calls a function generated
+///   by [`Module`] with the export's core parameters.
+/// * With synchronous name manglings, each export then calls some of the
+///   world's imported functions, lowering arbitrary but valid arguments and
+///   lifting their results. Imports whose arguments can't be created, such as
+///   those taking borrowed handles, futures, or streams, are never called.
+/// * Each export then lowers an arbitrary result, writing it through a return
+///   pointer or returning it directly. With asynchronous name manglings the
+///   result is passed to `task.return` instead.
+/// * `cabi_realloc` is a bump allocator which never frees memory.
+///
+/// Functions generated by [`Module`] are arbitrary valid code, so running the
+/// component may still trap. Exports whose arguments or results can't be
+/// handled, such as a result containing a borrowed handle, trap with
+/// `unreachable`.
+#[derive(Debug)]
+pub struct WitComponent {
+    module: Module,
+    bytes: Vec<u8>,
+}
+
+impl WitComponent {
+    /// Generate a new component which targets `world` within `resolve`.
+    ///
+    /// The `config` is used to generate the core module. Its
+    /// `available_imports`, `exports`, and `module_shape` options are
+    /// replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `wit-component` fails to encode the generated module, which
+    /// indicates a bug in either this crate or in `wit-component`.
+    pub fn new(
+        config: Config,
+        u: &mut Unstructured,
+        resolve: &Resolve,
+        world: WorldId,
+    ) -> Result<Self> {
+        let mangling = match u.int_in_range(0..=3)? {
+            0 => ManglingAndAbi::Standard32,
+            1 => ManglingAndAbi::Legacy(LiftLowerAbi::Sync),
+            2 => ManglingAndAbi::Legacy(LiftLowerAbi::AsyncCallback),
+            3 => ManglingAndAbi::Legacy(LiftLowerAbi::AsyncStackful),
+            _ => unreachable!(),
+        };
+        let encoding = match u.int_in_range(0..=2)? {
+            0 => StringEncoding::UTF8,
+            1 => StringEncoding::UTF16,
+            2 => StringEncoding::CompactUTF16,
+            _ => unreachable!(),
+        };
+        Self::new_with_abi(config, u, resolve, world, mangling, encoding)
+    }
+
+    /// Same as [`WitComponent::new`], but with an explicit name mangling and
+    /// ABI for the core module and string encoding for the component.
+    pub fn new_with_abi(
+        mut config: Config,
+        u: &mut Unstructured,
+        resolve: &Resolve,
+        world: WorldId,
+        mangling: ManglingAndAbi,
+        encoding: StringEncoding,
+    ) -> Result<Self> {
+        let exports = exported_funcs(resolve, world);
+
+        // The generated module implements each export, but doesn't import
+        // anything: calls to imports are generated here instead, as arbitrary
+        // arguments would violate the canonical ABI.
+        let mut shape = wasm_encoder::Module::new();
+        let mut types = TypeSection::new();
+        let mut funcs = FunctionSection::new();
+        let mut shape_exports = ExportSection::new();
+        let mut code = CodeSection::new();
+        for (i, (_, func)) in exports.iter().enumerate() {
+            let sig = resolve.wasm_signature(mangling.export_variant(), func);
+            let i = u32::try_from(i).unwrap();
+            types
+                .ty()
+                .function(sig.params.iter().map(|t| val_type(*t)), []);
+            funcs.function(i);
+            shape_exports.export(&inner_name(i), ExportKind::Func, i);
+            let mut body = Function::new([]);
+            body.instructions().unreachable().end();
+            code.function(&body);
+        }
+        shape
+            .section(&types)
+            .section(&funcs)
+            .section(&shape_exports)
+            .section(&code);
+        config.available_imports = None;
+        config.exports = None;
+        config.module_shape = Some(shape.finish());
+        // `wit-component` doesn't support a start function in the core module.
+        config.allow_start_export = false;
+        let module = Module::new(config, u)?;
+
+        let mut core = Assembler::new(resolve, world, mangling, encoding, &exports)
+            .assemble(u, &module.to_bytes())?;
+        wit_component::embed_component_metadata(&mut core, resolve, world, encoding)
+            .expect("failed to embed component metadata");
+        let bytes = ComponentEncoder::default()
+            .module(&core)
+            .and_then(|mut encoder| encoder.encode())
+            .unwrap_or_else(|e| panic!("failed to encode generated module: {e:?}"));
+        Ok(WitComponent { module, bytes })
+    }
+
+    /// The module generated by [`Module`] whose functions are called by the
+    /// component's exports.
+    pub fn core_module(&self) -> &Module {
+        &self.module
+    }
+
+    /// Encode this component into bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+}
+
+fn inner_name(i: u32) -> String {
+    format!("inner{i}")
+}
+
+/// All functions exported by `world`, in the order that `dummy_module`
+/// exports them.
+fn exported_funcs(resolve: &Resolve, world: WorldId) -> Vec<(Option<&WorldKey>, &WitFunction)> {
+    let mut funcs = Vec::new();
+    for (key, item) in resolve.worlds[world].exports.iter() {
+        match item {
+            WorldItem::Function(func) => funcs.push((None, func)),
+            WorldItem::Interface { id, .. } => {
+                for func in resolve.interfaces[*id].functions.values() {
+                    funcs.push((Some(key), func));
+                }
+            }
+            WorldItem::Type(_) => {}
+        }
+    }
+    funcs
+}
+
+/// Shifts the function indices of the module generated by [`Module`] past the
+/// imports of the final module.
+struct ShiftFunctions(u32);
+
+impl Reencode for ShiftFunctions {
+    type Error = Infallible;
+
+    fn function_index(&mut self, func: u32) -> Result<u32, reencode::Error<Infallible>> {
+        Ok(func + self.0)
+    }
+}
+
+/// A core function type.
+type Signature = (Vec<ValType>, Vec<ValType>);
+
+/// Builds the core module which implements a world, out of the imports and
+/// exports of `dummy_module` and the module generated by [`Module`].
+struct Assembler<'a> {
+    resolve: &'a Resolve,
+    world: WorldId,
+    mangling: ManglingAndAbi,
+    encoding: StringEncoding,
+    exports: &'a [(Option<&'a WorldKey>, &'a WitFunction)],
+    /// The imports of `dummy_module`, which are exactly the imports needed.
+    imports: Vec<(String, String, Signature)>,
+    /// The function exports of `dummy_module`.
+    func_exports: Vec<(String, Signature)>,
+    /// The name of the memory export.
+    memory: String,
+}
+
+impl<'a> Assembler<'a> {
+    fn new(
+        resolve: &'a Resolve,
+        world: WorldId,
+        mangling: ManglingAndAbi,
+        encoding: StringEncoding,
+        exports: &'a [(Option<&'a WorldKey>, &'a WitFunction)],
+    ) -> Self {
+        let dummy = wit_component::dummy_module(resolve, world, mangling);
+        let mut types = Vec::new();
+        let mut funcs = Vec::new();
+        let mut imports = Vec::new();
+        let mut func_exports = Vec::new();
+        let mut memory = None;
+        for payload in wasmparser::Parser::new(0).parse_all(&dummy) {
+            match payload.unwrap() {
+                wasmparser::Payload::TypeSection(s) => {
+                    for ty in s.into_iter_err_on_gc_types() {
+                        let ty = ty.unwrap();
+                        let vals = |tys: &[wasmparser::ValType]| {
+                            tys.iter()
+                                .map(|t| RoundtripReencoder.val_type(*t).unwrap())
+                                .collect::<Vec<_>>()
+                        };
+                        types.push((vals(ty.params()), vals(ty.results())));
+                    }
+                }
+                wasmparser::Payload::ImportSection(s) => {
+                    for import in s {
+                        let import = import.unwrap();
+                        let wasmparser::TypeRef::Func(ty) = import.ty else {
+                            unreachable!()
+                        };
+                        imports.push((
+                            import.module.to_string(),
+                            import.name.to_string(),
+                            types[ty as usize].clone(),
+                        ));
+                    }
+                }
+                wasmparser::Payload::FunctionSection(s) => {
+                    for ty in s {
+                        funcs.push(types[ty.unwrap() as usize].clone());
+                    }
+                }
+                wasmparser::Payload::ExportSection(s) => {
+                    for export in s {
+                        let export = export.unwrap();
+                        match export.kind {
+                            wasmparser::ExternalKind::Func => {
+                                let ty = funcs[export.index as usize - imports.len()].clone();
+                                func_exports.push((export.name.to_string(), ty));
+                            }
+                            wasmparser::ExternalKind::Memory => {
+                                memory = Some(export.name.to_string());
+                            }
+                            _ => unreachable!(),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Assembler {
+            resolve,
+            world,
+            mangling,
+            encoding,
+            exports,
+            imports,
+            func_exports,
+            memory: memory.unwrap(),
+        }
+    }
+
+    fn assemble(&self, u: &mut Unstructured, module: &[u8]) -> Result<Vec<u8>> {
+        let resolve = self.resolve;
+        let num_imports = u32::try_from(self.imports.len()).unwrap();
+        let mut shift = ShiftFunctions(num_imports);
+
+        let mut types = TypeSection::new();
+        let mut funcs = FunctionSection::new();
+        let mut tables = TableSection::new();
+        let mut memories = MemorySection::new();
+        let mut tags = TagSection::new();
+        let mut globals = GlobalSection::new();
+        let mut elems = ElementSection::new();
+        let mut data_count = None;
+        let mut code = CodeSection::new();
+        let mut data = DataSection::new();
+        let (mut num_types, mut num_funcs, mut num_memories, mut num_globals) = (0, 0, 0, 0);
+        let mut inner = HashMap::new();
+        for payload in wasmparser::Parser::new(0).parse_all(module) {
+            match payload.unwrap() {
+                wasmparser::Payload::TypeSection(s) => {
+                    for group in s.clone() {
+                        num_types += u32::try_from(group.unwrap().types().len()).unwrap();
+                    }
+                    shift.parse_type_section(&mut types, s).unwrap();
+                }
+                wasmparser::Payload::FunctionSection(s) => {
+                    num_funcs = s.count();
+                    shift.parse_function_section(&mut funcs, s).unwrap();
+                }
+                wasmparser::Payload::TableSection(s) => {
+                    shift.parse_table_section(&mut tables, s).unwrap();
+                }
+                wasmparser::Payload::MemorySection(s) => {
+                    num_memories = s.count();
+                    shift.parse_memory_section(&mut memories, s).unwrap();
+                }
+                wasmparser::Payload::TagSection(s) => {
+                    shift.parse_tag_section(&mut tags, s).unwrap();
+                }
+                wasmparser::Payload::GlobalSection(s) => {
+                    num_globals = s.count();
+                    shift.parse_global_section(&mut globals, s).unwrap();
+                }
+                wasmparser::Payload::ExportSection(s) => {
+                    for export in s {
+                        let export = export.unwrap();
+                        inner.insert(export.name.to_string(), export.index + num_imports);
+                    }
+                }
+                wasmparser::Payload::ElementSection(s) => {
+                    shift.parse_element_section(&mut elems, s).unwrap();
+                }
+                wasmparser::Payload::DataCountSection { count, .. } => {
+                    data_count = Some(count);
+                }
+                wasmparser::Payload::CodeSectionEntry(body) => {
+                    shift.parse_function_body(&mut code, body).unwrap();
+                }
+                wasmparser::Payload::DataSection(s) => {
+                    shift.parse_data_section(&mut data, s).unwrap();
+                }
+                _ => {}
+            }
+        }
+
+        // The generated functions are no longer exported, so declare them for
+        // any `ref.func` instructions which refer to them.
+        if num_funcs > 0 {
+            let generated = (num_imports..num_imports + num_funcs).collect::<Vec<_>>();
+            elems.declared(Elements::Functions(generated.into()));
+        }
+
+        let mut signatures = HashMap::new();
+        let mut signature = |types: &mut TypeSection, (params, results): &Signature| {
+            *signatures
+                .entry((params.clone(), results.clone()))
+                .or_insert_with(|| {
+                    types.ty().function(params.clone(), results.clone());
+                    num_types += 1;
+                    num_types - 1
+                })
+        };
+
+        let mut imports = ImportSection::new();
+        let mut import_indices = HashMap::new();
+        for (i, (module, name, ty)) in self.imports.iter().enumerate() {
+            let ty = signature(&mut types, ty);
+            imports.import(module, name, EntityType::Function(ty));
+            import_indices.insert((module.as_str(), name.as_str()), u32::try_from(i).unwrap());
+        }
+
+        let memory = num_memories;
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: false,
+            page_size_log2: None,
+        });
+        let heap = num_globals;
+        globals.global(
+            GlobalType {
+                val_type: ValType::I32,
+                mutable: true,
+                shared: false,
+            },
+            &ConstExpr::i32_const(HEAP_START),
+        );
+
+        let realloc = num_imports + num_funcs;
+        let ty = signature(&mut types, &(vec![ValType::I32; 4], vec![ValType::I32]));
+        funcs.function(ty);
+        code.function(&abi::realloc(memory, heap));
+
+        let cx = Context {
+            resolve,
+            sizes: {
+                let mut sizes = SizeAlign::default();
+                sizes.fill(resolve);
+                sizes
+            },
+            encoding: self.encoding,
+            memory,
+            realloc,
+            resource_drop: self.resource_intrinsics(&import_indices, true),
+            resource_new: self.resource_intrinsics(&import_indices, false),
+        };
+
+        // Imports which can be called with arbitrary arguments.
+        let callable = if self.mangling.is_async() {
+            Vec::new()
+        } else {
+            self.imported_funcs()
+                .into_iter()
+                .filter(|(_, func)| {
+                    matches!(
+                        func.kind,
+                        FunctionKind::Freestanding
+                            | FunctionKind::Static(_)
+                            | FunctionKind::Constructor(_)
+                    ) && func.params.iter().all(|(_, ty)| cx.can_lower(ty))
+                        && func.result.iter().all(|ty| cx.can_lift(ty))
+                })
+                .map(|(interface, func)| {
+                    let (module, name) = resolve
+                        .wasm_import_name(self.mangling, WasmImport::Func { interface, func });
+                    (import_indices[&(module.as_str(), name.as_str())], func)
+                })
+                .collect()
+        };
+
+        let mut wrappers = HashMap::new();
+        for (i, (interface, func)) in self.exports.iter().enumerate() {
+            let name = resolve.wasm_export_name(
+                self.mangling,
+                WasmExport::Func {
+                    interface: *interface,
+                    func,
+                    kind: WasmExportKind::Normal,
+                },
+            );
+            let inner = inner[&inner_name(u32::try_from(i).unwrap())];
+            wrappers.insert(name, (*interface, *func, inner));
+        }
+
+        let mut exports = ExportSection::new();
+        exports.export(&self.memory, ExportKind::Memory, memory);
+        let realloc_name = resolve.wasm_export_name(self.mangling, WasmExport::Realloc);
+        let mut next = realloc + 1;
+        for (name, ty) in &self.func_exports {
+            if *name == realloc_name {
+                exports.export(name, ExportKind::Func, realloc);
+                continue;
+            }
+            let body = match wrappers.get(name) {
+                Some((interface, func, inner)) => {
+                    self.wrapper(&cx, u, &callable, &import_indices, *interface, func, *inner)?
+                }
+                // Post-return functions, callbacks, destructors, and
+                // `_initialize` have nothing to do.
+                None => {
+                    let mut body = Function::new([]);
+                    let mut ins = body.instructions();
+                    for ty in &ty.1 {
+                        match ty {
+                            ValType::I32 => ins.i32_const(0),
+                            ValType::I64 => ins.i64_const(0),
+                            ValType::F32 => ins.f32_const(0.0.into()),
+                            ValType::F64 => ins.f64_const(0.0.into()),
+                            _ => unreachable!(),
+                        };
+                    }
+                    ins.end();
+                    body
+                }
+            };
+            funcs.function(signature(&mut types, ty));
+            code.function(&body);
+            exports.export(name, ExportKind::Func, next);
+            next += 1;
+        }
+
+        // Empty sections are omitted, as some, such as the tag section,
+        // require proposals even when empty.
+        let mut module = wasm_encoder::Module::new();
+        macro_rules! sections {
+            ($($section:expr),*) => {$(
+                if !$section.is_empty() {
+                    module.section(&$section);
+                }
+            )*};
+        }
+        sections!(
+            types, imports, funcs, tables, memories, tags, globals, exports, elems
+        );
+        if let Some(count) = data_count {
+            module.section(&DataCountSection { count });
+        }
+        sections!(code, data);
+        Ok(module.finish())
+    }
+
+    /// All functions imported by the world.
+    fn imported_funcs(&self) -> Vec<(Option<&'a WorldKey>, &'a WitFunction)> {
+        let resolve = self.resolve;
+        let mut funcs = Vec::new();
+        for (key, item) in resolve.worlds[self.world].imports.iter() {
+            match item {
+                WorldItem::Function(func) => funcs.push((None, func)),
+                WorldItem::Interface { id, .. } => {
+                    for func in resolve.interfaces[*id].functions.values() {
+                        funcs.push((Some(key), func));
+                    }
+                }
+                WorldItem::Type(_) => {}
+            }
+        }
+        funcs
+    }
+
+    /// The `[resource-drop]` intrinsics of imported resources or the
+    /// `[resource-new]` intrinsics of exported resources.
+    fn resource_intrinsics(
+        &self,
+        import_indices: &HashMap<(&str, &str), u32>,
+        imported: bool,
+    ) -> HashMap<TypeId, u32> {
+        let resolve = self.resolve;
+        let world = &resolve.worlds[self.world];
+        let items = if imported {
+            &world.imports
+        } else {
+            &world.exports
+        };
+        let mut intrinsics = HashMap::new();
+        for (key, item) in items.iter() {
+            let (interface, resources) = match item {
+                WorldItem::Interface { id, .. } => (
+                    Some(key),
+                    resolve.interfaces[*id].types.values().copied().collect(),
+                ),
+                WorldItem::Type(id) if imported => (None, vec![*id]),
+                _ => continue,
+            };
+            for resource in resources {
+                if !matches!(resolve.types[resource].kind, TypeDefKind::Resource) {
+                    continue;
+                }
+                let (module, name) = resolve.wasm_import_name(
+                    self.mangling.sync(),
+                    WasmImport::ResourceIntrinsic {
+                        interface,
+                        resource,
+                        intrinsic: if imported {
+                            ResourceIntrinsic::ImportedDrop
+                        } else {
+                            ResourceIntrinsic::ExportedNew
+                        },
+                    },
+                );
+                intrinsics.insert(resource, import_indices[&(module.as_str(), name.as_str())]);
+            }
+        }
+        intrinsics
+    }
+
+    /// The implementation of an exported function, which calls the generated
+    /// `inner` function.
+    fn wrapper(
+        &self,
+        cx: &Context,
+        u: &mut Unstructured,
+        callable: &[(u32, &WitFunction)],
+        import_indices: &HashMap<(&str, &str), u32>,
+        interface: Option<&WorldKey>,
+        func: &WitFunction,
+        inner: u32,
+    ) -> Result<Function> {
+        let resolve = self.resolve;
+        let sig = resolve.wasm_signature(self.mangling.export_variant(), func);
+        let num_params = u32::try_from(sig.params.len()).unwrap();
+        let params = func.params.iter().map(|(_, ty)| *ty).collect::<Vec<_>>();
+
+        let mut body = Body::new(cx, u, num_params);
+        if !params.iter().all(|ty| cx.can_lift(ty)) {
+            body.ins().unreachable();
+            return Ok(body.finish());
+        }
+        body.lift_params(&params, sig.indirect_params)?;
+        for i in 0..num_params {
+            body.ins().local_get(i);
+        }
+        body.ins().call(inner);
+
+        // The result of async exports is passed to `task.return`, rather
+        // than returned.
+        if self.mangling.is_async() {
+            let module = match interface {
+                Some(key) => format!("[export]{}", resolve.name_world_key(key)),
+                None => "[export]$root".to_string(),
+            };
+            let name = format!("[task-return]{}", func.name);
+            let task_return = import_indices[&(module.as_str(), name.as_str())];
+            if let Some(ty) = &func.result {
+                if !cx.can_lower(ty) {
+                    body.ins().unreachable();
+                    return Ok(body.finish());
+                }
+                let mut task_return_func = func.clone();
+                task_return_func.params = vec![("x".to_string(), *ty)];
+                task_return_func.result = None;
+                let sig = resolve.wasm_signature(AbiVariant::GuestImport, &task_return_func);
+                body.lower_params(&[*ty], sig.indirect_params)?;
+            }
+            body.ins().call(task_return);
+            if let ManglingAndAbi::Legacy(LiftLowerAbi::AsyncCallback) = self.mangling {
+                // The `EXIT` callback code.
+                body.ins().i32_const(0);
+            }
+            return Ok(body.finish());
+        }
+
+        for _ in 0..body.u().int_in_range(0..=2)? {
+            if callable.is_empty() {
+                break;
+            }
+            let (import, func) = *body.u().choose(callable)?;
+            let sig = resolve.wasm_signature(AbiVariant::GuestImport, func);
+            let params = func.params.iter().map(|(_, ty)| *ty).collect::<Vec<_>>();
+            body.lower_params(&params, sig.indirect_params)?;
+            let retptr = match &func.result {
+                Some(ty) if sig.retptr => Some(body.alloc_result(ty)),
+                _ => None,
+            };
+            body.ins().call(import);
+            if let Some(ty) = &func.result {
+                body.lift_result(ty, retptr)?;
+            }
+        }
+
+        match &func.result {
+            Some(ty) if cx.can_lower(ty) => body.lower_result(ty, sig.retptr)?,
+            Some(_) => {
+                body.ins().unreachable();
+            }
+            None => {}
+        }
+        Ok(body.finish())
+    }
+}
@@ -0,0 +1,1049 @@
+//! Generation of core function bodies which pass values across the canonical
+//! ABI.
+//!
+//! Values which are lifted by the component, such as the arguments of an
+//! export or the results of an import, are read out of their flat
+//! representation or out of linear memory. Strings and lists are walked
+//! element by element and borrowed handles of imported resources are dropped.
+//!
+//! Values which are lowered into the component, such as the arguments of an
+//! import or the results of an export, are arbitrary but valid: characters
+//! are Unicode scalar values, discriminants are in range, strings and lists
+//! are allocated with `cabi_realloc` and owned handles of exported resources
+//! are created with `[resource-new]`.
+
+use arbitrary::{Result, Unstructured};
+use std::collections::HashMap;
+use wasm_encoder::{BlockType, Function, InstructionSink, MemArg, ValType};
+use wit_component::StringEncoding;
+use wit_parser::abi::{FlatTypes, WasmType};
+use wit_parser::{Handle, Int, Resolve, SizeAlign, Type, TypeDefKind, TypeId};
+
+/// The maximum number of string characters and list elements which are
+/// lowered by a single function, which bounds the size of generated code.
+const MAX_LOWERED_ELEMENTS: u32 = 32;
+
+/// The module-level items which function bodies refer to.
+pub(super) struct Context<'a> {
+    pub resolve: &'a Resolve,
+    pub sizes: SizeAlign,
+    pub encoding: StringEncoding,
+    /// The memory which values are lifted from and lowered into.
+    pub memory: u32,
+    /// The `cabi_realloc` function.
+    pub realloc: u32,
+    /// The `[resource-drop]` intrinsic of each imported resource.
+    pub resource_drop: HashMap<TypeId, u32>,
+    /// The `[resource-new]` intrinsic of each exported resource.
+    pub resource_new: HashMap<TypeId, u32>,
+}
+
+enum Resource {
+    Imported {
+        drop: u32,
+    },
+    Exported {
+        new: u32,
+    },
+    /// The resource is both imported and exported, so which of the two a
+    /// handle refers to depends on where it's used.
+    Ambiguous,
+}
+
+impl Context<'_> {
+    fn resource(&self, mut id: TypeId) -> Resource {
+        while let TypeDefKind::Type(Type::Id(next)) = self.resolve.types[id].kind {
+            id = next;
+        }
+        match (self.resource_drop.get(&id), self.resource_new.get(&id)) {
+            (Some(drop), None) => Resource::Imported { drop: *drop },
+            (None, Some(new)) => Resource::Exported { new: *new },
+            _ => Resource::Ambiguous,
+        }
+    }
+
+    /// Whether values of `ty` can be lifted by generated code.
+    ///
+    /// Borrowed handles need to be dropped before returning, which requires
+    /// knowing which resource they refer to.
+    pub fn can_lift(&self, ty: &Type) -> bool {
+        let Type::Id(id) = ty else {
+            return true;
+        };
+        match &self.resolve.types[*id].kind {
+            TypeDefKind::Handle(Handle::Borrow(r)) => {
+                !matches!(self.resource(*r), Resource::Ambiguous)
+            }
+            TypeDefKind::Future(_) | TypeDefKind::Stream(_) => true,
+            kind => children(kind).iter().all(|ty| self.can_lift(ty)),
+        }
+    }
+
+    /// Whether arbitrary values of `ty` can be lowered by generated code.
+    ///
+    /// Only owned handles of exported resources can be created.
+    pub fn can_lower(&self, ty: &Type) -> bool {
+        let Type::Id(id) = ty else {
+            return !matches!(ty, Type::ErrorContext);
+        };
+        match &self.resolve.types[*id].kind {
+            TypeDefKind::Handle(Handle::Own(r)) => {
+                matches!(self.resource(*r), Resource::Exported { .. })
+            }
+            TypeDefKind::Handle(Handle::Borrow(_))
+            | TypeDefKind::Resource
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_)
+            | TypeDefKind::Unknown => false,
+            kind => children(kind).iter().all(|ty| self.can_lower(ty)),
+        }
+    }
+
+    fn flat(&self, ty: &Type) -> Vec<WasmType> {
+        // Only used for values which are passed as flat values, so these
+        // never exceed the maximum number of flat parameters.
+        let mut storage = [WasmType::I32; 17];
+        let mut flat = FlatTypes::new(&mut storage);
+        assert!(self.resolve.push_flat(ty, &mut flat));
+        flat.to_vec()
+    }
+
+    fn size(&self, ty: &Type) -> u32 {
+        self.sizes.size(ty).size_wasm32().try_into().unwrap()
+    }
+
+    fn align(&self, ty: &Type) -> u32 {
+        self.sizes.align(ty).align_wasm32().try_into().unwrap()
+    }
+}
+
+/// The types directly contained in a type definition.
+fn children(kind: &TypeDefKind) -> Vec<Type> {
+    match kind {
+        TypeDefKind::Record(r) => r.fields.iter().map(|f| f.ty).collect(),
+        TypeDefKind::Tuple(t) => t.types.clone(),
+        TypeDefKind::Variant(v) => v.cases.iter().filter_map(|c| c.ty).collect(),
+        TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::FixedSizeList(ty, _) => {
+            vec![*ty]
+        }
+        TypeDefKind::Result(r) => r.ok.iter().chain(&r.err).copied().collect(),
+        TypeDefKind::Type(ty) => vec![*ty],
+        TypeDefKind::Future(ty) | TypeDefKind::Stream(ty) => ty.iter().copied().collect(),
+        TypeDefKind::Flags(_)
+        | TypeDefKind::Enum(_)
+        | TypeDefKind::Resource
+        | TypeDefKind::Handle(_)
+        | TypeDefKind::Unknown => Vec::new(),
+    }
+}
+
+/// The discriminant and case payloads of variant-like types.
+fn cases(kind: &TypeDefKind) -> Option<(Int, Vec<Option<Type>>)> {
+    match kind {
+        TypeDefKind::Variant(v) => Some((v.tag(), v.cases.iter().map(|c| c.ty).collect())),
+        TypeDefKind::Enum(e) => Some((e.tag(), vec![None; e.cases.len()])),
+        TypeDefKind::Option(ty) => Some((Int::U8, vec![None, Some(*ty)])),
+        TypeDefKind::Result(r) => Some((Int::U8, vec![r.ok, r.err])),
+        _ => None,
+    }
+}
+
+pub(super) fn val_type(ty: WasmType) -> ValType {
+    match ty {
+        WasmType::I32 | WasmType::Pointer | WasmType::Length => ValType::I32,
+        WasmType::I64 | WasmType::PointerOrI64 => ValType::I64,
+        WasmType::F32 => ValType::F32,
+        WasmType::F64 => ValType::F64,
+    }
+}
+
+/// A function body under construction.
+pub(super) struct Body<'a, 'u> {
+    cx: &'a Context<'a>,
+    u: &'a mut Unstructured<'u>,
+    num_params: u32,
+    locals: Vec<ValType>,
+    insts: Vec<u8>,
+    budget: u32,
+}
+
+impl<'a, 'u> Body<'a, 'u> {
+    pub fn new(cx: &'a Context<'a>, u: &'a mut Unstructured<'u>, num_params: u32) -> Self {
+        Body {
+            cx,
+            u,
+            num_params,
+            locals: Vec::new(),
+            insts: Vec::new(),
+            budget: MAX_LOWERED_ELEMENTS,
+        }
+    }
+
+    pub fn u(&mut self) -> &mut Unstructured<'u> {
+        self.u
+    }
+
+    pub fn ins(&mut self) -> InstructionSink<'_> {
+        InstructionSink::new(&mut self.insts)
+    }
+
+    pub fn finish(mut self) -> Function {
+        self.ins().end();
+        let mut func = Function::new_with_locals_types(self.locals);
+        func.raw(self.insts);
+        func
+    }
+
+    fn local(&mut self, ty: ValType) -> u32 {
+        self.locals.push(ty);
+        self.num_params + u32::try_from(self.locals.len()).unwrap() - 1
+    }
+
+    /// Pops the top of the stack into a new local.
+    fn set(&mut self, ty: ValType) -> u32 {
+        let local = self.local(ty);
+        self.ins().local_set(local);
+        local
+    }
+
+    fn memarg(&self, offset: u32) -> MemArg {
+        MemArg {
+            offset: offset.into(),
+            align: 0,
+            memory_index: self.cx.memory,
+        }
+    }
+
+    /// Allocates `size` bytes with `cabi_realloc`, returning the local which
+    /// holds the pointer.
+    fn alloc(&mut self, size: u32, align: u32) -> u32 {
+        let realloc = self.cx.realloc;
+        self.ins()
+            .i32_const(0)
+            .i32_const(0)
+            .i32_const(align as i32)
+            .i32_const(size as i32)
+            .call(realloc);
+        self.set(ValType::I32)
+    }
+
+    /// Runs `f` once for each index below the value of the `len` local.
+    fn for_each(&mut self, len: u32, f: impl Fn(&mut Self, u32) -> Result<()>) -> Result<()> {
+        let i = self.local(ValType::I32);
+        self.ins()
+            .i32_const(0)
+            .local_set(i)
+            .block(BlockType::Empty)
+            .loop_(BlockType::Empty)
+            .local_get(i)
+            .local_get(len)
+            .i32_ge_u()
+            .br_if(1);
+        f(self, i)?;
+        self.ins()
+            .local_get(i)
+            .i32_const(1)
+            .i32_add()
+            .local_set(i)
+            .br(0)
+            .end()
+            .end();
+        Ok(())
+    }
+
+    /// Lifts a function's parameters, which are either the flat parameters
+    /// of this function or stored behind the pointer in its first parameter.
+    pub fn lift_params(&mut self, params: &[Type], indirect: bool) -> Result<()> {
+        let cx = self.cx;
+        if indirect {
+            for (offset, ty) in cx.sizes.field_offsets(params) {
+                self.lift_mem(ty, 0, offset.size_wasm32().try_into().unwrap())?;
+            }
+        } else {
+            let mut next = 0;
+            for ty in params {
+                let n = u32::try_from(cx.flat(ty).len()).unwrap();
+                let vals = (next..next + n).collect::<Vec<_>>();
+                self.lift_flat(ty, &vals)?;
+                next += n;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowers arguments for `params` onto the stack, either as flat values or
+    /// as a pointer to a newly allocated record of them.
+    pub fn lower_params(&mut self, params: &[Type], indirect: bool) -> Result<()> {
+        let cx = self.cx;
+        if indirect {
+            let info = cx.sizes.record(params);
+            let ptr = self.alloc(
+                info.size.size_wasm32().try_into().unwrap(),
+                info.align.align_wasm32().try_into().unwrap(),
+            );
+            for (offset, ty) in cx.sizes.field_offsets(params) {
+                self.lower_mem(ty, ptr, offset.size_wasm32().try_into().unwrap())?;
+            }
+            self.ins().local_get(ptr);
+        } else {
+            for ty in params {
+                self.lower_flat(ty)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowers a function result, either as a single flat value or as a
+    /// pointer to a newly allocated copy of it.
+    pub fn lower_result(&mut self, ty: &Type, retptr: bool) -> Result<()> {
+        if retptr {
+            let ptr = self.alloc(self.cx.size(ty), self.cx.align(ty));
+            self.lower_mem(ty, ptr, 0)?;
+            self.ins().local_get(ptr);
+            Ok(())
+        } else {
+            self.lower_flat(ty)
+        }
+    }
+
+    /// Allocates space for a result which is returned through a pointer
+    /// parameter, pushing that pointer onto the stack.
+    pub fn alloc_result(&mut self, ty: &Type) -> u32 {
+        let ptr = self.alloc(self.cx.size(ty), self.cx.align(ty));
+        self.ins().local_get(ptr);
+        ptr
+    }
+
+    /// Lifts a function result, which is either written to the memory at
+    /// `retptr` or is a single flat value on the stack.
+    pub fn lift_result(&mut self, ty: &Type, retptr: Option<u32>) -> Result<()> {
+        match retptr {
+            Some(ptr) => self.lift_mem(ty, ptr, 0),
+            None => {
+                let flat = self.cx.flat(ty);
+                assert_eq!(flat.len(), 1);
+                let val = self.set(val_type(flat[0]));
+                self.lift_flat(ty, &[val])
+            }
+        }
+    }
+
+    /// Lifts the value of `ty` held in the `vals` locals, whose types are
+    /// exactly the flat types of `ty`.
+    fn lift_flat(&mut self, ty: &Type, vals: &[u32]) -> Result<()> {
+        let cx = self.cx;
+        let id = match ty {
+            Type::String => return self.lift_string(vals[0], vals[1]),
+            Type::Id(id) => *id,
+            _ => return Ok(()),
+        };
+        let kind = &cx.resolve.types[id].kind;
+        match kind {
+            TypeDefKind::Record(r) => self.lift_flat_fields(r.fields.iter().map(|f| &f.ty), vals),
+            TypeDefKind::Tuple(t) => self.lift_flat_fields(t.types.iter(), vals),
+            TypeDefKind::FixedSizeList(ty, n) => {
+                self.lift_flat_fields(std::iter::repeat(ty).take(*n as usize), vals)
+            }
+            TypeDefKind::List(ty) => self.lift_list(ty, vals[0], vals[1]),
+            TypeDefKind::Handle(Handle::Borrow(r)) => {
+                if let Resource::Imported { drop } = cx.resource(*r) {
+                    self.ins().local_get(vals[0]).call(drop);
+                }
+                Ok(())
+            }
+            TypeDefKind::Type(ty) => self.lift_flat(ty, vals),
+            kind => {
+                let Some((_, cases)) = cases(kind) else {
+                    return Ok(());
+                };
+                let joined = cx.flat(&Type::Id(id));
+                for (i, case) in cases.iter().enumerate() {
+                    let Some(case) = case else { continue };
+                    self.ins()
+                        .local_get(vals[0])
+                        .i32_const(i as i32)
+                        .i32_eq()
+                        .if_(BlockType::Empty);
+                    let case_vals = cx
+                        .flat(case)
+                        .into_iter()
+                        .zip(&joined[1..])
+                        .zip(&vals[1..])
+                        .map(|((to, from), val)| self.unjoin(*val, *from, to))
+                        .collect::<Vec<_>>();
+                    self.lift_flat(case, &case_vals)?;
+                    self.ins().end();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn lift_flat_fields<'b>(
+        &mut self,
+        tys: impl Iterator<Item = &'b Type>,
+        vals: &[u32],
+    ) -> Result<()> {
+        let mut next = 0;
+        for ty in tys {
+            let n = self.cx.flat(ty).len();
+            self.lift_flat(ty, &vals[next..next + n])?;
+            next += n;
+        }
+        Ok(())
+    }
+
+    /// Converts the `val` local from a joined variant payload type to the
+    /// type of a case's payload.
+    fn unjoin(&mut self, val: u32, from: WasmType, to: WasmType) -> u32 {
+        let (from, to) = (val_type(from), val_type(to));
+        if from == to {
+            return val;
+        }
+        let mut ins = self.ins();
+        ins.local_get(val);
+        match (from, to) {
+            (ValType::I64, ValType::I32) => ins.i32_wrap_i64(),
+            (ValType::I32, ValType::F32) => ins.f32_reinterpret_i32(),
+            (ValType::I64, ValType::F32) => ins.i32_wrap_i64().f32_reinterpret_i32(),
+            (ValType::I64, ValType::F64) => ins.f64_reinterpret_i64(),
+            _ => unreachable!("no join from {from:?} to {to:?}"),
+        };
+        self.set(to)
+    }
+
+    /// Converts the value on top of the stack from the type of a case's
+    /// payload to the joined variant payload type.
+    fn join(&mut self, from: WasmType, to: WasmType) {
+        let mut ins = self.ins();
+        match (val_type(from), val_type(to)) {
+            (from, to) if from == to => {}
+            (ValType::I32, ValType::I64) => {
+                ins.i64_extend_i32_u();
+            }
+            (ValType::F32, ValType::I32) => {
+                ins.i32_reinterpret_f32();
+            }
+            (ValType::F32, ValType::I64) => {
+                ins.i32_reinterpret_f32().i64_extend_i32_u();
+            }
+            (ValType::F64, ValType::I64) => {
+                ins.i64_reinterpret_f64();
+            }
+            (from, to) => unreachable!("no join from {from:?} to {to:?}"),
+        }
+    }
+
+    /// Lifts the value of `ty` stored at `offset` from the pointer in the
+    /// `base` local.
+    fn lift_mem(&mut self, ty: &Type, base: u32, offset: u32) -> Result<()> {
+        let cx = self.cx;
+        let m = self.memarg(offset);
+        let id = match ty {
+            Type::Bool | Type::U8 => {
+                self.ins().local_get(base).i32_load8_u(m).drop();
+                return Ok(());
+            }
+            Type::S8 => {
+                self.ins().local_get(base).i32_load8_s(m).drop();
+                return Ok(());
+            }
+            Type::U16 => {
+                self.ins().local_get(base).i32_load16_u(m).drop();
+                return Ok(());
+            }
+            Type::S16 => {
+                self.ins().local_get(base).i32_load16_s(m).drop();
+                return Ok(());
+            }
+            Type::U32 | Type::S32 | Type::Char | Type::ErrorContext => {
+                self.ins().local_get(base).i32_load(m).drop();
+                return Ok(());
+            }
+            Type::U64 | Type::S64 => {
+                self.ins().local_get(base).i64_load(m).drop();
+                return Ok(());
+            }
+            Type::F32 => {
+                self.ins().local_get(base).f32_load(m).drop();
+                return Ok(());
+            }
+            Type::F64 => {
+                self.ins().local_get(base).f64_load(m).drop();
+                return Ok(());
+            }
+            Type::String => {
+                let (ptr, len) = self.load_ptr_len(base, offset);
+                return self.lift_string(ptr, len);
+            }
+            Type::Id(id) => *id,
+        };
+        let kind = &cx.resolve.types[id].kind;
+        match kind {
+            TypeDefKind::Record(r) => {
+                let offsets = cx.sizes.field_offsets(r.fields.iter().map(|f| &f.ty));
+                self.lift_mem_fields(offsets, base, offset)?;
+            }
+            TypeDefKind::Tuple(t) => {
+                let offsets = cx.sizes.field_offsets(&t.types);
+                self.lift_mem_fields(offsets, base, offset)?;
+            }
+            TypeDefKind::Flags(f) => match f.repr() {
+                wit_parser::FlagsRepr::U8 => {
+                    self.ins().local_get(base).i32_load8_u(m).drop();
+                }
+                wit_parser::FlagsRepr::U16 => {
+                    self.ins().local_get(base).i32_load16_u(m).drop();
+                }
+                wit_parser::FlagsRepr::U32(n) => {
+                    for i in 0..n as u32 {
+                        let m = self.memarg(offset + 4 * i);
+                        self.ins().local_get(base).i32_load(m).drop();
+                    }
+                }
+            },
+            TypeDefKind::List(ty) => {
+                let (ptr, len) = self.load_ptr_len(base, offset);
+                self.lift_list(ty, ptr, len)?;
+            }
+            TypeDefKind::FixedSizeList(ty, n) => {
+                self.ins()
+                    .local_get(base)
+                    .i32_const(offset as i32)
+                    .i32_add();
+                let ptr = self.set(ValType::I32);
+                self.ins().i32_const(*n as i32);
+                let len = self.set(ValType::I32);
+                self.lift_list(ty, ptr, len)?;
+            }
+            TypeDefKind::Handle(Handle::Borrow(r)) => {
+                self.ins().local_get(base).i32_load(m);
+                match cx.resource(*r) {
+                    Resource::Imported { drop } => self.ins().call(drop),
+                    _ => self.ins().drop(),
+                };
+            }
+            TypeDefKind::Handle(Handle::Own(_))
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_) => {
+                self.ins().local_get(base).i32_load(m).drop();
+            }
+            TypeDefKind::Type(ty) => self.lift_mem(ty, base, offset)?,
+            TypeDefKind::Resource | TypeDefKind::Unknown => unreachable!(),
+            kind => {
+                let (tag, cases) = cases(kind).unwrap();
+                self.ins().local_get(base);
+                self.load_int(tag, m);
+                let discriminant = self.set(ValType::I32);
+                let payload = offset
+                    + u32::try_from(
+                        cx.sizes
+                            .payload_offset(tag, cases.iter().map(|c| c.as_ref()))
+                            .size_wasm32(),
+                    )
+                    .unwrap();
+                for (i, case) in cases.iter().enumerate() {
+                    let Some(case) = case else { continue };
+                    self.ins()
+                        .local_get(discriminant)
+                        .i32_const(i as i32)
+                        .i32_eq()
+                        .if_(BlockType::Empty);
+                    self.lift_mem(case, base, payload)?;
+                    self.ins().end();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn lift_mem_fields(
+        &mut self,
+        offsets: Vec<(wit_parser::ArchitectureSize, &Type)>,
+        base: u32,
+        offset: u32,
+    ) -> Result<()> {
+        for (field, ty) in offsets {
+            let field = u32::try_from(field.size_wasm32()).unwrap();
+            self.lift_mem(ty, base, offset + field)?;
+        }
+        Ok(())
+    }
+
+    fn load_ptr_len(&mut self, base: u32, offset: u32) -> (u32, u32) {
+        let (m_ptr, m_len) = (self.memarg(offset), self.memarg(offset + 4));
+        self.ins().local_get(base).i32_load(m_ptr);
+        let ptr = self.set(ValType::I32);
+        self.ins().local_get(base).i32_load(m_len);
+        let len = self.set(ValType::I32);
+        (ptr, len)
+    }
+
+    fn load_int(&mut self, int: Int, m: MemArg) {
+        match int {
+            Int::U8 => self.ins().i32_load8_u(m),
+            Int::U16 => self.ins().i32_load16_u(m),
+            Int::U32 => self.ins().i32_load(m),
+            Int::U64 => unreachable!("discriminants are at most 32 bits"),
+        };
+    }
+
+    fn store_int(&mut self, int: Int, m: MemArg) {
+        match int {
+            Int::U8 => self.ins().i32_store8(m),
+            Int::U16 => self.ins().i32_store16(m),
+            Int::U32 => self.ins().i32_store(m),
+            Int::U64 => unreachable!("discriminants are at most 32 bits"),
+        };
+    }
+
+    /// Reads every byte of the string in the `ptr` and `len` locals.
+    fn lift_string(&mut self, ptr: u32, len: u32) -> Result<()> {
+        // The length counts code units, whose size depends on the encoding.
+        // With `latin1+utf16` the top bit of the length is set for UTF-16.
+        let encoding = self.cx.encoding;
+        let mut ins = self.ins();
+        ins.local_get(len);
+        match encoding {
+            StringEncoding::UTF8 => {}
+            StringEncoding::UTF16 => {
+                ins.i32_const(1).i32_shl();
+            }
+            StringEncoding::CompactUTF16 => {
+                ins.i32_const(i32::MAX)
+                    .i32_and()
+                    .local_get(len)
+                    .i32_const(31)
+                    .i32_shr_u()
+                    .i32_shl();
+            }
+        }
+        let bytes = self.set(ValType::I32);
+        let m = self.memarg(0);
+        self.for_each(bytes, |body, i| {
+            body.ins()
+                .local_get(ptr)
+                .local_get(i)
+                .i32_add()
+                .i32_load8_u(m)
+                .drop();
+            Ok(())
+        })
+    }
+
+    /// Lifts each element of the list in the `ptr` and `len` locals.
+    fn lift_list(&mut self, ty: &Type, ptr: u32, len: u32) -> Result<()> {
+        let size = self.cx.size(ty);
+        self.for_each(len, |body, i| {
+            body.ins()
+                .local_get(ptr)
+                .local_get(i)
+                .i32_const(size as i32)
+                .i32_mul()
+                .i32_add();
+            let elem = body.set(ValType::I32);
+            body.lift_mem(ty, elem, 0)
+        })
+    }
+
+    /// Pushes the flat representation of an arbitrary value of `ty`.
+    fn lower_flat(&mut self, ty: &Type) -> Result<()> {
+        let cx = self.cx;
+        let id = match ty {
+            Type::Bool => {
+                let b = self.u.arbitrary::<bool>()?;
+                self.ins().i32_const(b.into());
+                return Ok(());
+            }
+            Type::S8 => {
+                let i = self.u.arbitrary::<i8>()?;
+                self.ins().i32_const(i.into());
+                return Ok(());
+            }
+            Type::U8 => {
+                let i = self.u.arbitrary::<u8>()?;
+                self.ins().i32_const(i.into());
+                return Ok(());
+            }
+            Type::S16 => {
+                let i = self.u.arbitrary::<i16>()?;
+                self.ins().i32_const(i.into());
+                return Ok(());
+            }
+            Type::U16 => {
+                let i = self.u.arbitrary::<u16>()?;
+                self.ins().i32_const(i.into());
+                return Ok(());
+            }
+            Type::S32 | Type::U32 => {
+                let i = self.u.arbitrary::<i32>()?;
+                self.ins().i32_const(i);
+                return Ok(());
+            }
+            Type::Char => {
+                let c = self.u.arbitrary::<char>()?;
+                self.ins().i32_const(c as i32);
+                return Ok(());
+            }
+            Type::S64 | Type::U64 => {
+                let i = self.u.arbitrary::<i64>()?;
+                self.ins().i64_const(i);
+                return Ok(());
+            }
+            Type::F32 => {
+                let f = self.u.arbitrary::<f32>()?;
+                self.ins().f32_const(f.into());
+                return Ok(());
+            }
+            Type::F64 => {
+                let f = self.u.arbitrary::<f64>()?;
+                self.ins().f64_const(f.into());
+                return Ok(());
+            }
+            Type::String => {
+                let (ptr, len) = self.lower_string()?;
+                self.ins().local_get(ptr).i32_const(len as i32);
+                return Ok(());
+            }
+            Type::ErrorContext => unreachable!(),
+            Type::Id(id) => *id,
+        };
+        let kind = &cx.resolve.types[id].kind;
+        match kind {
+            TypeDefKind::Record(r) => {
+                for field in &r.fields {
+                    self.lower_flat(&field.ty)?;
+                }
+            }
+            TypeDefKind::Tuple(t) => {
+                for ty in &t.types {
+                    self.lower_flat(ty)?;
+                }
+            }
+            TypeDefKind::FixedSizeList(ty, n) => {
+                for _ in 0..*n {
+                    self.lower_flat(ty)?;
+                }
+            }
+            TypeDefKind::Flags(f) => {
+                for chunk in 0..f.repr().count() {
+                    let bits = self.flag_bits(f.flags.len(), chunk)?;
+                    self.ins().i32_const(bits as i32);
+                }
+            }
+            TypeDefKind::List(ty) => {
+                let (ptr, len) = self.lower_list(ty)?;
+                self.ins().local_get(ptr).i32_const(len as i32);
+            }
+            TypeDefKind::Handle(Handle::Own(r)) => self.new_resource(*r)?,
+            TypeDefKind::Type(ty) => self.lower_flat(ty)?,
+            TypeDefKind::Handle(Handle::Borrow(_))
+            | TypeDefKind::Resource
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_)
+            | TypeDefKind::Unknown => unreachable!(),
+            kind => {
+                let (_, cases) = cases(kind).unwrap();
+                let joined = cx.flat(&Type::Id(id));
+                let i = self.u.choose_index(cases.len())?;
+                self.ins().i32_const(i as i32);
+                let mut case_flat = Vec::new();
+                if let Some(case) = &cases[i] {
+                    // Lower the payload into locals so that each value can be
+                    // converted to the joined type of its slot.
+                    self.lower_flat(case)?;
+                    case_flat = cx.flat(case);
+                    let mut vals = case_flat
+                        .iter()
+                        .rev()
+                        .map(|ty| self.set(val_type(*ty)))
+                        .collect::<Vec<_>>();
+                    vals.reverse();
+                    for ((val, from), to) in vals.iter().zip(&case_flat).zip(&joined[1..]) {
+                        self.ins().local_get(*val);
+                        self.join(*from, *to);
+                    }
+                }
+                for ty in &joined[1 + case_flat.len()..] {
+                    let mut ins = self.ins();
+                    match val_type(*ty) {
+                        ValType::I32 => ins.i32_const(0),
+                        ValType::I64 => ins.i64_const(0),
+                        ValType::F32 => ins.f32_const(0.0.into()),
+                        ValType::F64 => ins.f64_const(0.0.into()),
+                        _ => unreachable!(),
+                    };
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stores an arbitrary value of `ty` at `offset` from the pointer in the
+    /// `base` local.
+    fn lower_mem(&mut self, ty: &Type, base: u32, offset: u32) -> Result<()> {
+        let cx = self.cx;
+        let m = self.memarg(offset);
+        let id = match ty {
+            Type::Bool | Type::U8 | Type::S8 => {
+                self.ins().local_get(base);
+                self.lower_flat(ty)?;
+                self.ins().i32_store8(m);
+                return Ok(());
+            }
+            Type::U16 | Type::S16 => {
+                self.ins().local_get(base);
+                self.lower_flat(ty)?;
+                self.ins().i32_store16(m);
+                return Ok(());
+            }
+            Type::U32 | Type::S32 | Type::Char => {
+                self.ins().local_get(base);
+                self.lower_flat(ty)?;
+                self.ins().i32_store(m);
+                return Ok(());
+            }
+            Type::U64 | Type::S64 => {
+                self.ins().local_get(base);
+                self.lower_flat(ty)?;
+                self.ins().i64_store(m);
+                return Ok(());
+            }
+            Type::F32 => {
+                self.ins().local_get(base);
+                self.lower_flat(ty)?;
+                self.ins().f32_store(m);
+                return Ok(());
+            }
+            Type::F64 => {
+                self.ins().local_get(base);
+                self.lower_flat(ty)?;
+                self.ins().f64_store(m);
+                return Ok(());
+            }
+            Type::String => {
+                let (ptr, len) = self.lower_string()?;
+                self.store_ptr_len(base, offset, ptr, len);
+                return Ok(());
+            }
+            Type::ErrorContext => unreachable!(),
+            Type::Id(id) => *id,
+        };
+        let kind = &cx.resolve.types[id].kind;
+        match kind {
+            TypeDefKind::Record(r) => {
+                let offsets = cx.sizes.field_offsets(r.fields.iter().map(|f| &f.ty));
+                self.lower_mem_fields(offsets, base, offset)?;
+            }
+            TypeDefKind::Tuple(t) => {
+                let offsets = cx.sizes.field_offsets(&t.types);
+                self.lower_mem_fields(offsets, base, offset)?;
+            }
+            TypeDefKind::FixedSizeList(ty, n) => {
+                let size = cx.size(ty);
+                for i in 0..*n {
+                    self.lower_mem(ty, base, offset + i * size)?;
+                }
+            }
+            TypeDefKind::Flags(f) => {
+                let repr = f.repr();
+                for chunk in 0..repr.count() {
+                    let bits = self.flag_bits(f.flags.len(), chunk)?;
+                    let m = self.memarg(offset + 4 * chunk as u32);
+                    let mut ins = self.ins();
+                    ins.local_get(base).i32_const(bits as i32);
+                    match repr {
+                        wit_parser::FlagsRepr::U8 => ins.i32_store8(m),
+                        wit_parser::FlagsRepr::U16 => ins.i32_store16(m),
+                        wit_parser::FlagsRepr::U32(_) => ins.i32_store(m),
+                    };
+                }
+            }
+            TypeDefKind::List(ty) => {
+                let (ptr, len) = self.lower_list(ty)?;
+                self.store_ptr_len(base, offset, ptr, len);
+            }
+            TypeDefKind::Handle(Handle::Own(r)) => {
+                self.ins().local_get(base);
+                self.new_resource(*r)?;
+                self.ins().i32_store(m);
+            }
+            TypeDefKind::Type(ty) => self.lower_mem(ty, base, offset)?,
+            TypeDefKind::Handle(Handle::Borrow(_))
+            | TypeDefKind::Resource
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_)
+            | TypeDefKind::Unknown => unreachable!(),
+            kind => {
+                let (tag, cases) = cases(kind).unwrap();
+                let i = self.u.choose_index(cases.len())?;
+                self.ins().local_get(base).i32_const(i as i32);
+                self.store_int(tag, m);
+                if let Some(case) = &cases[i] {
+                    let payload = cx
+                        .sizes
+                        .payload_offset(tag, cases.iter().map(|c| c.as_ref()))
+                        .size_wasm32();
+                    let payload = offset + u32::try_from(payload).unwrap();
+                    self.lower_mem(case, base, payload)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn lower_mem_fields(
+        &mut self,
+        offsets: Vec<(wit_parser::ArchitectureSize, &Type)>,
+        base: u32,
+        offset: u32,
+    ) -> Result<()> {
+        for (field, ty) in offsets {
+            let field = u32::try_from(field.size_wasm32()).unwrap();
+            self.lower_mem(ty, base, offset + field)?;
+        }
+        Ok(())
+    }
+
+    fn store_ptr_len(&mut self, base: u32, offset: u32, ptr: u32, len: u32) {
+        let (m_ptr, m_len) = (self.memarg(offset), self.memarg(offset + 4));
+        self.ins()
+            .local_get(base)
+            .local_get(ptr)
+            .i32_store(m_ptr)
+            .local_get(base)
+            .i32_const(len as i32)
+            .i32_store(m_len);
+    }
+
+    /// Arbitrary bits for the `chunk`th 32-bit chunk of a flags value, with
+    /// the bits that don't correspond to a flag cleared.
+    fn flag_bits(&mut self, flags: usize, chunk: usize) -> Result<u32> {
+        let bits = self.u.arbitrary::<u32>()?;
+        let valid = flags - 32 * chunk;
+        Ok(if valid >= 32 {
+            bits
+        } else {
+            bits & ((1 << valid) - 1)
+        })
+    }
+
+    /// Pushes a new handle to an exported resource with an arbitrary
+    /// representation.
+    fn new_resource(&mut self, resource: TypeId) -> Result<()> {
+        let Resource::Exported { new } = self.cx.resource(resource) else {
+            unreachable!()
+        };
+        let rep = self.u.arbitrary::<i32>()?;
+        self.ins().i32_const(rep).call(new);
+        Ok(())
+    }
+
+    /// Allocates and writes an arbitrary ASCII string, returning the local
+    /// which holds its pointer and its length in code units.
+    fn lower_string(&mut self) -> Result<(u32, u32)> {
+        let len = self.u.int_in_range(0..=self.budget.min(8))?;
+        self.budget -= len;
+        // ASCII strings are also valid latin1 strings, so with
+        // `latin1+utf16` the string is encoded as latin1.
+        let unit = match self.cx.encoding {
+            StringEncoding::UTF8 | StringEncoding::CompactUTF16 => 1,
+            StringEncoding::UTF16 => 2,
+        };
+        let ptr = self.alloc(len * unit, unit);
+        for i in 0..len {
+            let c = self.u.int_in_range(0x20..=0x7e)?;
+            let m = self.memarg(i * unit);
+            let mut ins = self.ins();
+            ins.local_get(ptr).i32_const(c);
+            match unit {
+                1 => ins.i32_store8(m),
+                _ => ins.i32_store16(m),
+            };
+        }
+        Ok((ptr, len))
+    }
+
+    /// Allocates and writes a list of arbitrary elements, returning the local
+    /// which holds its pointer and its length.
+    fn lower_list(&mut self, ty: &Type) -> Result<(u32, u32)> {
+        let len = self.u.int_in_range(0..=self.budget.min(4))?;
+        self.budget -= len;
+        let size = self.cx.size(ty);
+        let ptr = self.alloc(len * size, self.cx.align(ty));
+        for i in 0..len {
+            self.lower_mem(ty, ptr, i * size)?;
+        }
+        Ok((ptr, len))
+    }
+}
+
+/// A bump allocator for `cabi_realloc`, which allocates from the `heap`
+/// global and grows `memory` as necessary.
+///
+/// Memory is never freed, but reallocations copy the original contents.
+pub(super) fn realloc(memory: u32, heap: u32) -> Function {
+    const OLD_PTR: u32 = 0;
+    const OLD_SIZE: u32 = 1;
+    const ALIGN: u32 = 2;
+    const NEW_SIZE: u32 = 3;
+    const RET: u32 = 4;
+    let mut func = Function::new_with_locals_types([ValType::I32]);
+    func.instructions()
+        // ret = (heap + align - 1) & -align
+        .global_get(heap)
+        .local_get(ALIGN)
+        .i32_add()
+        .i32_const(1)
+        .i32_sub()
+        .i32_const(0)
+        .local_get(ALIGN)
+        .i32_sub()
+        .i32_and()
+        .local_tee(RET)
+        // heap = ret + new_size
+        .local_get(NEW_SIZE)
+        .i32_add()
+        .global_set(heap)
+        // Grow the memory by enough pages to hold the new heap end.
+        .block(BlockType::Empty)
+        .global_get(heap)
+        .memory_size(memory)
+        .i32_const(16)
+        .i32_shl()
+        .i32_le_u()
+        .br_if(0)
+        .global_get(heap)
+        .memory_size(memory)
+        .i32_const(16)
+        .i32_shl()
+        .i32_sub()
+        .i32_const(0xffff)
+        .i32_add()
+        .i32_const(16)
+        .i32_shr_u()
+        .memory_grow(memory)
+        .i32_const(-1)
+        .i32_eq()
+        .if_(BlockType::Empty)
+        .unreachable()
+        .end()
+        .end()
+        // Copy the smaller of the old and new sizes from a reallocated
+        // pointer.
+        .local_get(OLD_PTR)
+        .if_(BlockType::Empty)
+        .local_get(RET)
+        .local_get(OLD_PTR)
+        .local_get(OLD_SIZE)
+        .local_get(NEW_SIZE)
+        .local_get(OLD_SIZE)
+        .local_get(NEW_SIZE)
+        .i32_lt_u()
+        .select()
+        .memory_copy(memory, memory)
+        .end()
+        .local_get(RET)
+        .end();
+    func
+}
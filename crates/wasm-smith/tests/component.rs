@@ -2,10 +2,14 @@
 
 use arbitrary::{Arbitrary, Unstructured};
 use rand::{RngCore, SeedableRng, rngs::SmallRng};
+use std::collections::HashSet;
 use wasm_smith::Component;
+use wasmparser::{
+    CanonicalFunction, CanonicalOption, ComponentDefinedType, ComponentType, ComponentTypeRef,
+    Parser, Payload,
+};
 
 #[test]
-#[ignore] // FIXME(#1000): need to update wasm-smith's support for components
 fn smoke_test_component() {
     const NUM_RUNS: usize = 4096;
 
@@ -19,17 +23,7 @@ fn smoke_test_component() {
         if let Ok(component) = Component::arbitrary_take_rest(u) {
             ok_count += 1;
             let component = component.to_bytes();
-
-            let mut validator = wasmparser::Validator::new_with_features(
-                wasmparser::WasmFeatures::default() | wasmparser::WasmFeatures::COMPONENT_MODEL,
-            );
-            if let Err(e) = validator.validate_all(&component) {
-                std::fs::write("component.wasm", &component).unwrap();
-                panic!(
-                    "generated component should be valid; failing binary written \
-                     to `component.wasm`. Error: {e}"
-                );
-            }
+            validate(&component);
         }
     }
 
@@ -40,3 +34,115 @@ fn smoke_test_component() {
         ok_count as f64 / NUM_RUNS as f64 * 100.0
     );
 }
+
+#[test]
+fn smoke_test_canonical_abi() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 8192];
+    let mut seen = HashSet::new();
+
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let u = Unstructured::new(&buf);
+        let Ok(component) = Component::arbitrary_take_rest(u) else {
+            continue;
+        };
+        let component = component.to_bytes();
+        validate(&component);
+
+        for payload in Parser::new(0).parse_all(&component) {
+            match payload.unwrap() {
+                Payload::ComponentTypeSection(reader) => {
+                    for ty in reader {
+                        match ty.unwrap() {
+                            ComponentType::Resource { .. } => {
+                                seen.insert("resource");
+                            }
+                            ComponentType::Defined(ComponentDefinedType::Own(_)) => {
+                                seen.insert("own");
+                            }
+                            ComponentType::Defined(ComponentDefinedType::Borrow(_)) => {
+                                seen.insert("borrow");
+                            }
+                            ComponentType::Defined(ComponentDefinedType::Record(_)) => {
+                                seen.insert("record");
+                            }
+                            ComponentType::Defined(ComponentDefinedType::List(_)) => {
+                                seen.insert("list");
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Payload::ComponentCanonicalSection(reader) => {
+                    for func in reader {
+                        match func.unwrap() {
+                            CanonicalFunction::Lift { options, .. } => {
+                                if options
+                                    .iter()
+                                    .any(|o| matches!(o, CanonicalOption::Realloc(_)))
+                                {
+                                    seen.insert("lift with realloc");
+                                }
+                                if options
+                                    .iter()
+                                    .any(|o| matches!(o, CanonicalOption::PostReturn(_)))
+                                {
+                                    seen.insert("lift with post-return");
+                                }
+                            }
+                            CanonicalFunction::Lower { options, .. } => {
+                                if options
+                                    .iter()
+                                    .any(|o| matches!(o, CanonicalOption::Memory(_)))
+                                {
+                                    seen.insert("lower with memory");
+                                }
+                            }
+                            CanonicalFunction::ResourceNew { .. } => {
+                                seen.insert("resource.new");
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Payload::ComponentImportSection(reader) => {
+                    for import in reader {
+                        if let ComponentTypeRef::Func(_) = import.unwrap().ty {
+                            seen.insert("func import");
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for expected in [
+        "resource",
+        "own",
+        "borrow",
+        "record",
+        "list",
+        "lift with realloc",
+        "lift with post-return",
+        "lower with memory",
+        "resource.new",
+        "func import",
+    ] {
+        assert!(seen.contains(expected), "never generated: {expected}");
+    }
+}
+
+fn validate(component: &[u8]) {
+    let mut validator = wasmparser::Validator::new_with_features(
+        wasmparser::WasmFeatures::default() | wasmparser::WasmFeatures::COMPONENT_MODEL,
+    );
+    if let Err(e) = validator.validate_all(component) {
+        std::fs::write("component.wasm", component).unwrap();
+        panic!(
+            "generated component should be valid; failing binary written \
+             to `component.wasm`. Error: {e}"
+        );
+    }
+}
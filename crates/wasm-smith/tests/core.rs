@@ -1,5 +1,6 @@
 use arbitrary::{Arbitrary, Unstructured};
 use rand::{RngCore, SeedableRng, rngs::SmallRng};
+use std::collections::HashSet;
 use wasm_smith::{Config, Module};
 use wasmparser::{Validator, WasmFeatures};
 
@@ -127,6 +128,43 @@ fn smoke_test_no_trapping_mode() {
     }
 }
 
+#[test]
+fn smoke_test_differential_mode() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = Config::arbitrary(&mut u).unwrap();
+        cfg.differential_mode = true;
+        cfg.relaxed_simd_enabled = true;
+        cfg.threads_enabled = true;
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let features = WasmFeatures::all()
+                - WasmFeatures::RELAXED_SIMD
+                - WasmFeatures::THREADS
+                - WasmFeatures::SHARED_EVERYTHING_THREADS;
+            let mut validator = Validator::new_with_features(features);
+            validate(&mut validator, &wasm_bytes);
+
+            let text = wasmprinter::print_bytes(&wasm_bytes).unwrap();
+            assert!(text.contains(&format!(
+                "(export \"{}\" (func",
+                wasm_smith::DIFFERENTIAL_HASH_EXPORT
+            )));
+            for instr in [
+                "memory.grow",
+                "table.grow",
+                "array.new ",
+                "array.new_default",
+            ] {
+                assert!(!text.contains(instr), "found `{instr}` in:\n{text}");
+            }
+        }
+    }
+}
+
 #[test]
 fn smoke_test_disallow_floats() {
     let mut rng = SmallRng::seed_from_u64(0);
@@ -186,6 +224,40 @@ fn smoke_test_wasm_gc() {
     }
 }
 
+#[test]
+fn smoke_test_wasm_stack_switching() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    let mut seen = HashSet::new();
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let config = Config {
+            stack_switching_enabled: true,
+            ..Config::default()
+        };
+        if let Ok(module) = Module::new(config, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let mut validator = Validator::new_with_features(WasmFeatures::all());
+            validate(&mut validator, &wasm_bytes);
+            let text = wasmprinter::print_bytes(&wasm_bytes).unwrap();
+            for instr in [
+                "cont.new",
+                "cont.bind",
+                "suspend",
+                "resume",
+                "resume_throw",
+                "switch",
+            ] {
+                if text.contains(&format!("{instr} ")) {
+                    seen.insert(instr);
+                }
+            }
+        }
+    }
+    assert_eq!(seen.len(), 6, "only generated {seen:?}");
+}
+
 #[test]
 fn smoke_test_wasm_custom_page_sizes() {
     let mut rng = SmallRng::seed_from_u64(0);
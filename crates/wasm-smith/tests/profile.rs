@@ -0,0 +1,127 @@
+#![cfg(feature = "wasmparser")]
+
+use arbitrary::Unstructured;
+use rand::{RngCore, SeedableRng, rngs::SmallRng};
+use std::collections::HashSet;
+use wasm_smith::{Module, Profile};
+use wasmparser::{Parser, Payload, Validator};
+
+mod common;
+use common::validate;
+
+const CORPUS: &str = r#"
+    (module
+        (import "env" "log" (func $log (param i32)))
+        (import "env" "memory" (memory 1))
+        (func (export "f") (param i32 i32) (result i32)
+            (local i32)
+            local.get 0
+            local.get 1
+            i32.add
+            local.tee 2
+            call $log
+            block
+                local.get 2
+                i32.eqz
+                br_if 0
+                local.get 2
+                i32.const 4
+                i32.load
+                i32.store
+            end
+            local.get 2
+        )
+    )
+"#;
+
+#[test]
+fn smoke_test_profile() {
+    let mut profile = Profile::new();
+    profile
+        .add_module(&wat::parse_str(CORPUS).unwrap())
+        .unwrap();
+    let config = profile.config();
+
+    assert!(!config.simd_enabled);
+    assert!(!config.gc_enabled);
+    assert!(!config.exceptions_enabled);
+    assert!(!config.bulk_memory_enabled);
+    assert_eq!(config.max_funcs, 2);
+    assert_eq!(config.max_memories, 1);
+    assert_eq!(config.max_nesting_depth, 1);
+    assert!(config.available_imports.is_some());
+    let weight = |name: &str| {
+        config
+            .instruction_weights
+            .iter()
+            .find(|(n, _)| *n == name)
+            .unwrap()
+            .1
+    };
+    assert!(weight("local_get") > weight("i32_add"));
+    assert!(weight("i32_add") > 0);
+    assert_eq!(weight("f64_add"), 0);
+
+    // Only operators used by the corpus should be chosen when generating
+    // instructions, aside from those used to produce results of the right
+    // type and to mix dropped values into globals.
+    let allowed = [
+        "drop",
+        "global.get",
+        "global.set",
+        "i32.xor",
+        "i64.xor",
+        "i32.reinterpret_f32",
+        "i64.reinterpret_f64",
+        "i64.const",
+        "f32.const",
+        "f64.const",
+        "local.get",
+        "local.set",
+        "local.tee",
+        "i32.add",
+        "i32.eqz",
+        "i32.const",
+        "i32.load",
+        "i32.store",
+        "call",
+        "block",
+        "br_if",
+        "end",
+    ];
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    let mut seen = HashSet::new();
+    for _ in 0..256 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let config = config.clone();
+        let features = config.features();
+        let Ok(module) = Module::new(config, &mut u) else {
+            continue;
+        };
+        let wasm = module.to_bytes();
+        validate(&mut Validator::new_with_features(features), &wasm);
+
+        for payload in Parser::new(0).parse_all(&wasm) {
+            if let Payload::CodeSectionEntry(body) = payload.unwrap() {
+                let mut reader = body.get_operators_reader().unwrap();
+                while !reader.eof() {
+                    let op = format!("{:?}", reader.read().unwrap());
+                    let name = op.split([' ', '{']).next().unwrap().to_string();
+                    seen.insert(name);
+                }
+            }
+        }
+    }
+    let allowed = allowed
+        .iter()
+        .map(|s| s.replace(['.', '_'], ""))
+        .collect::<HashSet<_>>();
+    for op in seen {
+        assert!(
+            allowed.contains(&op.to_lowercase()),
+            "generated `{op}` which isn't in the corpus"
+        );
+    }
+}
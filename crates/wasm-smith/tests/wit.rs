@@ -0,0 +1,91 @@
+#![cfg(feature = "wit")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use rand::{RngCore, SeedableRng, rngs::SmallRng};
+use wasm_smith::{Config, WitComponent};
+use wit_component::DecodedWasm;
+use wit_parser::{Resolve, TypeDefKind, WorldItem, WorldKey};
+
+#[test]
+fn smoke_test_wit_component() {
+    const NUM_RUNS: usize = 256;
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 8192];
+
+    for _ in 0..NUM_RUNS {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let Ok(wit) =
+            wit_smith::Config::arbitrary(&mut u).and_then(|c| wit_smith::smith(&c, &mut u))
+        else {
+            continue;
+        };
+        let (resolve, _) = match wit_component::decode(&wit).unwrap() {
+            DecodedWasm::WitPackage(resolve, pkg) => (resolve, pkg),
+            DecodedWasm::Component(..) => unreachable!(),
+        };
+        for (world, _) in resolve.worlds.iter().take(4) {
+            let Ok(config) = Config::arbitrary(&mut u) else {
+                break;
+            };
+            let Ok(component) = WitComponent::new(config, &mut u, &resolve, world) else {
+                break;
+            };
+            let wasm = component.to_bytes();
+
+            // The world may use any component model feature, such as `future`
+            // or `stream` types, so validate with everything enabled.
+            let mut validator =
+                wasmparser::Validator::new_with_features(wasmparser::WasmFeatures::all());
+            if let Err(e) = validator.validate_all(&wasm) {
+                std::fs::write("component.wasm", &wasm).unwrap();
+                panic!("generated component should be valid; failed with: {e}");
+            }
+
+            // The component's imports and exports must be those of the world
+            // it was generated for.
+            let (decoded, decoded_world) = match wit_component::decode(&wasm).unwrap() {
+                DecodedWasm::Component(resolve, world) => (resolve, world),
+                DecodedWasm::WitPackage(..) => unreachable!(),
+            };
+            let expected = &resolve.worlds[world];
+            let actual = &decoded.worlds[decoded_world];
+            let imports = names(&decoded, actual.imports.keys());
+            for import in &imports {
+                assert!(
+                    names(&resolve, expected.imports.keys()).contains(import),
+                    "component imports `{import}` which isn't imported by the world"
+                );
+            }
+            // Imports which only provide types may be omitted, but those which
+            // the core module imports functions from must not be.
+            let required = expected.imports.iter().filter(|(_, item)| match item {
+                WorldItem::Function(_) => true,
+                WorldItem::Interface { id, .. } => {
+                    let interface = &resolve.interfaces[*id];
+                    !interface.functions.is_empty()
+                        || interface
+                            .types
+                            .values()
+                            .any(|ty| matches!(resolve.types[*ty].kind, TypeDefKind::Resource))
+                }
+                WorldItem::Type(_) => false,
+            });
+            for import in names(&resolve, required.map(|(key, _)| key)) {
+                assert!(
+                    imports.contains(&import),
+                    "component doesn't import `{import}` from the world"
+                );
+            }
+            assert_eq!(
+                names(&resolve, expected.exports.keys()),
+                names(&decoded, actual.exports.keys())
+            );
+        }
+    }
+}
+
+fn names<'a>(resolve: &Resolve, keys: impl Iterator<Item = &'a WorldKey>) -> Vec<String> {
+    keys.map(|k| resolve.name_world_key(k)).collect()
+}
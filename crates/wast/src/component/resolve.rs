@@ -689,9 +689,12 @@ impl<'a> Resolver<'a> {
                     ComponentTypeDecl::Alias(alias) => {
                         state.register_alias(alias)?;
                     }
-                    ComponentTypeDecl::CoreType(ty) => {
-                        state.core_types.register(ty.id, "core type")?;
-                    }
+                    ComponentTypeDecl::CoreType(ty) => match &ty.def {
+                        CoreTypeDef::Def(_) => {} // done above in `core_ty`
+                        CoreTypeDef::Module(_) => {
+                            state.core_types.register(ty.id, "core type")?;
+                        }
+                    },
                     ComponentTypeDecl::Type(ty) => {
                         state.types.register(ty.id, "type")?;
                     }
@@ -721,9 +724,12 @@ impl<'a> Resolver<'a> {
                     InstanceTypeDecl::Alias(alias) => {
                         state.register_alias(alias)?;
                     }
-                    InstanceTypeDecl::CoreType(ty) => {
-                        state.core_types.register(ty.id, "core type")?;
-                    }
+                    InstanceTypeDecl::CoreType(ty) => match &ty.def {
+                        CoreTypeDef::Def(_) => {} // done above in `core_ty`
+                        CoreTypeDef::Module(_) => {
+                            state.core_types.register(ty.id, "core type")?;
+                        }
+                    },
                     InstanceTypeDecl::Type(ty) => {
                         state.types.register(ty.id, "type")?;
                     }
@@ -2683,6 +2683,7 @@ pub struct ComponentEncoder {
     realloc_via_memory_grow: bool,
     merge_imports_based_on_semver: Option<bool>,
     pub(super) reject_legacy_names: bool,
+    merge_producers: bool,
 }
 
 impl ComponentEncoder {
@@ -2737,6 +2738,18 @@ impl ComponentEncoder {
         self
     }
 
+    /// Sets whether the producers sections of the main module and all adapters
+    /// and libraries are merged into the producers section of the component.
+    ///
+    /// This makes the languages, tools and SDKs used to build the inner
+    /// modules visible at the top level of the component.
+    ///
+    /// This is disabled by default.
+    pub fn merge_producers(mut self, merge: bool) -> Self {
+        self.merge_producers = merge;
+        self
+    }
+
     /// Sets whether to reject the historical mangling/name scheme for core wasm
     /// imports/exports as they map to the component model.
     ///
@@ -2902,9 +2915,22 @@ impl ComponentEncoder {
         for name in self.adapters.keys() {
             state.encode_exports(CustomModule::Adapter(name))?;
         }
+        let producers = if self.merge_producers {
+            let mut producers = wasm_metadata::Producers::empty();
+            for wasm in std::iter::once(&self.module).chain(self.adapters.values().map(|a| &a.wasm))
+            {
+                if let Some(inner) = wasm_metadata::Producers::from_wasm(wasm)? {
+                    producers.merge(&inner);
+                }
+            }
+            producers.merge(&crate::base_producers());
+            producers
+        } else {
+            crate::base_producers()
+        };
         state
             .component
-            .raw_custom_section(&crate::base_producers().raw_custom_section());
+            .raw_custom_section(&producers.raw_custom_section());
         let bytes = state.component.finish();
 
         if self.validate {
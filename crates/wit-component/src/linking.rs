@@ -1229,6 +1229,10 @@ pub struct Linker {
     /// from two different libraries, whether their imports are unified when the
     /// semver version ranges for interface allow it.
     merge_imports_based_on_semver: Option<bool>,
+
+    /// Whether to merge the producers sections of all libraries and adapters
+    /// into the producers section of the component.
+    merge_producers: bool,
 }
 
 impl Linker {
@@ -1286,6 +1290,15 @@ impl Linker {
         self
     }
 
+    /// Specify whether to merge the producers sections of all libraries and
+    /// adapters into the producers section of the component.
+    ///
+    /// See [crate::encoding::ComponentEncoder::merge_producers] for details.
+    pub fn merge_producers(mut self, merge: bool) -> Self {
+        self.merge_producers = merge;
+        self
+    }
+
     /// Encode the component and return the bytes
     pub fn encode(mut self) -> Result<Vec<u8>> {
         if self.use_built_in_libdl {
@@ -1432,7 +1445,9 @@ impl Linker {
             self.stack_size.unwrap_or(DEFAULT_STACK_SIZE_BYTES),
         );
 
-        let mut encoder = ComponentEncoder::default().validate(self.validate);
+        let mut encoder = ComponentEncoder::default()
+            .validate(self.validate)
+            .merge_producers(self.merge_producers);
         if let Some(merge) = self.merge_imports_based_on_semver {
             encoder = encoder.merge_imports_based_on_semver(merge);
         };
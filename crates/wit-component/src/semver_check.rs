@@ -3,33 +3,52 @@ use crate::{
     encoding::encode_world,
 };
 use anyhow::{Context, Result, bail};
+use indexmap::IndexMap;
+use semver::Version;
+use std::fmt;
 use wasm_encoder::{ComponentBuilder, ComponentExportKind, ComponentTypeRef};
 use wasmparser::Validator;
-use wit_parser::{ManglingAndAbi, Resolve, WorldId};
+use wit_parser::{
+    Function, FunctionKind, Handle, InterfaceId, Location, ManglingAndAbi, PackageId, PackageName,
+    Resolve, Stability, Type, TypeDefKind, TypeId, TypeOwner, WorldId, WorldItem, WorldKey,
+};
 
 /// Tests whether `new` is a semver-compatible upgrade from the world `prev`.
 ///
 /// This function is will test whether a WIT-level semver-compatible predicate
 /// holds. Internally this will ignore all versions associated with packages and
 /// will instead test for structural equality between types and such. For
-/// example `new` is allowed to have more imports and fewer exports, but types
-/// and such must have the exact same structure otherwise (e.g. function params,
-/// record fields, etc).
+/// example `new` is allowed to have fewer imports, more exports, and more
+/// imports gated behind an `@unstable` feature, so that hosts of `prev` can
+/// still run components targeting `new`. Types and such must have the exact
+/// same structure otherwise (e.g. function params, record fields, etc).
+///
+/// The returned error lists each breaking change found by [`semver_diff`].
 //
-// NB: the general implementation strategy here is similar to the `targets`
+// NB: after the WIT-level comparison in `semver_diff` finds no breaking
+// changes the general implementation strategy here is similar to the `targets`
 // function where components are synthesized and we effectively rely on
-// wasmparser to figure out everything for us. Specifically what happens is:
+// wasmparser to double-check everything for us. Specifically what happens is:
 //
-// 1. A dummy component representing `prev` is created.
-// 2. A component importing a component of shape `new` is created.
+// 1. A dummy component representing `new` is created.
+// 2. A component importing a component of shape `prev` is created.
 // 3. The component from (2) is instantiated with the component from (1).
 //
 // If that all type-checks and is valid then the semver compatible predicate
 // holds. Otherwise something has gone wrong.
-//
-// Note that this does not produce great error messages, so this implementation
-// likely wants to be improved in the future.
 pub fn semver_check(mut resolve: Resolve, prev: WorldId, new: WorldId) -> Result<()> {
+    let breaking = semver_diff(&resolve, prev, &resolve, new)?
+        .into_iter()
+        .filter(|change| change.severity == SemverSeverity::Major)
+        .collect::<Vec<_>>();
+    if !breaking.is_empty() {
+        let mut msg = String::from("new world is not semver-compatible with the previous world");
+        for change in breaking {
+            msg.push_str(&format!("\n  * {change}"));
+        }
+        bail!("{msg}");
+    }
+
     // First up clear out all version information. This is required to ensure
     // that the strings line up for wasmparser's validation which does exact
     // string matching.
@@ -47,43 +66,34 @@ pub fn semver_check(mut resolve: Resolve, prev: WorldId, new: WorldId) -> Result
         pkg.name.version = None;
     }
 
-    let old_pkg_id = resolve.worlds[prev]
-        .package
-        .context("old world not in named package")?;
-    let old_pkg_name = &resolve.packages[old_pkg_id].name;
-    let new_pkg_id = resolve.worlds[new]
-        .package
-        .context("new world not in named package")?;
-    let new_pkg_name = &resolve.packages[new_pkg_id].name;
-    if old_pkg_name != new_pkg_name {
-        bail!(
-            "the old world is in package {old_pkg_name}, which is not the same as the new world, which is in package {new_pkg_name}",
-        )
-    }
+    // Hosts of `prev` can leave the features of new imports gated behind
+    // `@unstable` disabled, which wasmparser doesn't know about, so they're
+    // removed from `new` before comparing the two.
+    remove_unstable_imports(&mut resolve, prev, new);
 
     // Component that will be validated at the end.
     let mut root_component = ComponentBuilder::default();
 
-    // (1) above - create a dummy component which has the shape of `prev`.
-    let mut prev_as_module = dummy_module(&resolve, prev, ManglingAndAbi::Standard32);
-    embed_component_metadata(&mut prev_as_module, &resolve, prev, StringEncoding::UTF8)
+    // (1) above - create a dummy component which has the shape of `new`.
+    let mut new_as_module = dummy_module(&resolve, new, ManglingAndAbi::Standard32);
+    embed_component_metadata(&mut new_as_module, &resolve, new, StringEncoding::UTF8)
         .context("failed to embed component metadata")?;
-    let prev_as_component = ComponentEncoder::default()
-        .module(&prev_as_module)
-        .context("failed to register previous world encoded as a module")?
+    let new_as_component = ComponentEncoder::default()
+        .module(&new_as_module)
+        .context("failed to register new world encoded as a module")?
         .encode()
-        .context("failed to encode previous world as a component")?;
-    let component_to_test_idx = root_component.component_raw(&prev_as_component);
+        .context("failed to encode new world as a component")?;
+    let component_to_test_idx = root_component.component_raw(&new_as_component);
 
     // (2) above - create a component which imports a component of the shape of
-    // `new`.
+    // `prev`.
     let test_component_idx = {
-        let component_ty =
-            encode_world(&resolve, new).context("failed to encode the new world as a type")?;
+        let component_ty = encode_world(&resolve, prev)
+            .context("failed to encode the previous world as a type")?;
         let mut component = ComponentBuilder::default();
         let component_ty_idx = component.type_component(&component_ty);
         component.import(
-            &resolve.worlds[new].name,
+            &resolve.worlds[prev].name,
             ComponentTypeRef::Component(component_ty_idx),
         );
         root_component.component(component)
@@ -93,7 +103,7 @@ pub fn semver_check(mut resolve: Resolve, prev: WorldId, new: WorldId) -> Result
     root_component.instantiate(
         test_component_idx,
         [(
-            resolve.worlds[new].name.clone(),
+            resolve.worlds[prev].name.clone(),
             ComponentExportKind::Component,
             component_to_test_idx,
         )],
@@ -110,3 +120,1283 @@ pub fn semver_check(mut resolve: Resolve, prev: WorldId, new: WorldId) -> Result
 
     Ok(())
 }
+
+/// Removes the imports of `new`, and the members of interfaces it imports,
+/// which are gated behind `@unstable` and aren't present in `prev`.
+fn remove_unstable_imports(resolve: &mut Resolve, prev: WorldId, new: WorldId) {
+    let is_unstable = |stability: &Stability| matches!(stability, Stability::Unstable { .. });
+    let prev_imports = resolve.worlds[prev]
+        .imports
+        .iter()
+        .map(|(key, item)| (world_key_name(resolve, key), item))
+        .collect::<IndexMap<_, _>>();
+    let mut removed = Vec::new();
+    let mut interfaces = Vec::new();
+    for (key, item) in resolve.worlds[new].imports.iter() {
+        match prev_imports.get(&world_key_name(resolve, key)) {
+            Some(WorldItem::Interface { id: a, .. }) => {
+                if let WorldItem::Interface { id: b, .. } = item {
+                    if a != b {
+                        interfaces.push((*a, *b));
+                    }
+                }
+            }
+            Some(_) => {}
+            None => {
+                if is_unstable(item.stability(resolve)) {
+                    removed.push(key.clone());
+                }
+            }
+        }
+    }
+    for key in removed {
+        resolve.worlds[new].imports.shift_remove(&key);
+    }
+    for (a, b) in interfaces {
+        let prev = &resolve.interfaces[a];
+        let new = &resolve.interfaces[b];
+        let types = new
+            .types
+            .iter()
+            .filter(|(name, id)| {
+                !prev.types.contains_key(*name) && is_unstable(&resolve.types[**id].stability)
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let functions = new
+            .functions
+            .iter()
+            .filter(|(name, func)| {
+                !prev.functions.contains_key(*name) && is_unstable(&func.stability)
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let new = &mut resolve.interfaces[b];
+        for name in types {
+            new.types.shift_remove(&name);
+        }
+        for name in functions {
+            new.functions.shift_remove(&name);
+        }
+    }
+}
+
+/// A difference between two versions of a world found by [`semver_diff`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SemverChange {
+    /// Whether this change is backwards-compatible or not.
+    pub severity: SemverSeverity,
+    /// Whether `item` was added, removed or changed.
+    pub kind: SemverChangeKind,
+    /// A description of the item which changed, for example
+    /// "field `x` of record `r` of import `a:b/c`".
+    pub item: String,
+    /// Further details about how the item changed, if any.
+    pub detail: Option<String>,
+    /// The location in WIT source text of the item.
+    ///
+    /// Removed items point into the previous world's sources and all other
+    /// items point into the new world's sources. This is `None` if the
+    /// [`Resolve`] doesn't know the location of the item, for example if it
+    /// was decoded from a WebAssembly binary.
+    pub location: Option<Location>,
+    /// The stability annotation of an added item in the new world's sources.
+    ///
+    /// This is `None` for items which aren't added or which can't be
+    /// annotated, such as record fields.
+    pub stability: Option<Stability>,
+}
+
+impl fmt::Display for SemverChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {} was {}", self.severity, self.item, self.kind)?;
+        if let Some(detail) = &self.detail {
+            write!(f, ": {detail}")?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, " (at {location})")?;
+        }
+        Ok(())
+    }
+}
+
+/// How a [`SemverChange`] affects the version of a world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SemverSeverity {
+    /// No change to the API, which only requires a patch version bump.
+    ///
+    /// This is never the severity of an individual [`SemverChange`], only of
+    /// an empty set of changes.
+    Patch,
+    /// A backwards-compatible change, which requires a minor version bump.
+    Minor,
+    /// A breaking change, which requires a major version bump.
+    Major,
+}
+
+impl fmt::Display for SemverSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SemverSeverity::Patch => f.write_str("patch"),
+            SemverSeverity::Minor => f.write_str("minor"),
+            SemverSeverity::Major => f.write_str("major"),
+        }
+    }
+}
+
+/// What happened to the item of a [`SemverChange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SemverChangeKind {
+    /// The item is only present in the new world.
+    Added,
+    /// The item is only present in the previous world.
+    Removed,
+    /// The item is present in both worlds but differs.
+    Changed,
+}
+
+impl fmt::Display for SemverChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SemverChangeKind::Added => f.write_str("added"),
+            SemverChangeKind::Removed => f.write_str("removed"),
+            SemverChangeKind::Changed => f.write_str("changed"),
+        }
+    }
+}
+
+/// Compares the world `prev` in `prev_resolve` against the world `new` in
+/// `new_resolve`, returning every difference between the two.
+///
+/// The two worlds may live in the same [`Resolve`] or in different ones, for
+/// example when comparing two versions of a package. Versions of packages are
+/// ignored when matching up interfaces between the two worlds.
+///
+/// Changes are classified following [`semver_check`]: a host of `prev` must be
+/// able to run components targeting `new`. Removing imports, adding exports,
+/// and adding imports gated behind an `@unstable` feature is therefore
+/// [`SemverSeverity::Minor`]. Adding any other import, removing exports, and
+/// any change to the structure of a function or type is
+/// [`SemverSeverity::Major`]. The same rules apply to the members of imported
+/// and exported interfaces.
+///
+/// Returns an error if the two worlds aren't in the same package.
+pub fn semver_diff(
+    prev_resolve: &Resolve,
+    prev: WorldId,
+    new_resolve: &Resolve,
+    new: WorldId,
+) -> Result<Vec<SemverChange>> {
+    let package_name = |resolve: &Resolve, world: WorldId, which: &str| {
+        let pkg = resolve.worlds[world]
+            .package
+            .with_context(|| format!("{which} world not in named package"))?;
+        Ok::<_, anyhow::Error>(resolve.packages[pkg].name.clone())
+    };
+    let old_pkg_name = package_name(prev_resolve, prev, "old")?;
+    let new_pkg_name = package_name(new_resolve, new, "new")?;
+    if !same_package(&old_pkg_name, &new_pkg_name) {
+        bail!(
+            "the old world is in package {old_pkg_name}, which is not the same as the new world, which is in package {new_pkg_name}",
+        )
+    }
+
+    let mut diff = Diff {
+        prev: prev_resolve,
+        new: new_resolve,
+        packages: None,
+        changes: Vec::new(),
+    };
+    diff.world(prev, new, "");
+    Ok(diff.changes)
+}
+
+/// Compares the package `prev` in `prev_resolve` against the package `new` in
+/// `new_resolve`, returning every difference between the two.
+///
+/// Interfaces and worlds of the two packages are matched up by name. Adding
+/// interfaces, worlds, or items to an interface is a
+/// [`SemverSeverity::Minor`] change while removing them is a
+/// [`SemverSeverity::Major`] change. Worlds present in both packages are
+/// compared with the same rules as [`semver_diff`].
+///
+/// Returns an error if the two packages have different names, ignoring their
+/// versions.
+pub fn semver_diff_packages(
+    prev_resolve: &Resolve,
+    prev: PackageId,
+    new_resolve: &Resolve,
+    new: PackageId,
+) -> Result<Vec<SemverChange>> {
+    let prev_pkg = &prev_resolve.packages[prev];
+    let new_pkg = &new_resolve.packages[new];
+    if !same_package(&prev_pkg.name, &new_pkg.name) {
+        bail!(
+            "the old package is {}, which is not the same as the new package {}",
+            prev_pkg.name,
+            new_pkg.name,
+        )
+    }
+
+    let mut diff = Diff {
+        prev: prev_resolve,
+        new: new_resolve,
+        packages: Some((prev, new)),
+        changes: Vec::new(),
+    };
+    for (name, a) in prev_pkg.interfaces.iter() {
+        let item = format!("interface `{name}`");
+        match new_pkg.interfaces.get(name) {
+            Some(b) => diff.interface(Direction::Definition, &item, *a, *b),
+            None => {
+                let location = prev_resolve.locations.interface(*a);
+                let stability = &prev_resolve.interfaces[*a].stability;
+                diff.added_or_removed(Direction::Definition, Side::Prev, item, location, stability);
+            }
+        }
+    }
+    for (name, b) in new_pkg.interfaces.iter() {
+        if !prev_pkg.interfaces.contains_key(name) {
+            let location = new_resolve.locations.interface(*b);
+            let stability = &new_resolve.interfaces[*b].stability;
+            let item = format!("interface `{name}`");
+            diff.added_or_removed(Direction::Definition, Side::New, item, location, stability);
+        }
+    }
+    for (name, a) in prev_pkg.worlds.iter() {
+        match new_pkg.worlds.get(name) {
+            Some(b) => diff.world(*a, *b, &format!(" of world `{name}`")),
+            None => {
+                let location = prev_resolve.locations.world(*a);
+                let stability = &prev_resolve.worlds[*a].stability;
+                let item = format!("world `{name}`");
+                diff.added_or_removed(Direction::Definition, Side::Prev, item, location, stability);
+            }
+        }
+    }
+    for (name, b) in new_pkg.worlds.iter() {
+        if !prev_pkg.worlds.contains_key(name) {
+            let location = new_resolve.locations.world(*b);
+            let stability = &new_resolve.worlds[*b].stability;
+            let item = format!("world `{name}`");
+            diff.added_or_removed(Direction::Definition, Side::New, item, location, stability);
+        }
+    }
+    Ok(diff.changes)
+}
+
+/// The result of [`semver_suggest`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SemverSuggestion {
+    /// The kind of version bump required by `changes`.
+    pub bump: SemverSeverity,
+    /// The next version of the package, based on the version of the previous
+    /// package and `bump`.
+    ///
+    /// This is `None` if the previous package has no version.
+    pub version: Option<Version>,
+    /// Every difference between the two packages.
+    pub changes: Vec<SemverChange>,
+    /// Problems with the version of the new package and the `@since`
+    /// annotations of items added in it.
+    pub errors: Vec<SemverVersionError>,
+}
+
+/// A problem found by [`semver_suggest`] with the version of the new package
+/// or the `@since` annotation of an item added in it.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SemverVersionError {
+    /// A description of the problem.
+    pub message: String,
+    /// The location in WIT source text of the problem, if known.
+    pub location: Option<Location>,
+}
+
+impl fmt::Display for SemverVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if let Some(location) = &self.location {
+            write!(f, " (at {location})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Suggests the next version of the package `prev` in `prev_resolve` given
+/// its new contents, the package `new` in `new_resolve`.
+///
+/// The two packages are compared with [`semver_diff_packages`] and the most
+/// severe change determines the suggested version bump. Following Cargo's
+/// conventions a package with a major version of 0 has its minor version
+/// bumped for breaking changes and its patch version bumped otherwise. A
+/// pre-release is bumped to its release, such as `1.0.0-rc.1` to `1.0.0`,
+/// unless that isn't a large enough bump for the changes. Build metadata of
+/// the previous version is dropped.
+///
+/// The version the new package is proposed to be released as is its own
+/// version, if it has one, or otherwise the suggested version. This is
+/// verified to be at least the suggested version, and items added in the new
+/// package must have an `@since(version = ...)` annotation matching the
+/// proposed version. Added items without any annotation are only reported if
+/// the new package uses `@since` elsewhere.
+pub fn semver_suggest(
+    prev_resolve: &Resolve,
+    prev: PackageId,
+    new_resolve: &Resolve,
+    new: PackageId,
+) -> Result<SemverSuggestion> {
+    let changes = semver_diff_packages(prev_resolve, prev, new_resolve, new)?;
+    let bump = changes
+        .iter()
+        .map(|c| c.severity)
+        .max()
+        .unwrap_or(SemverSeverity::Patch);
+    let version = prev_resolve.packages[prev]
+        .name
+        .version
+        .as_ref()
+        .map(|v| next_version(v, bump));
+
+    let mut errors = Vec::new();
+    let new_version = new_resolve.packages[new].name.version.as_ref();
+    if let (Some(new_version), Some(suggested)) = (new_version, &version) {
+        if new_version < suggested {
+            errors.push(SemverVersionError {
+                message: format!(
+                    "package version {new_version} is lower than the suggested version \
+                     {suggested} for a {bump} change"
+                ),
+                location: None,
+            });
+        }
+    }
+
+    if let Some(proposed) = new_version.or(version.as_ref()) {
+        let uses_since = package_uses_since(new_resolve, new);
+        for change in changes.iter() {
+            let found = match &change.stability {
+                Some(Stability::Stable { since, .. }) => Some(since),
+                Some(Stability::Unknown) if uses_since => None,
+                _ => continue,
+            };
+            let message = match found {
+                Some(since) if since == proposed => continue,
+                Some(since) => format!(
+                    "{} is annotated with `@since(version = {since})` but is added in \
+                     version {proposed}",
+                    change.item
+                ),
+                None => format!(
+                    "{} is missing an `@since(version = {proposed})` annotation",
+                    change.item
+                ),
+            };
+            errors.push(SemverVersionError {
+                message,
+                location: change.location.clone(),
+            });
+        }
+    }
+
+    Ok(SemverSuggestion {
+        bump,
+        version,
+        changes,
+        errors,
+    })
+}
+
+fn next_version(prev: &Version, bump: SemverSeverity) -> Version {
+    let bump = match (bump, prev.major) {
+        (SemverSeverity::Major, 0) => SemverSeverity::Minor,
+        (SemverSeverity::Minor, 0) => SemverSeverity::Patch,
+        (bump, _) => bump,
+    };
+    let mut next = Version::new(prev.major, prev.minor, prev.patch);
+
+    // A pre-release is bumped to its release version if that release is
+    // already a large enough bump. For example `1.0.0-rc.1` becomes `1.0.0`
+    // for any change while `1.2.0-rc.1` becomes `2.0.0` for a major change.
+    if !prev.pre.is_empty() {
+        let release = if prev.patch != 0 {
+            SemverSeverity::Patch
+        } else if prev.minor != 0 || prev.major == 0 {
+            SemverSeverity::Minor
+        } else {
+            SemverSeverity::Major
+        };
+        if bump <= release {
+            return next;
+        }
+    }
+
+    match bump {
+        SemverSeverity::Major => {
+            next.major += 1;
+            next.minor = 0;
+            next.patch = 0;
+        }
+        SemverSeverity::Minor => {
+            next.minor += 1;
+            next.patch = 0;
+        }
+        SemverSeverity::Patch => next.patch += 1,
+    }
+    next
+}
+
+/// Returns whether any item in the package `pkg` has an `@since`
+/// annotation.
+fn package_uses_since(resolve: &Resolve, pkg: PackageId) -> bool {
+    let is_stable = |s: &Stability| matches!(s, Stability::Stable { .. });
+    let pkg = &resolve.packages[pkg];
+    pkg.interfaces.values().any(|id| {
+        let iface = &resolve.interfaces[*id];
+        is_stable(&iface.stability)
+            || iface.functions.values().any(|f| is_stable(&f.stability))
+            || iface
+                .types
+                .values()
+                .any(|t| is_stable(&resolve.types[*t].stability))
+    }) || pkg.worlds.values().any(|id| {
+        let world = &resolve.worlds[*id];
+        is_stable(&world.stability)
+            || world
+                .imports
+                .values()
+                .chain(world.exports.values())
+                .any(|item| is_stable(item.stability(resolve)))
+    })
+}
+
+fn same_package(a: &PackageName, b: &PackageName) -> bool {
+    a.namespace == b.namespace && a.name == b.name
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Import,
+    Export,
+    /// Items defined by a package, as compared by `semver_diff_packages`.
+    Definition,
+}
+
+impl Direction {
+    /// Returns the severity of adding or removing an item with `stability` in
+    /// this direction.
+    ///
+    /// Worlds are compared from the point of view of a host: a host of the
+    /// previous world must still be able to run components targeting the new
+    /// world. Such a host doesn't provide new imports, unless they're gated
+    /// behind a feature which it may leave disabled, and calls all of the
+    /// exports it already knows about. Adding imports and removing exports is
+    /// therefore major while removing imports and adding exports is minor.
+    /// This applies to items of a world as well as to the members of the
+    /// interfaces it imports or exports.
+    ///
+    /// Packages may gain new definitions but not lose them.
+    fn severity(&self, kind: SemverChangeKind, stability: &Stability) -> SemverSeverity {
+        match (self, kind) {
+            (Direction::Import, SemverChangeKind::Added) => match stability {
+                Stability::Unstable { .. } => SemverSeverity::Minor,
+                _ => SemverSeverity::Major,
+            },
+            (Direction::Import, SemverChangeKind::Removed)
+            | (Direction::Export, SemverChangeKind::Added)
+            | (Direction::Definition, SemverChangeKind::Added) => SemverSeverity::Minor,
+            _ => SemverSeverity::Major,
+        }
+    }
+}
+
+/// Which side of the comparison an item comes from.
+#[derive(Clone, Copy)]
+enum Side {
+    Prev,
+    New,
+}
+
+struct Diff<'a> {
+    prev: &'a Resolve,
+    new: &'a Resolve,
+    /// The packages being compared by `semver_diff_packages`, whose
+    /// interfaces are compared on their own rather than as part of worlds.
+    packages: Option<(PackageId, PackageId)>,
+    changes: Vec<SemverChange>,
+}
+
+impl<'a> Diff<'a> {
+    fn resolve(&self, side: Side) -> &'a Resolve {
+        match side {
+            Side::Prev => self.prev,
+            Side::New => self.new,
+        }
+    }
+
+    fn push(
+        &mut self,
+        severity: SemverSeverity,
+        kind: SemverChangeKind,
+        item: String,
+        detail: Option<String>,
+        location: Option<Location>,
+    ) {
+        self.changes.push(SemverChange {
+            severity,
+            kind,
+            item,
+            detail,
+            location,
+            stability: None,
+        });
+    }
+
+    /// Reports an item which is only present on one side of the comparison.
+    fn added_or_removed(
+        &mut self,
+        direction: Direction,
+        side: Side,
+        item: String,
+        location: Option<Location>,
+        stability: &Stability,
+    ) {
+        let kind = match side {
+            Side::Prev => SemverChangeKind::Removed,
+            Side::New => SemverChangeKind::Added,
+        };
+        self.push(
+            direction.severity(kind, stability),
+            kind,
+            item,
+            None,
+            location,
+        );
+        if let Side::New = side {
+            self.changes.last_mut().unwrap().stability = Some(stability.clone());
+        }
+    }
+
+    fn changed(&mut self, item: String, detail: String, location: Option<Location>) {
+        self.push(
+            SemverSeverity::Major,
+            SemverChangeKind::Changed,
+            item,
+            Some(detail),
+            location,
+        );
+    }
+
+    /// Compares the imports and exports of two worlds, where `suffix` is
+    /// appended to the description of each item.
+    fn world(&mut self, prev: WorldId, new: WorldId, suffix: &str) {
+        self.world_items(Direction::Import, prev, new, suffix);
+        self.world_items(Direction::Export, prev, new, suffix);
+    }
+
+    fn world_items(&mut self, direction: Direction, prev: WorldId, new: WorldId, suffix: &str) {
+        let items = |resolve: &'a Resolve, world: WorldId| {
+            let world = &resolve.worlds[world];
+            let items = match direction {
+                Direction::Import => &world.imports,
+                Direction::Export => &world.exports,
+                Direction::Definition => unreachable!(),
+            };
+            items
+                .iter()
+                .map(|(key, item)| (world_key_name(resolve, key), (key, item)))
+                .collect::<IndexMap<_, _>>()
+        };
+        let prev_items = items(self.prev, prev);
+        let new_items = items(self.new, new);
+        let prefix = match direction {
+            Direction::Import => "import",
+            Direction::Export => "export",
+            Direction::Definition => unreachable!(),
+        };
+
+        for (name, (prev_key, prev_item)) in prev_items.iter() {
+            let item = format!("{prefix} `{name}`{suffix}");
+            let Some((new_key, new_item)) = new_items.get(name) else {
+                let location =
+                    self.world_item_location(direction, Side::Prev, prev, prev_key, prev_item);
+                let stability = prev_item.stability(self.prev);
+                self.added_or_removed(direction, Side::Prev, item, location, stability);
+                continue;
+            };
+            match (prev_item, new_item) {
+                (WorldItem::Interface { id: a, .. }, WorldItem::Interface { id: b, .. }) => {
+                    let local = self.packages.is_some_and(|(pa, pb)| {
+                        self.prev.interfaces[*a].package == Some(pa)
+                            && self.new.interfaces[*b].package == Some(pb)
+                    });
+                    if !local {
+                        self.interface(direction, &item, *a, *b)
+                    }
+                }
+                (WorldItem::Function(a), WorldItem::Function(b)) => {
+                    let location =
+                        self.world_item_location(direction, Side::New, new, new_key, new_item);
+                    self.function(&item, a, b, location)
+                }
+                (WorldItem::Type(a), WorldItem::Type(b)) => self.type_def(&item, *a, *b),
+                _ => {
+                    let location =
+                        self.world_item_location(direction, Side::New, new, new_key, new_item);
+                    self.changed(
+                        item,
+                        format!(
+                            "changed from {} to {}",
+                            world_item_desc(prev_item),
+                            world_item_desc(new_item)
+                        ),
+                        location,
+                    )
+                }
+            }
+        }
+        for (name, (new_key, new_item)) in new_items.iter() {
+            if prev_items.contains_key(name) {
+                continue;
+            }
+            let location = self.world_item_location(direction, Side::New, new, new_key, new_item);
+            let item = format!("{prefix} `{name}`{suffix}");
+            let stability = new_item.stability(self.new);
+            self.added_or_removed(direction, Side::New, item, location, stability);
+        }
+    }
+
+    /// Returns the location of an import or export of a world, falling back
+    /// to the location of the item itself or of the world.
+    fn world_item_location(
+        &self,
+        direction: Direction,
+        side: Side,
+        world: WorldId,
+        key: &WorldKey,
+        item: &WorldItem,
+    ) -> Option<Location> {
+        let resolve = self.resolve(side);
+        match direction {
+            Direction::Import => resolve.locations.world_import(world, key),
+            Direction::Export => resolve.locations.world_export(world, key),
+            Direction::Definition => unreachable!(),
+        }
+        .or_else(|| match item {
+            WorldItem::Interface { id, .. } => resolve.locations.interface(*id),
+            WorldItem::Type(id) => resolve.locations.ty(*id),
+            WorldItem::Function(_) => None,
+        })
+        .or_else(|| resolve.locations.world(world))
+    }
+
+    fn interface(&mut self, direction: Direction, parent: &str, a: InterfaceId, b: InterfaceId) {
+        let prev = &self.prev.interfaces[a];
+        let new = &self.new.interfaces[b];
+
+        for (name, prev_ty) in prev.types.iter() {
+            let desc = format!("`{name}` of {parent}");
+            match new.types.get(name) {
+                Some(new_ty) => self.type_def(&desc, *prev_ty, *new_ty),
+                None => {
+                    let item = format!("type {desc}");
+                    let location = self.prev.locations.ty(*prev_ty);
+                    let stability = &self.prev.types[*prev_ty].stability;
+                    self.added_or_removed(direction, Side::Prev, item, location, stability);
+                }
+            }
+        }
+        for (name, new_ty) in new.types.iter() {
+            if !prev.types.contains_key(name) {
+                let location = self.new.locations.ty(*new_ty);
+                let item = format!("type `{name}` of {parent}");
+                let stability = &self.new.types[*new_ty].stability;
+                self.added_or_removed(direction, Side::New, item, location, stability);
+            }
+        }
+
+        for (name, prev_func) in prev.functions.iter() {
+            let item = format!("{} of {parent}", function_desc(self.prev, prev_func));
+            match new.functions.get(name) {
+                Some(new_func) => {
+                    let location = self.new.locations.function(b, name);
+                    self.function(&item, prev_func, new_func, location)
+                }
+                None => {
+                    let location = self.prev.locations.function(a, name);
+                    let stability = &prev_func.stability;
+                    self.added_or_removed(direction, Side::Prev, item, location, stability);
+                }
+            }
+        }
+        for (name, new_func) in new.functions.iter() {
+            if !prev.functions.contains_key(name) {
+                let location = self.new.locations.function(b, name);
+                let item = format!("{} of {parent}", function_desc(self.new, new_func));
+                let stability = &new_func.stability;
+                self.added_or_removed(direction, Side::New, item, location, stability);
+            }
+        }
+    }
+
+    fn function(&mut self, item: &str, a: &Function, b: &Function, location: Option<Location>) {
+        let len = a.params.len().max(b.params.len());
+        for i in 0..len {
+            match (a.params.get(i), b.params.get(i)) {
+                (Some((a_name, a_ty)), Some((b_name, b_ty))) => {
+                    if a_name != b_name {
+                        self.changed(
+                            format!("parameter {i} of {item}"),
+                            format!("renamed from `{a_name}` to `{b_name}`"),
+                            location.clone(),
+                        );
+                    } else if !self.ty_eq(a_ty, b_ty) {
+                        self.changed(
+                            format!("parameter `{a_name}` of {item}"),
+                            self.type_change(a_ty, b_ty),
+                            location.clone(),
+                        );
+                    }
+                }
+                (Some((name, _)), None) => self.push(
+                    SemverSeverity::Major,
+                    SemverChangeKind::Removed,
+                    format!("parameter `{name}` of {item}"),
+                    None,
+                    location.clone(),
+                ),
+                (None, Some((name, _))) => self.push(
+                    SemverSeverity::Major,
+                    SemverChangeKind::Added,
+                    format!("parameter `{name}` of {item}"),
+                    None,
+                    location.clone(),
+                ),
+                (None, None) => unreachable!(),
+            }
+        }
+
+        let same_result = match (&a.result, &b.result) {
+            (Some(a), Some(b)) => self.ty_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        if !same_result {
+            let detail = format!(
+                "changed from {} to {}",
+                self.result_name(Side::Prev, a.result.as_ref()),
+                self.result_name(Side::New, b.result.as_ref()),
+            );
+            self.changed(format!("result of {item}"), detail, location);
+        }
+    }
+
+    fn result_name(&self, side: Side, ty: Option<&Type>) -> String {
+        match ty {
+            Some(ty) => format!("`{}`", type_name(self.resolve(side), ty)),
+            None => "nothing".to_string(),
+        }
+    }
+
+    fn type_change(&self, a: &Type, b: &Type) -> String {
+        format!(
+            "type changed from `{}` to `{}`",
+            type_name(self.prev, a),
+            type_name(self.new, b)
+        )
+    }
+
+    /// Compares the definitions of two named types, where `desc` describes
+    /// the type without its kind.
+    fn type_def(&mut self, desc: &str, a: TypeId, b: TypeId) {
+        let location = self.new.locations.ty(b);
+        let prev = &self.prev.types[a];
+        let new = &self.new.types[b];
+        let item = format!("{} {desc}", prev.kind.as_str());
+
+        match (&prev.kind, &new.kind) {
+            (TypeDefKind::Record(x), TypeDefKind::Record(y)) => self.named_members(
+                &item,
+                "field",
+                x.fields.iter().map(|f| (f.name.as_str(), Some(&f.ty))),
+                y.fields.iter().map(|f| (f.name.as_str(), Some(&f.ty))),
+                location,
+            ),
+            (TypeDefKind::Variant(x), TypeDefKind::Variant(y)) => self.named_members(
+                &item,
+                "case",
+                x.cases.iter().map(|c| (c.name.as_str(), c.ty.as_ref())),
+                y.cases.iter().map(|c| (c.name.as_str(), c.ty.as_ref())),
+                location,
+            ),
+            (TypeDefKind::Enum(x), TypeDefKind::Enum(y)) => self.named_members(
+                &item,
+                "case",
+                x.cases.iter().map(|c| (c.name.as_str(), None)),
+                y.cases.iter().map(|c| (c.name.as_str(), None)),
+                location,
+            ),
+            (TypeDefKind::Flags(x), TypeDefKind::Flags(y)) => self.named_members(
+                &item,
+                "flag",
+                x.flags.iter().map(|f| (f.name.as_str(), None)),
+                y.flags.iter().map(|f| (f.name.as_str(), None)),
+                location,
+            ),
+            (TypeDefKind::Resource, TypeDefKind::Resource) => {}
+            (a_kind, b_kind) if a_kind.as_str() == b_kind.as_str() => {
+                if !self.kind_eq(a_kind, b_kind) {
+                    let detail = format!(
+                        "changed from `{}` to `{}`",
+                        type_def_body(self.prev, a_kind),
+                        type_def_body(self.new, b_kind)
+                    );
+                    self.changed(item, detail, location);
+                }
+            }
+            (a_kind, b_kind) => self.changed(
+                item,
+                format!(
+                    "changed from a {} to a {}",
+                    a_kind.as_str(),
+                    b_kind.as_str()
+                ),
+                location,
+            ),
+        }
+    }
+
+    /// Compares the fields of records, or cases of variants, enums and flags.
+    fn named_members<'b>(
+        &mut self,
+        item: &str,
+        member: &str,
+        a: impl Iterator<Item = (&'b str, Option<&'b Type>)>,
+        b: impl Iterator<Item = (&'b str, Option<&'b Type>)>,
+        location: Option<Location>,
+    ) {
+        let a = a.collect::<IndexMap<_, _>>();
+        let b = b.collect::<IndexMap<_, _>>();
+        for (name, a_ty) in a.iter() {
+            let member_item = format!("{member} `{name}` of {item}");
+            match b.get(name) {
+                Some(b_ty) => {
+                    let same = match (a_ty, b_ty) {
+                        (Some(a), Some(b)) => self.ty_eq(a, b),
+                        (None, None) => true,
+                        _ => false,
+                    };
+                    if !same {
+                        let detail = format!(
+                            "type changed from {} to {}",
+                            self.result_name(Side::Prev, *a_ty),
+                            self.result_name(Side::New, *b_ty),
+                        );
+                        self.changed(member_item, detail, location.clone());
+                    }
+                }
+                None => self.push(
+                    SemverSeverity::Major,
+                    SemverChangeKind::Removed,
+                    member_item,
+                    None,
+                    location.clone(),
+                ),
+            }
+        }
+        for name in b.keys() {
+            if !a.contains_key(name) {
+                self.push(
+                    SemverSeverity::Major,
+                    SemverChangeKind::Added,
+                    format!("{member} `{name}` of {item}"),
+                    None,
+                    location.clone(),
+                );
+            }
+        }
+        let common_a = a.keys().filter(|k| b.contains_key(*k));
+        let common_b = b.keys().filter(|k| a.contains_key(*k));
+        if !common_a.eq(common_b) {
+            self.changed(
+                item.to_string(),
+                format!("{member}s were reordered"),
+                location,
+            );
+        }
+    }
+
+    /// Tests whether two types are the same.
+    ///
+    /// Named types are compared by name, and their definitions are compared
+    /// separately as part of the interface or world which defines them.
+    fn ty_eq(&self, a: &Type, b: &Type) -> bool {
+        match (a, b) {
+            (Type::Id(a), Type::Id(b)) => self.type_id_eq(*a, *b),
+            (Type::Id(_), _) | (_, Type::Id(_)) => false,
+            (a, b) => a == b,
+        }
+    }
+
+    fn type_id_eq(&self, a: TypeId, b: TypeId) -> bool {
+        let a_def = &self.prev.types[a];
+        let b_def = &self.new.types[b];
+        match (&a_def.name, &b_def.name) {
+            (Some(a_name), Some(b_name)) => {
+                a_name == b_name
+                    && type_owner_name(self.prev, a_def.owner)
+                        == type_owner_name(self.new, b_def.owner)
+            }
+            (None, None) => self.kind_eq(&a_def.kind, &b_def.kind),
+            _ => false,
+        }
+    }
+
+    fn opt_ty_eq(&self, a: Option<&Type>, b: Option<&Type>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => self.ty_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Structurally compares two type definitions.
+    fn kind_eq(&self, a: &TypeDefKind, b: &TypeDefKind) -> bool {
+        match (a, b) {
+            (TypeDefKind::Record(a), TypeDefKind::Record(b)) => {
+                a.fields.len() == b.fields.len()
+                    && a.fields
+                        .iter()
+                        .zip(&b.fields)
+                        .all(|(a, b)| a.name == b.name && self.ty_eq(&a.ty, &b.ty))
+            }
+            (TypeDefKind::Variant(a), TypeDefKind::Variant(b)) => {
+                a.cases.len() == b.cases.len()
+                    && a.cases.iter().zip(&b.cases).all(|(a, b)| {
+                        a.name == b.name && self.opt_ty_eq(a.ty.as_ref(), b.ty.as_ref())
+                    })
+            }
+            (TypeDefKind::Enum(a), TypeDefKind::Enum(b)) => a
+                .cases
+                .iter()
+                .map(|c| &c.name)
+                .eq(b.cases.iter().map(|c| &c.name)),
+            (TypeDefKind::Flags(a), TypeDefKind::Flags(b)) => a
+                .flags
+                .iter()
+                .map(|f| &f.name)
+                .eq(b.flags.iter().map(|f| &f.name)),
+            (TypeDefKind::Tuple(a), TypeDefKind::Tuple(b)) => {
+                a.types.len() == b.types.len()
+                    && a.types.iter().zip(&b.types).all(|(a, b)| self.ty_eq(a, b))
+            }
+            (TypeDefKind::Handle(Handle::Own(a)), TypeDefKind::Handle(Handle::Own(b)))
+            | (TypeDefKind::Handle(Handle::Borrow(a)), TypeDefKind::Handle(Handle::Borrow(b))) => {
+                self.type_id_eq(*a, *b)
+            }
+            (TypeDefKind::Option(a), TypeDefKind::Option(b))
+            | (TypeDefKind::List(a), TypeDefKind::List(b))
+            | (TypeDefKind::Type(a), TypeDefKind::Type(b)) => self.ty_eq(a, b),
+            (TypeDefKind::FixedSizeList(a, n), TypeDefKind::FixedSizeList(b, m)) => {
+                n == m && self.ty_eq(a, b)
+            }
+            (TypeDefKind::Result(a), TypeDefKind::Result(b)) => {
+                self.opt_ty_eq(a.ok.as_ref(), b.ok.as_ref())
+                    && self.opt_ty_eq(a.err.as_ref(), b.err.as_ref())
+            }
+            (TypeDefKind::Future(a), TypeDefKind::Future(b))
+            | (TypeDefKind::Stream(a), TypeDefKind::Stream(b)) => {
+                self.opt_ty_eq(a.as_ref(), b.as_ref())
+            }
+            (TypeDefKind::Resource, TypeDefKind::Resource) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Returns the name of a world item's key, without any package versions.
+fn world_key_name(resolve: &Resolve, key: &WorldKey) -> String {
+    match key {
+        WorldKey::Name(name) => name.clone(),
+        WorldKey::Interface(id) => interface_name(resolve, *id),
+    }
+}
+
+/// Returns the fully-qualified name of an interface without its package's
+/// version, or `<anonymous>` for interfaces without a name.
+fn interface_name(resolve: &Resolve, id: InterfaceId) -> String {
+    let iface = &resolve.interfaces[id];
+    match (&iface.name, iface.package) {
+        (Some(name), Some(pkg)) => {
+            let pkg = &resolve.packages[pkg].name;
+            format!("{}:{}/{name}", pkg.namespace, pkg.name)
+        }
+        (Some(name), None) => name.clone(),
+        (None, _) => "<anonymous>".to_string(),
+    }
+}
+
+fn type_owner_name(resolve: &Resolve, owner: TypeOwner) -> Option<String> {
+    match owner {
+        TypeOwner::Interface(id) => Some(interface_name(resolve, id)),
+        TypeOwner::World(id) => Some(resolve.worlds[id].name.clone()),
+        TypeOwner::None => None,
+    }
+}
+
+fn world_item_desc(item: &WorldItem) -> &'static str {
+    match item {
+        WorldItem::Interface { .. } => "an interface",
+        WorldItem::Function(_) => "a function",
+        WorldItem::Type(_) => "a type",
+    }
+}
+
+fn function_desc(resolve: &Resolve, func: &Function) -> String {
+    let resource_name = |id: &TypeId| resolve.types[*id].name.as_deref().unwrap_or("<anonymous>");
+    let name = func.item_name();
+    match &func.kind {
+        FunctionKind::Freestanding | FunctionKind::AsyncFreestanding => {
+            format!("function `{name}`")
+        }
+        FunctionKind::Method(id) | FunctionKind::AsyncMethod(id) => {
+            format!("method `{name}` of resource `{}`", resource_name(id))
+        }
+        FunctionKind::Static(id) | FunctionKind::AsyncStatic(id) => {
+            format!(
+                "static function `{name}` of resource `{}`",
+                resource_name(id)
+            )
+        }
+        FunctionKind::Constructor(id) => {
+            format!("constructor of resource `{}`", resource_name(id))
+        }
+    }
+}
+
+/// Renders `ty` the way it would be written in WIT.
+fn type_name(resolve: &Resolve, ty: &Type) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::S8 => "s8".to_string(),
+        Type::S16 => "s16".to_string(),
+        Type::S32 => "s32".to_string(),
+        Type::S64 => "s64".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String => "string".to_string(),
+        Type::ErrorContext => "error-context".to_string(),
+        Type::Id(id) => {
+            let ty = &resolve.types[*id];
+            match &ty.name {
+                Some(name) => name.clone(),
+                None => type_def_body(resolve, &ty.kind),
+            }
+        }
+    }
+}
+
+/// Renders the definition of an anonymous type the way it would be written
+/// in WIT.
+fn type_def_body(resolve: &Resolve, kind: &TypeDefKind) -> String {
+    let opt = |ty: Option<&Type>| match ty {
+        Some(ty) => type_name(resolve, ty),
+        None => "_".to_string(),
+    };
+    match kind {
+        TypeDefKind::Option(ty) => format!("option<{}>", type_name(resolve, ty)),
+        TypeDefKind::List(ty) => format!("list<{}>", type_name(resolve, ty)),
+        TypeDefKind::FixedSizeList(ty, n) => format!("list<{}, {n}>", type_name(resolve, ty)),
+        TypeDefKind::Type(ty) => type_name(resolve, ty),
+        TypeDefKind::Tuple(t) => format!(
+            "tuple<{}>",
+            t.types
+                .iter()
+                .map(|ty| type_name(resolve, ty))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TypeDefKind::Result(r) => match (&r.ok, &r.err) {
+            (None, None) => "result".to_string(),
+            (ok, err) => format!("result<{}, {}>", opt(ok.as_ref()), opt(err.as_ref())),
+        },
+        TypeDefKind::Handle(Handle::Own(id)) => type_name(resolve, &Type::Id(*id)),
+        TypeDefKind::Handle(Handle::Borrow(id)) => {
+            format!("borrow<{}>", type_name(resolve, &Type::Id(*id)))
+        }
+        TypeDefKind::Future(ty) => match ty {
+            Some(ty) => format!("future<{}>", type_name(resolve, ty)),
+            None => "future".to_string(),
+        },
+        TypeDefKind::Stream(ty) => match ty {
+            Some(ty) => format!("stream<{}>", type_name(resolve, ty)),
+            None => "stream".to_string(),
+        },
+        other => other.as_str().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SemverChangeKind, SemverSeverity, next_version, semver_diff, semver_diff_packages,
+    };
+    use semver::Version;
+    use wit_parser::Resolve;
+
+    /// Compares the worlds `prev` and `new` of the package in `wit`.
+    fn diff(wit: &str) -> Vec<(SemverSeverity, SemverChangeKind, String)> {
+        let mut resolve = Resolve::default();
+        resolve.features.insert("f".to_string());
+        let pkg = resolve.push_str("test.wit", wit).unwrap();
+        let prev = resolve.select_world(pkg, Some("prev")).unwrap();
+        let new = resolve.select_world(pkg, Some("new")).unwrap();
+        semver_diff(&resolve, prev, &resolve, new)
+            .unwrap()
+            .into_iter()
+            .map(|change| (change.severity, change.kind, change.item))
+            .collect()
+    }
+
+    #[track_caller]
+    fn assert_change(wit: &str, severity: SemverSeverity, kind: SemverChangeKind, item: &str) {
+        assert_eq!(diff(wit), [(severity, kind, item.to_string())]);
+    }
+
+    /// Two versions of an interface `i` which differ by the function `f`.
+    const VERSIONS: &str = "
+        package a:i@1.0.0 { interface i { g: func(); } }
+        package a:i@2.0.0 { interface i { g: func(); f: func(); } }
+    ";
+
+    #[test]
+    fn world_items() {
+        use SemverChangeKind::*;
+        use SemverSeverity::*;
+
+        let world = |prev: &str, new: &str| {
+            format!("package a:b; world prev {{ {prev} }} world new {{ {new} }}")
+        };
+        assert_change(&world("", "import f: func();"), Major, Added, "import `f`");
+        assert_change(
+            &world("import f: func();", ""),
+            Minor,
+            Removed,
+            "import `f`",
+        );
+        assert_change(&world("", "export f: func();"), Minor, Added, "export `f`");
+        assert_change(
+            &world("export f: func();", ""),
+            Major,
+            Removed,
+            "export `f`",
+        );
+        assert_change(
+            &world("", "@unstable(feature = f) import f: func();"),
+            Minor,
+            Added,
+            "import `f`",
+        );
+    }
+
+    #[test]
+    fn interface_members() {
+        use SemverChangeKind::*;
+        use SemverSeverity::*;
+
+        let world = |dir: &str, prev: &str, new: &str| {
+            format!(
+                "package a:b; {VERSIONS} \
+                 world prev {{ {dir} a:i/i@{prev}; }} \
+                 world new {{ {dir} a:i/i@{new}; }}"
+            )
+        };
+        let item = |dir: &str| format!("function `f` of {dir} `a:i/i`");
+        assert_change(
+            &world("import", "1.0.0", "2.0.0"),
+            Major,
+            Added,
+            &item("import"),
+        );
+        assert_change(
+            &world("import", "2.0.0", "1.0.0"),
+            Minor,
+            Removed,
+            &item("import"),
+        );
+        assert_change(
+            &world("export", "1.0.0", "2.0.0"),
+            Minor,
+            Added,
+            &item("export"),
+        );
+        assert_change(
+            &world("export", "2.0.0", "1.0.0"),
+            Major,
+            Removed,
+            &item("export"),
+        );
+    }
+
+    #[test]
+    fn definitions() {
+        use SemverChangeKind::*;
+        use SemverSeverity::*;
+
+        let mut resolve = Resolve::default();
+        let v1 = resolve.push_str("v1.wit", "package a:i@1.0.0; interface i { g: func(); }");
+        let v2 = resolve.push_str(
+            "v2.wit",
+            "package a:i@2.0.0; interface i { g: func(); f: func(); }",
+        );
+        let (v1, v2) = (v1.unwrap(), v2.unwrap());
+        let changes = |prev, new| {
+            semver_diff_packages(&resolve, prev, &resolve, new)
+                .unwrap()
+                .into_iter()
+                .map(|change| (change.severity, change.kind, change.item))
+                .collect::<Vec<_>>()
+        };
+        let item = "function `f` of interface `i`".to_string();
+        assert_eq!(changes(v1, v2), [(Minor, Added, item.clone())]);
+        assert_eq!(changes(v2, v1), [(Major, Removed, item)]);
+    }
+
+    #[track_caller]
+    fn assert_next(prev: &str, bump: SemverSeverity, expected: &str) {
+        let prev = Version::parse(prev).unwrap();
+        assert_eq!(next_version(&prev, bump).to_string(), expected);
+    }
+
+    #[test]
+    fn releases() {
+        assert_next("1.2.3", SemverSeverity::Major, "2.0.0");
+        assert_next("1.2.3", SemverSeverity::Minor, "1.3.0");
+        assert_next("1.2.3", SemverSeverity::Patch, "1.2.4");
+        assert_next("0.2.3", SemverSeverity::Major, "0.3.0");
+        assert_next("0.2.3", SemverSeverity::Minor, "0.2.4");
+        assert_next("0.2.3", SemverSeverity::Patch, "0.2.4");
+        assert_next("1.2.3+build", SemverSeverity::Patch, "1.2.4");
+    }
+
+    #[test]
+    fn pre_releases() {
+        assert_next("1.0.0-rc.1", SemverSeverity::Major, "1.0.0");
+        assert_next("1.0.0-rc.1", SemverSeverity::Minor, "1.0.0");
+        assert_next("1.0.0-rc.1", SemverSeverity::Patch, "1.0.0");
+        assert_next("1.2.0-rc.1", SemverSeverity::Major, "2.0.0");
+        assert_next("1.2.0-rc.1", SemverSeverity::Minor, "1.2.0");
+        assert_next("1.2.3-rc.1", SemverSeverity::Minor, "1.3.0");
+        assert_next("1.2.3-rc.1", SemverSeverity::Patch, "1.2.3");
+        assert_next("0.2.0-rc", SemverSeverity::Major, "0.2.0");
+        assert_next("0.2.3-rc", SemverSeverity::Major, "0.3.0");
+        assert_next("0.2.3-rc", SemverSeverity::Patch, "0.2.3");
+    }
+}
@@ -37,6 +37,9 @@ use wit_parser::{PackageId, Resolve, UnresolvedPackageGroup};
 /// * [optional] `use-built-in-libdl` - if linking libraries and this file
 ///   exists, `Linker::use_built_in_libdl` will be set to `true`.  The contents
 ///   of the file are ignored.
+/// * [optional] `merge-producers` - if this file exists,
+///   `ComponentEncoder::merge_producers` or `Linker::merge_producers` will be
+///   set to `true`.  The contents of the file are ignored.
 ///
 /// And the output files are one of the following:
 ///
@@ -86,12 +89,15 @@ fn run_test(path: &Path) -> Result<()> {
 
     let module_path = path.join("module.wat");
     let mut adapters = glob::glob(path.join("adapt-*.wat").to_str().unwrap())?;
+    let merge_producers = path.join("merge-producers").is_file();
     let result = if module_path.is_file() {
         let module = read_core_module(&module_path, &resolve, pkg_id)
             .with_context(|| format!("failed to read core module at {module_path:?}"))?;
         adapters
             .try_fold(
-                ComponentEncoder::default().module(&module)?,
+                ComponentEncoder::default()
+                    .merge_producers(merge_producers)
+                    .module(&module)?,
                 |encoder, path| {
                     let (name, wasm) = read_name_and_module("adapt-", &path?, &resolve, pkg_id)?;
                     Ok::<_, Error>(encoder.adapter(&name, &wasm)?)
@@ -110,7 +116,9 @@ fn run_test(path: &Path) -> Result<()> {
         // Sort list to ensure deterministic order, which determines priority in cases of duplicate symbols:
         libs.sort_by(|(_, a, _), (_, b, _)| a.cmp(b));
 
-        let mut linker = Linker::default().validate(false);
+        let mut linker = Linker::default()
+            .validate(false)
+            .merge_producers(merge_producers);
 
         if path.join("stub-missing-functions").is_file() {
             linker = linker.stub_missing_functions(true);
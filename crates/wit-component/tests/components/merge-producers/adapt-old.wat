@@ -0,0 +1,8 @@
+(module
+  (import "__main_module__" "the_entrypoint" (func $entry))
+  (export "entrypoint" (func $entry))
+  (@producers
+    (language "C11" "")
+    (processed-by "clang" "18.1.2")
+  )
+)
@@ -0,0 +1,7 @@
+(module
+  (func (export "the_entrypoint"))
+  (@producers
+    (language "Rust" "")
+    (processed-by "rustc" "1.80.0")
+  )
+)
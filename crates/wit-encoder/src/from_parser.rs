@@ -1,7 +1,7 @@
 use crate::{
-    Enum, Flags, Ident, Interface, InterfaceItem, Package, PackageName, Params, Record, Resource,
-    ResourceFunc, Result_, StandaloneFunc, Tuple, Type, TypeDef, TypeDefKind, Variant, World,
-    WorldItem,
+    Docs, Enum, Flags, Ident, Interface, InterfaceItem, Package, PackageName, Params, Record,
+    Resource, ResourceFunc, Result_, Stability, StandaloneFunc, Tuple, Type, TypeDef, TypeDefKind,
+    Use, Variant, World, WorldItem, WorldNamedInterface,
 };
 use id_arena::Id;
 use wit_parser::PackageId;
@@ -11,6 +11,16 @@ pub fn packages_from_parsed(resolve: &wit_parser::Resolve) -> Vec<Package> {
     converter.convert()
 }
 
+/// Convert a single package from `resolve`.
+///
+/// References to other packages are rendered with their fully qualified
+/// names, so those packages can be emitted separately or nested within the
+/// returned package with [`Package::nested_package`].
+pub fn package_from_parsed(resolve: &wit_parser::Resolve, package: PackageId) -> Package {
+    let converter = Converter::new(resolve);
+    converter.convert_package(package, &resolve.packages[package])
+}
+
 struct Converter<'a> {
     resolve: &'a wit_parser::Resolve,
 }
@@ -30,6 +40,7 @@ impl<'a> Converter<'a> {
 
     fn convert_package(&self, package_id: PackageId, package: &wit_parser::Package) -> Package {
         let mut output = Package::new(self.convert_package_name(&package.name));
+        output.set_docs(convert_docs(&package.docs));
         for (_, id) in &package.interfaces {
             let interface = self.resolve.interfaces.get(*id).unwrap();
             output.interface(self.convert_interface(
@@ -41,7 +52,7 @@ impl<'a> Converter<'a> {
         }
         for (_, id) in &package.worlds {
             let world = self.resolve.worlds.get(*id).unwrap();
-            output.world(self.convert_world(package_id, world, wit_parser::TypeOwner::World(*id)));
+            output.world(self.convert_world(package_id, *id, world));
         }
         output
     }
@@ -57,26 +68,33 @@ impl<'a> Converter<'a> {
     fn convert_world(
         &self,
         package_id: PackageId,
+        world_id: wit_parser::WorldId,
         world: &wit_parser::World,
-        owner: wit_parser::TypeOwner,
     ) -> World {
         let mut output = World::new(world.name.clone());
+        output.set_docs(convert_docs(&world.docs));
+        output.set_stability(convert_stability(&world.stability));
+        let owner = wit_parser::TypeOwner::World(world_id);
 
         for (key, item) in &world.imports {
             match item {
-                wit_parser::WorldItem::Interface { id, .. } => {
+                wit_parser::WorldItem::Interface { id, stability } => {
                     let interface = self.resolve.interfaces.get(*id).unwrap();
                     let ident = self.interface_ident(package_id, Some(key), interface);
 
                     if interface.name.is_some() {
-                        output.item(WorldItem::named_interface_import(ident))
+                        let mut named = WorldNamedInterface::new(ident);
+                        named.set_stability(convert_stability(stability));
+                        output.item(WorldItem::named_interface_import(named))
                     } else {
-                        output.item(WorldItem::inline_interface_import(self.convert_interface(
+                        let mut interface = self.convert_interface(
                             package_id,
                             interface,
                             Some(ident),
-                            owner,
-                        )))
+                            wit_parser::TypeOwner::Interface(*id),
+                        );
+                        interface.set_stability(convert_stability(stability));
+                        output.item(WorldItem::inline_interface_import(interface))
                     }
                 }
                 wit_parser::WorldItem::Function(func) => {
@@ -85,26 +103,46 @@ impl<'a> Converter<'a> {
                     }
                 }
                 wit_parser::WorldItem::Type(type_id) => {
-                    let (target, item, rename) = self.convert_world_item_type(package_id, *type_id);
-                    output.use_type(target, item, rename)
+                    let type_def = self.resolve.types.get(*type_id).unwrap();
+                    if self.underlying_type_def(type_def).owner == owner {
+                        if let Some(type_def) = self.convert_type_def(type_def, *type_id) {
+                            output.item(WorldItem::type_def(type_def));
+                        }
+                    } else {
+                        let (target, item, rename) =
+                            self.convert_world_item_type(package_id, *type_id);
+                        let stability = convert_stability(&type_def.stability);
+                        match output
+                            .uses_mut()
+                            .iter_mut()
+                            .find(|u| u.target() == &target && u.stability() == &stability)
+                        {
+                            Some(use_) => use_.item(item, rename),
+                            None => output.use_(new_use(target, item, rename, stability)),
+                        }
+                    }
                 }
             }
         }
 
         for (key, item) in &world.exports {
             match item {
-                wit_parser::WorldItem::Interface { id, .. } => {
+                wit_parser::WorldItem::Interface { id, stability } => {
                     let interface = self.resolve.interfaces.get(*id).unwrap();
                     let ident = self.interface_ident(package_id, Some(key), interface);
                     if interface.name.is_some() {
-                        output.item(WorldItem::named_interface_export(ident));
+                        let mut named = WorldNamedInterface::new(ident);
+                        named.set_stability(convert_stability(stability));
+                        output.item(WorldItem::named_interface_export(named));
                     } else {
-                        output.item(WorldItem::inline_interface_export(self.convert_interface(
+                        let mut interface = self.convert_interface(
                             package_id,
                             interface,
                             Some(ident),
-                            owner,
-                        )));
+                            wit_parser::TypeOwner::Interface(*id),
+                        );
+                        interface.set_stability(convert_stability(stability));
+                        output.item(WorldItem::inline_interface_export(interface));
                     }
                 }
                 wit_parser::WorldItem::Function(func) => {
@@ -112,10 +150,7 @@ impl<'a> Converter<'a> {
                         output.item(WorldItem::function_export(func));
                     }
                 }
-                wit_parser::WorldItem::Type(type_id) => {
-                    let (target, item, rename) = self.convert_world_item_type(package_id, *type_id);
-                    output.use_type(target, item, rename)
-                }
+                wit_parser::WorldItem::Type(_) => unreachable!("worlds can't export types"),
             }
         }
 
@@ -161,6 +196,8 @@ impl<'a> Converter<'a> {
                     .clone()
                     .expect("inlined interface must pass in inlined_name")
             }));
+        output.set_docs(convert_docs(&interface.docs));
+        output.set_stability(convert_stability(&interface.stability));
 
         for (_, func) in &interface.functions {
             if let Some(func) = self.standalone_func_convert(func) {
@@ -181,14 +218,24 @@ impl<'a> Converter<'a> {
                     .expect("Type not part of an interface");
                 let local_type_name = type_def.name.clone().unwrap();
                 let underlying_local_type_name = underlying_type_def.name.clone().unwrap();
-                if underlying_local_type_name == local_type_name {
-                    output.use_type(interface_ident, local_type_name, None);
+                let rename = if underlying_local_type_name == local_type_name {
+                    None
                 } else {
-                    output.use_type(
+                    Some(local_type_name.into())
+                };
+                let stability = convert_stability(&type_def.stability);
+                match output
+                    .uses
+                    .iter_mut()
+                    .find(|u| u.target() == &interface_ident && u.stability() == &stability)
+                {
+                    Some(use_) => use_.item(underlying_local_type_name, rename),
+                    None => output.use_(new_use(
                         interface_ident,
-                        underlying_local_type_name,
-                        Some(local_type_name.into()),
-                    );
+                        underlying_local_type_name.into(),
+                        rename,
+                        stability,
+                    )),
                 }
             }
         }
@@ -213,7 +260,7 @@ impl<'a> Converter<'a> {
                         TypeDefKind::Record(output)
                     }
                     wit_parser::TypeDefKind::Resource => {
-                        let output = self.convert_resource(type_def_id, name, &type_def.owner);
+                        let output = self.convert_resource(type_def_id, &type_def.owner);
                         TypeDefKind::Resource(output)
                     }
                     wit_parser::TypeDefKind::Flags(flags) => {
@@ -248,7 +295,12 @@ impl<'a> Converter<'a> {
                         TypeDefKind::Type(output)
                     }
                     wit_parser::TypeDefKind::Handle(handle) => {
-                        let output = self.handle_to_type(handle);
+                        // `type a = b` is an alias of the resource `b`, so the
+                        // handle has to be spelled out to round-trip.
+                        let output = match self.handle_to_type(handle) {
+                            Type::Named(name) => Type::own(name),
+                            other => other,
+                        };
                         TypeDefKind::Type(output)
                     }
                     wit_parser::TypeDefKind::Result(result) => {
@@ -266,7 +318,10 @@ impl<'a> Converter<'a> {
                     wit_parser::TypeDefKind::Unknown => unreachable!(),
                 };
 
-                Some(TypeDef::new(name.clone(), kind))
+                let mut output = TypeDef::new(name.clone(), kind);
+                output.set_docs(convert_docs(&type_def.docs));
+                output.set_stability(convert_stability(&type_def.stability));
+                Some(output)
             }
         }
     }
@@ -336,53 +391,64 @@ impl<'a> Converter<'a> {
     fn convert_enum(&self, enum_: &wit_parser::Enum) -> Enum {
         let mut output = Enum::empty();
         for case in &enum_.cases {
-            output.case(case.name.clone())
+            let mut output_case = crate::EnumCase::new(case.name.clone());
+            output_case.set_docs(convert_docs(&case.docs));
+            output.case(output_case)
         }
         output
     }
     fn convert_record(&self, record: &wit_parser::Record) -> Record {
         Record::new(record.fields.iter().map(|field| {
-            let name = field.name.clone();
-            let type_ = self.convert_type(&field.ty);
-            (name, type_)
+            let mut output = crate::Field::new(field.name.clone(), self.convert_type(&field.ty));
+            output.set_docs(convert_docs(&field.docs));
+            output
         }))
     }
     fn convert_variant(&self, variant: &wit_parser::Variant) -> Variant {
         let mut output = Variant::empty();
         for case in &variant.cases {
-            match &case.ty {
-                Some(ty) => {
-                    let ty = self.convert_type(ty);
-                    output.case((case.name.clone(), ty))
-                }
-                None => output.case((case.name.clone(),)),
-            }
+            let mut output_case = match &case.ty {
+                Some(ty) => crate::VariantCase::value(case.name.clone(), self.convert_type(ty)),
+                None => crate::VariantCase::empty(case.name.clone()),
+            };
+            output_case.set_docs(convert_docs(&case.docs));
+            output.case(output_case)
         }
         output
     }
     fn convert_flags(&self, flags: &wit_parser::Flags) -> Flags {
-        Flags::new(flags.flags.iter().map(|flag| (flag.name.clone(),)))
+        Flags::new(flags.flags.iter().map(|flag| {
+            let mut output = crate::Flag::new(flag.name.clone());
+            output.set_docs(convert_docs(&flag.docs));
+            output
+        }))
     }
 
     fn convert_resource(
         &self,
         resource_id: Id<wit_parser::TypeDef>,
-        name: &str,
         owner: &wit_parser::TypeOwner,
     ) -> Resource {
-        let functions = match owner {
-            wit_parser::TypeOwner::World(_) => {
-                todo!("Enable once win-encoder supports `include`")
-            }
+        // Resources defined in a world have their methods imported as
+        // functions alongside them.
+        let functions: Vec<&wit_parser::Function> = match owner {
+            wit_parser::TypeOwner::World(id) => self.resolve.worlds[*id]
+                .imports
+                .values()
+                .filter_map(|item| match item {
+                    wit_parser::WorldItem::Function(func) => Some(func),
+                    _ => None,
+                })
+                .collect(),
             wit_parser::TypeOwner::Interface(id) => {
-                &self.resolve.interfaces.get(*id).unwrap().functions
+                self.resolve.interfaces[*id].functions.values().collect()
             }
             wit_parser::TypeOwner::None => panic!("Resource has to have an owner"),
         };
 
         let mut output = Resource::empty();
-        for (_, func) in functions {
-            if let Some(method) = self.convert_resource_func(resource_id, name, func) {
+        for func in functions {
+            if let Some(method) = self.convert_resource_func(resource_id, func) {
                 output.func(method);
             }
         }
@@ -392,7 +458,6 @@ impl<'a> Converter<'a> {
     fn convert_resource_func(
         &self,
         resource_id: Id<wit_parser::TypeDef>,
-        resource_name: &str,
         func: &wit_parser::Function,
     ) -> Option<ResourceFunc> {
         // skip first argument for methods, as they're just `self`.
@@ -407,7 +472,7 @@ impl<'a> Converter<'a> {
                     return None;
                 }
                 skip_first_param = true;
-                let name = clean_func_name(resource_name, &func.name);
+                let name = func.item_name().to_string();
                 let async_ = matches!(func.kind, wit_parser::FunctionKind::AsyncMethod(_));
                 ResourceFunc::method(name, async_)
             }
@@ -415,7 +480,7 @@ impl<'a> Converter<'a> {
                 if id != resource_id {
                     return None;
                 }
-                let name = clean_func_name(resource_name, &func.name);
+                let name = func.item_name().to_string();
                 let async_ = matches!(func.kind, wit_parser::FunctionKind::AsyncStatic(_));
                 ResourceFunc::static_(name, async_)
             }
@@ -436,6 +501,8 @@ impl<'a> Converter<'a> {
         if with_returns {
             method.set_result(func.result.as_ref().map(|ty| self.convert_type(ty)));
         }
+        method.set_docs(convert_docs(&func.docs));
+        method.set_stability(convert_stability(&func.stability));
 
         Some(method)
     }
@@ -450,10 +517,12 @@ impl<'a> Converter<'a> {
             wit_parser::FunctionKind::Freestanding
             | wit_parser::FunctionKind::AsyncFreestanding => {
                 let async_ = matches!(func.kind, wit_parser::FunctionKind::AsyncFreestanding);
-                let mut output = StandaloneFunc::new(func.name.clone(), async_);
+                let mut output = StandaloneFunc::new(func.item_name().to_string(), async_);
 
                 output.set_params(self.convert_params(&func.params));
                 output.set_result(func.result.map(|ty| self.convert_type(&ty)));
+                output.set_docs(convert_docs(&func.docs));
+                output.set_stability(convert_stability(&func.stability));
 
                 Some(output)
             }
@@ -574,17 +643,30 @@ impl<'a> Converter<'a> {
     }
 }
 
-fn clean_func_name(resource_name: &str, method_name: &str) -> String {
-    const METHOD_PREFIX: &str = "[method]";
-    const STATIC_PREFIX: &str = "[static]";
-
-    let method_name = method_name
-        .strip_prefix(METHOD_PREFIX)
-        .unwrap_or(method_name);
-    let method_name = method_name
-        .strip_prefix(STATIC_PREFIX)
-        .unwrap_or(method_name);
-    let method_name = method_name.strip_prefix(resource_name).unwrap();
-    let method_name = method_name.strip_prefix(".").unwrap();
-    method_name.to_string()
+fn convert_docs(docs: &wit_parser::Docs) -> Option<Docs> {
+    docs.contents.as_ref().map(Docs::new)
+}
+
+fn convert_stability(stability: &wit_parser::Stability) -> Stability {
+    match stability {
+        wit_parser::Stability::Unknown => Stability::Unknown,
+        wit_parser::Stability::Unstable {
+            feature,
+            deprecated,
+        } => Stability::Unstable {
+            feature: Ident::new(feature.clone()),
+            deprecated: deprecated.clone(),
+        },
+        wit_parser::Stability::Stable { since, deprecated } => Stability::Stable {
+            since: since.clone(),
+            deprecated: deprecated.clone(),
+        },
+    }
+}
+
+fn new_use(target: Ident, item: Ident, rename: Option<Ident>, stability: Stability) -> Use {
+    let mut use_ = Use::new(target);
+    use_.item(item, rename);
+    use_.set_stability(stability);
+    use_
 }
@@ -1,6 +1,6 @@
 use std::fmt::{self, Display};
 
-use crate::{Docs, Type, ident::Ident};
+use crate::{Docs, Stability, Type, ident::Ident};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -75,6 +75,11 @@ pub struct StandaloneFunc {
     pub(crate) result: Option<Type>,
     pub(crate) docs: Option<Docs>,
     pub(crate) async_: bool,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Stability::is_unknown")
+    )]
+    pub(crate) stability: Stability,
 }
 
 impl StandaloneFunc {
@@ -85,6 +90,7 @@ impl StandaloneFunc {
             result: None,
             docs: None,
             async_,
+            stability: Stability::Unknown,
         }
     }
 
@@ -143,4 +149,12 @@ impl StandaloneFunc {
     pub fn async_mut(&mut self) -> &mut bool {
         &mut self.async_
     }
+
+    pub fn set_stability(&mut self, stability: Stability) {
+        self.stability = stability;
+    }
+
+    pub fn stability(&self) -> &Stability {
+        &self.stability
+    }
 }
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{Ident, Render};
+use crate::{Ident, Render, Stability};
 
 /// Enable the union of a world with another world
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -9,6 +9,11 @@ use crate::{Ident, Render};
 pub struct Include {
     use_path: Ident,
     include_names_list: Vec<(String, String)>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Stability::is_unknown")
+    )]
+    stability: Stability,
 }
 
 impl Include {
@@ -16,6 +21,7 @@ impl Include {
         Self {
             use_path: use_path.into(),
             include_names_list: vec![],
+            stability: Stability::Unknown,
         }
     }
 
@@ -39,10 +45,19 @@ impl Include {
         self.include_names_list
             .push((id.to_string(), alias.to_string()));
     }
+
+    pub fn set_stability(&mut self, stability: Stability) {
+        self.stability = stability;
+    }
+
+    pub fn stability(&self) -> &Stability {
+        &self.stability
+    }
 }
 
 impl Render for Include {
     fn render(&self, f: &mut fmt::Formatter<'_>, opts: &crate::RenderOpts) -> fmt::Result {
+        self.stability.render(f, opts)?;
         match self.include_names_list.len() {
             0 => write!(f, "{}include {};\n", opts.spaces(), self.use_path)?,
             len => {
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{Docs, Ident, Render, RenderOpts, StandaloneFunc, TypeDef, Use};
+use crate::{Docs, Ident, Render, RenderOpts, Stability, StandaloneFunc, TypeDef, Use};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -17,6 +17,13 @@ pub struct Interface {
 
     /// Documentation associated with this interface.
     pub(crate) docs: Option<Docs>,
+
+    /// Stability annotation of this interface.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Stability::is_unknown")
+    )]
+    pub(crate) stability: Stability,
 }
 
 impl Interface {
@@ -27,6 +34,7 @@ impl Interface {
             uses: vec![],
             items: vec![],
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -102,6 +110,30 @@ impl Interface {
     pub fn docs(&self) -> &Option<Docs> {
         &self.docs
     }
+
+    /// Set the stability of this interface.
+    pub fn set_stability(&mut self, stability: Stability) {
+        self.stability = stability;
+    }
+
+    pub fn stability(&self) -> &Stability {
+        &self.stability
+    }
+
+    /// Render the braced body of this interface, shared by package-level and
+    /// world-inlined interfaces.
+    pub(crate) fn render_body(&self, f: &mut fmt::Formatter<'_>, opts: &RenderOpts) -> fmt::Result {
+        write!(f, "{{")?;
+        if !self.uses.is_empty() || !self.items.is_empty() {
+            write!(f, "\n")?;
+            self.uses.render(f, &opts.indent())?;
+            self.items.render(f, &opts.indent())?;
+            write!(f, "{}}}\n", opts.spaces())?;
+        } else {
+            write!(f, "}}\n")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -125,6 +157,7 @@ impl Render for InterfaceItems {
                     if let Some(docs) = &func.docs {
                         docs.render(f, opts)?;
                     }
+                    func.stability.render(f, opts)?;
                     let opt_async = if func.async_ { "async " } else { "" };
                     write!(
                         f,
@@ -17,6 +17,7 @@ mod record;
 mod render;
 mod resource;
 mod result;
+mod stability;
 mod tuple;
 mod ty;
 mod use_;
@@ -37,6 +38,7 @@ pub use record::*;
 pub use render::*;
 pub use resource::*;
 pub use result::*;
+pub use stability::*;
 pub use tuple::*;
 pub use ty::*;
 pub use use_::*;
@@ -2,7 +2,7 @@ use std::fmt;
 
 use semver::Version;
 
-use crate::{Interface, Render, RenderOpts, World, ident::Ident};
+use crate::{Docs, Interface, Render, RenderOpts, World, ident::Ident};
 
 /// A WIT package.
 ///
@@ -18,6 +18,9 @@ pub struct Package {
 
     /// World items
     items: Vec<PackageItem>,
+
+    /// Documentation associated with this package.
+    docs: Option<Docs>,
 }
 
 impl Package {
@@ -26,6 +29,7 @@ impl Package {
         Self {
             name,
             items: vec![],
+            docs: None,
         }
     }
 
@@ -47,6 +51,13 @@ impl Package {
         self.items.push(PackageItem::World(world))
     }
 
+    /// Add a nested `package foo:bar { ... }` declaration to the package.
+    ///
+    /// Nested packages can't themselves contain nested packages.
+    pub fn nested_package(&mut self, package: Package) {
+        self.items.push(PackageItem::Package(package))
+    }
+
     pub fn item(&mut self, item: impl Into<PackageItem>) {
         self.items.push(item.into());
     }
@@ -58,37 +69,59 @@ impl Package {
     pub fn items_mut(&mut self) -> &mut Vec<PackageItem> {
         &mut self.items
     }
-}
 
-impl Render for Package {
-    fn render(&self, f: &mut fmt::Formatter<'_>, opts: &RenderOpts) -> fmt::Result {
-        write!(f, "{}package {};\n", opts.spaces(), self.name)?;
-        for item in &self.items {
-            write!(f, "\n")?;
+    pub fn set_docs(&mut self, docs: Option<impl Into<Docs>>) {
+        self.docs = docs.map(|d| d.into());
+    }
+
+    pub fn docs(&self) -> Option<&Docs> {
+        self.docs.as_ref()
+    }
+
+    fn render_items(&self, f: &mut fmt::Formatter<'_>, opts: &RenderOpts) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                write!(f, "\n")?;
+            }
             match item {
                 PackageItem::Interface(interface) => {
                     if let Some(docs) = &interface.docs {
                         docs.render(f, opts)?;
                     }
-                    write!(f, "{}interface {} {{", opts.spaces(), interface.name)?;
-                    if !interface.uses.is_empty() || !interface.items.is_empty() {
-                        write!(f, "\n")?;
-                        interface.uses.render(f, &opts.indent())?;
-                        interface.items.render(f, &opts.indent())?;
-                        write!(f, "{}}}\n", opts.spaces())?;
-                    } else {
-                        write!(f, "}}\n")?;
-                    }
+                    interface.stability.render(f, opts)?;
+                    write!(f, "{}interface {} ", opts.spaces(), interface.name)?;
+                    interface.render_body(f, opts)?;
                 }
                 PackageItem::World(world) => {
                     world.render(f, opts)?;
                 }
+                PackageItem::Package(package) => {
+                    if let Some(docs) = &package.docs {
+                        docs.render(f, opts)?;
+                    }
+                    write!(f, "{}package {} {{\n", opts.spaces(), package.name)?;
+                    package.render_items(f, &opts.indent())?;
+                    write!(f, "{}}}\n", opts.spaces())?;
+                }
             }
         }
         Ok(())
     }
 }
 
+impl Render for Package {
+    fn render(&self, f: &mut fmt::Formatter<'_>, opts: &RenderOpts) -> fmt::Result {
+        if let Some(docs) = &self.docs {
+            docs.render(f, opts)?;
+        }
+        write!(f, "{}package {};\n", opts.spaces(), self.name)?;
+        if !self.items.is_empty() {
+            write!(f, "\n")?;
+        }
+        self.render_items(f, opts)
+    }
+}
+
 impl fmt::Display for Package {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.render(f, &RenderOpts::default())
@@ -101,6 +134,8 @@ impl fmt::Display for Package {
 pub enum PackageItem {
     Interface(Interface),
     World(World),
+    /// A nested `package foo:bar { ... }` declaration.
+    Package(Package),
 }
 
 /// A structure used to keep track of the name of a package, containing optional
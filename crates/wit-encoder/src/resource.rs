@@ -1,4 +1,4 @@
-use crate::{Docs, Params, Type, ident::Ident};
+use crate::{Docs, Params, Stability, Type, ident::Ident};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -32,6 +32,11 @@ pub struct ResourceFunc {
     pub(crate) kind: ResourceFuncKind,
     pub(crate) params: Params,
     pub(crate) docs: Option<Docs>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Stability::is_unknown")
+    )]
+    pub(crate) stability: Stability,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -49,6 +54,7 @@ impl ResourceFunc {
             kind: ResourceFuncKind::Method(name.into(), async_, None),
             params: Params::empty(),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -57,6 +63,7 @@ impl ResourceFunc {
             kind: ResourceFuncKind::Static(name.into(), async_, None),
             params: Params::empty(),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -65,6 +72,7 @@ impl ResourceFunc {
             kind: ResourceFuncKind::Constructor,
             params: Params::empty(),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -123,4 +131,12 @@ impl ResourceFunc {
     pub fn docs(&self) -> &Option<Docs> {
         &self.docs
     }
+
+    pub fn set_stability(&mut self, stability: Stability) {
+        self.stability = stability;
+    }
+
+    pub fn stability(&self) -> &Stability {
+        &self.stability
+    }
 }
@@ -0,0 +1,95 @@
+use std::fmt;
+
+use semver::Version;
+
+use crate::{Ident, Render, RenderOpts};
+
+/// The stability of an item, rendered as `@since`, `@unstable` and
+/// `@deprecated` annotations.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Stability {
+    /// The item has no annotation.
+    #[default]
+    Unknown,
+
+    /// `@unstable(feature = foo)`, optionally also `@deprecated`.
+    Unstable {
+        feature: Ident,
+        deprecated: Option<Version>,
+    },
+
+    /// `@since(version = 1.2.3)`, optionally also `@deprecated`.
+    Stable {
+        since: Version,
+        deprecated: Option<Version>,
+    },
+}
+
+impl Stability {
+    /// Create a `@since(version = ...)` annotation.
+    pub fn since(version: Version) -> Self {
+        Self::Stable {
+            since: version,
+            deprecated: None,
+        }
+    }
+
+    /// Create an `@unstable(feature = ...)` annotation.
+    pub fn unstable(feature: impl Into<Ident>) -> Self {
+        Self::Unstable {
+            feature: feature.into(),
+            deprecated: None,
+        }
+    }
+
+    /// Create a `@since(version = ...)` annotation for an item which is also
+    /// `@deprecated(version = ...)`.
+    pub fn since_deprecated(version: Version, deprecated: Version) -> Self {
+        Self::Stable {
+            since: version,
+            deprecated: Some(deprecated),
+        }
+    }
+
+    /// Create an `@unstable(feature = ...)` annotation for an item which is
+    /// also `@deprecated(version = ...)`.
+    pub fn unstable_deprecated(feature: impl Into<Ident>, deprecated: Version) -> Self {
+        Self::Unstable {
+            feature: feature.into(),
+            deprecated: Some(deprecated),
+        }
+    }
+
+    pub fn deprecated(&self) -> Option<&Version> {
+        match self {
+            Self::Unstable { deprecated, .. } | Self::Stable { deprecated, .. } => {
+                deprecated.as_ref()
+            }
+            Self::Unknown => None,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown)
+    }
+}
+
+impl Render for Stability {
+    fn render(&self, f: &mut fmt::Formatter<'_>, opts: &RenderOpts) -> fmt::Result {
+        match self {
+            Self::Unknown => return Ok(()),
+            Self::Unstable { feature, .. } => {
+                write!(f, "{}@unstable(feature = {feature})\n", opts.spaces())?;
+            }
+            Self::Stable { since, .. } => {
+                write!(f, "{}@since(version = {since})\n", opts.spaces())?;
+            }
+        }
+        if let Some(version) = self.deprecated() {
+            write!(f, "{}@deprecated(version = {version})\n", opts.spaces())?;
+        }
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt::{self, Display};
 
 use crate::{
     Docs, Enum, EnumCase, Field, Flag, Flags, Record, Render, RenderOpts, Resource, ResourceFunc,
-    Result_, Tuple, Variant, ident::Ident,
+    Result_, Stability, Tuple, Variant, ident::Ident,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -22,6 +22,7 @@ pub enum Type {
     F64,
     Char,
     String,
+    Own(Ident),
     Borrow(Ident),
     Option(Box<Type>),
     Result(Box<Result_>),
@@ -35,6 +36,9 @@ pub enum Type {
 }
 
 impl Type {
+    pub fn own(name: impl Into<Ident>) -> Self {
+        Type::Own(name.into())
+    }
     pub fn borrow(name: impl Into<Ident>) -> Self {
         Type::Borrow(name.into())
     }
@@ -110,6 +114,9 @@ impl Display for Type {
             Type::Char => write!(f, "char"),
             Type::String => write!(f, "string"),
             Type::Named(name) => write!(f, "{name}"),
+            Type::Own(type_) => {
+                write!(f, "own<{type_}>")
+            }
             Type::Borrow(type_) => {
                 write!(f, "borrow<{type_}>")
             }
@@ -234,6 +241,11 @@ pub struct TypeDef {
     name: Ident,
     kind: TypeDefKind,
     docs: Option<Docs>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Stability::is_unknown")
+    )]
+    stability: Stability,
 }
 
 impl TypeDef {
@@ -242,6 +254,7 @@ impl TypeDef {
             name: name.into(),
             kind,
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -253,6 +266,7 @@ impl TypeDef {
             name: name.into(),
             kind: TypeDefKind::record(fields),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -264,6 +278,7 @@ impl TypeDef {
             name: name.into(),
             kind: TypeDefKind::resource(funcs),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -272,6 +287,7 @@ impl TypeDef {
             name: name.into(),
             kind: TypeDefKind::flags(flags),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -283,6 +299,7 @@ impl TypeDef {
             name: name.into(),
             kind: TypeDefKind::variant(cases),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -294,6 +311,7 @@ impl TypeDef {
             name: name.into(),
             kind: TypeDefKind::enum_(cases),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -302,6 +320,7 @@ impl TypeDef {
             name: name.into(),
             kind: TypeDefKind::type_(type_),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -328,6 +347,14 @@ impl TypeDef {
     pub fn docs(&self) -> &Option<Docs> {
         &self.docs
     }
+
+    pub fn set_stability(&mut self, stability: Stability) {
+        self.stability = stability;
+    }
+
+    pub fn stability(&self) -> &Stability {
+        &self.stability
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -380,11 +407,12 @@ impl TypeDefKind {
 
 impl Render for TypeDef {
     fn render(&self, f: &mut fmt::Formatter<'_>, opts: &RenderOpts) -> fmt::Result {
+        if let Some(docs) = &self.docs {
+            docs.render(f, opts)?;
+        }
+        self.stability.render(f, opts)?;
         match &self.kind {
             TypeDefKind::Record(record) => {
-                if let Some(docs) = &self.docs {
-                    docs.render(f, opts)?;
-                }
                 write!(f, "{}record {} {{", opts.spaces(), self.name)?;
                 for (index, field) in record.fields.iter().enumerate() {
                     if index == 0 {
@@ -399,15 +427,13 @@ impl Render for TypeDef {
                 write!(f, "{}}}\n", opts.spaces())?;
             }
             TypeDefKind::Resource(resource) => {
-                if let Some(docs) = &self.docs {
-                    docs.render(f, opts)?;
-                }
                 write!(f, "{}resource {} {{\n", opts.spaces(), self.name)?;
                 for func in &resource.funcs {
                     let opts = opts.indent();
                     if let Some(docs) = &func.docs {
                         docs.render(f, &opts)?;
                     }
+                    func.stability.render(f, &opts)?;
                     match &func.kind {
                         crate::ResourceFuncKind::Method(name, async_, result) => {
                             let opt_async = if *async_ { "async " } else { "" };
@@ -443,9 +469,6 @@ impl Render for TypeDef {
                 write!(f, "{}}}\n", opts.spaces())?;
             }
             TypeDefKind::Flags(flags) => {
-                if let Some(docs) = &self.docs {
-                    docs.render(f, opts)?;
-                }
                 write!(f, "{}flags {} {{\n", opts.spaces(), self.name)?;
                 for flag in &flags.flags {
                     let opts = opts.indent();
@@ -457,9 +480,6 @@ impl Render for TypeDef {
                 write!(f, "{}}}\n", opts.spaces())?;
             }
             TypeDefKind::Variant(variant) => {
-                if let Some(docs) = &self.docs {
-                    docs.render(f, opts)?;
-                }
                 write!(f, "{}variant {} {{\n", opts.spaces(), self.name)?;
                 for case in &variant.cases {
                     let opts = opts.indent();
@@ -478,9 +498,6 @@ impl Render for TypeDef {
                 write!(f, "{}}}\n", opts.spaces())?;
             }
             TypeDefKind::Enum(enum_) => {
-                if let Some(docs) = &self.docs {
-                    docs.render(f, opts)?;
-                }
                 write!(f, "{}enum {} {{\n", opts.spaces(), self.name)?;
                 for case in &enum_.cases {
                     let opts = opts.indent();
@@ -492,9 +509,6 @@ impl Render for TypeDef {
                 write!(f, "{}}}\n", opts.spaces())?;
             }
             TypeDefKind::Type(type_) => {
-                if let Some(docs) = &self.docs {
-                    docs.render(f, opts)?;
-                }
                 write!(f, "{}type {} = {};\n", opts.spaces(), self.name, type_)?;
             }
         }
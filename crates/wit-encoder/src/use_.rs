@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{Ident, Render};
+use crate::{Ident, Render, Stability};
 
 /// Enable the union of a world with another world
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -9,6 +9,11 @@ use crate::{Ident, Render};
 pub struct Use {
     target: Ident,
     use_names_list: Vec<(Ident, Option<Ident>)>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Stability::is_unknown")
+    )]
+    stability: Stability,
 }
 
 impl Use {
@@ -16,6 +21,7 @@ impl Use {
         Self {
             target: use_target.into(),
             use_names_list: vec![],
+            stability: Stability::Unknown,
         }
     }
 
@@ -40,12 +46,21 @@ impl Use {
     pub fn use_names_list_mut(&mut self) -> &mut Vec<(Ident, Option<Ident>)> {
         &mut self.use_names_list
     }
+
+    pub fn set_stability(&mut self, stability: Stability) {
+        self.stability = stability;
+    }
+
+    pub fn stability(&self) -> &Stability {
+        &self.stability
+    }
 }
 
 impl Render for Use {
     fn render(&self, f: &mut fmt::Formatter<'_>, opts: &crate::RenderOpts) -> fmt::Result {
         let len = self.use_names_list.len();
 
+        self.stability.render(f, opts)?;
         write!(f, "{}use {}.{{ ", opts.spaces(), self.target)?;
         for (i, (id, alias)) in self.use_names_list.iter().enumerate() {
             if let Some(alias) = alias {
@@ -1,6 +1,9 @@
 use std::fmt;
 
-use crate::{Docs, Include, Interface, Render, RenderOpts, StandaloneFunc, Use, ident::Ident};
+use crate::{
+    Docs, Include, Interface, Render, RenderOpts, Stability, StandaloneFunc, TypeDef, Use,
+    ident::Ident,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -17,6 +20,13 @@ pub struct World {
 
     /// Documentation associated with this world declaration.
     docs: Option<Docs>,
+
+    /// Stability annotation of this world.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Stability::is_unknown")
+    )]
+    stability: Stability,
 }
 
 impl World {
@@ -27,6 +37,7 @@ impl World {
             uses: vec![],
             items: vec![],
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -73,6 +84,9 @@ impl World {
     pub fn include(&mut self, include: Include) {
         self.item(WorldItem::Include(include));
     }
+    pub fn type_def(&mut self, type_def: TypeDef) {
+        self.item(WorldItem::TypeDef(type_def));
+    }
 
     pub fn uses(&self) -> &[Use] {
         &self.uses
@@ -111,6 +125,15 @@ impl World {
     pub fn set_docs(&mut self, docs: Option<impl Into<Docs>>) {
         self.docs = docs.map(|d| d.into());
     }
+
+    pub fn stability(&self) -> &Stability {
+        &self.stability
+    }
+
+    /// Set the stability of this world.
+    pub fn set_stability(&mut self, stability: Stability) {
+        self.stability = stability;
+    }
 }
 
 impl Render for World {
@@ -134,6 +157,10 @@ impl Render for World {
             write!(f, ";\n")?;
             Ok(())
         }
+        if let Some(docs) = &self.docs {
+            docs.render(f, opts)?;
+        }
+        self.stability.render(f, opts)?;
         write!(f, "{}world {} {{\n", opts.spaces(), self.name)?;
         let opts = &opts.indent();
         self.uses.render(f, opts)?;
@@ -143,35 +170,25 @@ impl Render for World {
                     if let Some(docs) = &interface.docs {
                         docs.render(f, opts)?;
                     }
+                    interface.stability.render(f, opts)?;
                     import(f, opts)?;
-                    write!(f, "{}: interface {{", interface.name)?;
-                    if !interface.uses.is_empty() || !interface.items.is_empty() {
-                        write!(f, "\n")?;
-                        interface.uses.render(f, &opts.indent())?;
-                        interface.items.render(f, &opts.indent())?;
-                        write!(f, "{}}}\n", opts.spaces())?;
-                    } else {
-                        write!(f, "}}\n")?;
-                    }
+                    write!(f, "{}: interface ", interface.name)?;
+                    interface.render_body(f, opts)?;
                 }
                 WorldItem::InlineInterfaceExport(interface) => {
                     if let Some(docs) = &interface.docs {
                         docs.render(f, opts)?;
                     }
+                    interface.stability.render(f, opts)?;
                     export(f, opts)?;
-                    write!(f, "{}: interface {{", interface.name)?;
-                    if !interface.items.is_empty() {
-                        write!(f, "\n")?;
-                        interface.items.render(f, &opts.indent())?;
-                        write!(f, "{}}}\n", opts.spaces())?;
-                    } else {
-                        write!(f, "}}\n")?;
-                    }
+                    write!(f, "{}: interface ", interface.name)?;
+                    interface.render_body(f, opts)?;
                 }
                 WorldItem::NamedInterfaceImport(interface) => {
                     if let Some(docs) = &interface.docs {
                         docs.render(f, opts)?;
                     }
+                    interface.stability.render(f, opts)?;
                     import(f, opts)?;
                     write!(f, "{};\n", interface.name)?;
                 }
@@ -179,6 +196,7 @@ impl Render for World {
                     if let Some(docs) = &interface.docs {
                         docs.render(f, opts)?;
                     }
+                    interface.stability.render(f, opts)?;
                     export(f, opts)?;
                     write!(f, "{};\n", interface.name)?;
                 }
@@ -186,6 +204,7 @@ impl Render for World {
                     if let Some(docs) = &function.docs {
                         docs.render(f, opts)?;
                     }
+                    function.stability.render(f, opts)?;
                     import(f, opts)?;
                     render_function(f, opts, function)?;
                 }
@@ -193,9 +212,11 @@ impl Render for World {
                     if let Some(docs) = &function.docs {
                         docs.render(f, opts)?;
                     }
+                    function.stability.render(f, opts)?;
                     export(f, opts)?;
                     render_function(f, opts, function)?;
                 }
+                WorldItem::TypeDef(type_def) => type_def.render(f, opts)?,
                 WorldItem::Include(include) => include.render(f, opts)?,
             }
         }
@@ -227,6 +248,9 @@ pub enum WorldItem {
     /// A function is being directly exported from this world.
     FunctionExport(StandaloneFunc),
 
+    /// A type defined directly in this world.
+    TypeDef(TypeDef),
+
     /// Include type
     Include(Include),
 }
@@ -253,6 +277,9 @@ impl WorldItem {
     pub fn include(value: impl Into<Ident>) -> Self {
         Self::Include(Include::new(value))
     }
+    pub fn type_def(value: TypeDef) -> Self {
+        Self::TypeDef(value)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -264,6 +291,13 @@ pub struct WorldNamedInterface {
 
     /// Documentation associated with this interface.
     pub(crate) docs: Option<Docs>,
+
+    /// Stability annotation of this import or export.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Stability::is_unknown")
+    )]
+    pub(crate) stability: Stability,
 }
 
 impl<N> From<N> for WorldNamedInterface
@@ -280,6 +314,7 @@ impl WorldNamedInterface {
         Self {
             name: name.into(),
             docs: None,
+            stability: Stability::Unknown,
         }
     }
 
@@ -298,4 +333,12 @@ impl WorldNamedInterface {
     pub fn docs(&self) -> Option<&Docs> {
         self.docs.as_ref()
     }
+
+    pub fn set_stability(&mut self, stability: Stability) {
+        self.stability = stability;
+    }
+
+    pub fn stability(&self) -> &Stability {
+        &self.stability
+    }
 }
@@ -0,0 +1,279 @@
+//! Round-trip every valid WIT test case in `wit-parser` through `wit-encoder`.
+//!
+//! Each test case is loaded into a `Resolve`, converted with
+//! `package_from_parsed`, rendered as a single file with all dependencies
+//! nested inside of it, and then parsed again. The two `Resolve`s must describe
+//! the same packages, and rendering the reparsed `Resolve` must produce the
+//! same text.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use pretty_assertions::assert_eq;
+use wit_encoder::{Package, package_from_parsed};
+use wit_parser::{
+    FunctionKind, Handle, InterfaceId, Resolve, Type, TypeDefKind, TypeOwner, WorldItem, WorldKey,
+};
+
+#[test]
+fn round_trip_wit_parser_ui() -> Result<()> {
+    let ui = Path::new(env!("CARGO_MANIFEST_DIR")).join("../wit-parser/tests/ui");
+    let mut tests = Vec::new();
+    for entry in std::fs::read_dir(&ui)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if path.file_name().unwrap() != "parse-fail" {
+                tests.push(path);
+            }
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "wit" || ext == "wat")
+        {
+            tests.push(path);
+        }
+    }
+    tests.sort();
+    assert!(!tests.is_empty());
+
+    for test in tests {
+        round_trip(&test).with_context(|| format!("failed to round-trip {test:?}"))?;
+    }
+    Ok(())
+}
+
+fn round_trip(path: &Path) -> Result<()> {
+    let mut resolve = Resolve {
+        all_features: true,
+        ..Resolve::default()
+    };
+    let (main, _) = resolve.push_path(path)?;
+    let rendered = render(&resolve, main);
+
+    let mut reparsed = Resolve {
+        all_features: true,
+        ..Resolve::default()
+    };
+    let reparsed_main = reparsed
+        .push_str(PathBuf::from("round-trip.wit"), &rendered)
+        .with_context(|| format!("failed to parse rendered WIT:\n{rendered}"))?;
+
+    assert_eq!(fingerprint(&resolve), fingerprint(&reparsed), "{rendered}");
+    assert_eq!(rendered, render(&reparsed, reparsed_main));
+    Ok(())
+}
+
+/// Render `main` with every other package in `resolve` nested inside of it.
+fn render(resolve: &Resolve, main: wit_parser::PackageId) -> String {
+    let mut package: Package = package_from_parsed(resolve, main);
+    let mut nested = resolve
+        .packages
+        .iter()
+        .filter(|(id, _)| *id != main)
+        .map(|(id, _)| package_from_parsed(resolve, id))
+        .collect::<Vec<_>>();
+    nested.sort_by_key(|p| p.name().to_string());
+    for p in nested {
+        package.nested_package(p);
+    }
+    package.to_string()
+}
+
+/// A description of everything in `resolve` which is independent of arena
+/// indices and declaration order.
+fn fingerprint(resolve: &Resolve) -> BTreeMap<String, String> {
+    let printer = Printer::new(resolve);
+    let mut out = BTreeMap::new();
+
+    for (_, package) in &resolve.packages {
+        out.insert(
+            format!("package {}", package.name),
+            format!("{:?}", package.docs),
+        );
+    }
+    for (id, interface) in &resolve.interfaces {
+        let name = printer.interface(id);
+        out.insert(
+            format!("interface {name}"),
+            format!("{:?} {:?}", interface.docs, interface.stability),
+        );
+        for (ty_name, ty) in &interface.types {
+            out.insert(format!("type {name}.{ty_name}"), printer.typedef(*ty));
+        }
+        for (func_name, func) in &interface.functions {
+            out.insert(format!("func {name}.{func_name}"), printer.func(func));
+        }
+    }
+    for (_, world) in &resolve.worlds {
+        let name = format!(
+            "{}/{}",
+            resolve.packages[world.package.unwrap()].name,
+            world.name
+        );
+        out.insert(
+            format!("world {name}"),
+            format!("{:?} {:?}", world.docs, world.stability),
+        );
+        for (dir, items) in [("import", &world.imports), ("export", &world.exports)] {
+            for (key, item) in items {
+                let key = match key {
+                    WorldKey::Name(n) => n.clone(),
+                    WorldKey::Interface(id) => printer.interface(*id),
+                };
+                let item = match item {
+                    WorldItem::Interface { id, stability } => {
+                        format!("interface {} {stability:?}", printer.interface(*id))
+                    }
+                    WorldItem::Function(func) => printer.func(func),
+                    WorldItem::Type(ty) => printer.typedef(*ty),
+                };
+                out.insert(format!("{dir} {name} {key}"), item);
+            }
+        }
+    }
+    out
+}
+
+struct Printer<'a> {
+    resolve: &'a Resolve,
+    inline_names: HashMap<InterfaceId, String>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(resolve: &'a Resolve) -> Self {
+        let mut inline_names = HashMap::new();
+        for (_, world) in &resolve.worlds {
+            let pkg = &resolve.packages[world.package.unwrap()].name;
+            for item in world.imports.iter().chain(&world.exports) {
+                if let (WorldKey::Name(key), WorldItem::Interface { id, .. }) = item {
+                    inline_names.insert(*id, format!("{pkg}/{}#{key}", world.name));
+                }
+            }
+        }
+        Printer {
+            resolve,
+            inline_names,
+        }
+    }
+
+    fn interface(&self, id: InterfaceId) -> String {
+        self.resolve
+            .id_of(id)
+            .unwrap_or_else(|| self.inline_names[&id].clone())
+    }
+
+    fn owner(&self, owner: TypeOwner) -> String {
+        match owner {
+            TypeOwner::Interface(id) => self.interface(id),
+            TypeOwner::World(id) => {
+                let world = &self.resolve.worlds[id];
+                let pkg = &self.resolve.packages[world.package.unwrap()].name;
+                format!("{pkg}/{}", world.name)
+            }
+            TypeOwner::None => String::new(),
+        }
+    }
+
+    fn typedef(&self, id: wit_parser::TypeId) -> String {
+        let ty = &self.resolve.types[id];
+        format!("{} {:?} {:?}", self.kind(&ty.kind), ty.docs, ty.stability)
+    }
+
+    fn func(&self, func: &wit_parser::Function) -> String {
+        let kind = match func.kind {
+            FunctionKind::Freestanding => "func".to_string(),
+            FunctionKind::AsyncFreestanding => "async func".to_string(),
+            FunctionKind::Method(id) => format!("method {}", self.ty(&Type::Id(id))),
+            FunctionKind::AsyncMethod(id) => format!("async method {}", self.ty(&Type::Id(id))),
+            FunctionKind::Static(id) => format!("static {}", self.ty(&Type::Id(id))),
+            FunctionKind::AsyncStatic(id) => format!("async static {}", self.ty(&Type::Id(id))),
+            FunctionKind::Constructor(id) => format!("constructor {}", self.ty(&Type::Id(id))),
+        };
+        let params = func
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{name}: {}", self.ty(ty)))
+            .collect::<Vec<_>>();
+        format!(
+            "{} {kind}({}) -> {} {:?} {:?}",
+            func.name,
+            params.join(", "),
+            self.opt(&func.result),
+            func.docs,
+            func.stability,
+        )
+    }
+
+    fn ty(&self, ty: &Type) -> String {
+        match ty {
+            Type::Id(id) => {
+                let def = &self.resolve.types[*id];
+                match &def.name {
+                    Some(name) => format!("{}.{name}", self.owner(def.owner)),
+                    None => self.kind(&def.kind),
+                }
+            }
+            other => format!("{other:?}"),
+        }
+    }
+
+    fn opt(&self, ty: &Option<Type>) -> String {
+        ty.as_ref().map(|t| self.ty(t)).unwrap_or_default()
+    }
+
+    fn kind(&self, kind: &TypeDefKind) -> String {
+        match kind {
+            TypeDefKind::Record(r) => {
+                let fields = r
+                    .fields
+                    .iter()
+                    .map(|f| format!("{}: {} {:?}", f.name, self.ty(&f.ty), f.docs))
+                    .collect::<Vec<_>>();
+                format!("record {{ {} }}", fields.join(", "))
+            }
+            TypeDefKind::Resource => "resource".to_string(),
+            TypeDefKind::Handle(Handle::Own(id)) => format!("own<{}>", self.ty(&Type::Id(*id))),
+            TypeDefKind::Handle(Handle::Borrow(id)) => {
+                format!("borrow<{}>", self.ty(&Type::Id(*id)))
+            }
+            TypeDefKind::Flags(f) => {
+                let flags = f
+                    .flags
+                    .iter()
+                    .map(|f| format!("{} {:?}", f.name, f.docs))
+                    .collect::<Vec<_>>();
+                format!("flags {{ {} }}", flags.join(", "))
+            }
+            TypeDefKind::Tuple(t) => {
+                let types = t.types.iter().map(|t| self.ty(t)).collect::<Vec<_>>();
+                format!("tuple<{}>", types.join(", "))
+            }
+            TypeDefKind::Variant(v) => {
+                let cases = v
+                    .cases
+                    .iter()
+                    .map(|c| format!("{}({}) {:?}", c.name, self.opt(&c.ty), c.docs))
+                    .collect::<Vec<_>>();
+                format!("variant {{ {} }}", cases.join(", "))
+            }
+            TypeDefKind::Enum(e) => {
+                let cases = e
+                    .cases
+                    .iter()
+                    .map(|c| format!("{} {:?}", c.name, c.docs))
+                    .collect::<Vec<_>>();
+                format!("enum {{ {} }}", cases.join(", "))
+            }
+            TypeDefKind::Option(t) => format!("option<{}>", self.ty(t)),
+            TypeDefKind::Result(r) => {
+                format!("result<{}, {}>", self.opt(&r.ok), self.opt(&r.err))
+            }
+            TypeDefKind::List(t) => format!("list<{}>", self.ty(t)),
+            TypeDefKind::FixedSizeList(t, n) => format!("list<{}, {n}>", self.ty(t)),
+            TypeDefKind::Future(t) => format!("future<{}>", self.opt(t)),
+            TypeDefKind::Stream(t) => format!("stream<{}>", self.opt(t)),
+            TypeDefKind::Type(t) => format!("type {}", self.ty(t)),
+            TypeDefKind::Unknown => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use pretty_assertions::assert_eq;
+use semver::Version;
+use wit_encoder::{
+    Include, Interface, Package, PackageName, Stability, StandaloneFunc, Type, TypeDef, World,
+};
+
+const PACKAGE: &str = indoc::indoc! {"
+    /// The root package.
+    package foo:foo;
+
+    @since(version = 0.2.0)
+    interface types {
+      @unstable(feature = fancy)
+      type t = own<r>;
+      @since(version = 0.2.0)
+      @deprecated(version = 0.3.0)
+      f: func();
+    }
+
+    @unstable(feature = fancy)
+    world base {
+      import a: func();
+    }
+
+    world top {
+      type local = u32;
+      @unstable(feature = fancy)
+      include base with { a as b };
+    }
+
+    package foo:dep {
+      interface i {}
+    }
+"};
+
+#[test]
+fn stability() {
+    let v = |s| Version::parse(s).unwrap();
+    let mut package = Package::new(PackageName::new("foo", "foo", None));
+    package.set_docs(Some("The root package."));
+
+    let mut interface = Interface::new("types");
+    interface.set_stability(Stability::since(v("0.2.0")));
+    let mut ty = TypeDef::type_("t", Type::own("r"));
+    ty.set_stability(Stability::unstable("fancy"));
+    interface.type_def(ty);
+    let mut func = StandaloneFunc::new("f", false);
+    func.set_stability(Stability::since_deprecated(v("0.2.0"), v("0.3.0")));
+    interface.function(func);
+    package.interface(interface);
+
+    let mut world = World::new("base");
+    world.set_stability(Stability::unstable("fancy"));
+    world.function_import(StandaloneFunc::new("a", false));
+    package.world(world);
+
+    let mut world = World::new("top");
+    world.type_def(TypeDef::type_("local", Type::U32));
+    let mut include = Include::new("base");
+    include.with("a", "b");
+    include.set_stability(Stability::unstable("fancy"));
+    world.include(include);
+    package.world(world);
+
+    let mut dep = Package::new(PackageName::new("foo", "dep", None));
+    dep.interface(Interface::new("i"));
+    package.nested_package(dep);
+
+    assert_eq!(package.to_string(), PACKAGE);
+}
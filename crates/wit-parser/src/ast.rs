@@ -1,4 +1,4 @@
-use crate::{Error, PackageNotFoundError, UnresolvedPackageGroup};
+use crate::{Error, ErrorLocation, Location, PackageNotFoundError, UnresolvedPackageGroup};
 use anyhow::{Context, Result, bail};
 use lex::{Span, Token, Tokenizer};
 use semver::Version;
@@ -6,7 +6,9 @@ use std::borrow::Cow;
 use std::fmt;
 use std::mem;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+mod format;
 pub mod lex;
 
 pub use resolve::Resolver;
@@ -1658,7 +1660,9 @@ fn eat_id(tokens: &mut Tokenizer<'_>, expected: &str) -> Result<Span> {
 /// [`UnresolvedPackage`]: crate::UnresolvedPackage
 #[derive(Clone, Default)]
 pub struct SourceMap {
-    sources: Vec<Source>,
+    // Shared so that clones, such as those kept for each package pushed into a
+    // `Resolve`, don't copy the contents of all files.
+    sources: Arc<Vec<Source>>,
     offset: u32,
     require_f32_f64: Option<bool>,
 }
@@ -1706,7 +1710,7 @@ impl SourceMap {
         // file.
         contents.push('\n');
         let new_offset = self.offset + u32::try_from(contents.len()).unwrap();
-        self.sources.push(Source {
+        Arc::make_mut(&mut self.sources).push(Source {
             offset: self.offset,
             path: path.to_path_buf(),
             contents,
@@ -1785,9 +1789,31 @@ impl SourceMap {
             Ok(t) => return Ok(t),
             Err(e) => e,
         };
+
+        // Lexer errors only know their position, so turn them into an error
+        // which points at the character found there.
+        if let Some(lex) = err.downcast_ref::<lex::Error>() {
+            let start = match lex {
+                lex::Error::Unexpected(at, _)
+                | lex::Error::UnterminatedComment(at)
+                | lex::Error::Wanted { at, .. }
+                | lex::Error::InvalidCharInId(at, _)
+                | lex::Error::IdPartEmpty(at)
+                | lex::Error::InvalidEscape(at, _) => *at,
+            };
+            let src = self.source_for_offset(start);
+            let len = src.contents[src.to_relative_offset(start)..]
+                .chars()
+                .next()
+                .map_or(0, char::len_utf8);
+            let end = start + u32::try_from(len).unwrap();
+            err = Error::new(Span { start, end }, lex.to_string()).into();
+        }
+
         if let Some(parse) = err.downcast_mut::<Error>() {
             if parse.highlighted.is_none() {
                 let msg = self.highlight_err(parse.span.start, Some(parse.span.end), &parse.msg);
+                parse.location = Some(self.error_location(parse.span, parse.msg.clone()));
                 parse.highlighted = Some(msg);
             }
         }
@@ -1796,11 +1822,10 @@ impl SourceMap {
         }
         if let Some(notfound) = err.downcast_mut::<PackageNotFoundError>() {
             if notfound.highlighted.is_none() {
-                let msg = self.highlight_err(
-                    notfound.span.start,
-                    Some(notfound.span.end),
-                    &format!("{notfound}"),
-                );
+                let message = notfound.to_string();
+                let msg =
+                    self.highlight_err(notfound.span.start, Some(notfound.span.end), &message);
+                notfound.location = Some(self.error_location(notfound.span, message));
                 notfound.highlighted = Some(msg);
             }
         }
@@ -1808,33 +1833,38 @@ impl SourceMap {
             return Err(err);
         }
 
-        if let Some(lex) = err.downcast_ref::<lex::Error>() {
-            let pos = match lex {
-                lex::Error::Unexpected(at, _)
-                | lex::Error::UnterminatedComment(at)
-                | lex::Error::Wanted { at, .. }
-                | lex::Error::InvalidCharInId(at, _)
-                | lex::Error::IdPartEmpty(at)
-                | lex::Error::InvalidEscape(at, _) => *at,
-            };
-            let msg = self.highlight_err(pos, None, lex);
-            bail!("{msg}")
-        }
-
         if let Some(sort) = err.downcast_mut::<toposort::Error>() {
             if sort.highlighted().is_none() {
                 let span = match sort {
                     toposort::Error::NonexistentDep { span, .. }
                     | toposort::Error::Cycle { span, .. } => *span,
                 };
-                let highlighted = self.highlight_err(span.start, Some(span.end), &sort);
-                sort.set_highlighted(highlighted);
+                let message = sort.to_string();
+                let highlighted = self.highlight_err(span.start, Some(span.end), &message);
+                sort.set_highlighted(highlighted, self.error_location(span, message));
             }
         }
 
         Err(err)
     }
 
+    fn error_location(&self, span: Span, message: String) -> ErrorLocation {
+        let src = self.source_for_offset(span.start);
+        let location = |offset| {
+            let (line, col) = src.linecol(src.to_relative_offset(offset));
+            Location {
+                path: src.path.clone(),
+                line: line + 1,
+                column: col + 1,
+            }
+        };
+        ErrorLocation {
+            message,
+            start: location(span.start),
+            end: location(span.end),
+        }
+    }
+
     fn highlight_err(&self, start: u32, end: Option<u32>, err: impl fmt::Display) -> String {
         let src = self.source_for_offset(start);
         let start = src.to_relative_offset(start);
@@ -1865,15 +1895,34 @@ impl SourceMap {
     }
 
     pub(crate) fn render_location(&self, span: Span) -> String {
+        let (file, line, col) = self.location(span);
+        format!("{file}:{line}:{col}", file = file.display())
+    }
+
+    /// Returns the file, 1-based line and 1-based column of the start of
+    /// `span`.
+    pub(crate) fn location(&self, span: Span) -> (&Path, usize, usize) {
         let src = self.source_for_offset(span.start);
         let start = src.to_relative_offset(span.start);
         let (line, col) = src.linecol(start);
-        format!(
-            "{file}:{line}:{col}",
-            file = src.path.display(),
-            line = line + 1,
-            col = col + 1,
-        )
+        (&src.path, line + 1, col + 1)
+    }
+
+    /// Returns the offset within this source map of the 1-based `line` and
+    /// `column` within the file `path`, if that file is part of this map.
+    pub(crate) fn offset(&self, path: &Path, line: usize, column: usize) -> Option<u32> {
+        let src = self.sources.iter().find(|src| src.path == path)?;
+        let start = src
+            .contents
+            .split_terminator('\n')
+            .take(line.checked_sub(1)?)
+            .map(|line| line.len() + 1)
+            .sum::<usize>();
+        let offset = start + column.checked_sub(1)?;
+        if offset > src.contents.len() {
+            return None;
+        }
+        Some(src.offset + u32::try_from(offset).ok()?)
     }
 
     fn source_for_offset(&self, start: u32) -> &Source {
@@ -1884,6 +1933,31 @@ impl SourceMap {
         &self.sources[i]
     }
 
+    /// Formats the single file within this source map, returning an error if
+    /// it isn't syntactically valid.
+    pub(crate) fn format(&self) -> Result<String> {
+        assert_eq!(self.sources.len(), 1);
+        let formatted = self.rewrite_error(|| {
+            let src = &self.sources[0];
+            let contents = &src.contents[..src.contents.len() - 1];
+            let mut tokens = Tokenizer::new(contents, src.offset, self.require_f32_f64)?;
+            PackageFile::parse(&mut tokens)?;
+            format::format(contents, src.offset, self.require_f32_f64)
+        })?;
+
+        // Double-check that formatting didn't break anything.
+        let mut tokens = Tokenizer::new(&formatted, 0, self.require_f32_f64)?;
+        if let Err(e) = PackageFile::parse(&mut tokens) {
+            bail!("formatting produced invalid WIT: {e}\n{formatted}");
+        }
+        Ok(formatted)
+    }
+
+    /// Returns whether `self` and `other` are clones of the same source map.
+    pub(crate) fn same_sources(&self, other: &SourceMap) -> bool {
+        Arc::ptr_eq(&self.sources, &other.sources)
+    }
+
     /// Returns an iterator over all filenames added to this source map.
     pub fn source_files(&self) -> impl Iterator<Item = &Path> {
         self.sources.iter().map(|src| src.path.as_path())
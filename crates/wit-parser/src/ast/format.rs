@@ -0,0 +1,564 @@
+//! A formatter for WIT source text.
+//!
+//! Formatting works on the token stream of a file rather than on its AST so
+//! that ordinary comments, which the parser discards, are preserved. The
+//! resulting style is:
+//!
+//! * two spaces of indentation per level of `{ ... }` block,
+//! * one item per line, with at most one blank line between items and a
+//!   blank line after the `package` header and between top-level items,
+//! * attributes such as `@since(...)` on their own line,
+//! * canonical spacing within a line, for example `x: list<u32>`,
+//!   `a:b/c@1.0.0` and `use a.{b, c};`,
+//! * trailing commas in record, flags, variant and enum bodies, and none
+//!   within parameter, type or `use` lists.
+//!
+//! Ordering items is out of scope: items are never reordered, not even `use`
+//! statements, as the order of items is reflected in the `Resolve` and so in
+//! encoded components.
+
+use super::lex::{Token, Tokenizer};
+use anyhow::Result;
+use std::mem;
+
+struct Comment<'a> {
+    text: &'a str,
+    /// Whether this comment started on its own line.
+    own_line: bool,
+    /// Whether there was a blank line before this comment.
+    blank_before: bool,
+    /// Whether this comment ended its line.
+    newline_after: bool,
+}
+
+struct Tok<'a> {
+    kind: Token,
+    text: &'a str,
+    /// Comments which precede this token.
+    leading: Vec<Comment<'a>>,
+    /// Comments which follow this token on the same line.
+    trailing: Vec<Comment<'a>>,
+    /// Whether there was a blank line before this token, or its leading
+    /// comments.
+    blank_before: bool,
+    /// Whether there was any whitespace or comment before this token.
+    space_before: bool,
+}
+
+/// Formats the WIT source `input`, which is assumed to be syntactically
+/// valid and to start at `offset` within its `SourceMap`.
+pub(crate) fn format(input: &str, offset: u32, require_f32_f64: Option<bool>) -> Result<String> {
+    let (toks, eof) = tokenize(input, offset, require_f32_f64)?;
+    let mut printer = Printer {
+        toks: &toks,
+        out: String::new(),
+        stack: Vec::new(),
+        line_start: true,
+        brk: Break::None,
+        stmt_start: true,
+        stmt_keyword: None,
+        attribute: false,
+        version: false,
+    };
+    for i in 0..toks.len() {
+        printer.token(i);
+    }
+    printer.finish(&eof);
+    Ok(printer.out)
+}
+
+/// Splits `input` into significant tokens with their surrounding comments
+/// attached, additionally returning any comments at the end of the input.
+fn tokenize<'a>(
+    input: &'a str,
+    offset: u32,
+    require_f32_f64: Option<bool>,
+) -> Result<(Vec<Tok<'a>>, Vec<Comment<'a>>)> {
+    let mut tokens = Tokenizer::new(input, offset, require_f32_f64)?;
+    let mut toks: Vec<Tok<'a>> = Vec::new();
+    let mut pending = Vec::new();
+    // Number of newlines since the last token or comment.
+    let mut newlines = 0;
+    let mut space = false;
+    while let Some((span, kind)) = tokens.next_raw()? {
+        let text = tokens.get_span(span);
+        match kind {
+            Token::Whitespace => {
+                newlines += text.matches('\n').count();
+                space = true;
+                if newlines > 0 {
+                    let last = match toks.last_mut() {
+                        _ if !pending.is_empty() => pending.last_mut(),
+                        Some(tok) => tok.trailing.last_mut(),
+                        None => None,
+                    };
+                    if let Some(comment) = last {
+                        comment.newline_after = true;
+                    }
+                }
+            }
+            Token::Comment => {
+                let comment = Comment {
+                    text: text.trim_end(),
+                    own_line: newlines > 0 || toks.is_empty(),
+                    blank_before: newlines > 1,
+                    newline_after: text.ends_with('\n'),
+                };
+                match toks.last_mut() {
+                    Some(last) if newlines == 0 && pending.is_empty() => {
+                        last.trailing.push(comment)
+                    }
+                    _ => pending.push(comment),
+                }
+                space = true;
+                // Line comments include their terminating newline.
+                newlines = if text.starts_with("//") && text.ends_with('\n') {
+                    1
+                } else {
+                    0
+                };
+            }
+            _ => {
+                toks.push(Tok {
+                    kind,
+                    text,
+                    blank_before: newlines > 1,
+                    space_before: mem::take(&mut space),
+                    leading: mem::take(&mut pending),
+                    trailing: Vec::new(),
+                });
+                newlines = 0;
+            }
+        }
+    }
+    Ok((toks, pending))
+}
+
+fn is_id(kind: Token) -> bool {
+    matches!(kind, Token::Id | Token::ExplicitId)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Break {
+    None,
+    Newline,
+    BlankLine,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Delim {
+    /// A `{ ... }` block whose contents are one per line. The flag indicates
+    /// whether the items are separated by commas, and the keyword is the one
+    /// which introduced the block.
+    Block {
+        commas: bool,
+        keyword: Option<Token>,
+    },
+    /// The `{...}` in `use a.{b, c}`.
+    UseNames,
+    /// The `{ ... }` in `include a with { b as c }`.
+    With,
+    Paren {
+        attribute: bool,
+    },
+    Angle,
+}
+
+struct Printer<'a, 'b> {
+    toks: &'b [Tok<'a>],
+    out: String,
+    stack: Vec<Delim>,
+    /// Whether nothing has been written to the current line yet.
+    line_start: bool,
+    /// The break requested before the next token.
+    brk: Break,
+    /// Whether the next token starts a new item.
+    stmt_start: bool,
+    /// The first keyword of the current item.
+    stmt_keyword: Option<Token>,
+    /// Whether the current token is within an attribute.
+    attribute: bool,
+    /// Whether the current token is within a version number.
+    version: bool,
+}
+
+impl<'a> Printer<'a, '_> {
+    fn blocks(&self) -> usize {
+        self.stack
+            .iter()
+            .filter(|d| matches!(d, Delim::Block { .. }))
+            .count()
+    }
+
+    fn newline(&mut self) {
+        while self.out.ends_with(' ') {
+            self.out.pop();
+        }
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.line_start = true;
+    }
+
+    fn blank_line(&mut self) {
+        self.newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.line_start {
+            let mut indent = self.blocks();
+            // Lines broken within a parameter list or similar, which only
+            // happens around comments, are indented one further level.
+            if matches!(self.stack.last(), Some(d) if !matches!(d, Delim::Block { .. })) {
+                indent += 1;
+            }
+            for _ in 0..indent {
+                self.out.push_str("  ");
+            }
+            self.line_start = false;
+        }
+        self.out.push_str(text);
+    }
+
+    /// Applies the pending break, upgrading it to a blank line if `blank` is
+    /// set and this isn't the start of a block.
+    fn apply_break(&mut self, blank: bool) {
+        let brk = match self.brk {
+            Break::Newline if blank && !self.after_block_open() => Break::BlankLine,
+            brk => brk,
+        };
+        self.brk = Break::None;
+        match brk {
+            Break::None => {}
+            Break::Newline => self.newline(),
+            Break::BlankLine => self.blank_line(),
+        }
+    }
+
+    fn after_block_open(&self) -> bool {
+        self.out.trim_end_matches([' ', '\n']).ends_with('{')
+            && matches!(self.stack.last(), Some(Delim::Block { .. }))
+    }
+
+    fn leading_comments(&mut self, comments: &[Comment<'_>]) {
+        for comment in comments {
+            if comment.own_line {
+                self.brk = self.brk.max(Break::Newline);
+                self.apply_break(comment.blank_before);
+            } else if self.brk != Break::None {
+                self.apply_break(false);
+            } else if !self.line_start && !self.out.ends_with(' ') {
+                self.write(" ");
+            }
+            self.write(comment.text);
+            if comment.newline_after {
+                self.brk = Break::Newline;
+            } else {
+                self.write(" ");
+            }
+        }
+    }
+
+    fn trailing_comments(&mut self, comments: &[Comment<'_>]) {
+        for comment in comments {
+            if !self.out.ends_with([' ', '(', '<']) {
+                self.write(" ");
+            }
+            self.write(comment.text);
+            if comment.newline_after {
+                self.brk = self.brk.max(Break::Newline);
+            } else {
+                self.write(" ");
+            }
+        }
+    }
+
+    fn token(&mut self, i: usize) {
+        let tok = &self.toks[i];
+        let prev = i.checked_sub(1).map(|i| self.toks[i].kind);
+        let next = self.toks.get(i + 1).map(|t| t.kind);
+        let top = self.stack.last().copied();
+
+        // A trailing comma within a parameter list or similar is dropped,
+        // but its comments are kept.
+        if tok.kind == Token::Comma
+            && !matches!(top, Some(Delim::Block { .. }))
+            && matches!(
+                next,
+                Some(Token::RightParen | Token::GreaterThan | Token::RightBrace)
+            )
+        {
+            self.leading_comments(&tok.leading);
+            self.trailing_comments(&tok.trailing);
+            return;
+        }
+
+        let closes_block =
+            tok.kind == Token::RightBrace && matches!(top, Some(Delim::Block { .. }));
+        let starts_item = self.stmt_start;
+        if starts_item {
+            self.stmt_start = false;
+            self.stmt_keyword = Some(tok.kind);
+            if tok.kind == Token::At {
+                self.attribute = true;
+            }
+        }
+
+        self.leading_comments(&tok.leading);
+
+        if closes_block {
+            let empty = prev == Some(Token::LeftBrace) && tok.leading.is_empty();
+            self.stack.pop();
+            self.brk = if empty { Break::None } else { Break::Newline };
+            self.apply_break(false);
+        } else {
+            self.apply_break(tok.blank_before);
+        }
+
+        // Versions are lexed as a sequence of tokens which are kept together
+        // as they were written.
+        self.version = match tok.kind {
+            Token::Integer => matches!(prev, Some(Token::At | Token::Equals)) || self.version,
+            Token::Id | Token::Period | Token::Minus | Token::Plus => self.version,
+            _ => false,
+        } && !(self.version && tok.space_before);
+        let glued = self.version && !matches!(prev, Some(Token::At | Token::Equals));
+
+        if !self.line_start && !glued && !self.out.ends_with(' ') && self.space_before(i, prev, top)
+        {
+            self.write(" ");
+        }
+        self.write(tok.text);
+
+        // Update the delimiter stack and figure out what follows this token.
+        match tok.kind {
+            Token::LeftBrace => {
+                let delim = match prev {
+                    Some(Token::Period) => Delim::UseNames,
+                    Some(Token::With) => Delim::With,
+                    _ => {
+                        let keyword = i.checked_sub(2).map(|j| self.toks[j].kind);
+                        let commas = matches!(
+                            keyword,
+                            Some(Token::Record | Token::Flags | Token::Variant | Token::Enum)
+                        );
+                        Delim::Block {
+                            commas,
+                            keyword: self.stmt_keyword,
+                        }
+                    }
+                };
+                self.stack.push(delim);
+                if let Delim::Block { .. } = delim {
+                    self.brk = Break::Newline;
+                    self.stmt_start = true;
+                }
+            }
+            Token::RightBrace if closes_block => {
+                self.stmt_start = true;
+                let top_level = self.stack.iter().all(|d| {
+                    matches!(
+                        d,
+                        Delim::Block {
+                            keyword: Some(Token::Package),
+                            ..
+                        }
+                    )
+                });
+                self.brk = if top_level {
+                    Break::BlankLine
+                } else {
+                    Break::Newline
+                };
+            }
+            Token::RightBrace | Token::RightParen | Token::GreaterThan => {
+                let ends_item = match self.stack.pop() {
+                    Some(Delim::Paren { attribute: true }) => {
+                        self.attribute = false;
+                        true
+                    }
+                    // `include ... with { ... }` isn't followed by a semicolon.
+                    Some(Delim::With) => true,
+                    _ => false,
+                };
+                if ends_item {
+                    self.stmt_start = true;
+                    self.brk = Break::Newline;
+                }
+            }
+            Token::LeftParen => self.stack.push(Delim::Paren {
+                attribute: self.attribute,
+            }),
+            Token::LessThan => self.stack.push(Delim::Angle),
+            Token::Semicolon => {
+                self.stmt_start = true;
+                let package = self.stmt_keyword == Some(Token::Package);
+                self.brk = if package && self.stack.is_empty() {
+                    Break::BlankLine
+                } else {
+                    Break::Newline
+                };
+            }
+            Token::Comma => {
+                if let Some(Delim::Block { .. }) = top {
+                    self.brk = Break::Newline;
+                }
+            }
+            _ => {}
+        }
+
+        // Record, flags, variant and enum bodies always end with a comma.
+        if let (Some(Delim::Block { commas: true, .. }), Some(Token::RightBrace)) =
+            (self.stack.last(), next)
+        {
+            if !matches!(tok.kind, Token::Comma | Token::LeftBrace) {
+                self.write(",");
+                self.brk = Break::Newline;
+            }
+        }
+
+        self.trailing_comments(&tok.trailing);
+    }
+
+    fn space_before(&self, i: usize, prev: Option<Token>, top: Option<Delim>) -> bool {
+        use Token::*;
+        let Some(prev) = prev else {
+            return false;
+        };
+        let kind = self.toks[i].kind;
+        match (prev, kind) {
+            (LeftBrace, RightBrace) => false,
+            (_, Comma | Semicolon | RightParen | GreaterThan | LessThan) => false,
+            (_, Colon | Period | Slash | Minus | Plus) => false,
+            (Period | Slash | Minus | Plus | At | LeftParen | LessThan, _) => false,
+            (Colon, _) => !self.is_path_colon(i - 1),
+            (_, At) => !matches!(prev, Id | ExplicitId),
+            (_, LeftParen) => !matches!(prev, Func | Constructor | Id | ExplicitId),
+            (LeftBrace, _) => top != Some(Delim::UseNames),
+            (_, RightBrace) => top != Some(Delim::UseNames),
+            _ => true,
+        }
+    }
+
+    /// Returns whether the colon at `i` separates a namespace from a package
+    /// name, as in `a:b/c`, rather than a name from its type.
+    fn is_path_colon(&self, i: usize) -> bool {
+        let kind = |j: Option<usize>| j.and_then(|j| self.toks.get(j)).map(|t| t.kind);
+        kind(i.checked_sub(1)).is_some_and(is_id)
+            && kind(Some(i + 1)).is_some_and(is_id)
+            && (matches!(kind(i.checked_sub(2)), Some(Token::Package | Token::Colon))
+                || matches!(kind(Some(i + 2)), Some(Token::Slash | Token::Colon)))
+    }
+
+    fn finish(&mut self, eof: &[Comment<'_>]) {
+        self.brk = self.brk.min(Break::Newline);
+        self.leading_comments(eof);
+        self.newline();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[track_caller]
+    fn assert_formats(input: &str, expected: &str) {
+        let formatted = crate::format("test.wit", input).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(crate::format("test.wit", &formatted).unwrap(), expected);
+    }
+
+    #[test]
+    fn spacing() {
+        assert_formats(
+            "package  a:b@1.0.0-rc.1 ;interface i{use a:c/d@1.0.0.{x,y as z,};\
+             f:func(a:list< u32 >,b:borrow<r>,)->result<_,string>;}",
+            "package a:b@1.0.0-rc.1;
+
+interface i {
+  use a:c/d@1.0.0.{x, y as z};
+  f: func(a: list<u32>, b: borrow<r>) -> result<_, string>;
+}
+",
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        assert_formats(
+            "package a:b;
+interface i {
+
+
+  record r { a: u32, b: u32 }
+  enum e {
+  }
+  resource x { constructor(); m: static func(); }
+}
+world w { include v with { a as b } @since(version = 1.0.0) import i; }
+world v {}
+",
+            "package a:b;
+
+interface i {
+  record r {
+    a: u32,
+    b: u32,
+  }
+  enum e {}
+  resource x {
+    constructor();
+    m: static func();
+  }
+}
+
+world w {
+  include v with { a as b }
+  @since(version = 1.0.0)
+  import i;
+}
+
+world v {}
+",
+        );
+    }
+
+    #[test]
+    fn comments() {
+        assert_formats(
+            "// leading
+package a:b;
+/// Docs for `i`.
+interface i { // after brace
+    /* before */ type t = u32; // after
+
+
+    // before `f`
+  f: func(/* x */ x: t);
+  // at the end
+}
+// trailing
+",
+            "// leading
+package a:b;
+
+/// Docs for `i`.
+interface i { // after brace
+  /* before */ type t = u32; // after
+
+  // before `f`
+  f: func(/* x */ x: t);
+  // at the end
+}
+// trailing
+",
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let err = crate::format("test.wit", "package a:b;\ninterface {}").unwrap_err();
+        assert!(err.to_string().contains("test.wit:2:11"), "{err}");
+    }
+}
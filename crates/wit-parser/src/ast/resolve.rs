@@ -81,6 +81,10 @@ pub struct Resolver<'a> {
     /// A list of `TypeDefKind::Unknown` types which are required to be
     /// resources when this package is resolved against its dependencies.
     required_resource_types: Vec<(TypeId, Span)>,
+
+    /// Names within the source which refer to other items, along with the
+    /// item they refer to.
+    references: Vec<(Span, Reference)>,
 }
 
 #[derive(PartialEq, Eq, Hash)]
@@ -244,6 +248,7 @@ impl<'a> Resolver<'a> {
             type_spans: mem::take(&mut self.type_spans),
             foreign_dep_spans: mem::take(&mut self.foreign_dep_spans),
             required_resource_types: mem::take(&mut self.required_resource_types),
+            references: mem::take(&mut self.references),
         })
     }
 
@@ -773,6 +778,7 @@ impl<'a> Resolver<'a> {
                 let stability = self.stability(attrs)?;
                 let (item, name, span) = self.resolve_ast_item_path(path)?;
                 let id = self.extract_iface_from_item(&item, &name, span)?;
+                self.references.push((span, Reference::Interface(id)));
                 Ok(WorldItem::Interface { id, stability })
             }
             ast::ExternKind::Func(name, func) => {
@@ -973,6 +979,7 @@ impl<'a> Resolver<'a> {
     fn resolve_use(&mut self, owner: TypeOwner, u: &ast::Use<'a>) -> Result<()> {
         let (item, name, span) = self.resolve_ast_item_path(&u.from)?;
         let use_from = self.extract_iface_from_item(&item, &name, span)?;
+        self.references.push((span, Reference::Interface(use_from)));
         let stability = self.stability(&u.attributes)?;
 
         for name in u.names.iter() {
@@ -991,6 +998,7 @@ impl<'a> Resolver<'a> {
                 )),
             };
             self.type_spans.push(name.name.span);
+            self.references.push((name.name.span, Reference::Type(id)));
             let name = name.as_.as_ref().unwrap_or(&name.name);
             let id = self.types.alloc(TypeDef {
                 docs: Docs::default(),
@@ -1009,6 +1017,7 @@ impl<'a> Resolver<'a> {
         let stability = self.stability(&i.attributes)?;
         let (item, name, span) = self.resolve_ast_item_path(&i.from)?;
         let include_from = self.extract_world_from_item(&item, &name, span)?;
+        self.references.push((span, Reference::World(include_from)));
         self.worlds[world_id]
             .includes
             .push((stability, include_from));
@@ -1304,7 +1313,10 @@ impl<'a> Resolver<'a> {
 
     fn resolve_type_name(&mut self, name: &ast::Id<'_>) -> Result<TypeId> {
         match self.type_lookup.get(name.name) {
-            Some((TypeOrItem::Type(id), _)) => Ok(*id),
+            Some((TypeOrItem::Type(id), _)) => {
+                self.references.push((name.span, Reference::Type(*id)));
+                Ok(*id)
+            }
             Some((TypeOrItem::Item(s), _)) => bail!(Error::new(
                 name.span,
                 format!("cannot use {s} `{name}` as a type", name = name.name),
@@ -1,3 +1,4 @@
+use crate::ErrorLocation;
 use crate::ast::{Id, Span};
 use anyhow::Result;
 use indexmap::IndexMap;
@@ -56,6 +57,7 @@ pub fn toposort<'a>(
                     name: edge.name.to_string(),
                     kind: kind.to_string(),
                     highlighted: None,
+                    location: None,
                 })?;
             states[j].reverse_deps.push(i);
         }
@@ -117,6 +119,7 @@ pub fn toposort<'a>(
                 name: dep.name.to_string(),
                 kind: kind.to_string(),
                 highlighted: None,
+                location: None,
             });
         }
     }
@@ -131,12 +134,14 @@ pub enum Error {
         name: String,
         kind: String,
         highlighted: Option<String>,
+        location: Option<ErrorLocation>,
     },
     Cycle {
         span: Span,
         name: String,
         kind: String,
         highlighted: Option<String>,
+        location: Option<ErrorLocation>,
     },
 }
 
@@ -148,10 +153,27 @@ impl Error {
             }
         }
     }
-    pub(crate) fn set_highlighted(&mut self, string: String) {
+    pub(crate) fn location(&self) -> Option<&ErrorLocation> {
         match self {
-            Error::NonexistentDep { highlighted, .. } | Error::Cycle { highlighted, .. } => {
+            Error::NonexistentDep { location, .. } | Error::Cycle { location, .. } => {
+                location.as_ref()
+            }
+        }
+    }
+    pub(crate) fn set_highlighted(&mut self, string: String, loc: ErrorLocation) {
+        match self {
+            Error::NonexistentDep {
+                highlighted,
+                location,
+                ..
+            }
+            | Error::Cycle {
+                highlighted,
+                location,
+                ..
+            } => {
                 *highlighted = Some(string);
+                *location = Some(loc);
             }
         }
     }
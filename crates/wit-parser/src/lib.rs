@@ -26,9 +26,13 @@ mod resolve;
 pub use resolve::*;
 mod live;
 pub use live::{LiveTypes, TypeIdVisitor};
+mod lint;
+pub use lint::{LintConfig, LintDiagnostic, LintRule};
+mod location;
+pub use location::{ErrorLocation, Location, Reference, SourceLocations};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "serde")]
 mod serde_;
 #[cfg(feature = "serde")]
@@ -115,6 +119,7 @@ pub struct UnresolvedPackage {
     type_spans: Vec<Span>,
     foreign_dep_spans: Vec<Span>,
     required_resource_types: Vec<(TypeId, Span)>,
+    references: Vec<(Span, Reference)>,
 }
 
 /// Tracks a set of packages, all pulled from the same group of WIT source files.
@@ -255,6 +260,7 @@ struct Error {
     span: Span,
     msg: String,
     highlighted: Option<String>,
+    location: Option<ErrorLocation>,
 }
 
 impl Error {
@@ -263,6 +269,7 @@ impl Error {
             span,
             msg: msg.into(),
             highlighted: None,
+            location: None,
         }
     }
 }
@@ -281,6 +288,7 @@ struct PackageNotFoundError {
     requested: PackageName,
     known: Vec<PackageName>,
     highlighted: Option<String>,
+    location: Option<ErrorLocation>,
 }
 
 impl PackageNotFoundError {
@@ -290,6 +298,7 @@ impl PackageNotFoundError {
             requested,
             known,
             highlighted: None,
+            location: None,
         }
     }
 }
@@ -321,6 +330,26 @@ impl fmt::Display for PackageNotFoundError {
 
 impl std::error::Error for PackageNotFoundError {}
 
+/// Formats the WIT source `contents` of the file `path`.
+///
+/// Unlike printing a [`Resolve`], formatting preserves the order of items
+/// within the file along with all of its comments, only normalizing
+/// whitespace, indentation, blank lines and punctuation such as trailing
+/// commas. Formatting is idempotent.
+///
+/// Ordering items is out of scope for the formatter. Items, including `use`
+/// statements, always stay where they were written because their order is
+/// reflected in the [`Resolve`] and so in encoded components.
+///
+/// The `path` argument is only used for error reporting. An error is returned
+/// if `contents` is not syntactically valid WIT, but no name resolution is
+/// performed so the file may refer to items defined elsewhere.
+pub fn format(path: impl AsRef<Path>, contents: &str) -> Result<String> {
+    let mut map = SourceMap::new();
+    map.push(path.as_ref(), contents);
+    map.format()
+}
+
 impl UnresolvedPackageGroup {
     /// Parses the given string as a wit document.
     ///
@@ -394,7 +423,7 @@ impl UnresolvedPackageGroup {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct World {
     /// The WIT identifier name of this world.
     pub name: String,
@@ -406,17 +435,26 @@ pub struct World {
     pub exports: IndexMap<WorldKey, WorldItem>,
 
     /// The package that owns this world.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_optional_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_optional_id",
+            deserialize_with = "deserialize_optional_id"
+        )
+    )]
     pub package: Option<PackageId>,
 
     /// Documentation associated with this world declaration.
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Docs::is_empty"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Docs::is_empty")
+    )]
     pub docs: Docs,
 
     /// Stability annotation for this world itself.
     #[cfg_attr(
         feature = "serde",
-        serde(skip_serializing_if = "Stability::is_unknown")
+        serde(default, skip_serializing_if = "Stability::is_unknown")
     )]
     pub stability: Stability,
 
@@ -526,17 +564,20 @@ impl WorldKey {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum WorldItem {
     /// An interface is being imported or exported from a world, indicating that
     /// it's a namespace of functions.
     Interface {
-        #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+        #[cfg_attr(
+            feature = "serde",
+            serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+        )]
         id: InterfaceId,
         #[cfg_attr(
             feature = "serde",
-            serde(skip_serializing_if = "Stability::is_unknown")
+            serde(default, skip_serializing_if = "Stability::is_unknown")
         )]
         stability: Stability,
     },
@@ -547,7 +588,10 @@ pub enum WorldItem {
     /// A type is being exported from this world.
     ///
     /// Note that types are never imported into worlds at this time.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     Type(TypeId),
 }
 
@@ -562,7 +606,7 @@ impl WorldItem {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Interface {
     /// Optionally listed name of this interface.
     ///
@@ -573,46 +617,64 @@ pub struct Interface {
     ///
     /// Export names are listed within the types themselves. Note that the
     /// export name here matches the name listed in the `TypeDef`.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id_map"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_id_map",
+            deserialize_with = "deserialize_id_map"
+        )
+    )]
     pub types: IndexMap<String, TypeId>,
 
     /// Exported functions from this interface.
     pub functions: IndexMap<String, Function>,
 
     /// Documentation associated with this interface.
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Docs::is_empty"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Docs::is_empty")
+    )]
     pub docs: Docs,
 
     /// Stability attribute for this interface.
     #[cfg_attr(
         feature = "serde",
-        serde(skip_serializing_if = "Stability::is_unknown")
+        serde(default, skip_serializing_if = "Stability::is_unknown")
     )]
     pub stability: Stability,
 
     /// The package that owns this interface.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_optional_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_optional_id",
+            deserialize_with = "deserialize_optional_id"
+        )
+    )]
     pub package: Option<PackageId>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeDef {
     pub name: Option<String>,
     pub kind: TypeDefKind,
     pub owner: TypeOwner,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Docs::is_empty"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Docs::is_empty")
+    )]
     pub docs: Docs,
     /// Stability attribute for this type.
     #[cfg_attr(
         feature = "serde",
-        serde(skip_serializing_if = "Stability::is_unknown")
+        serde(default, skip_serializing_if = "Stability::is_unknown")
     )]
     pub stability: Stability,
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum TypeDefKind {
     Record(Record),
@@ -664,14 +726,20 @@ impl TypeDefKind {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum TypeOwner {
     /// This type was defined within a `world` block.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     World(WorldId),
     /// This type was defined within an `interface` block.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     Interface(InterfaceId),
     /// This type wasn't inherently defined anywhere, such as a `list<T>`, which
     /// doesn't need an owner.
@@ -680,12 +748,18 @@ pub enum TypeOwner {
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum Handle {
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     Own(TypeId),
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     Borrow(TypeId),
 }
 
@@ -717,32 +791,38 @@ pub enum Int {
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Record {
     pub fields: Vec<Field>,
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Field {
     pub name: String,
     #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub ty: Type,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Docs::is_empty"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Docs::is_empty")
+    )]
     pub docs: Docs,
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Flags {
     pub flags: Vec<Flag>,
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Flag {
     pub name: String,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Docs::is_empty"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Docs::is_empty")
+    )]
     pub docs: Docs,
 }
 
@@ -775,24 +855,27 @@ impl FlagsRepr {
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Tuple {
     pub types: Vec<Type>,
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Variant {
     pub cases: Vec<Case>,
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Case {
     pub name: String,
     #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub ty: Option<Type>,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Docs::is_empty"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Docs::is_empty")
+    )]
     pub docs: Docs,
 }
 
@@ -803,16 +886,19 @@ impl Variant {
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Enum {
     pub cases: Vec<EnumCase>,
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EnumCase {
     pub name: String,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Docs::is_empty"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Docs::is_empty")
+    )]
     pub docs: Docs,
 }
 
@@ -834,14 +920,14 @@ fn discriminant_type(num_cases: usize) -> Int {
 }
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Result_ {
     pub ok: Option<Type>,
     pub err: Option<Type>,
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Docs {
     pub contents: Option<String>,
 }
@@ -853,26 +939,38 @@ impl Docs {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Function {
     pub name: String,
     pub kind: FunctionKind,
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_params"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_params",
+            deserialize_with = "deserialize_params"
+        )
+    )]
     pub params: Vec<(String, Type)>,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub result: Option<Type>,
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Docs::is_empty"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Docs::is_empty")
+    )]
     pub docs: Docs,
     /// Stability attribute for this function.
     #[cfg_attr(
         feature = "serde",
-        serde(skip_serializing_if = "Stability::is_unknown")
+        serde(default, skip_serializing_if = "Stability::is_unknown")
     )]
     pub stability: Stability,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum FunctionKind {
     /// A freestanding function.
@@ -903,7 +1001,10 @@ pub enum FunctionKind {
     ///     }
     /// }
     /// ```
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     Method(TypeId),
 
     /// An async resource method where the first parameter is implicitly
@@ -916,7 +1017,10 @@ pub enum FunctionKind {
     ///     }
     /// }
     /// ```
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     AsyncMethod(TypeId),
 
     /// A static resource method.
@@ -928,7 +1032,10 @@ pub enum FunctionKind {
     ///     }
     /// }
     /// ```
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     Static(TypeId),
 
     /// An async static resource method.
@@ -940,7 +1047,10 @@ pub enum FunctionKind {
     ///     }
     /// }
     /// ```
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     AsyncStatic(TypeId),
 
     /// A resource constructor where the return value is implicitly `own<T>`.
@@ -952,7 +1062,10 @@ pub enum FunctionKind {
     ///     }
     /// }
     /// ```
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")
+    )]
     Constructor(TypeId),
 }
 
@@ -1249,7 +1362,7 @@ fn find_futures_and_streams(resolve: &Resolve, ty: Type, results: &mut Vec<TypeI
 ///
 /// The order of the of enum values is significant since it is used with Ord and PartialOrd
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-#[cfg_attr(feature = "serde", derive(serde_derive::Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum Stability {
     /// This item does not have either `@since` or `@unstable`.
@@ -0,0 +1,788 @@
+use crate::{
+    Function, FunctionKind, Handle, InterfaceId, LiveTypes, Location, PackageId, Resolve,
+    Stability, Type, TypeDefKind, TypeId, TypeOwner, WorldId, WorldItem, WorldKey,
+};
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde_derive::Deserialize;
+
+/// A rule checked by [`Resolve::lint`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum LintRule {
+    /// Interfaces, worlds, types and functions without doc comments.
+    MissingDocs,
+    /// Functions with more parameters than [`LintConfig::max_params`], not
+    /// counting the `self` parameter of methods.
+    TooManyParams,
+    /// Functions returning, and types defined as, a `result` without an error
+    /// type.
+    ResultWithoutError,
+    /// Types imported with `use` which are never referred to.
+    UnusedUse,
+    /// Interfaces which aren't imported or exported, directly or through
+    /// their types, by any world.
+    UnusedInterface,
+    /// Words within names which are spelled with different case in different
+    /// names, for example `get-url` and `set-URL`.
+    InconsistentNaming,
+    /// Interfaces, worlds and the items within them without a `@since` or
+    /// `@unstable` annotation in a package with a version.
+    MissingSince,
+}
+
+impl LintRule {
+    /// All lint rules, in the order they're documented.
+    pub const ALL: &'static [LintRule] = &[
+        LintRule::MissingDocs,
+        LintRule::TooManyParams,
+        LintRule::ResultWithoutError,
+        LintRule::UnusedUse,
+        LintRule::UnusedInterface,
+        LintRule::InconsistentNaming,
+        LintRule::MissingSince,
+    ];
+
+    /// Returns the kebab-case name of this rule, as used in configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LintRule::MissingDocs => "missing-docs",
+            LintRule::TooManyParams => "too-many-params",
+            LintRule::ResultWithoutError => "result-without-error",
+            LintRule::UnusedUse => "unused-use",
+            LintRule::UnusedInterface => "unused-interface",
+            LintRule::InconsistentNaming => "inconsistent-naming",
+            LintRule::MissingSince => "missing-since",
+        }
+    }
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LintRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<LintRule> {
+        match LintRule::ALL.iter().find(|r| r.as_str() == s) {
+            Some(rule) => Ok(*rule),
+            None => bail!("unknown lint rule `{s}`"),
+        }
+    }
+}
+
+/// Configuration of the rules checked by [`Resolve::lint`].
+///
+/// With the `serde` feature this can be deserialized from, for example, JSON
+/// such as:
+///
+/// ```json
+/// {
+///     "rules": { "missing-docs": false },
+///     "max-params": 4
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "kebab-case", default, deny_unknown_fields)
+)]
+#[non_exhaustive]
+pub struct LintConfig {
+    /// Whether each rule is enabled. Rules which aren't listed are enabled.
+    pub rules: BTreeMap<LintRule, bool>,
+    /// The maximum number of parameters for [`LintRule::TooManyParams`].
+    pub max_params: usize,
+}
+
+impl Default for LintConfig {
+    fn default() -> LintConfig {
+        LintConfig {
+            rules: BTreeMap::new(),
+            max_params: 8,
+        }
+    }
+}
+
+impl LintConfig {
+    /// Creates a configuration with all rules enabled.
+    pub fn new() -> LintConfig {
+        LintConfig::default()
+    }
+
+    /// Enables or disables `rule`.
+    pub fn rule(&mut self, rule: LintRule, enabled: bool) -> &mut Self {
+        self.rules.insert(rule, enabled);
+        self
+    }
+
+    /// Sets the maximum number of parameters for [`LintRule::TooManyParams`].
+    pub fn max_params(&mut self, max: usize) -> &mut Self {
+        self.max_params = max;
+        self
+    }
+
+    /// Returns whether `rule` is enabled.
+    pub fn is_enabled(&self, rule: LintRule) -> bool {
+        self.rules.get(&rule).copied().unwrap_or(true)
+    }
+}
+
+/// A problem found by [`Resolve::lint`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct LintDiagnostic {
+    /// The rule which found this problem.
+    pub rule: LintRule,
+    /// A description of the problem.
+    pub message: String,
+    /// The location in WIT source text of the item with the problem, if known.
+    pub location: Option<Location>,
+}
+
+impl fmt::Display for LintDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.rule, self.message)?;
+        if let Some(location) = &self.location {
+            write!(f, " (at {location})")?;
+        }
+        Ok(())
+    }
+}
+
+impl Resolve {
+    /// Checks the items defined in the package `package` against the rules
+    /// enabled in `config`.
+    ///
+    /// Diagnostics are returned in the order the items are defined within
+    /// the package, interfaces first and then worlds, except that those for
+    /// [`LintRule::InconsistentNaming`] come last.
+    pub fn lint(&self, package: PackageId, config: &LintConfig) -> Vec<LintDiagnostic> {
+        let mut linter = Linter {
+            resolve: self,
+            package,
+            config,
+            diagnostics: Vec::new(),
+        };
+        linter.run();
+        linter.diagnostics
+    }
+}
+
+struct Linter<'a> {
+    resolve: &'a Resolve,
+    package: PackageId,
+    config: &'a LintConfig,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+/// An item within a package, along with its location.
+struct Item {
+    description: String,
+    location: Option<Location>,
+    names: Vec<String>,
+}
+
+impl Linter<'_> {
+    fn run(&mut self) {
+        let resolve = self.resolve;
+        let package = &resolve.packages[self.package];
+        let used = self.used_types();
+        let referenced = self.interfaces_referenced_by_worlds();
+
+        // Named interfaces come first, followed by any interfaces defined
+        // inline within worlds, whose own docs and stability are those of the
+        // world item.
+        let mut interfaces = package
+            .interfaces
+            .values()
+            .map(|id| (*id, true))
+            .collect::<Vec<_>>();
+        for id in package.worlds.values() {
+            let world = &resolve.worlds[*id];
+            for item in world.imports.values().chain(world.exports.values()) {
+                if let WorldItem::Interface { id, .. } = item {
+                    if resolve.interfaces[*id].name.is_none() && !interfaces.contains(&(*id, false))
+                    {
+                        interfaces.push((*id, false));
+                    }
+                }
+            }
+        }
+
+        let mut items = Vec::new();
+        for (id, named) in interfaces {
+            self.interface(id, named, &used, &referenced, &mut items);
+        }
+        for id in package.worlds.values() {
+            self.world(*id, &used, &mut items);
+        }
+        if self.config.is_enabled(LintRule::InconsistentNaming) {
+            self.naming(&items);
+        }
+    }
+
+    fn interface(
+        &mut self,
+        id: InterfaceId,
+        named: bool,
+        used: &HashSet<TypeId>,
+        referenced: &HashSet<InterfaceId>,
+        items: &mut Vec<Item>,
+    ) {
+        let resolve = self.resolve;
+        let iface = &resolve.interfaces[id];
+        if named {
+            let description = format!("interface `{}`", iface.name.as_deref().unwrap());
+            let location = resolve.locations.interface(id);
+            if iface.docs.contents.is_none() {
+                self.report(
+                    LintRule::MissingDocs,
+                    format!("{description} is missing documentation"),
+                    &location,
+                );
+            }
+            if !referenced.contains(&id) {
+                self.report(
+                    LintRule::UnusedInterface,
+                    format!("{description} is not used by any world"),
+                    &location,
+                );
+            }
+            self.since(&description, &iface.stability, &location);
+            items.push(Item {
+                description,
+                location,
+                names: vec![iface.name.clone().unwrap()],
+            });
+        }
+
+        for ty in iface.types.values() {
+            self.ty(*ty, TypeOwner::Interface(id), used, items);
+        }
+        for func in iface.functions.values() {
+            let location = resolve.locations.function(id, &func.name);
+            self.function(func, location, items);
+        }
+    }
+
+    fn world(&mut self, id: WorldId, used: &HashSet<TypeId>, items: &mut Vec<Item>) {
+        let resolve = self.resolve;
+        let world = &resolve.worlds[id];
+        let description = format!("world `{}`", world.name);
+        let location = resolve.locations.world(id);
+        if world.docs.contents.is_none() {
+            self.report(
+                LintRule::MissingDocs,
+                format!("{description} is missing documentation"),
+                &location,
+            );
+        }
+        self.since(&description, &world.stability, &location);
+        items.push(Item {
+            description: description.clone(),
+            location: location.clone(),
+            names: vec![world.name.clone()],
+        });
+
+        for (import, map) in [(true, &world.imports), (false, &world.exports)] {
+            for (key, item) in map {
+                let item_location = match import {
+                    true => resolve.locations.world_import(id, key),
+                    false => resolve.locations.world_export(id, key),
+                };
+                match item {
+                    WorldItem::Type(ty) => self.ty(*ty, TypeOwner::World(id), used, items),
+
+                    // Functions copied from other worlds with `include` are
+                    // linted where they're originally defined.
+                    WorldItem::Function(func) => {
+                        if item_location.is_some() || location.is_none() {
+                            self.function(func, item_location, items);
+                        }
+                    }
+
+                    // Names of inline interfaces are only checked for naming,
+                    // and interfaces imported by path are linted on their own.
+                    WorldItem::Interface { stability, .. } => {
+                        if item_location.is_none() && location.is_some() {
+                            continue;
+                        }
+                        let direction = if import { "import" } else { "export" };
+                        self.since(
+                            &format!(
+                                "{direction} `{}` of {description}",
+                                resolve.name_world_key(key)
+                            ),
+                            stability,
+                            &item_location,
+                        );
+                        if let WorldKey::Name(name) = key {
+                            items.push(Item {
+                                description: format!("interface `{name}`"),
+                                location: item_location,
+                                names: vec![name.clone()],
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn ty(&mut self, id: TypeId, owner: TypeOwner, used: &HashSet<TypeId>, items: &mut Vec<Item>) {
+        let resolve = self.resolve;
+        let ty = &resolve.types[id];
+        let name = match &ty.name {
+            Some(name) => name,
+            None => return,
+        };
+        let location = resolve.locations.ty(id);
+
+        // Types from `use` statements are aliases of types owned elsewhere.
+        if let TypeDefKind::Type(Type::Id(other)) = ty.kind {
+            if resolve.types[other].owner != owner {
+                if !used.contains(&id) {
+                    self.report(
+                        LintRule::UnusedUse,
+                        format!("type `{name}` is imported with `use` but never used"),
+                        &location,
+                    );
+                }
+                if ty.owner == owner {
+                    self.since(&format!("use of `{name}`"), &ty.stability, &location);
+                }
+                return;
+            }
+        }
+
+        // Types which are included into a world from another world are
+        // linted in the world that defines them.
+        if ty.owner != owner {
+            return;
+        }
+
+        let description = format!("{} `{name}`", ty.kind.as_str());
+        if ty.docs.contents.is_none() {
+            self.report(
+                LintRule::MissingDocs,
+                format!("{description} is missing documentation"),
+                &location,
+            );
+        }
+        self.since(&description, &ty.stability, &location);
+        if let TypeDefKind::Result(r) = &ty.kind {
+            if r.err.is_none() {
+                self.report(
+                    LintRule::ResultWithoutError,
+                    format!("{description} is a `result` without an error type"),
+                    &location,
+                );
+            }
+        }
+
+        let mut names = vec![name.clone()];
+        match &ty.kind {
+            TypeDefKind::Record(r) => names.extend(r.fields.iter().map(|f| f.name.clone())),
+            TypeDefKind::Flags(f) => names.extend(f.flags.iter().map(|f| f.name.clone())),
+            TypeDefKind::Variant(v) => names.extend(v.cases.iter().map(|c| c.name.clone())),
+            TypeDefKind::Enum(e) => names.extend(e.cases.iter().map(|c| c.name.clone())),
+            _ => {}
+        }
+        items.push(Item {
+            description,
+            location,
+            names,
+        });
+    }
+
+    fn function(&mut self, func: &Function, location: Option<Location>, items: &mut Vec<Item>) {
+        let description = describe_function(func);
+        if func.docs.contents.is_none() {
+            self.report(
+                LintRule::MissingDocs,
+                format!("{description} is missing documentation"),
+                &location,
+            );
+        }
+        self.since(&description, &func.stability, &location);
+
+        let params = match func.kind {
+            FunctionKind::Method(_) | FunctionKind::AsyncMethod(_) => func.params.len() - 1,
+            _ => func.params.len(),
+        };
+        if params > self.config.max_params {
+            self.report(
+                LintRule::TooManyParams,
+                format!(
+                    "{description} has {params} parameters, more than the maximum of {}",
+                    self.config.max_params
+                ),
+                &location,
+            );
+        }
+
+        if let Some(Type::Id(id)) = func.result {
+            let ty = &self.resolve.types[id];
+            if let (None, TypeDefKind::Result(r)) = (&ty.name, &ty.kind) {
+                if r.err.is_none() {
+                    self.report(
+                        LintRule::ResultWithoutError,
+                        format!("{description} returns a `result` without an error type"),
+                        &location,
+                    );
+                }
+            }
+        }
+
+        let mut names = vec![func.item_name().to_string()];
+        names.extend(
+            func.params
+                .iter()
+                .filter(|(name, _)| name != "self")
+                .map(|(name, _)| name.clone()),
+        );
+        items.push(Item {
+            description,
+            location,
+            names,
+        });
+    }
+
+    fn since(&mut self, description: &str, stability: &Stability, location: &Option<Location>) {
+        let name = &self.resolve.packages[self.package].name;
+        if name.version.is_some() && stability.is_unknown() {
+            self.report(
+                LintRule::MissingSince,
+                format!(
+                    "{description} is missing a `@since` annotation in versioned package `{name}`"
+                ),
+                location,
+            );
+        }
+    }
+
+    /// Reports names which spell a word differently than the most common
+    /// spelling of that word within the package.
+    fn naming(&mut self, items: &[Item]) {
+        let mut spellings = HashMap::<String, HashMap<&str, usize>>::new();
+        for item in items {
+            for word in item.names.iter().flat_map(|n| n.split('-')) {
+                *spellings
+                    .entry(word.to_lowercase())
+                    .or_default()
+                    .entry(word)
+                    .or_default() += 1;
+            }
+        }
+
+        // The preferred spelling is the most common one, with ties going to
+        // the lowercase spelling.
+        let preferred = spellings
+            .iter()
+            .filter(|(_, s)| s.len() > 1)
+            .map(|(lower, s)| {
+                let (word, _) = s
+                    .iter()
+                    .max_by_key(|(word, count)| (**count, **word == lower))
+                    .unwrap();
+                (lower.as_str(), *word)
+            })
+            .collect::<HashMap<_, _>>();
+
+        for item in items {
+            for name in item.names.iter() {
+                let Some((word, preferred)) = name.split('-').find_map(|word| {
+                    let preferred = *preferred.get(word.to_lowercase().as_str())?;
+                    (preferred != word).then_some((word, preferred))
+                }) else {
+                    continue;
+                };
+                let message = match item.names[0] == *name {
+                    true => format!(
+                        "{} spells `{word}` as `{preferred}` elsewhere in this package",
+                        item.description
+                    ),
+                    false => format!(
+                        "`{name}` of {} spells `{word}` as `{preferred}` elsewhere in this package",
+                        item.description
+                    ),
+                };
+                self.report(LintRule::InconsistentNaming, message, &item.location);
+            }
+        }
+    }
+
+    /// Returns the interfaces which are imported or exported by any world, or
+    /// which have types used by any world.
+    fn interfaces_referenced_by_worlds(&self) -> HashSet<InterfaceId> {
+        let resolve = self.resolve;
+        let mut referenced = HashSet::new();
+        let mut live = LiveTypes::default();
+        for (id, world) in resolve.worlds.iter() {
+            for item in world.imports.values().chain(world.exports.values()) {
+                if let WorldItem::Interface { id, .. } = item {
+                    referenced.insert(*id);
+                }
+            }
+            live.add_world(resolve, id);
+        }
+        referenced.extend(live.iter().filter_map(|ty| match resolve.types[ty].owner {
+            TypeOwner::Interface(id) => Some(id),
+            _ => None,
+        }));
+        referenced
+    }
+
+    /// Returns all types which are referred to from another type or a
+    /// function anywhere within the `Resolve`.
+    fn used_types(&self) -> HashSet<TypeId> {
+        let resolve = self.resolve;
+        let mut used = HashSet::new();
+        let mut add = |ty: &Type| {
+            if let Type::Id(id) = ty {
+                used.insert(*id);
+            }
+        };
+        for (_, ty) in resolve.types.iter() {
+            match &ty.kind {
+                TypeDefKind::Record(r) => r.fields.iter().for_each(|f| add(&f.ty)),
+                TypeDefKind::Tuple(t) => t.types.iter().for_each(&mut add),
+                TypeDefKind::Variant(v) => v
+                    .cases
+                    .iter()
+                    .filter_map(|c| c.ty.as_ref())
+                    .for_each(&mut add),
+                TypeDefKind::Option(t)
+                | TypeDefKind::List(t)
+                | TypeDefKind::FixedSizeList(t, _)
+                | TypeDefKind::Type(t) => add(t),
+                TypeDefKind::Result(r) => r.ok.iter().chain(&r.err).for_each(&mut add),
+                TypeDefKind::Future(t) | TypeDefKind::Stream(t) => t.iter().for_each(&mut add),
+                TypeDefKind::Handle(Handle::Own(id) | Handle::Borrow(id)) => add(&Type::Id(*id)),
+                TypeDefKind::Resource
+                | TypeDefKind::Flags(_)
+                | TypeDefKind::Enum(_)
+                | TypeDefKind::Unknown => {}
+            }
+        }
+        let functions = resolve
+            .interfaces
+            .iter()
+            .flat_map(|(_, i)| i.functions.values())
+            .chain(resolve.worlds.iter().flat_map(|(_, w)| {
+                w.imports
+                    .values()
+                    .chain(w.exports.values())
+                    .filter_map(|item| match item {
+                        WorldItem::Function(f) => Some(f),
+                        _ => None,
+                    })
+            }));
+        for func in functions {
+            func.parameter_and_result_types().for_each(|t| add(&t));
+            match func.kind {
+                FunctionKind::Method(id)
+                | FunctionKind::AsyncMethod(id)
+                | FunctionKind::Static(id)
+                | FunctionKind::AsyncStatic(id)
+                | FunctionKind::Constructor(id) => add(&Type::Id(id)),
+                FunctionKind::Freestanding | FunctionKind::AsyncFreestanding => {}
+            }
+        }
+        used
+    }
+
+    fn report(&mut self, rule: LintRule, message: String, location: &Option<Location>) {
+        if self.config.is_enabled(rule) {
+            self.diagnostics.push(LintDiagnostic {
+                rule,
+                message,
+                location: location.clone(),
+            });
+        }
+    }
+}
+
+fn describe_function(func: &Function) -> String {
+    match func.kind {
+        FunctionKind::Freestanding | FunctionKind::AsyncFreestanding => {
+            format!("function `{}`", func.item_name())
+        }
+        FunctionKind::Method(_) | FunctionKind::AsyncMethod(_) => {
+            format!("method `{}.{}`", resource_name(func), func.item_name())
+        }
+        FunctionKind::Static(_) | FunctionKind::AsyncStatic(_) => {
+            format!(
+                "static function `{}.{}`",
+                resource_name(func),
+                func.item_name()
+            )
+        }
+        FunctionKind::Constructor(_) => {
+            format!("constructor of `{}`", resource_name(func))
+        }
+    }
+}
+
+/// Returns the name of the resource that the method `func` belongs to, which
+/// is encoded within its name.
+fn resource_name(func: &Function) -> &str {
+    let name = &func.name[func.name.find(']').map_or(0, |i| i + 1)..];
+    name.split('.').next().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(wit: &str, config: &LintConfig) -> Vec<String> {
+        let mut resolve = Resolve::default();
+        let pkg = resolve.push_str("test.wit", wit).unwrap();
+        resolve
+            .lint(pkg, config)
+            .iter()
+            .map(|d| d.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn rules() {
+        let wit = "package a:b@1.0.0;
+
+/// Docs.
+@since(version = 1.0.0)
+interface types {
+  /// Docs.
+  @since(version = 1.0.0)
+  type size = u32;
+  /// Docs.
+  @since(version = 1.0.0)
+  type maybe = result<u32>;
+}
+
+interface api {
+  @since(version = 1.0.0)
+  use types.{size, maybe};
+  /// Docs.
+  @since(version = 1.0.0)
+  get-url: func() -> result<string>;
+  /// Docs.
+  @since(version = 1.0.0)
+  set-URL: func(a: u32, b: u32, c: u32);
+}
+
+/// Docs.
+@since(version = 1.0.0)
+world w {
+  @since(version = 1.0.0)
+  import api;
+}
+";
+        let mut config = LintConfig::new();
+        config.max_params(2);
+        assert_eq!(
+            lint(wit, &config),
+            [
+                "[result-without-error] result `maybe` is a `result` without an error type (at test.wit:11:8)",
+                "[missing-docs] interface `api` is missing documentation (at test.wit:14:11)",
+                "[missing-since] interface `api` is missing a `@since` annotation in versioned package `a:b@1.0.0` (at test.wit:14:11)",
+                "[unused-use] type `size` is imported with `use` but never used (at test.wit:16:14)",
+                "[unused-use] type `maybe` is imported with `use` but never used (at test.wit:16:20)",
+                "[result-without-error] function `get-url` returns a `result` without an error type (at test.wit:19:3)",
+                "[too-many-params] function `set-URL` has 3 parameters, more than the maximum of 2 (at test.wit:22:3)",
+                "[inconsistent-naming] function `set-URL` spells `URL` as `url` elsewhere in this package (at test.wit:22:3)",
+            ]
+        );
+
+        let mut config = LintConfig::new();
+        config
+            .rule(LintRule::UnusedUse, false)
+            .rule(LintRule::MissingDocs, false)
+            .rule(LintRule::ResultWithoutError, false)
+            .rule(LintRule::InconsistentNaming, false)
+            .rule(LintRule::MissingSince, false);
+        assert!(lint(wit, &config).is_empty());
+    }
+
+    #[test]
+    fn missing_since() {
+        let wit = "package a:b@1.0.0;
+
+@since(version = 1.0.0)
+interface types {
+  type size = u32;
+  @since(version = 1.0.0)
+  f: func();
+}
+
+@unstable(feature = x)
+interface unstable {
+  @unstable(feature = x)
+  use types.{size};
+  @unstable(feature = x)
+  g: func(s: size);
+}
+
+@since(version = 1.0.0)
+world w {
+  import types;
+  @since(version = 1.0.0)
+  import h: func();
+  export i: func();
+}
+";
+        let mut config = LintConfig::new();
+        config
+            .rule(LintRule::MissingDocs, false)
+            .rule(LintRule::UnusedInterface, false);
+        assert_eq!(
+            lint(wit, &config),
+            [
+                "[missing-since] type `size` is missing a `@since` annotation in versioned package `a:b@1.0.0` (at test.wit:5:8)",
+                "[missing-since] import `a:b/types@1.0.0` of world `w` is missing a `@since` annotation in versioned package `a:b@1.0.0` (at test.wit:20:10)",
+                "[missing-since] function `i` is missing a `@since` annotation in versioned package `a:b@1.0.0` (at test.wit:23:10)",
+            ]
+        );
+    }
+
+    #[test]
+    fn unused_interface() {
+        let wit = "package a:b;
+
+/// Docs.
+interface used-by-type {
+  /// Docs.
+  type t = u32;
+}
+
+/// Docs.
+interface imported {
+  use used-by-type.{t};
+  /// Docs.
+  f: func() -> t;
+}
+
+/// Docs.
+interface unused {}
+
+/// Docs.
+world w {
+  import imported;
+}
+";
+        assert_eq!(
+            lint(wit, &LintConfig::new()),
+            ["[unused-interface] interface `unused` is not used by any world (at test.wit:17:11)"]
+        );
+    }
+}
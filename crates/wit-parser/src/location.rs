@@ -0,0 +1,289 @@
+use crate::ast::lex::Span;
+use crate::{InterfaceId, Remap, SourceMap, TypeId, WorldId, WorldKey};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A position within a WIT source file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Location {
+    /// The path of the source file, as it was provided when parsing.
+    pub path: PathBuf,
+    /// The 1-based line number within the file.
+    pub line: usize,
+    /// The 1-based column number within the line.
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+/// The range of WIT source text that an error points at.
+///
+/// Errors returned while parsing and resolving WIT which point at source text
+/// render that text as part of their message. This is the same information in
+/// a structured form, for example to report errors in an editor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorLocation {
+    /// The message of the error, without the source text that is rendered
+    /// when the error is displayed.
+    pub message: String,
+    /// The start of the range.
+    pub start: Location,
+    /// The end of the range (exclusive).
+    pub end: Location,
+}
+
+impl ErrorLocation {
+    /// Returns the location of the first error in `err`'s chain which points
+    /// at WIT source text, if any.
+    pub fn find(err: &anyhow::Error) -> Option<&ErrorLocation> {
+        err.chain().find_map(|cause| {
+            if let Some(err) = cause.downcast_ref::<crate::Error>() {
+                return err.location.as_ref();
+            }
+            if let Some(err) = cause.downcast_ref::<crate::PackageNotFoundError>() {
+                return err.location.as_ref();
+            }
+            if let Some(err) = cause.downcast_ref::<crate::ast::toposort::Error>() {
+                return err.location();
+            }
+            None
+        })
+    }
+}
+
+/// An item referred to by name from WIT source text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Reference {
+    /// A reference to an interface, for example in a `use` statement.
+    Interface(InterfaceId),
+    /// A reference to a world, for example in an `include` statement.
+    World(WorldId),
+    /// A reference to a type.
+    Type(TypeId),
+}
+
+/// The locations in WIT source text of the items within a [`Resolve`].
+///
+/// Locations are only known for items which were parsed from WIT text, for
+/// example with [`Resolve::push_path`]. Items decoded from a WebAssembly
+/// binary, or synthesized by methods such as [`Resolve::importize`], have no
+/// location.
+///
+/// [`Resolve`]: crate::Resolve
+/// [`Resolve::push_path`]: crate::Resolve::push_path
+/// [`Resolve::importize`]: crate::Resolve::importize
+#[derive(Clone, Default)]
+pub struct SourceLocations {
+    sources: Vec<SourceMap>,
+    interfaces: HashMap<InterfaceId, (usize, Span)>,
+    worlds: HashMap<WorldId, (usize, Span)>,
+    types: HashMap<TypeId, (usize, Span)>,
+    functions: HashMap<(InterfaceId, String), (usize, Span)>,
+    world_items: HashMap<(WorldId, WorldKey, bool), (usize, Span)>,
+    references: Vec<(usize, Span, Reference)>,
+}
+
+impl SourceLocations {
+    /// Returns the location of the name of the interface `id`.
+    pub fn interface(&self, id: InterfaceId) -> Option<Location> {
+        self.interfaces.get(&id).map(|s| self.location(*s))
+    }
+
+    /// Returns the location of the name of the world `id`.
+    pub fn world(&self, id: WorldId) -> Option<Location> {
+        self.worlds.get(&id).map(|s| self.location(*s))
+    }
+
+    /// Returns the location of the import `key` within the world `world`.
+    ///
+    /// Imports which were added to the world implicitly, for example as a
+    /// dependency of another import, have no location.
+    pub fn world_import(&self, world: WorldId, key: &WorldKey) -> Option<Location> {
+        self.world_item(world, key, true)
+    }
+
+    /// Returns the location of the export `key` within the world `world`.
+    pub fn world_export(&self, world: WorldId, key: &WorldKey) -> Option<Location> {
+        self.world_item(world, key, false)
+    }
+
+    fn world_item(&self, world: WorldId, key: &WorldKey, import: bool) -> Option<Location> {
+        self.world_items
+            .get(&(world, key.clone(), import))
+            .map(|s| self.location(*s))
+    }
+
+    /// Returns the location of the name of the type `id`.
+    pub fn ty(&self, id: TypeId) -> Option<Location> {
+        self.types.get(&id).map(|s| self.location(*s))
+    }
+
+    /// Returns the location of the name of the function `name` within the
+    /// interface `interface`.
+    pub fn function(&self, interface: InterfaceId, name: &str) -> Option<Location> {
+        self.functions
+            .get(&(interface, name.to_string()))
+            .map(|s| self.location(*s))
+    }
+
+    /// Returns the item referred to by the name at `line` and `column`, both
+    /// 1-based, within the file `path`.
+    ///
+    /// Names which refer to other items include the types used within type
+    /// definitions and function signatures, the interfaces and names in `use`
+    /// statements, interfaces imported or exported by path in worlds, and the
+    /// worlds named in `include` statements.
+    pub fn reference_at(&self, path: &Path, line: usize, column: usize) -> Option<Reference> {
+        let mut offsets = HashMap::new();
+        self.references
+            .iter()
+            .find(|(source, span, _)| {
+                let offset = *offsets
+                    .entry(*source)
+                    .or_insert_with(|| self.sources[*source].offset(path, line, column));
+                offset.is_some_and(|o| span.start <= o && o < span.end)
+            })
+            .map(|(_, _, r)| *r)
+    }
+
+    fn location(&self, (source, span): (usize, Span)) -> Location {
+        let (path, line, column) = self.sources[source].location(span);
+        Location {
+            path: path.to_path_buf(),
+            line,
+            column,
+        }
+    }
+
+    /// Returns the index of `source_map` within this set of locations, adding
+    /// it if it's not already present.
+    ///
+    /// All packages parsed together share one source map, which is only stored
+    /// once here.
+    pub(crate) fn push_source(&mut self, source_map: &SourceMap) -> usize {
+        if let Some(i) = self
+            .sources
+            .iter()
+            .rposition(|s| s.same_sources(source_map))
+        {
+            return i;
+        }
+        self.sources.push(source_map.clone());
+        self.sources.len() - 1
+    }
+
+    pub(crate) fn record_interface(
+        &mut self,
+        source: usize,
+        id: InterfaceId,
+        span: Span,
+        functions: impl IntoIterator<Item = (String, Span)>,
+    ) {
+        self.interfaces.insert(id, (source, span));
+        for (name, span) in functions {
+            self.functions.insert((id, name), (source, span));
+        }
+    }
+
+    pub(crate) fn record_world(
+        &mut self,
+        source: usize,
+        id: WorldId,
+        span: Span,
+        items: impl IntoIterator<Item = (WorldKey, bool, Span)>,
+    ) {
+        self.worlds.insert(id, (source, span));
+        for (key, import, span) in items {
+            self.world_items.insert((id, key, import), (source, span));
+        }
+    }
+
+    pub(crate) fn record_type(&mut self, source: usize, id: TypeId, span: Span) {
+        self.types.entry(id).or_insert((source, span));
+    }
+
+    pub(crate) fn record_reference(&mut self, source: usize, span: Span, reference: Reference) {
+        self.references.push((source, span, reference));
+    }
+
+    /// Moves all locations from `other` into `self`, translating the ids of
+    /// items through `remap`.
+    ///
+    /// Items which were already present in `self` keep their location.
+    pub(crate) fn merge(&mut self, other: SourceLocations, remap: &Remap) {
+        let base = self.sources.len();
+        self.sources.extend(other.sources);
+        for (id, (source, span)) in other.interfaces {
+            if let Some(Some(id)) = remap.interfaces.get(id.index()) {
+                self.interfaces.entry(*id).or_insert((base + source, span));
+            }
+        }
+        for (id, (source, span)) in other.worlds {
+            if let Some(Some(id)) = remap.worlds.get(id.index()) {
+                self.worlds.entry(*id).or_insert((base + source, span));
+            }
+        }
+        for (id, (source, span)) in other.types {
+            if let Some(Some(id)) = remap.types.get(id.index()) {
+                self.types.entry(*id).or_insert((base + source, span));
+            }
+        }
+        for ((id, name), (source, span)) in other.functions {
+            if let Some(Some(id)) = remap.interfaces.get(id.index()) {
+                self.functions
+                    .entry((*id, name))
+                    .or_insert((base + source, span));
+            }
+        }
+        for ((id, key, import), (source, span)) in other.world_items {
+            let Some(Some(id)) = remap.worlds.get(id.index()) else {
+                continue;
+            };
+            let key = match key {
+                WorldKey::Name(name) => WorldKey::Name(name),
+                WorldKey::Interface(i) => match remap.interfaces.get(i.index()) {
+                    Some(Some(i)) => WorldKey::Interface(*i),
+                    _ => continue,
+                },
+            };
+            self.world_items
+                .entry((*id, key, import))
+                .or_insert((base + source, span));
+        }
+        for (source, span, reference) in other.references {
+            let reference = match reference {
+                Reference::Interface(id) => match remap.interfaces.get(id.index()) {
+                    Some(Some(id)) => Reference::Interface(*id),
+                    _ => continue,
+                },
+                Reference::World(id) => match remap.worlds.get(id.index()) {
+                    Some(Some(id)) => Reference::World(*id),
+                    _ => continue,
+                },
+                Reference::Type(id) => match remap.types.get(id.index()) {
+                    Some(Some(id)) => Reference::Type(*id),
+                    _ => continue,
+                },
+            };
+            self.references.push((base + source, span, reference));
+        }
+    }
+}
+
+impl fmt::Debug for SourceLocations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SourceLocations")
+            .field("interfaces", &self.interfaces.len())
+            .field("worlds", &self.worlds.len())
+            .field("types", &self.types.len())
+            .field("functions", &self.functions.len())
+            .field("world_items", &self.world_items.len())
+            .field("references", &self.references.len())
+            .finish_non_exhaustive()
+    }
+}
@@ -527,9 +527,21 @@ impl InterfaceMetadata {
     fn extract(resolve: &Resolve, id: InterfaceId) -> Self {
         let interface = &resolve.interfaces[id];
 
-        let funcs = interface
-            .functions
-            .iter()
+        // Order functions the same way that `wit-component` encodes them, with
+        // resource-related functions first, so that the metadata is the same
+        // regardless of whether it's extracted from a package's source or its
+        // printed form.
+        let mut funcs = interface.functions.iter().collect::<Vec<_>>();
+        funcs.sort_by_key(|(_name, func)| match func.kind.resource() {
+            Some(id) => interface
+                .types
+                .values()
+                .position(|ty| *ty == id)
+                .unwrap_or(interface.types.len()),
+            None => interface.types.len(),
+        });
+        let funcs = funcs
+            .into_iter()
             .map(|(name, func)| (name.to_string(), FunctionMetadata::extract(func)))
             .filter(|(_, item)| !item.is_empty())
             .collect();
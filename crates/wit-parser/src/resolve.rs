@@ -10,20 +10,25 @@ use id_arena::{Arena, Id};
 use indexmap::{IndexMap, IndexSet};
 use semver::Version;
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::ast::lex::Span;
 use crate::ast::{ParsedUsePath, parse_use_path};
 #[cfg(feature = "serde")]
-use crate::serde_::{serialize_arena, serialize_id_map};
+use crate::serde_::{deserialize_id_map, serialize_arena, serialize_id_map};
 use crate::{
     AstItem, Docs, Error, Function, FunctionKind, Handle, IncludeName, Interface, InterfaceId,
-    InterfaceSpan, LiftLowerAbi, ManglingAndAbi, PackageName, PackageNotFoundError, SourceMap,
-    Stability, Type, TypeDef, TypeDefKind, TypeId, TypeIdVisitor, TypeOwner, UnresolvedPackage,
-    UnresolvedPackageGroup, World, WorldId, WorldItem, WorldKey, WorldSpan,
+    InterfaceSpan, LiftLowerAbi, ManglingAndAbi, PackageName, PackageNotFoundError, Reference,
+    SourceLocations, SourceMap, Stability, Type, TypeDef, TypeDefKind, TypeId, TypeIdVisitor,
+    TypeOwner, UnresolvedPackage, UnresolvedPackageGroup, World, WorldId, WorldItem, WorldKey,
+    WorldSpan,
 };
 
 mod clone;
+#[cfg(feature = "lockfile")]
+mod lockfile;
+#[cfg(feature = "lockfile")]
+pub use lockfile::{LockedPackage, Lockfile};
 
 /// Representation of a fully resolved set of WIT packages.
 ///
@@ -38,6 +43,17 @@ mod clone;
 ///
 /// Each item in a `Resolve` has a parent link to trace it back to the original
 /// package as necessary.
+///
+/// With the `serde` feature a `Resolve` can be serialized and deserialized, for
+/// example to and from JSON. Deserialization fails if the items of the
+/// `Resolve` aren't consistent with each other, for example if types aren't
+/// topologically sorted, and rebuilds `package_names` from `packages`.
+///
+/// The `@unstable` and `@since` gates of items are preserved. The `include`s
+/// of worlds are always elaborated into their imports and exports within a
+/// `Resolve` and so are never serialized. Source locations and the set of
+/// enabled features aren't serialized either, as they only affect packages
+/// which are yet to be parsed and pushed into the `Resolve`.
 #[derive(Default, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Resolve {
@@ -86,6 +102,13 @@ pub struct Resolve {
     /// Activate all features for this [`Resolve`].
     #[cfg_attr(feature = "serde", serde(skip))]
     pub all_features: bool,
+
+    /// The locations in WIT source text of items within this [`Resolve`].
+    ///
+    /// This is used to point diagnostics about items at where they were
+    /// defined.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub locations: SourceLocations,
 }
 
 /// A WIT package within a `Resolve`.
@@ -94,22 +117,37 @@ pub struct Resolve {
 /// have a unique identifier that affects generated components and uniquely
 /// identifiers this particular package.
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Package {
     /// A unique name corresponding to this package.
     pub name: PackageName,
 
     /// Documentation associated with this package.
-    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Docs::is_empty"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Docs::is_empty")
+    )]
     pub docs: Docs,
 
     /// All interfaces contained in this packaged, keyed by the interface's
     /// name.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id_map"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_id_map",
+            deserialize_with = "deserialize_id_map"
+        )
+    )]
     pub interfaces: IndexMap<String, InterfaceId>,
 
     /// All worlds contained in this package, keyed by the world's name.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_id_map"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_id_map",
+            deserialize_with = "deserialize_id_map"
+        )
+    )]
     pub worlds: IndexMap<String, WorldId>,
 }
 
@@ -186,6 +224,14 @@ enum ParsedFile {
     Unresolved(UnresolvedPackageGroup),
 }
 
+/// The contents of a file read by [`Resolve::parse_file`], before they're
+/// added to a [`Resolve`].
+enum FileContents {
+    #[cfg(feature = "decoding")]
+    Package(Resolve, PackageId),
+    Unresolved(UnresolvedPackageGroup),
+}
+
 /// Visitor helper for performing topological sort on a group of packages.
 fn visit<'a>(
     pkg: &'a UnresolvedPackage,
@@ -394,6 +440,29 @@ package {name} is defined in two different locations:\n\
     fn _push_dir(&mut self, path: &Path) -> Result<(PackageId, PackageSourceMap)> {
         let top_pkg = UnresolvedPackageGroup::parse_dir(path)
             .with_context(|| format!("failed to parse package: {}", path.display()))?;
+        self._push_dir_with_group(path, top_pkg)
+    }
+
+    /// Same as [`Resolve::push_dir`] except that the package within `path`
+    /// has already been parsed as `top_pkg`.
+    ///
+    /// This is useful when the WIT files in `path` have contents other than
+    /// those on the filesystem, for example documents being edited in an
+    /// editor. Dependencies are still read from the `deps` subdirectory of
+    /// `path`.
+    pub fn push_dir_with_group(
+        &mut self,
+        path: impl AsRef<Path>,
+        top_pkg: UnresolvedPackageGroup,
+    ) -> Result<(PackageId, PackageSourceMap)> {
+        self._push_dir_with_group(path.as_ref(), top_pkg)
+    }
+
+    fn _push_dir_with_group(
+        &mut self,
+        path: &Path,
+        top_pkg: UnresolvedPackageGroup,
+    ) -> Result<(PackageId, PackageSourceMap)> {
         let deps = path.join("deps");
         let deps = self
             .parse_deps_dir(&deps)
@@ -467,6 +536,17 @@ package {name} is defined in two different locations:\n\
     }
 
     fn _push_file(&mut self, path: &Path) -> Result<ParsedFile> {
+        match Resolve::parse_file(path)? {
+            #[cfg(feature = "decoding")]
+            FileContents::Package(resolve, pkg) => {
+                let remap = self.merge(resolve)?;
+                Ok(ParsedFile::Package(remap.packages[pkg.index()]))
+            }
+            FileContents::Unresolved(group) => Ok(ParsedFile::Unresolved(group)),
+        }
+    }
+
+    fn parse_file(path: &Path) -> Result<FileContents> {
         let contents = std::fs::read(path)
             .with_context(|| format!("failed to read path for WIT [{}]", path.display()))?;
 
@@ -493,8 +573,7 @@ package {name} is defined in two different locations:\n\
                         bail!("found an actual component instead of an encoded WIT package in wasm")
                     }
                     DecodedWasm::WitPackage(resolve, pkg) => {
-                        let remap = self.merge(resolve)?;
-                        return Ok(ParsedFile::Package(remap.packages[pkg.index()]));
+                        return Ok(FileContents::Package(resolve, pkg));
                     }
                 }
             }
@@ -506,7 +585,7 @@ package {name} is defined in two different locations:\n\
             Err(_) => bail!("input file is not valid utf-8 [{}]", path.display()),
         };
         let pkgs = UnresolvedPackageGroup::parse(path, text)?;
-        Ok(ParsedFile::Unresolved(pkgs))
+        Ok(FileContents::Unresolved(pkgs))
     }
 
     /// Appends a new [`UnresolvedPackage`] to this [`Resolve`], creating a
@@ -524,7 +603,8 @@ package {name} is defined in two different locations:\n\
         unresolved: UnresolvedPackage,
         source_map: &SourceMap,
     ) -> Result<PackageId> {
-        let ret = source_map.rewrite_error(|| Remap::default().append(self, unresolved));
+        let ret =
+            source_map.rewrite_error(|| Remap::default().append(self, unresolved, source_map));
         if ret.is_ok() {
             #[cfg(debug_assertions)]
             self.assert_valid();
@@ -655,6 +735,7 @@ package {name} is defined in two different locations:\n\
             packages,
             package_names,
             features: _,
+            locations,
             ..
         } = resolve;
 
@@ -810,6 +891,8 @@ package {name} is defined in two different locations:\n\
             assert!(prev.is_none());
         }
 
+        self.locations.merge(locations, &remap);
+
         log::trace!("now have {} packages", self.packages.len());
 
         #[cfg(debug_assertions)]
@@ -2756,6 +2839,7 @@ impl Remap {
         &mut self,
         resolve: &mut Resolve,
         unresolved: UnresolvedPackage,
+        source_map: &SourceMap,
     ) -> Result<PackageId> {
         let pkgid = resolve.packages.alloc(Package {
             name: unresolved.name.clone(),
@@ -2777,6 +2861,7 @@ impl Remap {
         let foreign_types = self.types.len();
         let foreign_interfaces = self.interfaces.len();
         let foreign_worlds = self.worlds.len();
+        let source = resolve.locations.push_source(source_map);
 
         // Copy over all types first, updating any intra-type references. Note
         // that types are sorted topologically which means this iteration
@@ -2816,6 +2901,7 @@ impl Remap {
                 // modified.
                 _ => new_id,
             };
+            resolve.locations.record_type(source, new_id, *span);
             self.types.push(Some(new_id));
         }
 
@@ -2850,8 +2936,17 @@ impl Remap {
             }
             assert!(iface.package.is_none());
             iface.package = Some(pkgid);
+            let functions = iface
+                .functions
+                .keys()
+                .cloned()
+                .zip(span.funcs.iter().copied())
+                .collect::<Vec<_>>();
             self.update_interface(resolve, &mut iface, Some(span))?;
             let new_id = resolve.interfaces.alloc(iface);
+            resolve
+                .locations
+                .record_interface(source, new_id, span.span, functions);
             assert_eq!(self.interfaces.len(), id.index());
             self.interfaces.push(Some(new_id));
         }
@@ -2901,9 +2996,12 @@ impl Remap {
                 self.worlds.push(None);
                 continue;
             }
-            self.update_world(&mut world, resolve, &pkgid, &span)?;
+            let item_spans = self.update_world(&mut world, resolve, &pkgid, &span)?;
 
             let new_id = resolve.worlds.alloc(world);
+            resolve
+                .locations
+                .record_world(source, new_id, span.span, item_spans);
             assert_eq!(self.worlds.len(), id.index());
             self.worlds.push(Some(new_id));
         }
@@ -2990,6 +3088,17 @@ impl Remap {
                 .insert(world.name.clone(), id);
             assert!(prev.is_none());
         }
+
+        for (span, reference) in unresolved.references.iter() {
+            let reference = match *reference {
+                Reference::Interface(id) => self.interfaces[id.index()].map(Reference::Interface),
+                Reference::World(id) => self.worlds[id.index()].map(Reference::World),
+                Reference::Type(id) => self.types[id.index()].map(Reference::Type),
+            };
+            if let Some(reference) = reference {
+                resolve.locations.record_reference(source, *span, reference);
+            }
+        }
         Ok(pkgid)
     }
 
@@ -3398,7 +3507,7 @@ impl Remap {
         resolve: &mut Resolve,
         pkg_id: &PackageId,
         spans: &WorldSpan,
-    ) -> Result<()> {
+    ) -> Result<Vec<(WorldKey, bool, Span)>> {
         assert_eq!(world.imports.len(), spans.imports.len());
         assert_eq!(world.exports.len(), spans.exports.len());
 
@@ -3409,6 +3518,7 @@ impl Remap {
         let imports = imports.zip(&spans.imports).map(|p| (p, true));
         let exports = mem::take(&mut world.exports).into_iter();
         let exports = exports.zip(&spans.exports).map(|p| (p, false));
+        let mut item_spans = Vec::new();
         for (((mut name, mut item), span), import) in imports.chain(exports) {
             // Update the `id` eagerly here so `item.stability(..)` below
             // works.
@@ -3440,11 +3550,12 @@ impl Remap {
             } else {
                 &mut world.exports
             };
+            item_spans.push((name.clone(), import, *span));
             let prev = dst.insert(name, item);
             assert!(prev.is_none());
         }
 
-        Ok(())
+        Ok(item_spans)
     }
 
     fn process_world_includes(
@@ -3576,11 +3687,15 @@ impl Remap {
     ) -> Result<()> {
         match item.0 {
             WorldKey::Name(n) => {
+                let item_name = include_item_name(n, item.1);
                 let n = if let Some(found) = names
                     .into_iter()
-                    .find(|include_name| include_name.name == n.clone())
+                    .find(|include_name| include_name.name == item_name)
                 {
-                    found.as_.clone()
+                    // Preserve the `[async]` prefix, if any, of the original
+                    // name as `with` only refers to the item's name.
+                    let prefix = &n[..n.len() - item_name.len()];
+                    format!("{prefix}{}", found.as_)
                 } else {
                     n.clone()
                 };
@@ -3594,9 +3709,25 @@ impl Remap {
                 let key = WorldKey::Name(n.clone());
                 cloner.world_item(&key, &mut new_item);
                 match &mut new_item {
-                    WorldItem::Function(f) => f.name = n.clone(),
-                    WorldItem::Type(id) => cloner.resolve.types[*id].name = Some(n.clone()),
-                    WorldItem::Interface { .. } => {}
+                    WorldItem::Function(f) => {
+                        f.name = n.clone();
+                        if is_external_include {
+                            drop_external_stability(&mut f.stability);
+                        }
+                    }
+                    WorldItem::Type(id) => {
+                        let ty = &mut cloner.resolve.types[*id];
+                        ty.name = Some(n.clone());
+                        if is_external_include {
+                            drop_external_stability(&mut ty.stability);
+                        }
+                    }
+                    WorldItem::Interface { id, stability } => {
+                        if is_external_include {
+                            drop_external_stability(stability);
+                            drop_external_stability(&mut cloner.resolve.interfaces[*id].stability);
+                        }
+                    }
                 }
 
                 let prev = get_items(cloner.resolve).insert(key, new_item);
@@ -3638,7 +3769,8 @@ impl Remap {
     fn remove_matching_name(&self, item: (&WorldKey, &WorldItem), names: &mut Vec<IncludeName>) {
         match item.0 {
             WorldKey::Name(n) => {
-                names.retain(|name| name.name != n.clone());
+                let n = include_item_name(n, item.1);
+                names.retain(|name| name.name != n);
             }
             _ => {}
         }
@@ -4057,6 +4189,16 @@ fn update_stability(from: &Stability, into: &mut Stability) -> Result<()> {
     bail!("mismatch in stability from '{:?}' to '{:?}'", from, into)
 }
 
+/// Returns the name by which `include ... with` refers to the world item
+/// `item` keyed by `key`, which for async functions excludes the `[async]`
+/// prefix of the key.
+fn include_item_name<'a>(key: &'a str, item: &'a WorldItem) -> &'a str {
+    match item {
+        WorldItem::Function(f) => f.item_name(),
+        _ => key,
+    }
+}
+
 fn merge_include_stability(
     from: &Stability,
     into: &mut Stability,
@@ -4071,6 +4213,16 @@ fn merge_include_stability(
     return update_stability(from, into);
 }
 
+/// Drops the `@since` stability of an item of a world included from another
+/// package, as its versions refer to that package rather than the one doing
+/// the including. Feature-gated items keep their gates.
+fn drop_external_stability(stability: &mut Stability) {
+    if stability.is_stable() {
+        log::trace!("dropped stability from external package");
+        *stability = Stability::Unknown;
+    }
+}
+
 /// An error that can be returned during "world elaboration" during various
 /// [`Resolve`] operations.
 ///
@@ -4101,8 +4253,9 @@ impl std::error::Error for InvalidTransitiveDependency {}
 
 #[cfg(test)]
 mod tests {
-    use crate::Resolve;
+    use crate::{Reference, Resolve, WorldKey};
     use anyhow::Result;
+    use std::path::Path;
 
     #[test]
     fn select_world() -> Result<()> {
@@ -4163,4 +4316,199 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize() -> Result<()> {
+        let mut resolve = Resolve::default();
+        resolve.push_str(
+            "test.wit",
+            "package foo:bar@1.0.0;
+
+interface i {
+  type t = u32;
+  f: func(x: t);
+}
+
+world w {
+  import i;
+}
+",
+        )?;
+        let json = serde_json::to_string(&resolve)?;
+        let resolve: Resolve = serde_json::from_str(&json)?;
+
+        // Ids are usable with the arenas of the deserialized `Resolve`.
+        let (pkg, _) = resolve.packages.iter().next().unwrap();
+        let w = resolve.select_world(pkg, Some("w"))?;
+        let (key, _) = resolve.worlds[w].imports.first().unwrap();
+        assert_eq!(resolve.name_world_key(key), "foo:bar/i@1.0.0");
+        let i = resolve.packages[pkg].interfaces["i"];
+        let t = resolve.interfaces[i].types["t"];
+        assert_eq!(resolve.types[t].name.as_deref(), Some("t"));
+        assert_eq!(
+            resolve.package_names[&resolve.packages[pkg].name],
+            resolve.interfaces[i].package.unwrap()
+        );
+
+        // Out-of-bounds ids are rejected.
+        let err =
+            serde_json::from_str::<Resolve>(&json.replace(r#""package":0"#, r#""package":10"#))
+                .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("package index 10 is out of bounds, there are only 1 packages"),
+            "{err}"
+        );
+
+        // Items which don't agree on their owner are rejected.
+        let err = serde_json::from_str::<Resolve>(&json.replace(r#""name":"t""#, r#""name":"u""#))
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("type `t` of interface 0 does not belong to it"),
+            "{err}"
+        );
+
+        // Types referring to themselves are rejected.
+        let err = serde_json::from_str::<Resolve>(
+            &json.replace(r#""kind":{"type":"u32"}"#, r#""kind":{"type":0}"#),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("type 0 refers to type 0, which isn't defined before it"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn locations() -> Result<()> {
+        let mut resolve = Resolve::default();
+        let pkg = resolve.push_str(
+            "test.wit",
+            "package foo:bar;
+
+interface i {
+  type t = u32;
+  f: func();
+}
+
+world w {
+  import i;
+  export g: func();
+}
+",
+        )?;
+        let i = resolve.packages[pkg].interfaces["i"];
+        let w = resolve.packages[pkg].worlds["w"];
+        let t = resolve.interfaces[i].types["t"];
+        let locations = &resolve.locations;
+        assert_eq!(locations.interface(i).unwrap().to_string(), "test.wit:3:11");
+        assert_eq!(locations.ty(t).unwrap().to_string(), "test.wit:4:8");
+        assert_eq!(
+            locations.function(i, "f").unwrap().to_string(),
+            "test.wit:5:3"
+        );
+        assert_eq!(locations.world(w).unwrap().to_string(), "test.wit:8:7");
+        assert!(locations.function(i, "g").is_none());
+        assert_eq!(
+            locations
+                .world_import(w, &WorldKey::Interface(i))
+                .unwrap()
+                .to_string(),
+            "test.wit:9:10"
+        );
+        let g = WorldKey::Name("g".to_string());
+        assert_eq!(
+            locations.world_export(w, &g).unwrap().to_string(),
+            "test.wit:10:10"
+        );
+        assert!(locations.world_import(w, &g).is_none());
+
+        // Locations are carried over when merging resolves.
+        let mut merged = Resolve::default();
+        merged.push_str("other.wit", "package foo:baz;")?;
+        let remap = merged.merge(resolve)?;
+        let i = remap.interfaces[i.index()].unwrap();
+        assert_eq!(
+            merged.locations.function(i, "f").unwrap().to_string(),
+            "test.wit:5:3"
+        );
+        let w = remap.worlds[w.index()].unwrap();
+        assert_eq!(
+            merged
+                .locations
+                .world_import(w, &WorldKey::Interface(i))
+                .unwrap()
+                .to_string(),
+            "test.wit:9:10"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn references() -> Result<()> {
+        let mut resolve = Resolve::default();
+        let pkg = resolve.push_str(
+            "test.wit",
+            "package foo:bar;
+
+interface i {
+  type t = u32;
+}
+
+interface j {
+  use i.{t};
+  f: func(x: t);
+}
+
+world base {}
+world w {
+  include base;
+  import j;
+}
+",
+        )?;
+        let i = resolve.packages[pkg].interfaces["i"];
+        let j = resolve.packages[pkg].interfaces["j"];
+        let base = resolve.packages[pkg].worlds["base"];
+        let t = resolve.interfaces[i].types["t"];
+        let t_in_j = resolve.interfaces[j].types["t"];
+        let path = Path::new("test.wit");
+        let locations = &resolve.locations;
+        assert_eq!(
+            locations.reference_at(path, 8, 7),
+            Some(Reference::Interface(i))
+        );
+        assert_eq!(
+            locations.reference_at(path, 8, 10),
+            Some(Reference::Type(t))
+        );
+        assert_eq!(
+            locations.reference_at(path, 9, 14),
+            Some(Reference::Type(t_in_j))
+        );
+        assert_eq!(
+            locations.reference_at(path, 14, 11),
+            Some(Reference::World(base))
+        );
+        assert_eq!(
+            locations.reference_at(path, 15, 10),
+            Some(Reference::Interface(j))
+        );
+        assert_eq!(locations.reference_at(path, 9, 3), None);
+        assert_eq!(locations.reference_at(Path::new("x.wit"), 8, 7), None);
+
+        let mut merged = Resolve::default();
+        merged.push_str("other.wit", "package foo:baz;")?;
+        let remap = merged.merge(resolve)?;
+        let t = remap.types[t.index()].unwrap();
+        assert_eq!(
+            merged.locations.reference_at(path, 8, 10),
+            Some(Reference::Type(t))
+        );
+        Ok(())
+    }
 }
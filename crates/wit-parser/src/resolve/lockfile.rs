@@ -0,0 +1,446 @@
+use crate::{PackageId, PackageName, PackageSourceMap, Resolve, UnresolvedPackageGroup};
+use anyhow::{Context, Result, bail};
+use serde_derive::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A mapping from the names of WIT packages to their locations on the
+/// filesystem, used to resolve dependencies with
+/// [`Resolve::push_path_with_lockfile`].
+///
+/// Lockfiles are written as JSON such as:
+///
+/// ```json
+/// {
+///     "registry": "../wit-registry",
+///     "packages": [
+///         { "name": "wasi:io@0.2.0", "path": "../wasi-io/wit" },
+///         { "name": "wasi:io@0.2.3", "sha256": "9f8e..." },
+///         { "name": "my:types", "path": "types.wasm", "sha256": "0c1d..." }
+///     ]
+/// }
+/// ```
+///
+/// Each package is located at its `path` if one is given. Otherwise it's
+/// looked up within the `registry` directory as `$namespace/$name@$version`,
+/// or `$namespace/$name` for packages without a version, which is either a
+/// directory of WIT files or has a `.wit`, `.wasm` or `.wat` extension.
+/// Relative paths are relative to the directory containing the lockfile.
+///
+/// Packages are identified by their full name including the version, so
+/// multiple versions of a package can be used side-by-side.
+///
+/// If a package lists a `sha256` hash then the contents of the package are
+/// verified against it when the package is loaded. The hash of a file is the
+/// SHA-256 of its contents, and the hash of a directory is computed with
+/// [`Lockfile::hash_path`].
+#[derive(Debug, Clone, Default)]
+pub struct Lockfile {
+    registry: Option<PathBuf>,
+    packages: HashMap<PackageName, LockedPackage>,
+}
+
+/// A package listed in a [`Lockfile`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct LockedPackage {
+    /// The full name of this package, including its version if it has one.
+    pub name: PackageName,
+    /// The path to this package, if it's not in the lockfile's registry.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// The expected hash of this package's contents, as lowercase hex.
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LockfileJson {
+    #[serde(default)]
+    registry: Option<PathBuf>,
+    #[serde(default)]
+    packages: Vec<LockedPackage>,
+}
+
+impl Lockfile {
+    /// Creates a new empty lockfile, with paths relative to the current
+    /// directory.
+    pub fn new() -> Lockfile {
+        Lockfile::default()
+    }
+
+    /// Reads the JSON lockfile at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Lockfile> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read lockfile [{}]", path.display()))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        Lockfile::parse(dir, &contents)
+            .with_context(|| format!("failed to parse lockfile [{}]", path.display()))
+    }
+
+    /// Parses the JSON lockfile `contents`, with relative paths being
+    /// relative to `dir`.
+    pub fn parse(dir: impl AsRef<Path>, contents: &str) -> Result<Lockfile> {
+        let dir = dir.as_ref();
+        let json: LockfileJson = serde_json::from_str(contents)?;
+        let mut lockfile = Lockfile::new();
+        if let Some(registry) = json.registry {
+            lockfile.registry(dir.join(registry));
+        }
+        for mut package in json.packages {
+            package.path = package.path.map(|p| dir.join(p));
+            lockfile.insert(package)?;
+        }
+        Ok(lockfile)
+    }
+
+    /// Sets the directory that packages without a path are located in.
+    pub fn registry(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.registry = Some(dir.into());
+        self
+    }
+
+    /// Adds `package` to this lockfile.
+    ///
+    /// Returns an error if a package with the same name is already present.
+    pub fn insert(&mut self, package: LockedPackage) -> Result<()> {
+        if self.packages.contains_key(&package.name) {
+            bail!("package `{}` is listed more than once", package.name);
+        }
+        self.packages.insert(package.name.clone(), package);
+        Ok(())
+    }
+
+    /// Returns the package named `name`, if it's listed.
+    pub fn get(&self, name: &PackageName) -> Option<&LockedPackage> {
+        self.packages.get(name)
+    }
+
+    /// Returns the path of the package `name`, if it's listed.
+    ///
+    /// Packages within the registry must exist for a path to be returned.
+    pub fn path(&self, name: &PackageName) -> Option<PathBuf> {
+        let package = self.packages.get(name)?;
+        if let Some(path) = &package.path {
+            return Some(path.clone());
+        }
+        let dir = self.registry.as_ref()?.join(&name.namespace);
+        let base = match &name.version {
+            Some(version) => format!("{}@{version}", name.name),
+            None => name.name.clone(),
+        };
+        if dir.join(&base).is_dir() {
+            return Some(dir.join(base));
+        }
+        ["wit", "wasm", "wat"]
+            .iter()
+            .map(|ext| dir.join(format!("{base}.{ext}")))
+            .find(|path| path.is_file())
+    }
+
+    /// Computes the hash of the package at `path`, as listed in the `sha256`
+    /// field of a lockfile.
+    ///
+    /// For a file this is the SHA-256 of its contents. For a directory this is
+    /// the SHA-256 of every file within it, including those in subdirectories
+    /// such as `deps`, sorted by their paths relative to the directory. Each
+    /// file contributes its relative path with `/` separators, a zero byte,
+    /// the length of its contents as a little-endian 64-bit integer, and then
+    /// its contents.
+    pub fn hash_path(path: impl AsRef<Path>) -> Result<String> {
+        let path = path.as_ref();
+        let mut hasher = Sha256::new();
+        if path.is_dir() {
+            let mut files = Vec::new();
+            list_files(path, "", &mut files)?;
+            files.sort();
+            for (name, file) in files {
+                let contents = std::fs::read(&file)
+                    .with_context(|| format!("failed to read [{}]", file.display()))?;
+                hasher.update(name.as_bytes());
+                hasher.update([0]);
+                hasher.update((contents.len() as u64).to_le_bytes());
+                hasher.update(&contents);
+            }
+        } else {
+            let contents = std::fs::read(path)
+                .with_context(|| format!("failed to read [{}]", path.display()))?;
+            hasher.update(&contents);
+        }
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect())
+    }
+}
+
+impl Resolve {
+    /// Parses the WIT package at `path` like [`Resolve::push_path`],
+    /// additionally resolving its dependencies from `lockfile`.
+    ///
+    /// The `path` can be a directory or a single WIT file. Packages found in
+    /// the `deps` directory of a directory are used as with
+    /// [`Resolve::push_dir`]. Any other dependency, and transitively their
+    /// dependencies, are then loaded from the locations listed in `lockfile`,
+    /// verifying their hashes if the lockfile lists them.
+    ///
+    /// Packages in the lockfile which aren't needed are never read.
+    pub fn push_path_with_lockfile(
+        &mut self,
+        path: impl AsRef<Path>,
+        lockfile: &Lockfile,
+    ) -> Result<(PackageId, PackageSourceMap)> {
+        let path = path.as_ref();
+        let (top, mut deps) = if path.is_dir() {
+            let top = UnresolvedPackageGroup::parse_dir(path)
+                .with_context(|| format!("failed to parse package: {}", path.display()))?;
+            let deps = path.join("deps");
+            let deps = self.parse_deps_dir(&deps).with_context(|| {
+                format!("failed to parse dependency directory: {}", deps.display())
+            })?;
+            (top, deps)
+        } else {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read path for WIT [{}]", path.display()))?;
+            (UnresolvedPackageGroup::parse(path, &contents)?, Vec::new())
+        };
+
+        // Starting from the packages parsed so far, load every dependency
+        // which isn't otherwise known from the lockfile.
+        let mut known = HashSet::new();
+        let mut needed = Vec::new();
+        for group in [&top].into_iter().chain(&deps) {
+            add_group(group, &mut known, &mut needed);
+        }
+        while let Some(name) = needed.pop() {
+            if known.contains(&name) || self.package_names.contains_key(&name) {
+                continue;
+            }
+            if lockfile.get(&name).is_none() {
+                continue;
+            }
+            let group = self
+                .load_locked_package(lockfile, &name)
+                .with_context(|| format!("failed to load package `{name}` from lockfile"))?;
+            known.insert(name);
+            if let Some(group) = group {
+                add_group(&group, &mut known, &mut needed);
+                deps.push(group);
+            }
+        }
+
+        self.sort_unresolved_packages(top, deps)
+    }
+
+    /// Loads the package `name` from `lockfile`, returning the parsed package
+    /// or `None` if it was a wasm-encoded package which was added directly to
+    /// `self`.
+    fn load_locked_package(
+        &mut self,
+        lockfile: &Lockfile,
+        name: &PackageName,
+    ) -> Result<Option<UnresolvedPackageGroup>> {
+        let package = lockfile.get(name).unwrap();
+        let path = match lockfile.path(name) {
+            Some(path) => path,
+            None => bail!("package not found in the registry"),
+        };
+        if let Some(expected) = &package.sha256 {
+            let actual = Lockfile::hash_path(&path)?;
+            if !expected.eq_ignore_ascii_case(&actual) {
+                bail!(
+                    "hash mismatch for [{}]\n  expected: {expected}\n  actual:   {actual}",
+                    path.display()
+                );
+            }
+        }
+
+        let group = if path.is_dir() {
+            UnresolvedPackageGroup::parse_dir(&path)
+                .with_context(|| format!("failed to parse package: {}", path.display()))?
+        } else {
+            match Resolve::parse_file(&path)? {
+                #[cfg(feature = "decoding")]
+                super::FileContents::Package(resolve, pkg) => {
+                    let found = &resolve.packages[pkg].name;
+                    if found != name {
+                        bail!("[{}] contains package `{found}`", path.display());
+                    }
+                    self.merge(resolve)?;
+                    return Ok(None);
+                }
+                super::FileContents::Unresolved(group) => group,
+            }
+        };
+        if group.main.name != *name {
+            bail!(
+                "[{}] contains package `{}`",
+                path.display(),
+                group.main.name
+            );
+        }
+        Ok(Some(group))
+    }
+}
+
+/// Collects every file within the directory `dir`, recursively, along with
+/// its path relative to the directory being hashed, where `prefix` is the
+/// relative path of `dir`.
+fn list_files(dir: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    for entry in dir
+        .read_dir()
+        .with_context(|| format!("failed to read directory [{}]", dir.display()))?
+    {
+        let entry = entry?;
+        let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+        let path = entry.path();
+        if path.is_dir() {
+            list_files(&path, &format!("{name}/"), files)?;
+        } else {
+            files.push((name, path));
+        }
+    }
+    Ok(())
+}
+
+/// Records the packages defined in `group` as known and their dependencies as
+/// needed.
+fn add_group(
+    group: &UnresolvedPackageGroup,
+    known: &mut HashSet<PackageName>,
+    needed: &mut Vec<PackageName>,
+) {
+    for pkg in group.nested.iter().chain([&group.main]) {
+        known.insert(pkg.name.clone());
+        needed.extend(pkg.foreign_deps.keys().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn versions_side_by_side() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dir = dir.path();
+        write(
+            &dir.join("main/main.wit"),
+            "package a:main;
+world w {
+  import b:dep/i@1.0.0;
+  import b:dep/i@2.0.0;
+}",
+        );
+        write(
+            &dir.join("registry/b/dep@1.0.0.wit"),
+            "package b:dep@1.0.0; interface i { use c:leaf/types.{t}; }",
+        );
+        write(
+            &dir.join("registry/b/dep@2.0.0/dep.wit"),
+            "package b:dep@2.0.0; interface i {}",
+        );
+        write(
+            &dir.join("leaf.wit"),
+            "package c:leaf; interface types { type t = u32; }",
+        );
+        let leaf = Lockfile::hash_path(dir.join("leaf.wit"))?;
+        let dep2 = Lockfile::hash_path(dir.join("registry/b/dep@2.0.0"))?;
+        let json = format!(
+            r#"{{
+                "registry": "registry",
+                "packages": [
+                    {{ "name": "b:dep@1.0.0" }},
+                    {{ "name": "b:dep@2.0.0", "sha256": "{dep2}" }},
+                    {{ "name": "c:leaf", "path": "leaf.wit", "sha256": "{leaf}" }},
+                    {{ "name": "d:unused", "path": "does-not-exist" }}
+                ]
+            }}"#
+        );
+        write(&dir.join("wit.lock"), &json);
+        let lockfile = Lockfile::from_file(dir.join("wit.lock"))?;
+
+        let mut resolve = Resolve::new();
+        let (pkg, _) = resolve.push_path_with_lockfile(dir.join("main"), &lockfile)?;
+        let world = resolve.select_world(pkg, None)?;
+        assert_eq!(resolve.worlds[world].imports.len(), 3);
+        let names = resolve
+            .packages
+            .iter()
+            .map(|(_, p)| p.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["c:leaf", "b:dep@1.0.0", "b:dep@2.0.0", "a:main"]);
+
+        // Changing a package invalidates its hash.
+        write(
+            &dir.join("leaf.wit"),
+            "package c:leaf; interface types { type t = u64; }",
+        );
+        let err = Resolve::new()
+            .push_path_with_lockfile(dir.join("main"), &lockfile)
+            .unwrap_err();
+        let err = format!("{err:?}");
+        assert!(err.contains("hash mismatch"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn hash_whole_directory() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dir = dir.path();
+        write(&dir.join("a.wit"), "package a:b;");
+        write(&dir.join("deps/c/c.wit"), "package c:d;");
+        let hash = Lockfile::hash_path(dir)?;
+
+        // Changes to dependencies in subdirectories change the hash.
+        write(&dir.join("deps/c/c.wit"), "package c:d; interface i {}");
+        let changed = Lockfile::hash_path(dir)?;
+        assert_ne!(hash, changed);
+
+        // Moving a file changes the hash even if its contents are unchanged.
+        std::fs::rename(dir.join("deps/c/c.wit"), dir.join("deps/c.wit"))?;
+        assert_ne!(changed, Lockfile::hash_path(dir)?);
+        Ok(())
+    }
+
+    #[test]
+    fn wasm_package_with_wrong_name() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let dir = dir.path();
+        write(
+            &dir.join("main.wit"),
+            "package a:main; world w { import c:d/x; }",
+        );
+        write(
+            &dir.join("pkg.wat"),
+            r#"(component
+                (type $x (component (export "a:b/x" (instance))))
+                (export "x" (type $x))
+            )"#,
+        );
+        write(
+            &dir.join("wit.lock"),
+            r#"{ "packages": [{ "name": "c:d", "path": "pkg.wat" }] }"#,
+        );
+        let lockfile = Lockfile::from_file(dir.join("wit.lock"))?;
+
+        let mut resolve = Resolve::new();
+        let err = resolve
+            .push_path_with_lockfile(dir.join("main.wit"), &lockfile)
+            .unwrap_err();
+        let err = format!("{err:?}");
+        assert!(err.contains("contains package `a:b`"), "{err}");
+        assert_eq!(resolve.packages.len(), 0);
+        Ok(())
+    }
+}
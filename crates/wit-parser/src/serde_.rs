@@ -1,9 +1,15 @@
-use crate::Type;
-use id_arena::{Arena, Id};
+use crate::{
+    Function, Interface, Package, PackageName, Resolve, SourceLocations, Type, TypeDef,
+    TypeDefKind, TypeId, TypeIdVisitor, TypeOwner, World, WorldItem, WorldKey,
+};
+use id_arena::{Arena, ArenaBehavior, DefaultArenaBehavior, Id};
 use indexmap::IndexMap;
 use semver::Version;
+use serde::de::{Deserializer, Visitor};
 use serde::ser::{SerializeMap, SerializeSeq, Serializer};
 use serde::{Deserialize, Serialize, de::Error};
+use std::cell::RefCell;
+use std::fmt;
 
 pub fn serialize_none<S>(serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -93,9 +99,17 @@ where
     seq.end()
 }
 
-#[derive(Debug, Clone, PartialEq, serde_derive::Serialize)]
+pub fn deserialize_params<'de, D>(deserializer: D) -> Result<Vec<(String, Type)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let params = Vec::<Param>::deserialize(deserializer)?;
+    Ok(params.into_iter().map(|p| (p.name, p.typ)).collect())
+}
+
+#[derive(Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
 struct Param {
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub name: String,
     #[serde(rename = "type")]
     pub typ: Type,
@@ -138,3 +152,436 @@ where
         None => Ok(None),
     }
 }
+
+// Ids within a `Resolve` are serialized as indices into their arena, but an
+// `Id` additionally records which arena it belongs to. To deserialize ids the
+// arenas of the `Resolve` being deserialized are created up-front and their
+// identifiers are stored here while the rest of the `Resolve` is deserialized.
+thread_local! {
+    static ARENAS: RefCell<Option<ArenaState>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone, Copy)]
+struct ArenaState {
+    ids: [u32; 4],
+    /// One more than the largest index deserialized for each arena, used to
+    /// validate ids once the arenas are filled in.
+    limits: [usize; 4],
+}
+
+/// An item stored in one of the arenas of a `Resolve`.
+pub trait ArenaItem: Sized {
+    const ARENA: usize;
+    const NAME: &'static str;
+}
+
+impl ArenaItem for World {
+    const ARENA: usize = 0;
+    const NAME: &'static str = "world";
+}
+
+impl ArenaItem for Interface {
+    const ARENA: usize = 1;
+    const NAME: &'static str = "interface";
+}
+
+impl ArenaItem for TypeDef {
+    const ARENA: usize = 2;
+    const NAME: &'static str = "type";
+}
+
+impl ArenaItem for Package {
+    const ARENA: usize = 3;
+    const NAME: &'static str = "package";
+}
+
+fn arena_id<T>(arena: &Arena<T>) -> u32 {
+    DefaultArenaBehavior::<T>::arena_id(arena.next_id())
+}
+
+fn id_from_index<T: ArenaItem, E: Error>(index: u64) -> Result<Id<T>, E> {
+    let index = usize::try_from(index).map_err(E::custom)?;
+    ARENAS.with(|arenas| {
+        let mut arenas = arenas.borrow_mut();
+        let state = arenas
+            .as_mut()
+            .ok_or_else(|| E::custom("ids can only be deserialized as part of a `Resolve`"))?;
+        let limit = &mut state.limits[T::ARENA];
+        *limit = (*limit).max(index + 1);
+        Ok(DefaultArenaBehavior::<T>::new_id(
+            state.ids[T::ARENA],
+            index,
+        ))
+    })
+}
+
+pub fn deserialize_id<'de, T, D>(deserializer: D) -> Result<Id<T>, D::Error>
+where
+    T: ArenaItem,
+    D: Deserializer<'de>,
+{
+    id_from_index(u64::deserialize(deserializer)?)
+}
+
+pub fn deserialize_optional_id<'de, T, D>(deserializer: D) -> Result<Option<Id<T>>, D::Error>
+where
+    T: ArenaItem,
+    D: Deserializer<'de>,
+{
+    match Option::<u64>::deserialize(deserializer)? {
+        Some(index) => Ok(Some(id_from_index(index)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn deserialize_id_map<'de, T, D>(deserializer: D) -> Result<IndexMap<String, Id<T>>, D::Error>
+where
+    T: ArenaItem,
+    D: Deserializer<'de>,
+{
+    IndexMap::<String, u64>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, index)| Ok((name, id_from_index(index)?)))
+        .collect()
+}
+
+impl<'de> Deserialize<'de> for Type {
+    fn deserialize<D>(deserializer: D) -> Result<Type, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TypeVisitor;
+
+        impl<'de> Visitor<'de> for TypeVisitor {
+            type Value = Type;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a primitive type name or a type index")
+            }
+
+            fn visit_u64<E: Error>(self, index: u64) -> Result<Type, E> {
+                Ok(Type::Id(id_from_index(index)?))
+            }
+
+            fn visit_str<E: Error>(self, name: &str) -> Result<Type, E> {
+                Ok(match name {
+                    "bool" => Type::Bool,
+                    "u8" => Type::U8,
+                    "u16" => Type::U16,
+                    "u32" => Type::U32,
+                    "u64" => Type::U64,
+                    "s8" => Type::S8,
+                    "s16" => Type::S16,
+                    "s32" => Type::S32,
+                    "s64" => Type::S64,
+                    "f32" => Type::F32,
+                    "f64" => Type::F64,
+                    "char" => Type::Char,
+                    "string" => Type::String,
+                    "error-context" => Type::ErrorContext,
+                    _ => return Err(E::custom(format!("unknown primitive type `{name}`"))),
+                })
+            }
+        }
+
+        deserializer.deserialize_any(TypeVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for PackageName {
+    fn deserialize<D>(deserializer: D) -> Result<PackageName, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        let (rest, version) = match name.split_once('@') {
+            Some((rest, version)) => (rest, Some(version.parse().map_err(D::Error::custom)?)),
+            None => (name.as_str(), None),
+        };
+        let (namespace, name) = rest
+            .split_once(':')
+            .ok_or_else(|| D::Error::custom(format!("invalid package name `{name}`")))?;
+        Ok(PackageName {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for WorldKey {
+    fn deserialize<D>(deserializer: D) -> Result<WorldKey, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Interfaces are serialized as `interface-N`, which can't be confused
+        // with a kebab-name as each word of a kebab-name starts with a letter.
+        let name = String::deserialize(deserializer)?;
+        if let Some(index) = name.strip_prefix("interface-") {
+            if let Ok(index) = index.parse() {
+                return Ok(WorldKey::Interface(id_from_index(index)?));
+            }
+        }
+        Ok(WorldKey::Name(name))
+    }
+}
+
+impl<'de> Deserialize<'de> for Resolve {
+    fn deserialize<D>(deserializer: D) -> Result<Resolve, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(serde_derive::Deserialize)]
+        struct Arenas {
+            worlds: Vec<World>,
+            interfaces: Vec<Interface>,
+            types: Vec<TypeDef>,
+            packages: Vec<Package>,
+        }
+
+        let mut worlds = Arena::new();
+        let mut interfaces = Arena::new();
+        let mut types = Arena::new();
+        let mut packages = Arena::new();
+        let state = ArenaState {
+            ids: [
+                arena_id(&worlds),
+                arena_id(&interfaces),
+                arena_id(&types),
+                arena_id(&packages),
+            ],
+            limits: [0; 4],
+        };
+
+        let prev = ARENAS.with(|a| a.borrow_mut().replace(state));
+        let result = Arenas::deserialize(deserializer);
+        let state = ARENAS
+            .with(|a| std::mem::replace(&mut *a.borrow_mut(), prev))
+            .unwrap();
+        let arenas = result?;
+
+        let lens = [
+            arenas.worlds.len(),
+            arenas.interfaces.len(),
+            arenas.types.len(),
+            arenas.packages.len(),
+        ];
+        let names = [World::NAME, Interface::NAME, TypeDef::NAME, Package::NAME];
+        for ((limit, len), name) in state.limits.iter().zip(lens).zip(names) {
+            if *limit > len {
+                return Err(D::Error::custom(format!(
+                    "{name} index {} is out of bounds, there are only {len} {name}s",
+                    limit - 1
+                )));
+            }
+        }
+
+        for world in arenas.worlds {
+            worlds.alloc(world);
+        }
+        for interface in arenas.interfaces {
+            interfaces.alloc(interface);
+        }
+        for ty in arenas.types {
+            types.alloc(ty);
+        }
+        for package in arenas.packages {
+            packages.alloc(package);
+        }
+        let mut package_names = IndexMap::new();
+        for (id, package) in packages.iter() {
+            if package_names.insert(package.name.clone(), id).is_some() {
+                return Err(D::Error::custom(format!(
+                    "package `{}` is defined more than once",
+                    package.name
+                )));
+            }
+        }
+
+        let resolve = Resolve {
+            worlds,
+            interfaces,
+            types,
+            packages,
+            package_names,
+            features: Default::default(),
+            all_features: false,
+            locations: SourceLocations::default(),
+        };
+        validate(&resolve).map_err(D::Error::custom)?;
+        Ok(resolve)
+    }
+}
+
+/// Checks the invariants of a `Resolve` which its serialized form can't
+/// guarantee by construction, such as items agreeing on which package,
+/// interface or world owns them and types being topologically sorted.
+fn validate(resolve: &Resolve) -> Result<(), String> {
+    for (id, pkg) in resolve.packages.iter() {
+        for (name, iface) in pkg.interfaces.iter() {
+            let iface = &resolve.interfaces[*iface];
+            if iface.name.as_ref() != Some(name) || iface.package != Some(id) {
+                return Err(format!(
+                    "interface `{name}` of package `{}` does not belong to it",
+                    pkg.name
+                ));
+            }
+        }
+        for (name, world) in pkg.worlds.iter() {
+            let world = &resolve.worlds[*world];
+            if world.name != *name || world.package != Some(id) {
+                return Err(format!(
+                    "world `{name}` of package `{}` does not belong to it",
+                    pkg.name
+                ));
+            }
+        }
+    }
+
+    for (id, iface) in resolve.interfaces.iter() {
+        for (name, ty) in iface.types.iter() {
+            let ty = &resolve.types[*ty];
+            if ty.name.as_ref() != Some(name) || ty.owner != TypeOwner::Interface(id) {
+                return Err(format!(
+                    "type `{name}` of interface {} does not belong to it",
+                    id.index()
+                ));
+            }
+        }
+        for (name, func) in iface.functions.iter() {
+            validate_function(resolve, name, func)?;
+        }
+    }
+
+    for (id, world) in resolve.worlds.iter() {
+        for (key, item) in world.imports.iter().chain(world.exports.iter()) {
+            match (key, item) {
+                (WorldKey::Interface(a), WorldItem::Interface { id: b, .. }) if a == b => {}
+                (WorldKey::Interface(a), _) => {
+                    return Err(format!(
+                        "world `{}` has an item keyed by interface {} which isn't that interface",
+                        world.name,
+                        a.index()
+                    ));
+                }
+                (WorldKey::Name(_), WorldItem::Interface { .. }) => {}
+                (WorldKey::Name(name), WorldItem::Function(func)) => {
+                    validate_function(resolve, name, func)?;
+                }
+                (WorldKey::Name(name), WorldItem::Type(ty)) => {
+                    let ty = &resolve.types[*ty];
+                    if ty.name.as_ref() != Some(name) || ty.owner != TypeOwner::World(id) {
+                        return Err(format!(
+                            "type `{name}` of world `{}` does not belong to it",
+                            world.name
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    for (id, ty) in resolve.types.iter() {
+        if let TypeDefKind::Unknown = ty.kind {
+            return Err(format!("type {} is of an unknown kind", id.index()));
+        }
+        if let TypeDefKind::Type(Type::Id(other)) = ty.kind {
+            let owner = resolve.types[other].owner;
+            if owner != ty.owner && !matches!(owner, TypeOwner::Interface(_)) {
+                return Err(format!(
+                    "type {} is an alias of type {}, which isn't defined in an interface",
+                    id.index(),
+                    other.index()
+                ));
+            }
+        }
+        let mut deps = DirectDeps(Vec::new());
+        deps.visit_type_def(resolve, ty);
+        if let Some(dep) = deps.0.iter().find(|dep| dep.index() >= id.index()) {
+            return Err(format!(
+                "type {} refers to type {}, which isn't defined before it",
+                id.index(),
+                dep.index()
+            ));
+        }
+    }
+
+    let interfaces = resolve.interfaces.iter().map(|(id, _)| id).collect();
+    if let Some(id) = find_cycle(interfaces, |id| resolve.interface_direct_deps(id).collect()) {
+        return Err(format!(
+            "interface {} depends on itself through its types",
+            id.index()
+        ));
+    }
+    let packages = resolve.packages.iter().map(|(id, _)| id).collect();
+    if let Some(id) = find_cycle(packages, |id| resolve.package_direct_deps(id).collect()) {
+        return Err(format!(
+            "package `{}` depends on itself",
+            resolve.packages[id].name
+        ));
+    }
+    Ok(())
+}
+
+fn validate_function(resolve: &Resolve, name: &str, func: &Function) -> Result<(), String> {
+    if func.name != name {
+        return Err(format!(
+            "function `{}` is listed under the name `{name}`",
+            func.name
+        ));
+    }
+    if let Some(resource) = func.kind.resource() {
+        if !matches!(resolve.types[resource].kind, TypeDefKind::Resource) {
+            return Err(format!(
+                "function `{name}` belongs to type {}, which isn't a resource",
+                resource.index()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Collects the types referred to by a type definition, without recursing
+/// into them.
+struct DirectDeps(Vec<TypeId>);
+
+impl TypeIdVisitor for DirectDeps {
+    fn before_visit_type_id(&mut self, id: TypeId) -> bool {
+        self.0.push(id);
+        false
+    }
+}
+
+/// Returns an item of `ids` which transitively depends on itself through
+/// `deps`, if any.
+fn find_cycle<T>(ids: Vec<Id<T>>, deps: impl Fn(Id<T>) -> Vec<Id<T>>) -> Option<Id<T>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Unvisited,
+        Visiting,
+        Done,
+    }
+
+    fn visit<T>(
+        id: Id<T>,
+        deps: &impl Fn(Id<T>) -> Vec<Id<T>>,
+        states: &mut [State],
+    ) -> Option<Id<T>> {
+        match states[id.index()] {
+            State::Done => return None,
+            State::Visiting => return Some(id),
+            State::Unvisited => {}
+        }
+        states[id.index()] = State::Visiting;
+        for dep in deps(id) {
+            if let Some(id) = visit(dep, deps, states) {
+                return Some(id);
+            }
+        }
+        states[id.index()] = State::Done;
+        None
+    }
+
+    let mut states = vec![State::Unvisited; ids.len()];
+    ids.into_iter().find_map(|id| visit(id, &deps, &mut states))
+}
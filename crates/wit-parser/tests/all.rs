@@ -96,6 +96,15 @@ impl Runner {
             let json_result = serde_json::to_string_pretty(&resolve)?;
             // "foo.wit" => "foo.wit.json"
             self.read_or_write_to_file(test, &json_result, "json")?;
+
+            // Deserializing the json should produce the same `Resolve`.
+            let roundtrip: Resolve = serde_json::from_str(&json_result)
+                .context("failed to deserialize json representation")?;
+            assert_eq!(serde_json::to_string_pretty(&roundtrip)?, json_result);
+
+            if test.extension() == Some(OsStr::new("wit")) {
+                self.check_format(test, &json_result)?;
+            }
             return Ok(());
         };
 
@@ -105,6 +114,33 @@ impl Runner {
         return Ok(());
     }
 
+    /// Formatting a test should be idempotent and should produce the same
+    /// `Resolve` as the original.
+    fn check_format(&mut self, test: &Path, json_result: &str) -> Result<()> {
+        let contents = fs::read_to_string(test)?;
+        let formatted = wit_parser::format(test, &contents)?;
+        let reformatted = wit_parser::format(test, &formatted)?;
+        if formatted != reformatted {
+            bail!(
+                "formatting is not idempotent:{}",
+                StrComparison::new(&formatted, &reformatted),
+            );
+        }
+        let mut resolve = Resolve::new();
+        resolve.features.insert("active".to_string());
+        resolve
+            .push_str(test, &formatted)
+            .with_context(|| format!("formatted test failed to parse:\n{formatted}"))?;
+        let json = serde_json::to_string_pretty(&resolve)?;
+        if json != json_result {
+            bail!(
+                "formatted test resolved differently:\n{formatted}{}",
+                StrComparison::new(json_result, &json),
+            );
+        }
+        Ok(())
+    }
+
     fn read_or_write_to_file(
         &mut self,
         test: &Path,
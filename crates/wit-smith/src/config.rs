@@ -19,6 +19,23 @@ pub struct Config {
     pub max_files_per_package: usize,
     #[cfg_attr(feature = "clap", clap(long, default_value_t = Config::default().max_resource_items))]
     pub max_resource_items: usize,
+    /// The maximum number of versions of the same package to generate.
+    #[cfg_attr(feature = "clap", clap(long, default_value_t = Config::default().max_package_versions))]
+    pub max_package_versions: usize,
+    /// Whether to generate `@since`, `@unstable`, and `@deprecated`
+    /// annotations on items of versioned packages.
+    #[cfg_attr(feature = "clap", clap(long, default_value_t = Config::default().feature_gates, action = clap::ArgAction::Set))]
+    pub feature_gates: bool,
+    /// Whether to generate `async` functions and `future` and `stream` types.
+    #[cfg_attr(feature = "clap", clap(long = "async", default_value_t = Config::default().async_, action = clap::ArgAction::Set))]
+    pub async_: bool,
+    /// Whether to generate the `error-context` type.
+    #[cfg_attr(feature = "clap", clap(long, default_value_t = Config::default().error_context, action = clap::ArgAction::Set))]
+    pub error_context: bool,
+    /// Whether to rename items of included worlds with `include ... with`
+    /// even when they don't conflict with other items.
+    #[cfg_attr(feature = "clap", clap(long, default_value_t = Config::default().include_renames, action = clap::ArgAction::Set))]
+    pub include_renames: bool,
 }
 
 impl Default for Config {
@@ -32,6 +49,11 @@ impl Default for Config {
             max_type_parts: 10,
             max_files_per_package: 10,
             max_resource_items: 10,
+            max_package_versions: 3,
+            feature_gates: true,
+            async_: true,
+            error_context: true,
+            include_renames: true,
         }
     }
 }
@@ -47,6 +69,11 @@ impl Arbitrary<'_> for Config {
             max_pkg_items: u.int_in_range(1..=10)?,
             max_type_parts: u.int_in_range(1..=10)?,
             max_resource_items: u.int_in_range(0..=10)?,
+            max_package_versions: u.int_in_range(1..=4)?,
+            feature_gates: u.arbitrary()?,
+            async_: u.arbitrary()?,
+            error_context: u.arbitrary()?,
+            include_renames: u.arbitrary()?,
         })
     }
 }
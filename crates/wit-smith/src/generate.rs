@@ -72,6 +72,14 @@ impl Packages {
         false
     }
 
+    fn names(&self, package_name: &str, world_name: &str) -> impl Iterator<Item = &String> {
+        let key = PackageWorldKey {
+            package_name: package_name.to_string(),
+            world_name: world_name.to_string(),
+        };
+        self.package_unique_names.get(&key).into_iter().flatten()
+    }
+
     fn intersect(
         &self,
         current_world: PackageWorldKey,
@@ -120,7 +128,8 @@ impl Generator {
         while self.packages.list.len() < self.config.max_packages
             && (self.packages.list.is_empty() || u.arbitrary()?)
         {
-            let pkg = self.gen_package(u, &mut names)?;
+            let name = self.gen_package_name(u, &mut names)?;
+            let pkg = self.gen_package(u, name)?;
             let i = self.packages.list.len();
             if pkg.file.interfaces.len() > 0 {
                 self.packages.packages_with_interfaces.push(i);
@@ -133,25 +142,63 @@ impl Generator {
         Ok(mem::take(&mut self.packages.list))
     }
 
-    fn gen_package(
-        &mut self,
+    /// Generates the name of a new package, which is either a fresh name or,
+    /// if allowed, a newer version of a previously generated package.
+    fn gen_package_name(
+        &self,
         u: &mut Unstructured<'_>,
         names: &mut HashSet<String>,
-    ) -> Result<Package> {
-        let namespace = gen_unique_name(u, names)?;
-        let package_name = gen_unique_name(u, names)?;
+    ) -> Result<PackageName> {
+        let mut versions = IndexMap::new();
+        for pkg in self.packages.list.iter() {
+            let Some(version) = &pkg.name.version else {
+                continue;
+            };
+            let (count, latest) = versions
+                .entry((&pkg.name.namespace, &pkg.name.name))
+                .or_insert((0, version));
+            *count += 1;
+            if version > *latest {
+                *latest = version;
+            }
+        }
+        let candidates = versions
+            .into_iter()
+            .filter(|(_, (count, _))| *count < self.config.max_package_versions)
+            .collect::<Vec<_>>();
+        if !candidates.is_empty() && u.arbitrary()? {
+            let ((namespace, name), (_, latest)) = u.choose(&candidates)?;
+            return Ok(PackageName {
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+                version: Some(gen_version_greater_than(u, latest)?),
+            });
+        }
 
+        let namespace = gen_unique_name(u, names)?;
+        let name = gen_unique_name(u, names)?;
         let version = if u.arbitrary()? {
             Some(gen_version(u)?)
         } else {
             None
         };
+        Ok(PackageName {
+            namespace,
+            name,
+            version,
+        })
+    }
+
+    fn gen_package(&mut self, u: &mut Unstructured<'_>, name: PackageName) -> Result<Package> {
+        // Multiple versions of the same package may be generated, so names
+        // are tracked per version.
+        let package_name = match &name.version {
+            Some(version) => format!("{}@{version}", name.name),
+            None => name.name.clone(),
+        };
+        let version = name.version.clone();
         let mut ret = Package {
-            name: PackageName {
-                namespace,
-                name: package_name.clone(),
-                version: version.clone(),
-            },
+            name,
             file: File::default(),
             sources: SourceMap::new(),
         };
@@ -231,7 +278,7 @@ impl Generator {
                     let id = self.next_interface_id;
                     self.next_interface_id += 1;
                     let (src, types) =
-                        self.gen_interface(u, Some(&name), file, &package_name, None, None)?;
+                        self.gen_interface(u, Some(&name), file, &package_name, version.clone())?;
                     file.items.push(src);
                     if types.is_empty() {
                         continue;
@@ -367,11 +414,10 @@ impl Generator {
         name: Option<&str>,
         file: &mut File,
         package_name: &str,
-        world_name: Option<&str>,
         version: Option<Version>,
     ) -> Result<(String, Vec<Type>)> {
         let mut generator = InterfaceGenerator::new(self, file, package_name, version);
-        let ret = generator.gen_interface(u, name, world_name)?;
+        let ret = generator.gen_interface(u, name)?;
         Ok((ret, generator.types_in_interface))
     }
 
@@ -518,7 +564,7 @@ impl<'a> InterfaceGenerator<'a> {
     // Generate a feature gate annotation (@since, @unstable, or @deprecated)
     // If version is provided, ensures the annotation is compatible with the version
     fn gen_feature_annotation(&self, u: &mut Unstructured<'_>) -> Result<Option<String>> {
-        if u.arbitrary()? {
+        if !self.generator.config.feature_gates || u.arbitrary()? {
             return Ok(None);
         }
 
@@ -556,12 +602,7 @@ impl<'a> InterfaceGenerator<'a> {
         }
     }
 
-    fn gen_interface(
-        &mut self,
-        u: &mut Unstructured<'_>,
-        name: Option<&str>,
-        world_name: Option<&str>,
-    ) -> Result<String> {
+    fn gen_interface(&mut self, u: &mut Unstructured<'_>, name: Option<&str>) -> Result<String> {
         let mut ret = String::new();
 
         if let Some(annotation) = self.gen_feature_annotation(u)? {
@@ -594,7 +635,9 @@ impl<'a> InterfaceGenerator<'a> {
 
             match u.arbitrary()? {
                 Generate::Use => {
-                    if !self.gen_use(u, &mut part, world_name)? {
+                    // Names used within an interface aren't names of any
+                    // world, even for interfaces defined within a world.
+                    if !self.gen_use(u, &mut part, None)? {
                         continue;
                     }
                 }
@@ -753,7 +796,7 @@ impl<'a> InterfaceGenerator<'a> {
                 ItemKind::AnonInterface(_) => {
                     let iface =
                         InterfaceGenerator::new(self.generator, self.file, self.package_name, None)
-                            .gen_interface(u, None, Some(world_name))?;
+                            .gen_interface(u, None)?;
                     part.push_str(&iface);
                 }
 
@@ -800,27 +843,65 @@ impl<'a> InterfaceGenerator<'a> {
                                 package_name: self.package_name.to_owned(),
                                 world_name: name.to_owned(),
                             };
-                            let intersection = self
+                            let conflicts = self
                                 .generator
                                 .packages
-                                .intersect(current_world, include_world);
-                            if let Some(names) = intersection {
-                                part.push_str(" with { %");
+                                .intersect(current_world, include_world)
+                                .map(|names| names.cloned().collect::<HashSet<_>>())
+                                .unwrap_or_default();
+                            let mut included_names = self
+                                .generator
+                                .packages
+                                .names(self.package_name, name)
+                                .cloned()
+                                .collect::<Vec<_>>();
+                            included_names.sort();
+
+                            // Claim all included names so that nothing else
+                            // in this world, including renamed items, uses
+                            // them.
+                            for n in included_names.iter() {
+                                self.unique_names.insert(n.clone());
+                                export_names.insert(n.clone());
+                            }
 
-                                for n in names {
-                                    part.push_str(n);
+                            let mut renames = Vec::new();
+                            for n in included_names {
+                                let rename = conflicts.contains(&n)
+                                    || (self.generator.config.include_renames && u.arbitrary()?);
+                                let new_name = if rename {
+                                    let mut new_name = gen_unique_name(u, &mut self.unique_names)?;
+                                    while !export_names.insert(new_name.clone()) {
+                                        new_name = gen_unique_name(u, &mut self.unique_names)?;
+                                    }
+                                    renames.push((n, new_name.clone()));
+                                    new_name
+                                } else {
+                                    n
+                                };
+                                // Record the name in this world too so that
+                                // worlds including this one can detect
+                                // conflicts.
+                                self.generator.packages.add_name(
+                                    self.package_name.to_string(),
+                                    world_name.to_string(),
+                                    new_name,
+                                );
+                            }
+
+                            if renames.is_empty() {
+                                // ; is only used if not renaming
+                                part.push_str(";");
+                            } else {
+                                part.push_str(" with { ");
+                                for (n, new_name) in renames {
+                                    part.push_str("%");
+                                    part.push_str(&n);
                                     part.push_str(" as %");
-                                    // we know it is in one of the worlds, lets
-                                    // add it here just for good measure
-                                    self.unique_names.insert(n.to_string());
-                                    let new_name = gen_unique_name(u, &mut self.unique_names)?;
                                     part.push_str(&new_name);
                                     part.push_str(",");
                                 }
                                 part.push_str("}");
-                            } else {
-                                // ; is only used if not renaming
-                                part.push_str(";");
                             }
                         }
                         WorldPath::Remote => {
@@ -1209,6 +1290,8 @@ impl<'a> InterfaceGenerator<'a> {
                         (false, false) => {}
                     }
                 }
+                Kind::Stream | Kind::Future if !self.generator.config.async_ => continue,
+                Kind::ErrorContext if !self.generator.config.error_context => continue,
                 Kind::Stream => {
                     *fuel = match fuel.checked_sub(1) {
                         Some(fuel) => fuel,
@@ -1254,7 +1337,7 @@ impl<'a> InterfaceGenerator<'a> {
         dst: &mut String,
         method: bool,
     ) -> Result<()> {
-        if u.arbitrary()? {
+        if self.generator.config.async_ && u.arbitrary()? {
             dst.push_str("async ");
         }
         dst.push_str("func");
@@ -1492,3 +1575,33 @@ fn gen_version_less_than(
 fn gen_version(u: &mut Unstructured<'_>) -> Result<Version> {
     gen_version_less_than(u, &None)
 }
+
+fn gen_version_greater_than(
+    u: &mut Unstructured<'_>,
+    existing_version: &Version,
+) -> Result<Version> {
+    let mut new_version = Version::new(
+        existing_version.major,
+        existing_version.minor,
+        existing_version.patch,
+    );
+    match u.int_in_range(0..=2)? {
+        0 => {
+            new_version.major += 1;
+            new_version.minor = 0;
+            new_version.patch = 0;
+        }
+        1 => {
+            new_version.minor += 1;
+            new_version.patch = 0;
+        }
+        _ => new_version.patch += 1,
+    }
+
+    assert!(
+        &new_version > existing_version,
+        "{new_version} > {existing_version}"
+    );
+
+    Ok(new_version)
+}
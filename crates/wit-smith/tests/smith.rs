@@ -0,0 +1,109 @@
+use arbitrary::Unstructured;
+use rand::{RngCore, SeedableRng, rngs::SmallRng};
+use std::collections::HashSet;
+use wit_component::DecodedWasm;
+use wit_parser::{Function, FunctionKind, Resolve, Stability, TypeDefKind, WorldItem};
+use wit_smith::Config;
+
+const NUM_RUNS: usize = 200;
+
+/// Which of the optional kinds of WIT were generated.
+#[derive(Debug, Default, PartialEq)]
+struct Seen {
+    stability: bool,
+    async_: bool,
+    versions: bool,
+}
+
+/// Generates `NUM_RUNS` documents with `config`, checking that each is valid,
+/// and returns which optional kinds of WIT were seen across all of them.
+fn survey(config: &Config) -> Seen {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 4096];
+    let mut seen = Seen::default();
+    for _ in 0..NUM_RUNS {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let Ok(wasm) = wit_smith::smith(config, &mut u) else {
+            continue;
+        };
+        let resolve = match wit_component::decode(&wasm).unwrap() {
+            DecodedWasm::WitPackage(resolve, _) => resolve,
+            DecodedWasm::Component(..) => unreachable!(),
+        };
+        resolve.assert_valid();
+        seen.stability |= has_stability(&resolve);
+        seen.async_ |= has_async(&resolve);
+        seen.versions |= has_multiple_versions(&resolve);
+    }
+    seen
+}
+
+fn has_stability(resolve: &Resolve) -> bool {
+    let gated = |s: &Stability| !s.is_unknown();
+    resolve
+        .interfaces
+        .iter()
+        .any(|(_, i)| gated(&i.stability) || i.functions.values().any(|f| gated(&f.stability)))
+        || resolve.worlds.iter().any(|(_, w)| gated(&w.stability))
+        || resolve.types.iter().any(|(_, t)| gated(&t.stability))
+}
+
+fn has_async(resolve: &Resolve) -> bool {
+    let functions = resolve
+        .interfaces
+        .iter()
+        .flat_map(|(_, i)| i.functions.values())
+        .chain(resolve.worlds.iter().flat_map(|(_, w)| {
+            w.imports
+                .values()
+                .chain(w.exports.values())
+                .filter_map(|item| match item {
+                    WorldItem::Function(f) => Some(f),
+                    _ => None,
+                })
+        }))
+        .collect::<Vec<&Function>>();
+    functions.iter().any(|f| {
+        matches!(
+            f.kind,
+            FunctionKind::AsyncFreestanding
+                | FunctionKind::AsyncMethod(_)
+                | FunctionKind::AsyncStatic(_)
+        )
+    }) || resolve
+        .types
+        .iter()
+        .any(|(_, t)| matches!(t.kind, TypeDefKind::Future(_) | TypeDefKind::Stream(_)))
+}
+
+fn has_multiple_versions(resolve: &Resolve) -> bool {
+    let mut names = HashSet::new();
+    resolve
+        .packages
+        .iter()
+        .filter(|(_, p)| p.name.version.is_some())
+        .any(|(_, p)| !names.insert((&p.name.namespace, &p.name.name)))
+}
+
+#[test]
+fn generates_gates_async_and_versions() {
+    let config = Config::default();
+    assert_eq!(
+        survey(&config),
+        Seen {
+            stability: true,
+            async_: true,
+            versions: true,
+        }
+    );
+}
+
+#[test]
+fn respects_disabled_options() {
+    let mut config = Config::default();
+    config.feature_gates = false;
+    config.async_ = false;
+    config.max_package_versions = 1;
+    assert_eq!(survey(&config), Seen::default());
+}
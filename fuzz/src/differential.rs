@@ -0,0 +1,149 @@
+use arbitrary::{Result, Unstructured};
+use wasmparser::WasmFeatures;
+#[cfg(feature = "wasmtime")]
+use wasmtime::*;
+
+#[cfg(feature = "wasmtime")]
+#[path = "../../crates/fuzz-stats/src/lib.rs"]
+pub mod fuzz_stats;
+
+// Generate a module in differential mode, run it with Cranelift's optimizations
+// both disabled and enabled, and assert that both runs observe the same
+// results and the same final state.
+pub fn run(u: &mut Unstructured<'_>) -> Result<()> {
+    let (wasm_bytes, _) = crate::generate_valid_module(u, |config, _| {
+        config.differential_mode = true;
+
+        // Not implemented in wasmtime at this time.
+        config.exceptions_enabled = false;
+        config.gc_enabled = false;
+        config.custom_page_sizes_enabled = false;
+        config.wide_arithmetic_enabled = false;
+        config.shared_everything_threads_enabled = false;
+        config.stack_switching_enabled = false;
+
+        // NB: should re-enable once wasmtime implements the table64 extension
+        // to the memory64 proposal.
+        config.memory64_enabled = false;
+        Ok(())
+    })?;
+
+    // Differential mode must not use features with nondeterministic behavior.
+    let features = WasmFeatures::all() - WasmFeatures::RELAXED_SIMD - WasmFeatures::THREADS;
+    let mut validator = wasmparser::Validator::new_with_features(features);
+    if let Err(e) = validator.validate_all(&wasm_bytes) {
+        panic!("Invalid module: {e}");
+    }
+
+    #[cfg(feature = "wasmtime")]
+    {
+        let Some(unoptimized) = execute(&wasm_bytes, OptLevel::None) else {
+            return Ok(());
+        };
+        let Some(optimized) = execute(&wasm_bytes, OptLevel::Speed) else {
+            return Ok(());
+        };
+        assert_eq!(unoptimized, optimized);
+    }
+    Ok(())
+}
+
+/// Instantiate `wasm` and call each of its exported functions in order, then
+/// the state hash, returning a description of everything that was observed.
+///
+/// Returns `None` if execution hit a resource limit of the host, such as stack
+/// overflow, in which case the results aren't comparable.
+#[cfg(feature = "wasmtime")]
+fn execute(wasm: &[u8], opt_level: OptLevel) -> Option<Vec<String>> {
+    let mut eng_conf = wasmtime::Config::new();
+    eng_conf.cranelift_opt_level(opt_level);
+    eng_conf.wasm_multi_memory(true);
+    eng_conf.wasm_tail_call(true);
+    let engine = Engine::new(&eng_conf).unwrap();
+    let module = Module::from_binary(&engine, wasm).unwrap();
+
+    let mut store = Store::new(
+        &engine,
+        fuzz_stats::limits::StoreLimits {
+            remaining_memory: 1 << 30,
+            oom: false,
+        },
+    );
+    store.limiter(|s| s as &mut dyn ResourceLimiter);
+
+    let mut observed = Vec::new();
+    let instance = match fuzz_stats::dummy::dummy_imports(&mut store, &module)
+        .and_then(|imports| Instance::new(&mut store, &module, &imports))
+    {
+        Ok(instance) => instance,
+        Err(err) => {
+            observed.push(describe_error(err)?);
+            return Some(observed);
+        }
+    };
+
+    let exports = module
+        .exports()
+        .filter_map(|e| match e.ty() {
+            ExternType::Func(ty) if e.name() != wasm_smith::DIFFERENTIAL_HASH_EXPORT => {
+                Some((e.name().to_string(), ty))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for (name, ty) in exports {
+        let args = fuzz_stats::dummy::dummy_values(ty.params());
+        let mut results = fuzz_stats::dummy::dummy_values(ty.results());
+        let func = instance.get_func(&mut store, &name).unwrap();
+        match func.call(&mut store, &args, &mut results) {
+            Ok(()) => observed.extend(results.iter().map(describe_val)),
+            Err(err) => observed.push(describe_error(err)?),
+        }
+    }
+
+    let hash = instance
+        .get_typed_func::<(), i64>(&mut store, wasm_smith::DIFFERENTIAL_HASH_EXPORT)
+        .unwrap();
+    match hash.call(&mut store, ()) {
+        Ok(hash) => observed.push(format!("hash {hash:#x}")),
+        Err(err) => observed.push(describe_error(err)?),
+    }
+    Some(observed)
+}
+
+#[cfg(feature = "wasmtime")]
+fn describe_val(val: &Val) -> String {
+    match val {
+        Val::I32(i) => format!("i32 {i}"),
+        Val::I64(i) => format!("i64 {i}"),
+        Val::F32(f) => format!("f32 {f:#x}"),
+        Val::F64(f) => format!("f64 {f:#x}"),
+        Val::V128(v) => format!("v128 {:#x}", v.as_u128()),
+        Val::FuncRef(f) => format!("funcref null={}", f.is_none()),
+        Val::ExternRef(r) => format!("externref null={}", r.is_none()),
+        Val::AnyRef(r) => format!("anyref null={}", r.is_none()),
+    }
+}
+
+#[cfg(feature = "wasmtime")]
+fn describe_error(err: anyhow::Error) -> Option<String> {
+    match err.downcast_ref::<Trap>() {
+        // Stack overflow depends on the size of frames which differs between
+        // optimization levels.
+        Some(Trap::StackOverflow) => None,
+        Some(trap) => Some(format!("trap {trap:?}")),
+        None => {
+            let s = err.to_string();
+            if s.contains("Insufficient resources") {
+                None
+            } else {
+                Some(s)
+            }
+        }
+    }
+}
+
+#[test]
+fn smoke() {
+    super::test::test_n_times(100, run);
+}
@@ -3,6 +3,7 @@ use std::fmt::Debug;
 use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
 use wasm_smith::{Component, Config, Module};
 
+pub mod differential;
 pub mod incremental_parse;
 pub mod mutate;
 pub mod no_traps;
@@ -14,6 +15,7 @@ pub mod text_parser;
 pub mod validate;
 pub mod validate_valid_module;
 pub mod wit64;
+pub mod wit_component;
 
 pub fn generate_valid_module(
     u: &mut Unstructured,
@@ -27,6 +29,7 @@ pub fn generate_valid_module(
     config.custom_page_sizes_enabled = u.arbitrary()?;
     config.wide_arithmetic_enabled = u.arbitrary()?;
     config.shared_everything_threads_enabled = u.arbitrary()?;
+    config.stack_switching_enabled = u.arbitrary()?;
 
     configure(&mut config, u)?;
 
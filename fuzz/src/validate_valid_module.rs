@@ -5,14 +5,10 @@ use arbitrary::{Result, Unstructured};
 pub fn run(u: &mut Unstructured<'_>) -> Result<()> {
     // We want to prioritize fuzzing of modules for the time being
     // so we'll only generate a component 10% of the time
-    //
-    // TODO: remove this `false && ...` once this fuzzer works again for
-    // components.
-    let generate_component = false
-        && match u.ratio::<u8>(1, 10) {
-            Ok(b) => b,
-            Err(_) => false,
-        };
+    let generate_component = match u.ratio::<u8>(1, 10) {
+        Ok(b) => b,
+        Err(_) => false,
+    };
     let (wasm_bytes, config) = if generate_component {
         crate::generate_valid_component(u, |c, u| {
             c.max_components = u.int_in_range(0..=1_000)?;
@@ -25,7 +21,11 @@ pub fn run(u: &mut Unstructured<'_>) -> Result<()> {
     };
 
     // Validate the module or component and assert that it passes validation.
-    let mut validator = crate::validator_for_config(&config);
+    let mut features = config.features();
+    if generate_component {
+        features |= wasmparser::WasmFeatures::COMPONENT_MODEL;
+    }
+    let mut validator = wasmparser::Validator::new_with_features(features);
     if let Err(e) = validator.validate_all(&wasm_bytes) {
         let component_or_module = if generate_component {
             "component"
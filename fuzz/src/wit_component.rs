@@ -0,0 +1,41 @@
+use arbitrary::{Result, Unstructured};
+use wasm_smith::WitComponent;
+use wit_component::DecodedWasm;
+
+pub fn run(u: &mut Unstructured<'_>) -> Result<()> {
+    let wit = u.arbitrary().and_then(|config| {
+        log::debug!("wit-smith config: {config:#?}");
+        wit_smith::smith(&config, u)
+    })?;
+    let resolve = match wit_component::decode(&wit).unwrap() {
+        DecodedWasm::WitPackage(resolve, _) => resolve,
+        DecodedWasm::Component(..) => unreachable!(),
+    };
+    let worlds = resolve.worlds.iter().map(|(id, _)| id).collect::<Vec<_>>();
+    if worlds.is_empty() {
+        return Ok(());
+    }
+    let world = *u.choose(&worlds)?;
+    log::debug!(
+        "generating a component for world {}",
+        resolve.worlds[world].name
+    );
+
+    let config: wasm_smith::Config = u.arbitrary()?;
+    let component = WitComponent::new(config.clone(), u, &resolve, world)?;
+    let wasm = component.to_bytes();
+    crate::log_wasm(&wasm, &config);
+
+    // The world may use any component model feature so validate with
+    // everything enabled.
+    wasmparser::Validator::new_with_features(wasmparser::WasmFeatures::all())
+        .validate_all(&wasm)
+        .unwrap();
+    wit_component::decode(&wasm).unwrap();
+    Ok(())
+}
+
+#[test]
+fn smoke() {
+    super::test::test_n_times(100, run);
+}
@@ -3,7 +3,7 @@
 use anyhow::{Context, Result, bail};
 use clap::Parser;
 use std::collections::HashMap;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::mem;
 use std::path::{Path, PathBuf};
 use wasm_encoder::ModuleType;
@@ -16,7 +16,10 @@ use wit_component::{
     ComponentEncoder, DecodedWasm, Linker, StringEncoding, WitPrinter, embed_component_metadata,
     metadata,
 };
-use wit_parser::{LiftLowerAbi, Mangling, ManglingAndAbi, PackageId, Resolve};
+use wit_parser::{
+    LiftLowerAbi, Mangling, ManglingAndAbi, PackageId, Resolve, Type, TypeDefKind, WorldItem,
+    WorldKey,
+};
 
 /// WebAssembly wit-based component tooling.
 #[derive(Parser)]
@@ -56,6 +59,49 @@ impl Opts {
     }
 }
 
+/// Renders the lines which differ between `old` and `new`, the contents of
+/// `path` before and after formatting.
+fn format_diff(path: &Path, old: &str, new: &str) -> String {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // Compute the longest common subsequence of lines, from the end.
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    let mut in_hunk = false;
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+            in_hunk = false;
+            continue;
+        }
+        if !in_hunk {
+            diff.push_str(&format!("{}:{}:\n", path.display(), i + 1));
+            in_hunk = true;
+        }
+        if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push_str(&format!("-{}\n", old[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+{}\n", new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
 fn parse_optionally_name_file(s: &str) -> (&str, &str) {
     let mut parts = s.splitn(2, '=');
     let name_or_path = parts.next().unwrap();
@@ -160,6 +206,11 @@ pub struct NewOpts {
     /// removal of the old scheme in the future.
     #[clap(long)]
     reject_legacy_names: bool,
+
+    /// Merge the producers sections of the input module and adapters into the
+    /// producers section of the component.
+    #[clap(long)]
+    merge_producers: bool,
 }
 
 impl NewOpts {
@@ -172,7 +223,8 @@ impl NewOpts {
         let wasm = self.io.get_input_wasm()?;
         let mut encoder = ComponentEncoder::default()
             .validate(!self.skip_validation)
-            .reject_legacy_names(self.reject_legacy_names);
+            .reject_legacy_names(self.reject_legacy_names)
+            .merge_producers(self.merge_producers);
 
         if let Some(merge) = self.merge_imports_based_on_semver {
             encoder = encoder.merge_imports_based_on_semver(merge);
@@ -501,6 +553,11 @@ pub struct LinkOpts {
     /// This is enabled by default.
     #[clap(long, value_name = "MERGE")]
     merge_imports_based_on_semver: Option<bool>,
+
+    /// Merge the producers sections of the input libraries and adapters into
+    /// the producers section of the component.
+    #[clap(long)]
+    merge_producers: bool,
 }
 
 impl LinkOpts {
@@ -513,7 +570,8 @@ impl LinkOpts {
         let mut linker = Linker::default()
             .validate(!self.skip_validation)
             .stub_missing_functions(self.stub_missing_functions)
-            .use_built_in_libdl(self.use_built_in_libdl);
+            .use_built_in_libdl(self.use_built_in_libdl)
+            .merge_producers(self.merge_producers);
 
         if let Some(stack_size) = self.stack_size {
             linker = linker.stack_size(stack_size);
@@ -604,6 +662,9 @@ pub struct WitOpts {
     skip_validation: bool,
 
     /// Emit the WIT document as JSON instead of text.
+    ///
+    /// The JSON output can be passed back to this command as input, for
+    /// example to print it as WIT text again or to encode it with `--wasm`.
     #[clap(
         short,
         long,
@@ -678,6 +739,149 @@ pub struct WitOpts {
     /// items are otherwise hidden by default.
     #[clap(long)]
     all_features: bool,
+
+    /// JSON lockfile to resolve the dependencies of the input WIT package
+    /// from, in addition to its `deps` directory.
+    ///
+    /// The lockfile maps package names, including versions, to directories or
+    /// files containing WIT packages, which may be text or wasm-encoded:
+    ///
+    /// `{"registry": "dir", "packages": [{"name": "a:b@1.0.0", "path": "b",
+    /// "sha256": "..."}]}`
+    ///
+    /// Packages without a `path` are looked up in the `registry` directory as
+    /// `$namespace/$name@$version` with an optional `.wit`, `.wasm` or `.wat`
+    /// extension. When a `sha256` is listed the package's contents are
+    /// verified against it.
+    #[clap(long, value_name = "PATH")]
+    lockfile: Option<PathBuf>,
+
+    /// Format WIT source files instead of printing the WIT document.
+    ///
+    /// The input must be a `*.wit` file or a directory, in which case all
+    /// `*.wit` files directly within it are formatted but its `deps` are not.
+    /// Files are rewritten in place unless `--output` is given for a single
+    /// file. If no input is given then WIT source is read from stdin and the
+    /// formatted source is printed to stdout.
+    ///
+    /// Formatting preserves all comments and the order of items, normalizing
+    /// only whitespace, indentation, blank lines and punctuation. Ordering
+    /// items, for example grouping `use` statements, is out of scope as the
+    /// order of items is reflected in encoded components.
+    #[clap(
+        long,
+        conflicts_with = "wasm",
+        conflicts_with = "wat",
+        conflicts_with = "json",
+        conflicts_with = "out_dir",
+        conflicts_with = "importize",
+        conflicts_with = "importize_world",
+        conflicts_with = "merge_world_imports_based_on_semver"
+    )]
+    fmt: bool,
+
+    /// With `--fmt`, check whether the input is already formatted instead of
+    /// formatting it.
+    ///
+    /// This prints the differences for each file that isn't formatted and
+    /// fails if there are any, which is useful in CI.
+    #[clap(long, requires = "fmt")]
+    check: bool,
+
+    /// Check the WIT package for common problems instead of printing it.
+    ///
+    /// Each problem found is printed along with the name of the rule that
+    /// found it and its location, and the command fails if there are any.
+    /// The rules are:
+    ///
+    /// * `missing-docs` - interfaces, worlds, types and functions without doc
+    ///   comments.
+    ///
+    /// * `too-many-params` - functions with more than `max-params`
+    ///   parameters, 8 by default.
+    ///
+    /// * `result-without-error` - functions returning, or types defined as, a
+    ///   `result` without an error type.
+    ///
+    /// * `unused-use` - types imported with `use` which are never used.
+    ///
+    /// * `unused-interface` - interfaces not used by any world.
+    ///
+    /// * `inconsistent-naming` - words spelled with different case in
+    ///   different names, such as `get-url` and `set-URL`.
+    ///
+    /// * `missing-since` - interfaces, worlds and the items within them
+    ///   without `@since` in a package with a version.
+    #[clap(
+        long,
+        conflicts_with = "fmt",
+        conflicts_with = "wasm",
+        conflicts_with = "wat",
+        conflicts_with = "json",
+        conflicts_with = "out_dir",
+        conflicts_with = "importize",
+        conflicts_with = "importize_world",
+        conflicts_with = "merge_world_imports_based_on_semver"
+    )]
+    lint: bool,
+
+    /// JSON configuration file for `--lint`.
+    ///
+    /// Rules can be disabled with for example
+    /// `{"rules": {"missing-docs": false}}` and the maximum number of
+    /// parameters is set with `{"max-params": 4}`.
+    #[clap(long, requires = "lint", value_name = "PATH")]
+    lint_config: Option<PathBuf>,
+
+    /// Infer a draft WIT world from the imports and exports of a core wasm
+    /// module.
+    ///
+    /// The input must be a core wasm module in the binary or text format.
+    /// Imported functions are grouped by module name into inline interfaces,
+    /// except for the `$root` module whose functions are imported directly
+    /// into the world. Exported functions are exported from the world, or
+    /// from an inline interface for exports named `interface#function`.
+    /// Functions whose names aren't valid WIT identifiers are skipped, since
+    /// a component built from the module must use the same names. Skipped
+    /// functions are listed in the world's documentation.
+    ///
+    /// Core `i32`, `i64`, `f32` and `f64` map to `u32`, `u64`, `f32` and
+    /// `f64`. A pair of `i32` parameters named like `msg_ptr` and `msg_len`
+    /// in the name section becomes a single `string` parameter, or
+    /// `list<u8>` unless the name suggests text. Functions using other types,
+    /// with multiple results, or with more than 16 parameters are skipped as
+    /// the canonical ABI passes those through linear memory instead.
+    #[clap(
+        long,
+        conflicts_with = "fmt",
+        conflicts_with = "lint",
+        conflicts_with = "lockfile"
+    )]
+    infer_from_module: bool,
+
+    /// Print the canonical ABI layout of the WIT package's types and
+    /// functions instead of the WIT document.
+    ///
+    /// For every type this prints its size and alignment in linear memory
+    /// along with the offsets of record fields and the size of variant
+    /// discriminants. Values for 64-bit memories are shown in parentheses
+    /// when they differ from 32-bit memories.
+    ///
+    /// For every function this prints the flattened core wasm signature for
+    /// sync and async imports and exports, assuming a 32-bit memory. Notes
+    /// are added when parameters are passed indirectly through memory, which
+    /// happens when they flatten to more than 16 core values (4 for async
+    /// imports), and when results are passed through a return pointer.
+    #[clap(
+        long,
+        conflicts_with = "wasm",
+        conflicts_with = "wat",
+        conflicts_with = "json",
+        conflicts_with = "out_dir",
+        conflicts_with = "fmt",
+        conflicts_with = "lint"
+    )]
+    abi_layout: bool,
 }
 
 impl WitOpts {
@@ -687,6 +891,12 @@ impl WitOpts {
 
     /// Executes the application.
     fn run(self) -> Result<()> {
+        if self.fmt {
+            return self.format();
+        }
+        if self.lint {
+            return self.lint();
+        }
         let mut decoded = self.decode_input()?;
 
         if self.importize {
@@ -719,7 +929,9 @@ impl WitOpts {
 
         // Now that the WIT document has been decoded, it's time to emit it.
         // This interprets all of the output options and performs such a task.
-        if self.json {
+        if self.abi_layout {
+            self.emit_abi_layout(&decoded)?;
+        } else if self.json {
             self.emit_json(&decoded)?;
         } else if self.wasm || self.wat {
             self.emit_wasm(&decoded)?;
@@ -729,7 +941,176 @@ impl WitOpts {
         Ok(())
     }
 
+    fn format(&self) -> Result<()> {
+        let files = match &self.input {
+            Some(input) if input != Path::new("-") => {
+                if input.is_dir() {
+                    let mut files = Vec::new();
+                    for entry in input
+                        .read_dir()
+                        .with_context(|| format!("failed to read directory {input:?}"))?
+                    {
+                        let path = entry?.path();
+                        if path.extension().is_some_and(|e| e == "wit") && !path.is_dir() {
+                            files.push(path);
+                        }
+                    }
+                    files.sort();
+                    files
+                } else {
+                    vec![input.clone()]
+                }
+            }
+            _ => {
+                let mut contents = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut contents)
+                    .context("failed to read stdin")?;
+                let formatted = wit_parser::format("<stdin>", &contents)?;
+                if self.check {
+                    if formatted != contents {
+                        eprint!(
+                            "{}",
+                            format_diff(Path::new("<stdin>"), &contents, &formatted)
+                        );
+                        bail!("stdin is not formatted");
+                    }
+                    return Ok(());
+                }
+                let mut output = self.output.output_writer(self.general.color)?;
+                output.write_all(formatted.as_bytes())?;
+                return Ok(());
+            }
+        };
+        if files.len() > 1 && self.output_path().is_some() {
+            bail!("the `--output` option cannot be used when formatting a directory");
+        }
+
+        let mut unformatted = 0;
+        for file in files.iter() {
+            let contents = std::fs::read_to_string(file)
+                .with_context(|| format!("failed to read {file:?}"))?;
+            let formatted = wit_parser::format(file, &contents)?;
+            if self.check {
+                if formatted != contents {
+                    eprint!("{}", format_diff(file, &contents, &formatted));
+                    unformatted += 1;
+                }
+            } else if self.output_path().is_some() {
+                let mut output = self.output.output_writer(self.general.color)?;
+                output.write_all(formatted.as_bytes())?;
+            } else if formatted != contents {
+                std::fs::write(file, formatted)
+                    .with_context(|| format!("failed to write {file:?}"))?;
+            }
+        }
+        match unformatted {
+            0 => Ok(()),
+            1 => bail!("1 file is not formatted"),
+            n => bail!("{n} files are not formatted"),
+        }
+    }
+
+    fn lint(&self) -> Result<()> {
+        let config = match &self.lint_config {
+            Some(path) => {
+                let json = std::fs::read_to_string(path)
+                    .with_context(|| format!("failed to read lint config: {}", path.display()))?;
+                serde_json::from_str(&json)
+                    .with_context(|| format!("failed to decode lint config: {}", path.display()))?
+            }
+            None => wit_parser::LintConfig::new(),
+        };
+        let (resolve, pkg) = match self.decode_input()? {
+            DecodedWasm::WitPackage(resolve, pkg) => (resolve, pkg),
+            DecodedWasm::Component(..) => {
+                bail!("the `--lint` flag requires a WIT package as input, not a component")
+            }
+        };
+        let diagnostics = resolve.lint(pkg, &config);
+        let mut output = self.output.output_writer(self.general.color)?;
+        for diagnostic in diagnostics.iter() {
+            writeln!(output, "{diagnostic}")?;
+        }
+        match diagnostics.len() {
+            0 => Ok(()),
+            1 => bail!("1 lint problem found"),
+            n => bail!("{n} lint problems found"),
+        }
+    }
+
+    fn output_path(&self) -> Option<&Path> {
+        self.output.output_path()
+    }
+
+    fn read_input(&self) -> Result<(Vec<u8>, &Path)> {
+        Ok(match &self.input {
+            Some(input) => (
+                std::fs::read(input).with_context(|| format!("failed to read {input:?}"))?,
+                input.as_path(),
+            ),
+            None => {
+                let mut stdin = Vec::new();
+                std::io::stdin()
+                    .read_to_end(&mut stdin)
+                    .context("failed to read <stdin>")?;
+                (stdin, Path::new("<stdin>"))
+            }
+        })
+    }
+
+    fn infer_from_module(&self) -> Result<DecodedWasm> {
+        let (input, path) = self.read_input()?;
+        let wasm = wat::parse_bytes(&input).map_err(|mut e| {
+            e.set_path(path);
+            e
+        })?;
+        if wasmparser::Parser::is_component(&wasm) {
+            bail!(
+                "the `--infer-from-module` flag requires a core wasm module as input, not a component"
+            );
+        }
+        let default_name = match &self.input {
+            Some(input) if input != Path::new("-") => input
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("module"),
+            _ => "module",
+        };
+        let package = infer_wit_from_module(&wasm, default_name)?;
+        let wit = package.to_string();
+        let mut resolve = WitResolve::resolve_with_features(&self.features, self.all_features);
+        let pkg = resolve
+            .push_str("inferred.wit", &wit)
+            .with_context(|| format!("failed to parse inferred WIT:\n{wit}"))?;
+        Ok(DecodedWasm::WitPackage(resolve, pkg))
+    }
+
     fn decode_input(&self) -> Result<DecodedWasm> {
+        // Lockfiles resolve the dependencies of WIT packages, so they only
+        // make sense for a directory or a single WIT file.
+        if let Some(lockfile) = &self.lockfile {
+            let input = match &self.input {
+                Some(input)
+                    if !self.infer_from_module
+                        && (input.is_dir() || input.extension().is_some_and(|e| e == "wit")) =>
+                {
+                    input
+                }
+                _ => {
+                    bail!("the `--lockfile` flag requires a WIT directory or `*.wit` file as input")
+                }
+            };
+            let mut resolve = WitResolve::resolve_with_features(&self.features, self.all_features);
+            let lockfile = wit_parser::Lockfile::from_file(lockfile)?;
+            let (pkg_id, _) = resolve.push_path_with_lockfile(input, &lockfile)?;
+            return Ok(DecodedWasm::WitPackage(resolve, pkg_id));
+        }
+
+        if self.infer_from_module {
+            return self.infer_from_module();
+        }
+
         // If the input is a directory then it's probably raw WIT files, so use
         // `parse_wit_from_path`.
         if let Some(input) = &self.input {
@@ -746,19 +1127,7 @@ impl WitOpts {
         // use `parse_wit_from_path` because this wants to additionally handle
         // the input case that the input is a core wasm binary with a
         // `component-type` section inside of it.
-        let (input, path) = match &self.input {
-            Some(input) => (
-                std::fs::read(input).with_context(|| format!("failed to read {input:?}"))?,
-                input.as_path(),
-            ),
-            None => {
-                let mut stdin = Vec::new();
-                std::io::stdin()
-                    .read_to_end(&mut stdin)
-                    .context("failed to read <stdin>")?;
-                (stdin, Path::new("<stdin>"))
-            }
-        };
+        let (input, path) = self.read_input()?;
 
         match Detect::from_bytes(&input) {
             Detect::WasmBinary | Detect::WasmText => {
@@ -791,6 +1160,22 @@ impl WitOpts {
                     Ok(s) => s,
                     Err(_) => bail!("input was not valid utf-8"),
                 };
+
+                // WIT can't start with `{`, so this must be the JSON
+                // representation of a `Resolve` as printed by `--json`. The
+                // last package is the one that was parsed, as dependencies
+                // always come first.
+                if input.trim_start().starts_with('{') {
+                    let resolve: Resolve = serde_json::from_str(input)
+                        .with_context(|| format!("failed to parse JSON in {path:?}"))?;
+                    let (id, _) = resolve
+                        .packages
+                        .iter()
+                        .last()
+                        .context("JSON input does not contain any packages")?;
+                    return Ok(DecodedWasm::WitPackage(resolve, id));
+                }
+
                 let mut resolve =
                     WitResolve::resolve_with_features(&self.features, self.all_features);
                 let id = resolve.push_str(path, input)?;
@@ -930,6 +1315,683 @@ impl WitOpts {
 
         Ok(())
     }
+
+    fn emit_abi_layout(&self, decoded: &DecodedWasm) -> Result<()> {
+        let resolve = decoded.resolve();
+        let mut sizes = wit_parser::SizeAlign::default();
+        sizes.fill(resolve);
+        let mut printer = AbiLayoutPrinter {
+            resolve,
+            sizes,
+            output: String::new(),
+        };
+        printer.package(decoded.package());
+        let mut output = self.output.output_writer(self.general.color)?;
+        output.write_all(printer.output.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Builds a draft `local:$name` package with a single world from the imports
+/// and exports of the core module `wasm`, where `$name` is the module's name
+/// from its name section or `default_name`.
+fn infer_wit_from_module(wasm: &[u8], default_name: &str) -> Result<wit_encoder::Package> {
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+    use wasmparser::{ExternalKind, KnownCustom, Name, TypeRef};
+    use wit_encoder::{Interface, Package, PackageName, World};
+
+    let types = wasmparser::Validator::new_with_features(WasmFeatures::all()).validate_all(wasm)?;
+
+    let mut imports = Vec::new();
+    let mut exports = Vec::new();
+    let mut module_name = None;
+    let mut param_names = HashMap::new();
+    let mut func_index = 0;
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        match payload? {
+            Payload::ImportSection(s) => {
+                for import in s {
+                    let import = import?;
+                    if let TypeRef::Func(_) = import.ty {
+                        imports.push((import.module, import.name, func_index));
+                        func_index += 1;
+                    }
+                }
+            }
+            Payload::ExportSection(s) => {
+                for export in s {
+                    let export = export?;
+                    if export.kind == ExternalKind::Func {
+                        exports.push((export.name, export.index));
+                    }
+                }
+            }
+            Payload::CustomSection(c) => {
+                if let KnownCustom::Name(reader) = c.as_known() {
+                    for name in reader {
+                        match name? {
+                            Name::Module { name, .. } => module_name = Some(name),
+                            Name::Local(map) => {
+                                for naming in map {
+                                    let naming = naming?;
+                                    let mut names = HashMap::new();
+                                    for local in naming.names {
+                                        let local = local?;
+                                        names.insert(local.index, local.name);
+                                    }
+                                    param_names.insert(naming.index, names);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Functions which are left out of the world are listed in its docs, along
+    // with the reason why.
+    let skipped = RefCell::new(Vec::new());
+    let skip = |what: &str, reason: &str| {
+        skipped.borrow_mut().push(format!("{what}: {reason}"));
+        false
+    };
+
+    let infer_func = |what: &str, name: &str, index: u32| {
+        let ty = types[types.as_ref().core_function_at(index)].unwrap_func();
+        let names = param_names.get(&index);
+        match infer_signature(ty, names) {
+            Ok((params, result)) => {
+                let mut func = wit_encoder::StandaloneFunc::new(name.to_string(), false);
+                func.set_params(params);
+                func.set_result(result);
+                Some(func)
+            }
+            Err(reason) => {
+                skip(what, &reason);
+                None
+            }
+        }
+    };
+
+    // Components must use the module's names as-is, so functions whose
+    // names aren't valid in WIT are left out rather than renamed.
+    let valid = |what: &str, names: &[&str]| {
+        names.iter().all(|n| wit_parser::validate_id(n).is_ok())
+            || skip(what, "not a valid WIT identifier")
+    };
+    let claim = |what: &str, taken: &mut HashSet<String>, name: &str| {
+        taken.insert(name.to_string()) || skip(what, "the name is already used")
+    };
+
+    let name = kebab_name(module_name.unwrap_or(default_name));
+    let mut world = World::new(name.clone());
+
+    // Imports and exports each have their own namespace within the world.
+    let mut import_names = HashSet::new();
+    let mut interfaces: Vec<(&str, Interface)> = Vec::new();
+    for (module, field, index) in imports {
+        if module == "$root" {
+            let what = format!("import `{field}`");
+            if !valid(&what, &[field]) || !claim(&what, &mut import_names, field) {
+                continue;
+            }
+            if let Some(func) = infer_func(&what, field, index) {
+                world.function_import(func);
+            }
+            continue;
+        }
+        let what = format!("import `{field}` from `{module}`");
+        if !valid(&what, &[module, field]) {
+            continue;
+        }
+        let i = match interfaces.iter().position(|(m, _)| *m == module) {
+            Some(i) => i,
+            None => {
+                if !claim(&what, &mut import_names, module) {
+                    continue;
+                }
+                interfaces.push((module, Interface::new(module.to_string())));
+                interfaces.len() - 1
+            }
+        };
+        let interface = &mut interfaces[i].1;
+        if !claim(&what, &mut interface_item_names(interface), field) {
+            continue;
+        }
+        if let Some(func) = infer_func(&what, field, index) {
+            interface.function(func);
+        }
+    }
+    for (_, interface) in interfaces {
+        world.inline_interface_import(interface);
+    }
+
+    let mut export_names = HashSet::new();
+    let mut interfaces: Vec<(&str, Interface)> = Vec::new();
+    for (export, index) in exports {
+        // Skip functions which are part of the canonical ABI rather than the
+        // module's own interface.
+        if export.starts_with("cabi_") || export == "_initialize" {
+            continue;
+        }
+        let what = format!("export `{export}`");
+        let Some((module, field)) = export.split_once('#') else {
+            if !valid(&what, &[export]) || !claim(&what, &mut export_names, export) {
+                continue;
+            }
+            if let Some(func) = infer_func(&what, export, index) {
+                world.function_export(func);
+            }
+            continue;
+        };
+        if !valid(&what, &[module, field]) {
+            continue;
+        }
+        let i = match interfaces.iter().position(|(m, _)| *m == module) {
+            Some(i) => i,
+            None => {
+                if !claim(&what, &mut export_names, module) {
+                    continue;
+                }
+                interfaces.push((module, Interface::new(module.to_string())));
+                interfaces.len() - 1
+            }
+        };
+        let interface = &mut interfaces[i].1;
+        if !claim(&what, &mut interface_item_names(interface), field) {
+            continue;
+        }
+        if let Some(func) = infer_func(&what, field, index) {
+            interface.function(func);
+        }
+    }
+    for (_, interface) in interfaces {
+        world.inline_interface_export(interface);
+    }
+
+    let skipped = skipped.into_inner();
+    if !skipped.is_empty() {
+        let mut docs = "Functions of the module which have no WIT equivalent:\n".to_string();
+        for item in skipped {
+            docs.push_str(&format!("\n* {item}"));
+        }
+        world.set_docs(Some(docs));
+    }
+
+    let mut package = Package::new(PackageName::new("local", name, None));
+    package.world(world);
+    Ok(package)
+}
+
+/// Maps a core function type to WIT parameters and a result, returning why
+/// the function has no WIT equivalent on failure.
+fn infer_signature(
+    ty: &wasmparser::FuncType,
+    names: Option<&HashMap<u32, &str>>,
+) -> Result<(wit_encoder::Params, Option<wit_encoder::Type>), String> {
+    use wit_encoder::Type;
+
+    fn scalar(ty: wasmparser::ValType) -> Result<Type, String> {
+        match ty {
+            wasmparser::ValType::I32 => Ok(Type::U32),
+            wasmparser::ValType::I64 => Ok(Type::U64),
+            wasmparser::ValType::F32 => Ok(Type::F32),
+            wasmparser::ValType::F64 => Ok(Type::F64),
+            other => Err(format!("`{other}` has no WIT equivalent")),
+        }
+    }
+
+    // Beyond these limits the canonical ABI passes parameters and results
+    // through linear memory, which can't be recovered from the signature.
+    let params = ty.params();
+    if params.len() > 16 {
+        return Err("more than 16 parameters are passed through memory".to_string());
+    }
+    if ty.results().len() > 1 {
+        return Err("multiple results are returned through memory".to_string());
+    }
+
+    let name = |i: usize| {
+        names
+            .and_then(|names| names.get(&u32::try_from(i).unwrap()))
+            .map(|name| kebab_name(name))
+    };
+    let mut taken = std::collections::HashSet::new();
+    let mut output = wit_encoder::Params::empty();
+    let mut i = 0;
+    while i < params.len() {
+        let (ptr, len) = (name(i), name(i + 1));
+        if let (Some(ptr), Some(len)) = (&ptr, &len) {
+            let is_i32 = |j: usize| params.get(j) == Some(&wasmparser::ValType::I32);
+            let ptr_words = ptr.split('-').collect::<Vec<_>>();
+            let len_words = len.split('-').collect::<Vec<_>>();
+            let is_ptr = matches!(
+                ptr_words.last(),
+                Some(&("ptr" | "pointer" | "addr" | "buf" | "data"))
+            );
+            let is_len = matches!(
+                len_words.last(),
+                Some(&("len" | "length" | "size" | "count"))
+            );
+            if is_i32(i) && is_i32(i + 1) && is_ptr && is_len {
+                let base = if ptr_words.len() > 1 {
+                    &ptr_words[..ptr_words.len() - 1]
+                } else if len_words.len() > 1 {
+                    &len_words[..len_words.len() - 1]
+                } else {
+                    &["data"][..]
+                };
+                let is_text = base.iter().any(|w| {
+                    matches!(
+                        *w,
+                        "str"
+                            | "string"
+                            | "name"
+                            | "msg"
+                            | "message"
+                            | "text"
+                            | "path"
+                            | "key"
+                            | "url"
+                            | "uri"
+                            | "label"
+                    )
+                });
+                let ty = if is_text {
+                    Type::String
+                } else {
+                    Type::list(Type::U8)
+                };
+                output.push(unique_name(&mut taken, base.join("-")), ty);
+                i += 2;
+                continue;
+            }
+        }
+        let param = ptr.unwrap_or_else(|| format!("arg{i}"));
+        output.push(unique_name(&mut taken, param), scalar(params[i])?);
+        i += 1;
+    }
+
+    let result = match ty.results() {
+        [ty] => Some(scalar(*ty)?),
+        _ => None,
+    };
+    Ok((output, result))
+}
+
+/// Converts an arbitrary core wasm name, such as `wasi_snapshot_preview1` or
+/// `getValue`, to a valid WIT kebab-case name.
+fn kebab_name(name: &str) -> String {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(mem::take(&mut word));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && prev_lower {
+            words.push(mem::take(&mut word));
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        word.push(c.to_ascii_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    // Words can't start with a digit, so those are appended to the previous
+    // word instead.
+    let mut result = String::new();
+    for word in words {
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            if result.is_empty() {
+                result.push('x');
+            }
+        } else if !result.is_empty() {
+            result.push('-');
+        }
+        result.push_str(&word);
+    }
+    if result.is_empty() {
+        result.push_str("unnamed");
+    }
+    result
+}
+
+/// Returns `name`, or `name` with a suffix if it's already in `taken`, and
+/// adds the result to `taken`.
+fn unique_name(taken: &mut std::collections::HashSet<String>, name: String) -> String {
+    let mut candidate = name.clone();
+    let mut n = 2;
+    while taken.contains(&candidate) {
+        candidate = format!("{name}-v{n}");
+        n += 1;
+    }
+    taken.insert(candidate.clone());
+    candidate
+}
+
+fn interface_item_names(interface: &wit_encoder::Interface) -> std::collections::HashSet<String> {
+    interface
+        .items()
+        .iter()
+        .filter_map(|item| match item {
+            wit_encoder::InterfaceItem::Function(func) => Some(func.name().raw_name().to_string()),
+            wit_encoder::InterfaceItem::TypeDef(_) => None,
+        })
+        .collect()
+}
+
+/// Renders the report for `component wit --abi-layout`.
+struct AbiLayoutPrinter<'a> {
+    resolve: &'a Resolve,
+    sizes: wit_parser::SizeAlign,
+    output: String,
+}
+
+impl AbiLayoutPrinter<'_> {
+    fn package(&mut self, id: PackageId) {
+        let package = &self.resolve.packages[id];
+        for (_, id) in package.interfaces.iter() {
+            let name = self.resolve.id_of(*id).unwrap();
+            self.interface(&format!("interface {name}"), *id);
+        }
+        for (_, id) in package.worlds.iter() {
+            self.world(*id);
+        }
+    }
+
+    fn interface(&mut self, header: &str, id: wit_parser::InterfaceId) {
+        let interface = &self.resolve.interfaces[id];
+        self.output.push_str(&format!("{header}\n"));
+        for (_, ty) in interface.types.iter() {
+            self.type_def(*ty);
+        }
+        for (_, func) in interface.functions.iter() {
+            self.function(func, true, true);
+        }
+        self.output.push('\n');
+    }
+
+    fn world(&mut self, id: wit_parser::WorldId) {
+        let world = &self.resolve.worlds[id];
+        let name = format!(
+            "{}/{}",
+            self.resolve.packages[world.package.unwrap()].name,
+            world.name
+        );
+        let mut inline = Vec::new();
+        self.output.push_str(&format!("world {name}\n"));
+        for (import, items) in [(true, &world.imports), (false, &world.exports)] {
+            for (key, item) in items.iter() {
+                match item {
+                    WorldItem::Type(ty) => self.type_def(*ty),
+                    WorldItem::Function(func) => self.function(func, import, !import),
+                    WorldItem::Interface { id, .. } => {
+                        if let WorldKey::Name(key) = key {
+                            let dir = if import { "import" } else { "export" };
+                            inline.push((format!("world {name}, {dir} {key}"), *id));
+                        }
+                    }
+                }
+            }
+        }
+        self.output.push('\n');
+        for (header, id) in inline {
+            self.interface(&header, id);
+        }
+    }
+
+    fn type_def(&mut self, id: wit_parser::TypeId) {
+        let ty = &self.resolve.types[id];
+        let name = ty.name.as_deref().unwrap_or("<anonymous>");
+        let layout = Type::Id(id);
+        let size_align = if let TypeDefKind::Resource = ty.kind {
+            // Resources are only ever passed around as handles.
+            format!("handle {}", self.size_align(&Type::U32))
+        } else {
+            self.size_align(&layout)
+        };
+        let discriminant = |tag: wit_parser::Int| match tag {
+            wit_parser::Int::U8 => "u8",
+            wit_parser::Int::U16 => "u16",
+            wit_parser::Int::U32 => "u32",
+            wit_parser::Int::U64 => "u64",
+        };
+        let cases = |this: &Self, tag, cases: Vec<Option<&Type>>| {
+            let offset = this.sizes.payload_offset(tag, cases.iter().copied());
+            format!(
+                ", discriminant {}, payload at {}",
+                discriminant(tag),
+                arch_size(offset)
+            )
+        };
+        let (kind, extra) = match &ty.kind {
+            TypeDefKind::Record(_) => ("record".to_string(), String::new()),
+            TypeDefKind::Resource => ("resource".to_string(), String::new()),
+            TypeDefKind::Flags(f) => ("flags".to_string(), format!(", {} flag(s)", f.flags.len())),
+            TypeDefKind::Variant(v) => (
+                "variant".to_string(),
+                cases(
+                    self,
+                    v.tag(),
+                    v.cases.iter().map(|c| c.ty.as_ref()).collect(),
+                ),
+            ),
+            TypeDefKind::Enum(e) => (
+                "enum".to_string(),
+                format!(", discriminant {}", discriminant(e.tag())),
+            ),
+            TypeDefKind::Option(t) => (
+                format!("type {name} = option<{}>", wit_type_name(self.resolve, t)),
+                cases(self, wit_parser::Int::U8, vec![Some(t)]),
+            ),
+            TypeDefKind::Result(r) => (
+                format!("type {name} = {}", wit_type_name(self.resolve, &layout)),
+                cases(
+                    self,
+                    wit_parser::Int::U8,
+                    vec![r.ok.as_ref(), r.err.as_ref()],
+                ),
+            ),
+            _ => (
+                format!("type {name} = {}", wit_type_body(self.resolve, &ty.kind)),
+                String::new(),
+            ),
+        };
+        let header = if kind.starts_with("type ") {
+            kind
+        } else {
+            format!("{kind} {name}")
+        };
+        self.output
+            .push_str(&format!("  {header}: {size_align}{extra}\n"));
+
+        let fields: Vec<(String, &Type)> = match &ty.kind {
+            TypeDefKind::Record(r) => r.fields.iter().map(|f| (f.name.clone(), &f.ty)).collect(),
+            TypeDefKind::Tuple(t) => t
+                .types
+                .iter()
+                .enumerate()
+                .map(|(i, t)| (i.to_string(), t))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let offsets = self.sizes.field_offsets(fields.iter().map(|(_, ty)| *ty));
+        for ((name, ty), (offset, _)) in fields.iter().zip(offsets) {
+            self.output.push_str(&format!(
+                "    {name}: {} at {}, {}\n",
+                wit_type_name(self.resolve, ty),
+                arch_size(offset),
+                self.size_align(ty),
+            ));
+        }
+        if let TypeDefKind::Variant(v) = &ty.kind {
+            for case in v.cases.iter() {
+                match &case.ty {
+                    Some(ty) => self.output.push_str(&format!(
+                        "    {}({}): {}\n",
+                        case.name,
+                        wit_type_name(self.resolve, ty),
+                        self.size_align(ty),
+                    )),
+                    None => self.output.push_str(&format!("    {}\n", case.name)),
+                }
+            }
+        }
+    }
+
+    fn function(&mut self, func: &wit_parser::Function, import: bool, export: bool) {
+        use wit_parser::abi::AbiVariant;
+
+        let params = func
+            .params
+            .iter()
+            .map(|(name, ty)| format!("{name}: {}", wit_type_name(self.resolve, ty)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let result = match &func.result {
+            Some(ty) => format!(" -> {}", wit_type_name(self.resolve, ty)),
+            None => String::new(),
+        };
+        self.output
+            .push_str(&format!("  func {}({params}){result}\n", func.name));
+        let mut variants = Vec::new();
+        if import {
+            variants.push(("import", AbiVariant::GuestImport));
+        }
+        if export {
+            variants.push(("export", AbiVariant::GuestExport));
+        }
+        if import {
+            variants.push(("async import", AbiVariant::GuestImportAsync));
+        }
+        if export {
+            variants.push(("async export", AbiVariant::GuestExportAsync));
+        }
+        for (label, variant) in variants {
+            let sig = self.resolve.wasm_signature(variant, func);
+            let types = |tys: &[wit_parser::abi::WasmType]| {
+                tys.iter()
+                    .map(|ty| match ty {
+                        wit_parser::abi::WasmType::I32
+                        | wit_parser::abi::WasmType::Pointer
+                        | wit_parser::abi::WasmType::Length => "i32",
+                        wit_parser::abi::WasmType::I64
+                        | wit_parser::abi::WasmType::PointerOrI64 => "i64",
+                        wit_parser::abi::WasmType::F32 => "f32",
+                        wit_parser::abi::WasmType::F64 => "f64",
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let mut notes = Vec::new();
+            if sig.indirect_params {
+                notes.push("params passed indirectly");
+            }
+            if sig.retptr {
+                notes.push("return pointer");
+            }
+            let notes = if notes.is_empty() {
+                String::new()
+            } else {
+                format!(" ; {}", notes.join(", "))
+            };
+            self.output.push_str(&format!(
+                "    {label:<12} ({}) -> ({}){notes}\n",
+                types(&sig.params),
+                types(&sig.results),
+            ));
+        }
+    }
+
+    fn size_align(&self, ty: &Type) -> String {
+        let align = self.sizes.align(ty);
+        let (align32, align64) = (align.align_wasm32(), align.align_wasm64());
+        let align = if align32 == align64 {
+            align32.to_string()
+        } else {
+            format!("{align32} (wasm64 {align64})")
+        };
+        format!("size {}, align {align}", arch_size(self.sizes.size(ty)))
+    }
+}
+
+fn arch_size(size: wit_parser::ArchitectureSize) -> String {
+    let (size32, size64) = (size.size_wasm32(), size.size_wasm64());
+    if size32 == size64 {
+        size32.to_string()
+    } else {
+        format!("{size32} (wasm64 {size64})")
+    }
+}
+
+/// Renders `ty` the way it would be written in WIT.
+fn wit_type_name(resolve: &Resolve, ty: &Type) -> String {
+    match ty {
+        Type::Id(id) => match &resolve.types[*id].name {
+            Some(name) => name.clone(),
+            None => wit_type_body(resolve, &resolve.types[*id].kind),
+        },
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::S8 => "s8".to_string(),
+        Type::S16 => "s16".to_string(),
+        Type::S32 => "s32".to_string(),
+        Type::S64 => "s64".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::Char => "char".to_string(),
+        Type::String => "string".to_string(),
+        Type::ErrorContext => "error-context".to_string(),
+    }
+}
+
+/// Renders the right-hand side of `type x = ...` for `kind`.
+fn wit_type_body(resolve: &Resolve, kind: &TypeDefKind) -> String {
+    let name = |ty: &Type| wit_type_name(resolve, ty);
+    let opt = |ty: &Option<Type>| ty.as_ref().map(name).unwrap_or_else(|| "_".to_string());
+    match kind {
+        TypeDefKind::Type(ty) => name(ty),
+        TypeDefKind::Option(ty) => format!("option<{}>", name(ty)),
+        TypeDefKind::List(ty) => format!("list<{}>", name(ty)),
+        TypeDefKind::FixedSizeList(ty, n) => format!("list<{}, {n}>", name(ty)),
+        TypeDefKind::Tuple(t) => format!(
+            "tuple<{}>",
+            t.types.iter().map(name).collect::<Vec<_>>().join(", ")
+        ),
+        TypeDefKind::Result(r) => match (&r.ok, &r.err) {
+            (None, None) => "result".to_string(),
+            (ok, None) => format!("result<{}>", opt(ok)),
+            (ok, err) => format!("result<{}, {}>", opt(ok), opt(err)),
+        },
+        TypeDefKind::Handle(wit_parser::Handle::Own(id)) => {
+            format!("own<{}>", name(&Type::Id(*id)))
+        }
+        TypeDefKind::Handle(wit_parser::Handle::Borrow(id)) => {
+            format!("borrow<{}>", name(&Type::Id(*id)))
+        }
+        TypeDefKind::Future(ty) => match ty {
+            Some(ty) => format!("future<{}>", name(ty)),
+            None => "future".to_string(),
+        },
+        TypeDefKind::Stream(ty) => match ty {
+            Some(ty) => format!("stream<{}>", name(ty)),
+            None => "stream".to_string(),
+        },
+        other => other.as_str().to_string(),
+    }
 }
 
 /// Tool for verifying whether a component conforms to a world.
@@ -970,6 +2032,11 @@ impl TargetsOpts {
 
 /// Tool for verifying whether one world is a semver compatible evolution of
 /// another.
+///
+/// With `--suggest` this instead compares two versions of a whole package and
+/// suggests the next version of the package. The previous version of the
+/// package is selected with `--prev-package` and is typically placed in the
+/// `deps` directory of the new package.
 #[derive(Parser)]
 pub struct SemverCheckOpts {
     #[clap(flatten)]
@@ -981,15 +2048,41 @@ pub struct SemverCheckOpts {
     /// The "previous" world, or older version, of what's being tested.
     ///
     /// This is considered the baseline for the semver compatibility check.
-    #[clap(long)]
-    prev: String,
+    #[clap(long, required_unless_present = "suggest", conflicts_with = "suggest")]
+    prev: Option<String>,
 
     /// The "new" world which is the "prev" world but modified.
     ///
     /// This is what's being tested to see whether it is a backwards-compatible
     /// evolution of the "prev" world specified.
+    #[clap(long, required_unless_present = "suggest", conflicts_with = "suggest")]
+    new: Option<String>,
+
+    /// Print every difference between the two worlds as JSON to stdout.
+    ///
+    /// Each change is classified as either "major" (breaking) or "minor"
+    /// (backwards-compatible) and includes the location of the changed item
+    /// in WIT source text, if known. The command still fails if any breaking
+    /// change is found.
     #[clap(long)]
-    new: String,
+    json: bool,
+
+    /// Suggest the next version of the package from its differences with the
+    /// package named by `--prev-package`.
+    ///
+    /// The suggested version bump is "major" if there are breaking changes,
+    /// "minor" if there are only additive changes, and "patch" otherwise.
+    /// Items added in the new package must have an `@since(version = ...)`
+    /// annotation matching the version of the new package, or the suggested
+    /// version if the new package has no version. The command fails if any
+    /// annotation or the version of the new package doesn't match.
+    #[clap(long, requires = "prev_package")]
+    suggest: bool,
+
+    /// The name of the previous version of the package, for example
+    /// `wasi:http@0.2.0`, used with `--suggest`.
+    #[clap(long, value_name = "NAME", requires = "suggest")]
+    prev_package: Option<String>,
 }
 
 impl SemverCheckOpts {
@@ -999,13 +2092,88 @@ impl SemverCheckOpts {
 
     fn run(self) -> Result<()> {
         let (resolve, pkg_id) = self.resolve.load()?;
-        let prev = resolve.select_world(pkg_id, Some(self.prev.as_str()))?;
-        let new = resolve.select_world(pkg_id, Some(self.new.as_str()))?;
-        wit_component::semver_check(resolve, prev, new)?;
+        if let Some(prev_package) = &self.prev_package {
+            let prev = resolve
+                .packages
+                .iter()
+                .find(|(_, pkg)| pkg.name.to_string() == *prev_package)
+                .map(|(id, _)| id)
+                .with_context(|| format!("no package named `{prev_package}` found"))?;
+            return Self::suggest(&resolve, prev, pkg_id, self.json);
+        }
+
+        let prev = resolve.select_world(pkg_id, self.prev.as_deref())?;
+        let new = resolve.select_world(pkg_id, self.new.as_deref())?;
+        if !self.json {
+            return wit_component::semver_check(resolve, prev, new);
+        }
+
+        let changes = wit_component::semver_diff(&resolve, prev, &resolve, new)?;
+        let result = wit_component::semver_check(resolve, prev, new);
+        let output = serde_json::json!({
+            "compatible": result.is_ok(),
+            "changes": changes.iter().map(semver_change_json).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        result
+    }
+
+    fn suggest(resolve: &Resolve, prev: PackageId, new: PackageId, json: bool) -> Result<()> {
+        let suggestion = wit_component::semver_suggest(resolve, prev, resolve, new)?;
+        if json {
+            let output = serde_json::json!({
+                "bump": suggestion.bump.to_string(),
+                "version": suggestion.version.as_ref().map(|v| v.to_string()),
+                "changes": suggestion.changes.iter().map(semver_change_json).collect::<Vec<_>>(),
+                "errors": suggestion.errors.iter().map(|e| serde_json::json!({
+                    "message": e.message,
+                    "location": e.location.as_ref().map(location_json),
+                })).collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            println!("suggested bump: {}", suggestion.bump);
+            if let Some(version) = &suggestion.version {
+                println!("suggested version: {version}");
+            }
+            for change in suggestion.changes.iter() {
+                println!("  * {change}");
+            }
+        }
+
+        if !suggestion.errors.is_empty() {
+            let mut msg = format!(
+                "found {} problem(s) with the version of package `{}`",
+                suggestion.errors.len(),
+                resolve.packages[new].name,
+            );
+            for error in suggestion.errors.iter() {
+                msg.push_str(&format!("\n  * {error}"));
+            }
+            bail!("{msg}");
+        }
         Ok(())
     }
 }
 
+fn semver_change_json(change: &wit_component::SemverChange) -> serde_json::Value {
+    serde_json::json!({
+        "severity": change.severity.to_string(),
+        "kind": change.kind.to_string(),
+        "item": change.item,
+        "detail": change.detail,
+        "location": change.location.as_ref().map(location_json),
+    })
+}
+
+fn location_json(location: &wit_parser::Location) -> serde_json::Value {
+    serde_json::json!({
+        "path": location.path.display().to_string(),
+        "line": location.line,
+        "column": location.column,
+    })
+}
+
 /// Unbundled core wasm modules from a component, switching them from being
 /// embedded to being imported.
 ///
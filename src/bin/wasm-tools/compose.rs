@@ -38,6 +38,11 @@ pub struct Opts {
     #[clap(long = "no-imports")]
     disallow_imports: bool,
 
+    /// Merge the producers sections of all components into the producers
+    /// section of the composed output component.
+    #[clap(long)]
+    merge_producers: bool,
+
     /// The path to the root component to compose.
     #[clap(value_name = "COMPONENT")]
     component: PathBuf,
@@ -112,6 +117,7 @@ impl Opts {
             .extend(self.paths.iter().map(|p| cur_dir.join(p)));
         config.skip_validation |= self.skip_validation;
         config.disallow_imports |= self.disallow_imports;
+        config.merge_producers |= self.merge_producers;
         Ok(config)
     }
 }
@@ -1,18 +1,26 @@
 use bytesize::ByteSize;
+use indexmap::IndexMap;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use comfy_table::modifiers::UTF8_ROUND_CORNERS;
 use comfy_table::presets::UTF8_FULL;
 use comfy_table::{CellAlignment, ContentArrangement, Table};
 use termcolor::WriteColor;
-use wasm_metadata::{AddMetadata, Metadata, Payload};
+use wasm_metadata::{
+    AddMetadata, Metadata, Payload, Policy, Sbom, SbomFormat, Signature, SigningKey, VerifyingKey,
+};
 
 /// Manipulate metadata (module name, producers) to a WebAssembly file.
 #[derive(clap::Parser)]
 pub enum Opts {
     Show(ShowOpts),
     Add(AddOpts),
+    Sign(SignOpts),
+    Verify(VerifyOpts),
+    Sbom(SbomOpts),
+    Check(CheckOpts),
 }
 
 impl Opts {
@@ -20,6 +28,10 @@ impl Opts {
         match self {
             Opts::Show(opts) => opts.run(),
             Opts::Add(opts) => opts.run(),
+            Opts::Sign(opts) => opts.run(),
+            Opts::Verify(opts) => opts.run(),
+            Opts::Sbom(opts) => opts.run(),
+            Opts::Check(opts) => opts.run(),
         }
     }
 
@@ -27,6 +39,10 @@ impl Opts {
         match self {
             Opts::Show(opts) => opts.general_opts(),
             Opts::Add(opts) => opts.general_opts(),
+            Opts::Sign(opts) => opts.general_opts(),
+            Opts::Verify(opts) => opts.general_opts(),
+            Opts::Sbom(opts) => opts.general_opts(),
+            Opts::Check(opts) => opts.general_opts(),
         }
     }
 }
@@ -38,8 +54,17 @@ pub struct ShowOpts {
     io: wasm_tools::InputOutput,
 
     /// Output in JSON encoding
-    #[clap(long)]
+    #[clap(long, conflicts_with = "oci_annotations")]
     json: bool,
+
+    /// Output the metadata of the outermost module or component as OCI image
+    /// annotations.
+    ///
+    /// The output is a JSON object with an `annotations` field, in the same
+    /// shape as an OCI image manifest, which can be read back with
+    /// `metadata add --from-oci-manifest`.
+    #[clap(long)]
+    oci_annotations: bool,
 }
 
 impl ShowOpts {
@@ -52,7 +77,11 @@ impl ShowOpts {
         let mut output = self.io.output_writer()?;
 
         let payload = wasm_metadata::Payload::from_binary(&input)?;
-        if self.json {
+        if self.oci_annotations {
+            let annotations = payload.metadata().oci_annotations();
+            let manifest = serde_json::json!({ "annotations": annotations });
+            writeln!(output, "{}", serde_json::to_string_pretty(&manifest)?)?;
+        } else if self.json {
             write!(output, "{}", serde_json::to_string(&payload)?)?;
         } else {
             write_summary_table(&payload, &mut output)?;
@@ -71,6 +100,14 @@ pub struct AddOpts {
     #[clap(flatten)]
     add_metadata: wasm_metadata::AddMetadataOpts,
 
+    /// Add metadata from the `org.opencontainers.image.*` annotations of an OCI
+    /// image manifest or image config.
+    ///
+    /// Metadata passed explicitly through other flags takes precedence over
+    /// the annotations in the manifest.
+    #[clap(long, value_name = "PATH")]
+    from_oci_manifest: Option<PathBuf>,
+
     /// Output the text format of WebAssembly instead of the binary format
     #[clap(short = 't', long)]
     wat: bool,
@@ -84,7 +121,11 @@ impl AddOpts {
     pub fn run(&self) -> Result<()> {
         let input = self.io.get_input_wasm()?;
 
-        let add_metadata: AddMetadata = self.add_metadata.clone().into();
+        let mut add_metadata: AddMetadata = self.add_metadata.clone().into();
+        if let Some(path) = &self.from_oci_manifest {
+            let annotations = read_oci_annotations(path)?;
+            add_metadata.add_oci_annotations(&annotations)?;
+        }
         let output = add_metadata.to_wasm(&input)?;
 
         self.io.output_wasm(&output, self.wat)?;
@@ -92,6 +133,233 @@ impl AddOpts {
     }
 }
 
+/// Sign a WebAssembly file with an Ed25519 key.
+///
+/// The signature covers all top-level sections of the module or component,
+/// including nested modules and components, and is stored in a `signature`
+/// custom section together with an identifier of the public key which can
+/// verify it. Any previous signature is replaced.
+#[derive(clap::Parser)]
+pub struct SignOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Path to the Ed25519 private key to sign with, stored as a hex-encoded
+    /// 32-byte seed.
+    #[clap(long, value_name = "PATH")]
+    key: PathBuf,
+
+    /// Name of a custom section which shouldn't be covered by the signature.
+    ///
+    /// Excluded sections can be added, modified or removed after signing
+    /// without invalidating the signature.
+    #[clap(long, value_name = "NAME")]
+    exclude: Vec<String>,
+
+    /// Write the hex-encoded public key corresponding to `--key` to this path.
+    #[clap(long, value_name = "PATH")]
+    public_key_output: Option<PathBuf>,
+
+    /// Output the text format of WebAssembly instead of the binary format
+    #[clap(short = 't', long)]
+    wat: bool,
+}
+
+impl SignOpts {
+    pub fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        self.io.general_opts()
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let input = self.io.get_input_wasm()?;
+        let key = SigningKey::from_bytes(&read_key(&self.key)?);
+        if let Some(path) = &self.public_key_output {
+            std::fs::write(path, format!("{}\n", hex(key.verifying_key().as_bytes())))
+                .with_context(|| format!("failed to write {path:?}"))?;
+        }
+
+        let output = Signature::sign(&input, &key, &self.exclude)?;
+        self.io.output_wasm(&output, self.wat)?;
+        Ok(())
+    }
+}
+
+/// Verify the signature of a WebAssembly file created by `metadata sign`.
+///
+/// Fails if the file isn't signed, was signed with a different key, or was
+/// modified after it was signed.
+#[derive(clap::Parser)]
+pub struct VerifyOpts {
+    #[clap(flatten)]
+    general: wasm_tools::GeneralOpts,
+
+    #[clap(flatten)]
+    input: wasm_tools::InputArg,
+
+    /// Path to the Ed25519 public key to verify with, stored as a hex-encoded
+    /// 32-byte key.
+    #[clap(long, value_name = "PATH")]
+    public_key: PathBuf,
+}
+
+impl VerifyOpts {
+    pub fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        &self.general
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let input = self.input.get_binary_wasm()?;
+        let key = VerifyingKey::from_bytes(&read_key(&self.public_key)?)
+            .context("invalid Ed25519 public key")?;
+        let signature = Signature::verify(&input, &key)?;
+        log::info!("verified signature from key {}", signature.key_id());
+        Ok(())
+    }
+}
+
+/// Generate a Software Bill of Materials (SBOM) for a WebAssembly file.
+///
+/// The SBOM contains the file itself along with all nested modules and
+/// components, described using their metadata. Rust dependencies recorded by
+/// `cargo auditable` are included as packages as well.
+#[derive(clap::Parser)]
+pub struct SbomOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// The format of the SBOM document, either `spdx` or `cyclonedx`.
+    #[clap(long, default_value_t = SbomFormat::Spdx)]
+    format: SbomFormat,
+
+    /// The creation time of the SBOM as an RFC 3339 timestamp, defaults to the
+    /// current time.
+    #[clap(long, value_name = "TIMESTAMP")]
+    created: Option<String>,
+
+    /// The unique URI of the SPDX document, defaults to a URI derived from the
+    /// name and hash of the input.
+    #[clap(long, value_name = "URI")]
+    namespace: Option<String>,
+}
+
+impl SbomOpts {
+    pub fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        self.io.general_opts()
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let input = self.io.get_input_wasm()?;
+        let mut output = self.io.output_writer()?;
+
+        let mut sbom = Sbom::default();
+        sbom.format = self.format;
+        sbom.created = self.created.clone();
+        sbom.namespace = self.namespace.clone();
+        writeln!(output, "{}", sbom.to_json(&input)?)?;
+        Ok(())
+    }
+}
+
+/// Check the metadata of a WebAssembly file against a policy.
+///
+/// The policy is a JSON file listing metadata fields which are required, the
+/// SPDX licenses which are allowed, and producers which are banned. Every
+/// module and component in the file, including nested ones, is checked. All
+/// violations are printed and the command fails if any are found.
+///
+/// An example policy is:
+///
+///     {
+///       "required": ["licenses", "source", "revision"],
+///       "allowed-licenses": ["Apache-2.0", "MIT"],
+///       "banned-producers": [
+///         { "field": "processed-by", "name": "rustc", "versions": "<1.80" }
+///       ],
+///       "root-only": false
+///     }
+#[derive(clap::Parser)]
+pub struct CheckOpts {
+    #[clap(flatten)]
+    general: wasm_tools::GeneralOpts,
+
+    #[clap(flatten)]
+    input: wasm_tools::InputArg,
+
+    /// Path to the JSON policy file to check against.
+    #[clap(long, value_name = "PATH")]
+    policy: PathBuf,
+}
+
+impl CheckOpts {
+    pub fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        &self.general
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let policy = std::fs::read_to_string(&self.policy)
+            .with_context(|| format!("failed to read {:?}", self.policy))?;
+        let policy: Policy = serde_json::from_str(&policy)
+            .with_context(|| format!("failed to parse policy {:?}", self.policy))?;
+        let input = self.input.get_binary_wasm()?;
+        let payload = Payload::from_binary(&input)?;
+        let violations = policy.check(&payload)?;
+        if violations.is_empty() {
+            return Ok(());
+        }
+        let mut stdout = std::io::stdout().lock();
+        for violation in &violations {
+            writeln!(stdout, "{violation}")?;
+        }
+        bail!("{} policy violation(s) found", violations.len())
+    }
+}
+
+/// Read a hex-encoded 32-byte Ed25519 key from `path`.
+fn read_key(path: &Path) -> Result<[u8; 32]> {
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read {path:?}"))?;
+    let contents = contents.trim();
+    let mut key = [0; 32];
+    if contents.len() != key.len() * 2 || !contents.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("key in {path:?} should be 64 hexadecimal characters");
+    }
+    for (byte, digits) in key.iter_mut().zip(contents.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).unwrap();
+        *byte = u8::from_str_radix(digits, 16).unwrap();
+    }
+    Ok(key)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Read the annotations of an OCI image manifest, image index, or image config
+/// stored as JSON at `path`.
+fn read_oci_annotations(path: &Path) -> Result<IndexMap<String, String>> {
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read {path:?}"))?;
+    let json: serde_json::Value = serde_json::from_str(&contents)
+        .with_context(|| format!("failed to parse {path:?} as JSON"))?;
+
+    // Manifests and indexes store annotations at the top level while image
+    // configs store them as labels.
+    let annotations = json
+        .get("annotations")
+        .or_else(|| json.get("config").and_then(|c| c.get("Labels")))
+        .with_context(|| format!("no annotations found in {path:?}"))?;
+    let annotations = annotations
+        .as_object()
+        .with_context(|| format!("annotations in {path:?} are not an object"))?;
+    annotations
+        .iter()
+        .map(|(key, value)| match value.as_str() {
+            Some(value) => Ok((key.clone(), value.to_string())),
+            None => bail!("annotation `{key}` in {path:?} is not a string"),
+        })
+        .collect()
+}
+
 /// Write a table containing a summarized overview of a wasm binary's metadata to
 /// a writer.
 fn write_summary_table(payload: &Payload, f: &mut Box<dyn WriteColor>) -> Result<()> {
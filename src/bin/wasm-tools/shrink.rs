@@ -23,11 +23,25 @@ use wasm_shrink::{IsInteresting, WasmShrink};
 /// the crash and save it at `shrunken.wasm` with the following command:
 ///
 /// $ wasm-shrink compile.sh crasher.wasm -o shrunken.wasm
+///
+/// ## Shrinking `*.wast` scripts
+///
+/// If the input file has a `.wast` extension then it's treated as a script
+/// rather than a single Wasm file. Directives that aren't needed are removed
+/// from the script and each module that it defines is shrunk. The predicate
+/// script is given a candidate `*.wast` file and the shrunken script is
+/// written to a `.wast` file. Scripts are shrunk sequentially, so `--jobs`
+/// can't be greater than one.
 #[derive(Parser)]
 pub struct Opts {
     #[clap(flatten)]
     shrink: WasmShrink,
 
+    /// The number of candidate Wasm test cases to generate and test with the
+    /// predicate in parallel.
+    #[clap(short, long, default_value = "1")]
+    jobs: usize,
+
     /// The interestingness predicate script.
     predicate: PathBuf,
 
@@ -41,8 +55,15 @@ impl Opts {
     }
 
     pub fn run(self) -> Result<()> {
-        let input = self.io.get_input_wasm()?;
-        let initial_size = input.len();
+        let is_wast = self
+            .io
+            .input_path()
+            .is_some_and(|p| p.extension().is_some_and(|e| e == "wast"));
+        anyhow::ensure!(
+            !is_wast || self.jobs <= 1,
+            "Shrinking wast scripts is sequential and does not support more \
+             than one job."
+        );
 
         // Prerequisites for the predicate.
         anyhow::ensure!(
@@ -56,6 +77,13 @@ impl Opts {
             self.predicate.display()
         );
 
+        if is_wast {
+            return self.run_wast();
+        }
+
+        let input = self.io.get_input_wasm()?;
+        let initial_size = input.len();
+
         let output = self
             .io
             .output_path()
@@ -66,38 +94,14 @@ impl Opts {
             });
         log::info!("Will write shrunken Wasm file to: {}", output.display());
 
-        let predicate = make_predicate(&self.predicate);
+        let predicate = make_predicate(&self.predicate, "");
 
         let shrunken = self
             .shrink
             .on_new_smallest(Some(Box::new({
                 let output = output.clone();
                 move |new_smallest: &[u8]| {
-                    // Write the Wasm to a temp file and then move that to the output
-                    // path as a second, atomic step. This ensures that the output is
-                    // always a valid, interesting, shrunken Wasm file, even in the
-                    // presence of the user doing `Ctrl-C`.
-                    //
-                    // Note that to have the highest likelihood of the rename to succeed
-                    // the temporary file is placed in the same directory as the
-                    // destination. This attempts to avoid possibilities where the
-                    // system tmp directory is not on the same filesystem as the
-                    // destination, which would prevent a rename.
-                    let tmp = match output.parent() {
-                        Some(parent) => NamedTempFile::new_in(parent),
-                        None => NamedTempFile::new(),
-                    };
-                    let tmp = tmp.context("Failed to create a temporary file")?;
-                    std::fs::write(tmp.path(), new_smallest).with_context(|| {
-                        format!("Failed to write to file: {}", tmp.path().display())
-                    })?;
-                    std::fs::rename(tmp.path(), &output).with_context(|| {
-                        format!(
-                            "Failed to rename {} to {}",
-                            tmp.path().display(),
-                            output.display()
-                        )
-                    })?;
+                    write_atomically(&output, new_smallest)?;
 
                     println!(
                         "{} bytes ({:.02}% smaller)",
@@ -124,7 +128,7 @@ impl Opts {
                     Ok(())
                 }
             })))
-            .run(input, predicate)?;
+            .run_parallel(input, self.jobs, predicate)?;
 
         let wat = wasmprinter::print_bytes(&shrunken.output)
             .unwrap_or_else(|e| format!("<error disassembling WAT: {e}>"));
@@ -143,6 +147,78 @@ impl Opts {
 
         Ok(())
     }
+
+    fn run_wast(self) -> Result<()> {
+        let input_path = self.io.input_path().unwrap();
+        let input = std::fs::read_to_string(input_path)
+            .with_context(|| format!("Failed to read script: {}", input_path.display()))?;
+        let initial_size = input.len();
+
+        let output = self
+            .io
+            .output_path()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| input_path.with_extension("shrunken.wast"));
+        log::info!("Will write shrunken script to: {}", output.display());
+
+        let predicate = make_predicate(&self.predicate, ".wast");
+
+        let shrunken = self
+            .shrink
+            .on_new_smallest(Some(Box::new({
+                let output = output.clone();
+                move |new_smallest: &[u8]| {
+                    write_atomically(&output, new_smallest)?;
+                    println!(
+                        "{} bytes ({:.02}% smaller)",
+                        new_smallest.len(),
+                        (100.0 - (new_smallest.len() as f64 / initial_size as f64 * 100.0))
+                    );
+                    Ok(())
+                }
+            })))
+            .run_wast(&input, |wast| predicate(wast.as_bytes()))?;
+
+        println!(
+            "\n\
+         {} :: {} bytes ({:.02}% smaller)\n\
+         ================================================================================\n\
+         {}\n\
+         ================================================================================",
+            output.display(),
+            shrunken.output.len(),
+            100.0 - (shrunken.output.len() as f64 / initial_size as f64 * 100.0),
+            String::from_utf8_lossy(&shrunken.output).trim(),
+        );
+
+        Ok(())
+    }
+}
+
+/// Write `contents` to a temp file and then move that to the `output` path as a
+/// second, atomic step. This ensures that the output is always a valid,
+/// interesting, shrunken test case, even in the presence of the user doing
+/// `Ctrl-C`.
+fn write_atomically(output: &Path, contents: &[u8]) -> Result<()> {
+    // Note that to have the highest likelihood of the rename to succeed the
+    // temporary file is placed in the same directory as the destination. This
+    // attempts to avoid possibilities where the system tmp directory is not on
+    // the same filesystem as the destination, which would prevent a rename.
+    let tmp = match output.parent() {
+        Some(parent) => NamedTempFile::new_in(parent),
+        None => NamedTempFile::new(),
+    };
+    let tmp = tmp.context("Failed to create a temporary file")?;
+    std::fs::write(tmp.path(), contents)
+        .with_context(|| format!("Failed to write to file: {}", tmp.path().display()))?;
+    std::fs::rename(tmp.path(), output).with_context(|| {
+        format!(
+            "Failed to rename {} to {}",
+            tmp.path().display(),
+            output.display()
+        )
+    })?;
+    Ok(())
 }
 
 struct OutputIsInteresting(std::process::Output);
@@ -178,9 +254,13 @@ impl IsInteresting for OutputIsInteresting {
 
 fn make_predicate<'a>(
     predicate_script: &'a Path,
-) -> impl FnMut(&[u8]) -> Result<OutputIsInteresting> + 'a {
+    suffix: &'a str,
+) -> impl Fn(&[u8]) -> Result<OutputIsInteresting> + Sync + 'a {
     move |wasm| {
-        let tmp = NamedTempFile::new().context("Failed to create a temporary file.")?;
+        let tmp = tempfile::Builder::new()
+            .suffix(suffix)
+            .tempfile()
+            .context("Failed to create a temporary file.")?;
         std::fs::write(tmp.path(), wasm).with_context(|| {
             format!(
                 "Failed to write to temporary file: {}",
@@ -1,9 +1,9 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::Parser;
 use std::io::{Read, stdin};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
-use wasm_smith::Module;
+use wasm_smith::{Module, Profile, WitComponent};
 
 /// A WebAssembly test case generator.
 ///
@@ -69,6 +69,35 @@ pub struct Opts {
     #[clap(short, long)]
     config: Option<PathBuf>,
 
+    /// Generate a component which implements a WIT world instead of a core
+    /// module.
+    ///
+    /// This is a path to a WIT file or directory of WIT files. The generated
+    /// component has exactly the imports and exports of the world selected
+    /// with `--world`, and its core module's function bodies are generated
+    /// with the rest of the configuration provided here. Only the signatures
+    /// of those functions follow the canonical ABI, their bodies don't.
+    #[clap(long, value_name = "WIT")]
+    wit: Option<PathBuf>,
+
+    /// The world within `--wit` to generate a component for.
+    ///
+    /// This is only required if the main package of `--wit` has more than
+    /// one world.
+    #[clap(long, requires = "wit")]
+    world: Option<String>,
+
+    /// Derive the configuration from a corpus of existing modules.
+    ///
+    /// Every `*.wasm` core module within this directory, recursively, is
+    /// profiled. The generated module then only uses the proposals used by the
+    /// corpus, has at most as many of each kind of entity as a module in the
+    /// corpus, weighs instructions by how frequently the corpus uses them, and
+    /// may import the most common imports of the corpus. Other configuration
+    /// options take precedence over the profile.
+    #[clap(long, value_name = "DIR")]
+    profile_from: Option<PathBuf>,
+
     #[clap(flatten)]
     module_config: wasm_smith::InternalOptionalConfig,
 
@@ -106,7 +135,36 @@ impl Opts {
             None => wasm_smith::InternalOptionalConfig::default(),
         };
         let config = self.module_config.clone().or(json);
-        let config = wasm_smith::Config::try_from(config)?;
+        let config = match &self.profile_from {
+            Some(dir) => {
+                let mut profile = profile_corpus(dir)?.config();
+                let available_imports = profile.available_imports.take();
+                let mut config = wasm_smith::Config::try_from(config.or(
+                    wasm_smith::InternalOptionalConfig::try_from(&profile)?,
+                ))?;
+                if config.available_imports.is_none() {
+                    config.available_imports = available_imports;
+                }
+                config
+            }
+            None => wasm_smith::Config::try_from(config)?,
+        };
+        if let Some(wit) = &self.wit {
+            if self.ensure_termination {
+                bail!("`--ensure-termination` is not supported with `--wit`");
+            }
+            let mut resolve = wit_parser::Resolve::default();
+            let (pkg, _) = resolve.push_path(wit)?;
+            let world = resolve.select_world(pkg, self.world.as_deref())?;
+            let component =
+                WitComponent::new(config, &mut u, &resolve, world).unwrap_or_else(|e| {
+                    eprintln!("error: failed to generate component: {e}");
+                    process::exit(2);
+                });
+            return self
+                .output
+                .output_wasm(&self.general, &component.to_bytes(), self.wat);
+        }
         let mut module = Module::new(config, &mut u).unwrap_or_else(|e| {
             eprintln!("error: failed to generate module: {e}");
             process::exit(2);
@@ -127,3 +185,35 @@ impl Opts {
         Ok(())
     }
 }
+
+/// Add every `*.wasm` file within `dir`, recursively, to a new profile.
+fn profile_corpus(dir: &Path) -> Result<Profile> {
+    let mut profile = Profile::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    let mut modules = 0;
+    while let Some(dir) = dirs.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .with_context(|| format!("failed to read directory '{}'", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if path.extension().and_then(|e| e.to_str()) != Some("wasm") {
+                continue;
+            }
+            let wasm = std::fs::read(&path)
+                .with_context(|| format!("failed to read '{}'", path.display()))?;
+            match profile.add_module(&wasm) {
+                Ok(()) => modules += 1,
+                Err(e) => log::warn!("skipping '{}': {e:#}", path.display()),
+            }
+        }
+    }
+    if modules == 0 {
+        bail!("no valid core wasm modules found in '{}'", dir.display());
+    }
+    log::debug!("profiled {modules} modules from '{}'", dir.display());
+    Ok(profile)
+}
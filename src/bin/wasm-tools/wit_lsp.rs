@@ -0,0 +1,604 @@
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use url::Url;
+use wit_parser::{
+    ErrorLocation, InterfaceId, Location, Reference, Resolve, SizeAlign, SourceMap, Type,
+    TypeDefKind, UnresolvedPackageGroup, WorldItem, WorldKey,
+};
+
+/// A language server for WIT documents.
+///
+/// This command speaks the Language Server Protocol over stdin and stdout and
+/// is intended to be launched by an editor. It provides:
+///
+/// * diagnostics when a WIT document is opened, changed or saved,
+/// * go-to-definition for types and `use` paths, including those defined in
+///   packages within a `deps` directory,
+/// * hover information with doc comments and, for types, the canonical ABI
+///   size and alignment on 32-bit platforms,
+/// * a symbol outline of each document.
+///
+/// Each document is analyzed as part of the package in its directory, in the
+/// same manner as `wasm-tools component wit DIR`. Documents open in the editor
+/// are analyzed with their current, possibly unsaved, contents while all other
+/// files, including those in the `deps` directory, are read from disk.
+#[derive(Parser)]
+pub struct Opts {
+    /// Features to enable when resolving WIT documents.
+    ///
+    /// This flag enables the `@unstable` feature in WIT documents where the
+    /// items are otherwise hidden by default.
+    #[clap(long)]
+    features: Vec<String>,
+
+    /// Enable all features when resolving WIT documents.
+    ///
+    /// This flag enables all `@unstable` features in WIT documents where the
+    /// items are otherwise hidden by default.
+    #[clap(long)]
+    all_features: bool,
+
+    #[clap(flatten)]
+    general: wasm_tools::GeneralOpts,
+}
+
+impl Opts {
+    pub fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        &self.general
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let mut server = Server {
+            opts: self,
+            documents: HashMap::new(),
+            published: HashSet::new(),
+            shutdown: false,
+            stdout: io::stdout(),
+        };
+        let mut stdin = io::stdin().lock();
+        while let Some(message) = read_message(&mut stdin)? {
+            if message["method"] == "exit" {
+                if !server.shutdown {
+                    bail!("received an `exit` notification before a `shutdown` request");
+                }
+                return Ok(());
+            }
+            server.handle(message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a single JSON-RPC message framed with a `Content-Length` header,
+/// returning `None` at the end of the input.
+fn read_message(input: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = Some(value.trim().parse::<usize>()?);
+            }
+        }
+    }
+    let length = length.context("message is missing a `Content-Length` header")?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    let message = serde_json::from_slice(&body).context("failed to parse message")?;
+    Ok(Some(message))
+}
+
+struct Server<'a> {
+    opts: &'a Opts,
+    /// The text of all documents opened by the client.
+    documents: HashMap<PathBuf, String>,
+    /// Files for which non-empty diagnostics were last published.
+    published: HashSet<PathBuf>,
+    shutdown: bool,
+    stdout: io::Stdout,
+}
+
+/// An item within a `Resolve` which can be hovered over or jumped to.
+enum Item {
+    Reference(Reference),
+    Function(InterfaceId, String),
+}
+
+impl Server<'_> {
+    fn handle(&mut self, message: Value) -> Result<()> {
+        let Some(method) = message["method"].as_str() else {
+            // Responses to requests sent by the server, which never happens.
+            return Ok(());
+        };
+        let params = &message["params"];
+        let Some(id) = message.get("id") else {
+            return self.notification(method, params);
+        };
+        let result = if self.shutdown {
+            Err((-32600, "the server has been shut down".to_string()))
+        } else {
+            self.request(method, params)
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        self.send(&response)
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": {
+                        "openClose": true,
+                        "change": 1,
+                        "save": { "includeText": false },
+                    },
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": {
+                    "name": "wasm-tools wit-lsp",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => Ok(self.hover(params).unwrap_or(Value::Null)),
+            "textDocument/definition" => Ok(self.definition(params).unwrap_or(Value::Null)),
+            "textDocument/documentSymbol" => Ok(self.document_symbols(params).unwrap_or(json!([]))),
+            _ => Err((-32601, format!("unsupported method `{method}`"))),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Result<()> {
+        let Some(path) = document_path(params) else {
+            return Ok(());
+        };
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                self.documents.insert(path.clone(), text.to_string());
+                self.publish_diagnostics(&path)?;
+            }
+            "textDocument/didChange" => {
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes.and_then(|c| c.last()?["text"].as_str()) {
+                    self.documents.insert(path.clone(), text.to_string());
+                    self.publish_diagnostics(&path)?;
+                }
+            }
+            "textDocument/didSave" => self.publish_diagnostics(&path)?,
+            "textDocument/didClose" => {
+                self.documents.remove(&path);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, message: &Value) -> Result<()> {
+        let body = serde_json::to_string(message)?;
+        let mut stdout = self.stdout.lock();
+        write!(stdout, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    /// Resolves the package in the directory containing `path` along with its
+    /// dependencies.
+    fn analyze(&self, path: &Path) -> Result<Resolve> {
+        let mut resolve = Resolve::default();
+        resolve.all_features = self.opts.all_features;
+        for feature in self.opts.features.iter() {
+            for f in feature.split_whitespace() {
+                for f in f.split(',').filter(|s| !s.is_empty()) {
+                    resolve.features.insert(f.to_string());
+                }
+            }
+        }
+        let dir = path.parent().context("document has no parent directory")?;
+        let top_pkg = self
+            .parse_dir(dir)
+            .with_context(|| format!("failed to parse package: {}", dir.display()))?;
+        resolve.push_dir_with_group(dir, top_pkg)?;
+        Ok(resolve)
+    }
+
+    /// Parses the `*.wit` files in `dir` as a package in the same manner as
+    /// `UnresolvedPackageGroup::parse_dir`, using the client's copy of any
+    /// documents which are open.
+    fn parse_dir(&self, dir: &Path) -> Result<UnresolvedPackageGroup> {
+        let mut files = HashSet::new();
+        for entry in dir.read_dir()? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                files.insert(path);
+            }
+        }
+        files.extend(
+            self.documents
+                .keys()
+                .filter(|p| p.parent() == Some(dir))
+                .cloned(),
+        );
+        let mut files = files
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "wit"))
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let mut map = SourceMap::default();
+        for file in files {
+            match self.documents.get(&file) {
+                Some(text) => map.push(&file, text.as_str()),
+                None => map.push_file(&file)?,
+            }
+        }
+        map.parse()
+    }
+
+    /// Returns the text of `path`, preferring the client's copy if the
+    /// document is open.
+    fn text(&self, path: &Path) -> String {
+        match self.documents.get(path) {
+            Some(text) => text.clone(),
+            None => std::fs::read_to_string(path).unwrap_or_default(),
+        }
+    }
+
+    fn publish_diagnostics(&mut self, path: &Path) -> Result<()> {
+        let mut diagnostics = HashMap::<PathBuf, Vec<Value>>::new();
+        if let Err(err) = self.analyze(path) {
+            let (file, range, message) = match ErrorLocation::find(&err) {
+                Some(location) => {
+                    let text = self.text(&location.start.path);
+                    let start = lsp_position(&text, location.start.line, location.start.column);
+                    let end = lsp_position(&text, location.end.line, location.end.column);
+                    (
+                        location.start.path.clone(),
+                        json!({ "start": start, "end": end }),
+                        location.message.clone(),
+                    )
+                }
+                None => (
+                    path.to_path_buf(),
+                    json!({
+                        "start": { "line": 0, "character": 0 },
+                        "end": { "line": 0, "character": 0 },
+                    }),
+                    format!("{err:#}"),
+                ),
+            };
+            diagnostics.entry(file).or_default().push(json!({
+                "range": range,
+                "severity": 1,
+                "source": "wit",
+                "message": message,
+            }));
+        }
+
+        // Clear out diagnostics previously published for files in this
+        // package which no longer have any.
+        let dir = path.parent().unwrap_or(path);
+        let stale = self
+            .published
+            .iter()
+            .filter(|p| p.starts_with(dir) && !diagnostics.contains_key(*p))
+            .cloned()
+            .collect::<Vec<_>>();
+        for file in stale.into_iter().chain([path.to_path_buf()]) {
+            self.published.remove(&file);
+            diagnostics.entry(file).or_default();
+        }
+
+        for (file, diagnostics) in diagnostics {
+            let Ok(uri) = Url::from_file_path(&file) else {
+                continue;
+            };
+            if !diagnostics.is_empty() {
+                self.published.insert(file);
+            }
+            self.send(&json!({
+                "jsonrpc": "2.0",
+                "method": "textDocument/publishDiagnostics",
+                "params": { "uri": uri.as_str(), "diagnostics": diagnostics },
+            }))?;
+        }
+        Ok(())
+    }
+
+    /// Resolves the document and position in `params` to the item named at
+    /// that position, if any.
+    fn item_at(&self, params: &Value) -> Option<(Resolve, Item)> {
+        let path = document_path(params)?;
+        let text = self.text(&path);
+        let (line, column) = source_position(&text, &params["position"])?;
+        let (start, _) = word_at(&text, line, column)?;
+        let resolve = self.analyze(&path).ok()?;
+
+        if let Some(reference) = resolve.locations.reference_at(&path, line, start) {
+            return Some((resolve, Item::Reference(reference)));
+        }
+
+        // Otherwise see if the position is the name of an item's definition.
+        let here = Location {
+            path,
+            line,
+            column: start,
+        };
+        let locations = &resolve.locations;
+        let item = if let Some((id, _)) = resolve
+            .types
+            .iter()
+            .find(|(id, ty)| ty.name.is_some() && locations.ty(*id).as_ref() == Some(&here))
+        {
+            Item::Reference(Reference::Type(id))
+        } else if let Some((id, _)) = resolve
+            .interfaces
+            .iter()
+            .find(|(id, _)| locations.interface(*id).as_ref() == Some(&here))
+        {
+            Item::Reference(Reference::Interface(id))
+        } else if let Some((id, _)) = resolve
+            .worlds
+            .iter()
+            .find(|(id, _)| locations.world(*id).as_ref() == Some(&here))
+        {
+            Item::Reference(Reference::World(id))
+        } else {
+            resolve.interfaces.iter().find_map(|(id, iface)| {
+                iface
+                    .functions
+                    .keys()
+                    .find(|name| locations.function(id, name).as_ref() == Some(&here))
+                    .map(|name| Item::Function(id, name.clone()))
+            })?
+        };
+        Some((resolve, item))
+    }
+
+    fn hover(&self, params: &Value) -> Option<Value> {
+        let (resolve, item) = self.item_at(params)?;
+        let (signature, docs) = match &item {
+            Item::Reference(Reference::Interface(id)) => {
+                let iface = &resolve.interfaces[*id];
+                let name = resolve
+                    .id_of(*id)
+                    .or_else(|| iface.name.clone())
+                    .unwrap_or_else(|| "<anonymous>".to_string());
+                (format!("interface {name}"), &iface.docs)
+            }
+            Item::Reference(Reference::World(id)) => {
+                let world = &resolve.worlds[*id];
+                (format!("world {}", world.name), &world.docs)
+            }
+            Item::Reference(Reference::Type(id)) => {
+                let ty = &resolve.types[*id];
+                let keyword = match &ty.kind {
+                    TypeDefKind::Record(_) => "record",
+                    TypeDefKind::Resource => "resource",
+                    TypeDefKind::Flags(_) => "flags",
+                    TypeDefKind::Variant(_) => "variant",
+                    TypeDefKind::Enum(_) => "enum",
+                    _ => "type",
+                };
+                let name = ty.name.as_deref().unwrap_or("<anonymous>");
+                (format!("{keyword} {name}"), &ty.docs)
+            }
+            Item::Function(iface, name) => {
+                let func = &resolve.interfaces[*iface].functions[name];
+                (format!("func {}", func.item_name()), &func.docs)
+            }
+        };
+
+        let mut contents = format!("```wit\n{signature}\n```\n");
+        if let Some(docs) = &docs.contents {
+            contents.push_str("\n");
+            contents.push_str(docs.trim_end());
+            contents.push_str("\n");
+        }
+        if let Item::Reference(Reference::Type(id)) = item {
+            let mut sizes = SizeAlign::default();
+            sizes.fill(&resolve);
+            let ty = Type::Id(id);
+            contents.push_str(&format!(
+                "\nsize: {}, align: {}\n",
+                sizes.size(&ty).size_wasm32(),
+                sizes.align(&ty).align_wasm32(),
+            ));
+        }
+        Some(json!({
+            "contents": { "kind": "markdown", "value": contents },
+        }))
+    }
+
+    fn definition(&self, params: &Value) -> Option<Value> {
+        let (resolve, item) = self.item_at(params)?;
+        let locations = &resolve.locations;
+        let location = match item {
+            Item::Reference(Reference::Interface(id)) => locations.interface(id)?,
+            Item::Reference(Reference::World(id)) => locations.world(id)?,
+            Item::Reference(Reference::Type(id)) => locations.ty(id)?,
+            Item::Function(id, name) => locations.function(id, &name)?,
+        };
+        let uri = Url::from_file_path(&location.path).ok()?;
+        let range = self.name_range(&location);
+        Some(json!({ "uri": uri.as_str(), "range": range }))
+    }
+
+    fn document_symbols(&self, params: &Value) -> Option<Value> {
+        let path = document_path(params)?;
+        let resolve = self.analyze(&path).ok()?;
+        let locations = &resolve.locations;
+        let in_document = |location: Option<Location>| location.filter(|l| l.path == path);
+        let mut symbols = Vec::new();
+
+        for (id, iface) in resolve.interfaces.iter() {
+            let (Some(name), Some(location)) = (&iface.name, in_document(locations.interface(id)))
+            else {
+                continue;
+            };
+            let mut children = Vec::new();
+            for (name, ty) in iface.types.iter() {
+                if let Some(location) = in_document(locations.ty(*ty)) {
+                    let kind = type_symbol_kind(&resolve, *ty);
+                    children.push(self.symbol(name, kind, &location, Vec::new()));
+                }
+            }
+            for (name, func) in iface.functions.iter() {
+                if let Some(location) = in_document(locations.function(id, name)) {
+                    children.push(self.symbol(func.item_name(), 12, &location, Vec::new()));
+                }
+            }
+            symbols.push(self.symbol(name, 11, &location, children));
+        }
+
+        for (id, world) in resolve.worlds.iter() {
+            let Some(location) = in_document(locations.world(id)) else {
+                continue;
+            };
+            let mut children = Vec::new();
+            let items = world
+                .imports
+                .iter()
+                .map(|(key, item)| (key, item, locations.world_import(id, key)))
+                .chain(
+                    world
+                        .exports
+                        .iter()
+                        .map(|(key, item)| (key, item, locations.world_export(id, key))),
+                );
+            for (key, item, location) in items {
+                let Some(location) = in_document(location) else {
+                    continue;
+                };
+                let (name, kind) = match (key, item) {
+                    (WorldKey::Name(name), WorldItem::Interface { .. }) => (name.clone(), 11),
+                    (WorldKey::Interface(_), _) => (resolve.name_world_key(key), 11),
+                    (WorldKey::Name(_), WorldItem::Function(f)) => (f.item_name().to_string(), 12),
+                    (WorldKey::Name(name), WorldItem::Type(ty)) => {
+                        (name.clone(), type_symbol_kind(&resolve, *ty))
+                    }
+                };
+                children.push(self.symbol(&name, kind, &location, Vec::new()));
+            }
+            symbols.push(self.symbol(&world.name, 2, &location, children));
+        }
+
+        Some(Value::Array(symbols))
+    }
+
+    fn symbol(&self, name: &str, kind: u32, location: &Location, children: Vec<Value>) -> Value {
+        let range = self.name_range(location);
+        json!({
+            "name": name,
+            "kind": kind,
+            "range": range,
+            "selectionRange": range,
+            "children": children,
+        })
+    }
+
+    /// Returns the LSP range of the name which starts at `location`.
+    fn name_range(&self, location: &Location) -> Value {
+        let text = self.text(&location.path);
+        let end = word_at(&text, location.line, location.column)
+            .map(|(_, end)| end)
+            .unwrap_or(location.column);
+        json!({
+            "start": lsp_position(&text, location.line, location.column),
+            "end": lsp_position(&text, location.line, end),
+        })
+    }
+}
+
+/// Returns the LSP symbol kind used for the type `id`.
+fn type_symbol_kind(resolve: &Resolve, id: wit_parser::TypeId) -> u32 {
+    match resolve.types[id].kind {
+        TypeDefKind::Resource => 5,
+        TypeDefKind::Record(_) => 23,
+        TypeDefKind::Variant(_) | TypeDefKind::Enum(_) | TypeDefKind::Flags(_) => 10,
+        _ => 26,
+    }
+}
+
+fn document_path(params: &Value) -> Option<PathBuf> {
+    let uri = params["textDocument"]["uri"].as_str()?;
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+/// Converts a 1-based line and byte column in `text` to a 0-based LSP
+/// position counted in UTF-16 code units.
+fn lsp_position(text: &str, line: usize, column: usize) -> Value {
+    let line_text = text.split('\n').nth(line.saturating_sub(1)).unwrap_or("");
+    let prefix = line_text
+        .get(..column.saturating_sub(1))
+        .unwrap_or(line_text);
+    json!({
+        "line": line.saturating_sub(1),
+        "character": prefix.encode_utf16().count(),
+    })
+}
+
+/// Converts an LSP position to a 1-based line and byte column in `text`.
+fn source_position(text: &str, position: &Value) -> Option<(usize, usize)> {
+    let line = usize::try_from(position["line"].as_u64()?).ok()?;
+    let character = usize::try_from(position["character"].as_u64()?).ok()?;
+    let line_text = text.split('\n').nth(line)?;
+    let mut units = 0;
+    let column = line_text
+        .char_indices()
+        .find(|(_, c)| {
+            let found = units >= character;
+            units += c.len_utf16();
+            found
+        })
+        .map(|(i, _)| i)
+        .unwrap_or(line_text.len());
+    Some((line + 1, column + 1))
+}
+
+/// Returns the 1-based start and end byte columns of the identifier at `line`
+/// and `column` in `text`.
+///
+/// A position just after the end of an identifier is considered part of it.
+fn word_at(text: &str, line: usize, column: usize) -> Option<(usize, usize)> {
+    let is_id = |b: u8| b.is_ascii_alphanumeric() || b == b'-' || b == b'%';
+    let bytes = text.split('\n').nth(line.checked_sub(1)?)?.as_bytes();
+    let mut pos = column.checked_sub(1)?.min(bytes.len());
+    if !bytes.get(pos).copied().is_some_and(is_id) {
+        pos = pos.checked_sub(1)?;
+        if !is_id(bytes[pos]) {
+            return None;
+        }
+    }
+    let start = bytes[..pos]
+        .iter()
+        .rposition(|b| !is_id(*b))
+        .map_or(0, |i| i + 1);
+    let end = bytes[pos..]
+        .iter()
+        .position(|b| !is_id(*b))
+        .map_or(bytes.len(), |i| pos + i);
+    Some((start + 1, end + 1))
+}
@@ -211,6 +211,9 @@ fn assert_output(bless: bool, output: &[u8], path: &Path, tempdir: &TempDir) ->
             if let Some(start) = line.find("(processed-by \"wit-component\"") {
                 let (before, _) = line.split_at(start);
                 format!("{before}(processed-by \"wit-component\" \"%version\")")
+            } else if let Some(start) = line.find("\"Tool: wasm-metadata-") {
+                let (before, _) = line.split_at(start);
+                format!("{before}\"Tool: wasm-metadata-%version\"")
             } else {
                 line.to_owned()
             }
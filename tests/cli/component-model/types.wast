@@ -401,3 +401,16 @@
     (core type (func (param (ref 100))))
   )
   "type index out of bounds")
+
+(component
+  (type (component
+    (core type $f (func (param i32)))
+    (core type $m (module))
+    (core type (func (result i32)))
+  ))
+  (type (instance
+    (core type $f (func (param i32)))
+    (core type $m (module))
+    (core type (func (result i32)))
+  ))
+)
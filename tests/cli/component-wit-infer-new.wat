@@ -0,0 +1,35 @@
+;; RUN: component wit --infer-from-module % -o %tmpdir/inferred.wit | \
+;;      component embed %tmpdir/inferred.wit % | \
+;;      component new | \
+;;      component wit
+
+;; Parameter names of imports aren't emitted by the text format, so the name
+;; section is written out by hand to name the parameters of functions 0-2.
+(module
+  (import "host" "log" (func (param i32 i32)))
+
+  (memory (export "memory") 1)
+
+  (func (export "run") (param i32 i32) (result i32)
+    unreachable)
+  (func (export "calc#add") (param i64 i64) (result i64)
+    unreachable)
+
+  ;; These have no WIT equivalent and are left out of the world, but
+  ;; remain exported from the core module.
+  (func (export "getValue") (result i32)
+    unreachable)
+  (func (export "calc#sub#more") (param i64) (result i64)
+    unreachable)
+  (func (export "pair") (result i32 i32)
+    unreachable)
+
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+
+  (@custom "name"
+    "\02\33\03"
+    "\00\02\00\07msg_ptr\01\07msg_len"
+    "\01\02\00\08name_ptr\01\08name_len"
+    "\02\02\00\01a\01\01b")
+)
@@ -0,0 +1,25 @@
+;; RUN: component wit --infer-from-module %
+
+(module $my_module
+  (import "env" "log" (func (param $msg_ptr i32) (param $msg_len i32)))
+  (import "env" "now" (func (result i64)))
+  (import "host-math" "add-two" (func (param i32 i32) (result i32)))
+  (import "host_math" "addTwo" (func (param i32 i32) (result i32)))
+  (import "$root" "random" (func (param $buf i32) (param $len i32)))
+
+  (memory (export "memory") 1)
+
+  (func (export "process") (param $input_ptr i32) (param $input_len i32)
+    (param $scale f32) (result f64)
+    unreachable)
+  (func (export "split") (param i64) (result i32 i32)
+    unreachable)
+  (func (export "calc#add") (param $a i64) (param $b i64) (result i64)
+    unreachable)
+  (func (export "calc#sub#more") (param i64) (result i64)
+    unreachable)
+  (func (export "getValue") (result i32)
+    unreachable)
+  (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+    unreachable)
+)
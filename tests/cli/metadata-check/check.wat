@@ -0,0 +1,8 @@
+;; RUN[pass]: metadata add % --licenses MIT --source https://github.com/chashu/foo \
+;;   --revision de978e17a80c1118f606fce919ba9b7d5a04a5ad --processed-by rustc=1.80.0 | \
+;;   metadata check --policy tests/cli/metadata-check/policy.json
+;; FAIL[fail]: metadata add % --licenses GPL-3.0-only --processed-by rustc=1.79.0 | \
+;;   metadata check --policy tests/cli/metadata-check/policy.json
+;; FAIL[bad-policy]: metadata check % --policy tests/cli/metadata-check/bad-policy.json
+
+(module $foo)
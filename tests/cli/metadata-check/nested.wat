@@ -0,0 +1,7 @@
+;; FAIL[nested]: metadata add % --licenses MIT --source https://github.com/chashu/foo \
+;;   --revision de978e17a80c1118f606fce919ba9b7d5a04a5ad | \
+;;   metadata check --policy tests/cli/metadata-check/policy.json
+
+(component
+  (core module $inner)
+)
@@ -0,0 +1,11 @@
+;; RUN[show]: metadata add % --from-oci-manifest tests/cli/metadata-oci/manifest.json | \
+;;   metadata show --oci-annotations
+;; RUN[precedence]: metadata add % --from-oci-manifest tests/cli/metadata-oci/manifest.json \
+;;   --version 2.0.0 --clear-description | metadata show --oci-annotations
+;; RUN[export]: metadata add % --from-oci-manifest tests/cli/metadata-oci/manifest.json | \
+;;   metadata show --oci-annotations -o %tmpdir/a.json
+;; RUN[roundtrip]: metadata add % --from-oci-manifest %tmpdir/a.json | metadata show --oci-annotations
+;; FAIL[bad-license]: metadata add % --from-oci-manifest tests/cli/metadata-oci/config.json
+;; FAIL[missing]: metadata add % --from-oci-manifest tests/cli/metadata-oci/annotations.wat
+
+(component)
@@ -0,0 +1,8 @@
+;; RUN: metadata add % --name app --licenses MIT --version 1.0.0 | metadata sbom --created 2024-01-01T00:00:00Z
+;; FAIL[bad-format]: metadata sbom % --format json
+
+(component
+  (core module
+    (@custom "licenses" "Apache-2.0")
+  )
+)
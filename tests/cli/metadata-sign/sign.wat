@@ -0,0 +1,15 @@
+;; RUN[public-key]: metadata sign % --key tests/cli/metadata-sign/secret.key --public-key-output %tmpdir/public.key -o %tmpdir/signed.wasm
+;; RUN[public-key-verify]: metadata verify %tmpdir/signed.wasm --public-key %tmpdir/public.key
+;; RUN[verify]: metadata sign % --key tests/cli/metadata-sign/secret.key | metadata verify --public-key tests/cli/metadata-sign/public.key
+;; RUN[show]: metadata sign % --key tests/cli/metadata-sign/secret.key -t
+;; RUN[exclude]: metadata sign % --key tests/cli/metadata-sign/secret.key --exclude producers | metadata add --processed-by foo=1 | metadata verify --public-key tests/cli/metadata-sign/public.key
+;; FAIL[modified]: metadata sign % --key tests/cli/metadata-sign/secret.key | metadata add --name foo | metadata verify --public-key tests/cli/metadata-sign/public.key
+;; FAIL[other-key]: metadata sign % --key tests/cli/metadata-sign/other-secret.key | metadata verify --public-key tests/cli/metadata-sign/public.key
+;; FAIL[unsigned]: metadata verify % --public-key tests/cli/metadata-sign/public.key
+;; FAIL[non-ascii-key]: metadata sign % --key tests/cli/metadata-sign/non-ascii.key
+
+(component
+  (core module
+    (func (export "f"))
+  )
+)
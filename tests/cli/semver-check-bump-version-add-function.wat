@@ -3,7 +3,7 @@
 (component
   (type $prev (component
     (export "a:b/prev" (component
-      (import "a:a/a@1.0.0" (instance
+      (export "a:a/a@1.0.0" (instance
         (export "f" (func))
       ))
     ))
@@ -12,7 +12,7 @@
 
   (type $new (component
     (export "a:b/new" (component
-      (import "a:a/a@1.0.1" (instance
+      (export "a:a/a@1.0.1" (instance
         (export "f" (func))
         (export "f2" (func))
       ))
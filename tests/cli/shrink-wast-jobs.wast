@@ -0,0 +1,3 @@
+;; FAIL: shrink --jobs 2 % %
+
+(module)
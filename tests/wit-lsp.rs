@@ -0,0 +1,269 @@
+//! Tests for `wasm-tools wit-lsp`, driving the language server over stdio.
+
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+const MAIN: &str = "package a:b;
+
+/// Docs for `types`.
+interface types {
+  use c:d/shapes.{size};
+  record point {
+    x: u32,
+    y: u64,
+  }
+  f: func(p: point) -> size;
+}
+
+world w {
+  import types;
+}
+";
+
+const SHAPES: &str = "package c:d;
+
+interface shapes {
+  /// The size of a shape.
+  type size = u32;
+}
+";
+
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+    notifications: Vec<Value>,
+}
+
+impl Client {
+    fn spawn() -> Client {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_wasm-tools"))
+            .arg("wit-lsp")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Client {
+            child,
+            stdin,
+            stdout,
+            next_id: 0,
+            notifications: Vec::new(),
+        }
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn recv(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            self.stdout.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                length = value.parse().unwrap();
+            }
+        }
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        loop {
+            let message = self.recv();
+            if message["id"] == id {
+                assert!(message.get("error").is_none(), "{message}");
+                return message["result"].clone();
+            }
+            self.notifications.push(message);
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Returns the diagnostics in the next `publishDiagnostics` notification
+    /// for `uri`.
+    fn diagnostics(&mut self, uri: &str) -> Value {
+        loop {
+            let message = match self.notifications.is_empty() {
+                true => self.recv(),
+                false => self.notifications.remove(0),
+            };
+            if message["method"] == "textDocument/publishDiagnostics"
+                && message["params"]["uri"] == uri
+            {
+                return message["params"]["diagnostics"].clone();
+            }
+        }
+    }
+}
+
+fn uri(path: &Path) -> String {
+    url::Url::from_file_path(path).unwrap().to_string()
+}
+
+fn position(uri: &str, line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "position": { "line": line, "character": character },
+    })
+}
+
+#[test]
+fn wit_lsp() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path().canonicalize().unwrap();
+    let main = dir.join("main.wit");
+    let shapes = dir.join("deps/c/shapes.wit");
+    std::fs::create_dir_all(shapes.parent().unwrap()).unwrap();
+    std::fs::write(&main, MAIN).unwrap();
+    std::fs::write(&shapes, SHAPES).unwrap();
+    let main_uri = uri(&main);
+
+    let mut client = Client::spawn();
+    let result = client.request("initialize", json!({ "capabilities": {} }));
+    assert_eq!(result["capabilities"]["hoverProvider"], true);
+    client.notify("initialized", json!({}));
+
+    client.notify(
+        "textDocument/didOpen",
+        json!({
+            "textDocument": {
+                "uri": main_uri,
+                "languageId": "wit",
+                "version": 1,
+                "text": MAIN,
+            },
+        }),
+    );
+    assert_eq!(client.diagnostics(&main_uri), json!([]));
+
+    // Hovering a type shows its kind, docs, and canonical ABI layout.
+    let hover = client.request("textDocument/hover", position(&main_uri, 5, 11));
+    assert_eq!(
+        hover["contents"]["value"],
+        "```wit\nrecord point\n```\n\nsize: 16, align: 8\n"
+    );
+    let hover = client.request("textDocument/hover", position(&main_uri, 3, 12));
+    assert_eq!(
+        hover["contents"]["value"],
+        "```wit\ninterface a:b/types\n```\n\nDocs for `types`.\n"
+    );
+    let hover = client.request("textDocument/hover", position(&main_uri, 4, 19));
+    assert_eq!(
+        hover["contents"]["value"],
+        "```wit\ntype size\n```\n\nThe size of a shape.\n\nsize: 4, align: 4\n"
+    );
+    let hover = client.request("textDocument/hover", position(&main_uri, 1, 0));
+    assert_eq!(hover, Value::Null);
+
+    // Definitions resolve across `deps`.
+    let definition = client.request("textDocument/definition", position(&main_uri, 4, 19));
+    assert_eq!(
+        definition,
+        json!({
+            "uri": uri(&shapes),
+            "range": {
+                "start": { "line": 4, "character": 7 },
+                "end": { "line": 4, "character": 11 },
+            },
+        })
+    );
+    let definition = client.request("textDocument/definition", position(&main_uri, 4, 12));
+    assert_eq!(definition["uri"], uri(&shapes));
+    assert_eq!(
+        definition["range"]["start"],
+        json!({ "line": 2, "character": 10 })
+    );
+    let definition = client.request("textDocument/definition", position(&main_uri, 9, 14));
+    assert_eq!(definition["uri"], main_uri);
+    assert_eq!(
+        definition["range"]["start"],
+        json!({ "line": 5, "character": 9 })
+    );
+
+    let symbols = client.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": main_uri } }),
+    );
+    let names = |symbols: &Value| {
+        symbols
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&symbols), ["types", "w"]);
+    assert_eq!(names(&symbols[0]["children"]), ["size", "point", "f"]);
+    assert_eq!(names(&symbols[1]["children"]), ["a:b/types"]);
+
+    // Errors in unsaved changes are reported.
+    let change = |version: u32, text: &str| {
+        json!({
+            "textDocument": { "uri": main_uri, "version": version },
+            "contentChanges": [{ "text": text }],
+        })
+    };
+    client.notify(
+        "textDocument/didChange",
+        change(2, &MAIN.replace("p: point", "p: nope")),
+    );
+    let diagnostics = client.diagnostics(&main_uri);
+    assert_eq!(diagnostics[0]["message"], "name `nope` is not defined");
+    assert_eq!(
+        diagnostics[0]["range"],
+        json!({
+            "start": { "line": 9, "character": 13 },
+            "end": { "line": 9, "character": 17 },
+        })
+    );
+
+    // Syntax errors point at the offending character, with positions counted
+    // in UTF-16 code units.
+    client.notify(
+        "textDocument/didChange",
+        change(3, &MAIN.replace("f: func", "/* é */ f: func $")),
+    );
+    let diagnostics = client.diagnostics(&main_uri);
+    assert_eq!(diagnostics[0]["message"], "unexpected character '$'");
+    assert_eq!(
+        diagnostics[0]["range"],
+        json!({
+            "start": { "line": 9, "character": 18 },
+            "end": { "line": 9, "character": 19 },
+        })
+    );
+
+    // Fixing the error clears the diagnostics, and saving analyzes the
+    // document as it is in the editor.
+    client.notify("textDocument/didChange", change(4, MAIN));
+    assert_eq!(client.diagnostics(&main_uri), json!([]));
+    client.notify(
+        "textDocument/didSave",
+        json!({ "textDocument": { "uri": main_uri } }),
+    );
+    assert_eq!(client.diagnostics(&main_uri), json!([]));
+
+    assert_eq!(client.request("shutdown", Value::Null), Value::Null);
+    client.notify("exit", Value::Null);
+    assert!(client.child.wait().unwrap().success());
+}